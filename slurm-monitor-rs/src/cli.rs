@@ -1,24 +1,29 @@
 //! CLI entry point and command definitions.
 
-use crate::job_manager::JobManager;
+use crate::config::{Action, Config, MIN_DISCOVERY_INTERVAL};
+use crate::diagnostics::{self, Level};
+use crate::doctor::{self, Outcome};
+use crate::job_manager::{
+    step_suffix, JobInfo, JobManager, JobStep, JobSummary, SbatchError,
+};
 
-/// Write debug message to file
-fn debug_log(msg: &str) {
-    use std::io::Write;
-    if let Ok(mut f) = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("/tmp/slurm_monitor_debug.log")
-    {
-        let _ = writeln!(f, "{}", msg);
-    }
-}
-use crate::log_tailer::{LogTailer, LogUpdate};
+use crate::gpu_monitor::{GpuMonitor, GpuUpdate};
+use crate::log_tailer::{LogProblem, LogStatus, LogTailer, LogUpdate};
+use crate::plain::PlainOutput;
+use crate::recorder::StatusRecorder;
 use crate::status_monitor::{StatusMonitor, StatusUpdate};
-use crate::ui::{self, App};
-use crate::utils::get_all_job_ids_from_sacct;
+use crate::ui::{
+    self, App, Column, FormAction, LayoutMode, PromptKind, SortMode, SubmitForm, TextOverlay,
+    Theme, ThemeName, DASHBOARD_COLUMNS,
+};
+use crate::utils::{
+    parse_job_id_field, shell_words, validate_sacct_time, JobFilter, JobStatus, SystemRunner,
+    Throttle,
+};
+use crate::webhook::{validate_webhook_url, WebhookNotifier};
 use anyhow::{Context, Result};
-use clap::{Parser, Subcommand};
+use clap::builder::RangedU64ValueParser;
+use clap::{Args, Parser, Subcommand};
 use crossterm::{
     cursor::{Hide, Show},
     event::{self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind},
@@ -29,10 +34,16 @@ use crossterm::{
     },
 };
 use ratatui::prelude::*;
-use std::io::{self, stdout};
-use std::path::PathBuf;
+use signal_hook::consts::{SIGHUP, SIGINT, SIGTERM};
+use std::ffi::OsString;
+use std::io::{self, stdout, Write};
+use std::panic;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::mpsc::{self, Receiver};
-use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::thread;
 use std::time::{Duration, Instant};
 
 /// SLURM Job Monitor - Real-time monitoring tool for SLURM jobs.
@@ -43,6 +54,78 @@ use std::time::{Duration, Instant};
 pub struct Cli {
     #[command(subcommand)]
     pub command: Commands,
+
+    /// Run SLURM commands against this slurm.conf (sets SLURM_CONF), for
+    /// machines with several SLURM configurations
+    #[arg(long, global = true, value_name = "PATH")]
+    pub slurm_conf: Option<PathBuf>,
+
+    /// Directory holding the slurm.conf to use; same as
+    /// `--slurm-conf DIR/slurm.conf`
+    #[arg(long, global = true, value_name = "DIR", conflicts_with = "slurm_conf")]
+    pub config_dir: Option<PathBuf>,
+
+    /// Also append monitor diagnostics (the L pane) to this file
+    #[arg(long, global = true, value_name = "PATH")]
+    pub watch_output: Option<PathBuf>,
+
+    /// Least severe diagnostics written to `--watch-output` [default: debug]
+    #[arg(long, global = true, value_enum)]
+    pub log_level: Option<Level>,
+
+    /// Wait at least this long between starting two SLURM commands, to
+    /// spread polls of many jobs out [default: 0]
+    #[arg(long, global = true, value_name = "SECS", value_parser = parse_threshold)]
+    pub command_delay: Option<f64>,
+
+    /// Add up to this long at random to each wait between SLURM commands
+    /// [default: 0]
+    #[arg(long, global = true, value_name = "SECS", value_parser = parse_threshold)]
+    pub command_jitter: Option<f64>,
+
+    /// Run at most this many SLURM commands at once [default: 4]
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        value_parser = RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub max_slurm_commands: Option<usize>,
+}
+
+impl Cli {
+    /// Environment to run SLURM commands with, from `--slurm-conf` or
+    /// `--config-dir`.
+    pub fn slurm_env(&self) -> Result<Vec<(String, OsString)>> {
+        let conf = match (&self.slurm_conf, &self.config_dir) {
+            (Some(path), _) => path.clone(),
+            (None, Some(dir)) => dir.join("slurm.conf"),
+            (None, None) => return Ok(Vec::new()),
+        };
+        if !conf.is_file() {
+            anyhow::bail!("SLURM config not found: {}", conf.display());
+        }
+        Ok(vec![("SLURM_CONF".to_string(), conf.into_os_string())])
+    }
+
+    /// How fast to run SLURM commands, from the flags or else `config`.
+    pub fn slurm_throttle(&self, config: &Config) -> Throttle {
+        Throttle {
+            delay: Duration::from_secs_f64(self.command_delay.unwrap_or(config.command_delay)),
+            jitter: Duration::from_secs_f64(self.command_jitter.unwrap_or(config.command_jitter)),
+            max_concurrent: self.max_slurm_commands.unwrap_or(config.max_slurm_commands),
+        }
+    }
+
+    /// Start writing diagnostics to the `--watch-output` file, if given.
+    pub fn start_diagnostics_log(&self) -> Result<()> {
+        match (&self.watch_output, self.log_level) {
+            (Some(path), level) => diagnostics::log_to_file(path, level.unwrap_or(Level::Debug))
+                .with_context(|| format!("Cannot write diagnostics to {}", path.display())),
+            (None, Some(_)) => anyhow::bail!("--log-level needs --watch-output to say where to write"),
+            (None, None) => Ok(()),
+        }
+    }
 }
 
 #[derive(Subcommand)]
@@ -54,33 +137,389 @@ pub enum Commands {
         /// Do not start monitoring after submission
         #[arg(long)]
         no_watch: bool,
+        /// Print the sbatch command before running it, and all of sbatch's
+        /// error output if it fails (on stderr, keeping stdout to the job ID)
+        #[arg(short, long)]
+        verbose: bool,
+        /// Submit to and monitor on this cluster (sbatch --clusters)
+        #[arg(long, value_name = "NAME")]
+        cluster: Option<String>,
+        /// Fill in the job name, partition, time limit, CPUs and memory in a
+        /// form first, prefilled from the script's #SBATCH lines
+        #[arg(short, long)]
+        interactive: bool,
+        #[command(flatten)]
+        monitor: MonitorArgs,
     },
     /// Monitor one or more existing SLURM jobs
     Watch {
         /// Job IDs to monitor (if none provided, monitors all visible jobs)
         job_ids: Vec<u64>,
+        /// Also monitor the job IDs listed in this file, or `-` for stdin:
+        /// one or more per line, with `#` starting a comment
+        #[arg(long, value_name = "FILE")]
+        ids_from: Option<PathBuf>,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[command(flatten)]
+        monitor: MonitorArgs,
+    },
+    /// Show a live table of jobs, re-sorted as they change, without their logs
+    Dashboard {
+        /// Job IDs to show (if none provided, shows all visible jobs)
+        job_ids: Vec<u64>,
+        /// Sort the table by this key [default: id]
+        #[arg(long, value_enum)]
+        sort: Option<SortMode>,
+        /// Reverse the sort direction
+        #[arg(long)]
+        reverse: bool,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[command(flatten)]
+        monitor: MonitorArgs,
+    },
+    /// Watch jobs in the background without a terminal, e.g. as a systemd
+    /// service: only sends webhooks and records status changes
+    Daemon {
+        /// Job IDs to watch (if none provided, watches all visible jobs and
+        /// keeps adding new ones until stopped)
+        job_ids: Vec<u64>,
+        /// Append status changes to this file instead of printing them
+        #[arg(long, value_name = "PATH")]
+        log_file: Option<PathBuf>,
+        #[command(flatten)]
+        filter: FilterArgs,
+        #[command(flatten)]
+        monitor: MonitorArgs,
+    },
+    /// Tail arbitrary log files in the monitor UI, without querying SLURM
+    Tail {
+        /// Files to tail
+        #[arg(required = true)]
+        paths: Vec<PathBuf>,
     },
     /// List all currently tracked jobs
-    List,
+    List {
+        #[command(flatten)]
+        filter: FilterArgs,
+        /// Print each job with a template, e.g. "{job_id}\t{status}\t{name}"
+        /// (placeholders: job_id, status, name, state, elapsed, partition,
+        /// nodes, start, end)
+        #[arg(long, value_name = "TEMPLATE", conflicts_with = "json")]
+        format: Option<String>,
+        /// Print the jobs as a JSON array
+        #[arg(long)]
+        json: bool,
+    },
     /// Stop monitoring a specific job (does not cancel the job)
     Stop {
         /// Job ID to stop monitoring
         job_id: u64,
     },
+    /// Check that the SLURM tools can be found and queried, to find out why
+    /// jobs aren't shown or show as UNKNOWN
+    #[command(alias = "check")]
+    Doctor,
+}
+
+/// Job selection options shared by commands that query sacct.
+#[derive(Args, Debug, Clone, Default)]
+pub struct FilterArgs {
+    /// Only include jobs whose name matches a glob pattern (e.g. "train_*")
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Only include jobs active since this time (e.g. 2024-01-31, now-2days)
+    #[arg(long, value_parser = validate_sacct_time)]
+    pub since: Option<String>,
+    /// Only include jobs active before this time (same formats as --since)
+    #[arg(long, value_parser = validate_sacct_time)]
+    pub until: Option<String>,
+    /// Query jobs on this cluster of a federation or multi-cluster setup
+    #[arg(long, value_name = "NAME")]
+    pub cluster: Option<String>,
+}
+
+impl From<FilterArgs> for JobFilter {
+    fn from(args: FilterArgs) -> Self {
+        JobFilter {
+            name_pattern: args.name,
+            since: args.since,
+            until: args.until,
+            cluster: args.cluster,
+        }
+    }
+}
+
+/// Options for the interactive monitor shared by commands that open it.
+///
+/// Each flag overrides the matching setting from the config file.
+#[derive(Args, Debug, Clone, Default)]
+pub struct MonitorArgs {
+    /// Seconds between SLURM status polls [default: 3]
+    #[arg(long, value_name = "SECS", value_parser = parse_interval)]
+    pub interval: Option<f64>,
+
+    /// Add newly submitted jobs when watching without job IDs (the default)
+    #[arg(long, overrides_with = "no_auto_discover")]
+    pub auto_discover: bool,
+
+    /// Only monitor the jobs found at startup
+    #[arg(long, overrides_with = "auto_discover")]
+    pub no_auto_discover: bool,
+
+    /// Seconds between checks for new jobs, at least 2. Each check runs
+    /// sacct, so short intervals add load on the SLURM controller
+    /// [default: 10]
+    #[arg(long, value_name = "SECS", value_parser = parse_discovery_interval)]
+    pub discovery_interval: Option<f64>,
+
+    /// Show live GPU utilization for running jobs (runs nvidia-smi via srun)
+    #[arg(long)]
+    pub gpu: bool,
+
+    /// Switch to newly discovered jobs as they appear (with auto-discovery)
+    #[arg(long)]
+    pub follow_new: bool,
+
+    /// Comma-separated status table columns, e.g. `id,status,partition,nodes`
+    #[arg(long, value_enum, value_delimiter = ',')]
+    pub columns: Vec<Column>,
+
+    /// POST a JSON payload to this URL when a monitored job finishes
+    #[arg(long, value_name = "URL", value_parser = validate_webhook_url)]
+    pub webhook: Option<String>,
+
+    /// Append each job status change to this file as JSON lines (or CSV if
+    /// it ends in `.csv`)
+    #[arg(long, value_name = "PATH")]
+    pub record: Option<PathBuf>,
+
+    /// Color theme: dark, light, or mono for terminals with limited colors
+    #[arg(long, value_enum)]
+    pub theme: Option<ThemeName>,
+
+    /// Don't capture the mouse, so the terminal's text selection keeps working
+    /// (e.g. inside tmux or screen)
+    #[arg(long)]
+    pub no_mouse: bool,
+
+    /// Keep monitoring job IDs that SLURM doesn't know about
+    #[arg(long)]
+    pub keep_unknown: bool,
+
+    /// Print status changes and new log lines as plain text instead of
+    /// opening the interactive UI, e.g. for CI logs or output redirected to a
+    /// file; exits once every job has finished
+    #[arg(long)]
+    pub plain: bool,
+
+    /// Monitor at most this many jobs; further jobs found at startup or by
+    /// auto-discovery are left out (newest jobs are kept)
+    #[arg(long, value_name = "N", value_parser = RangedU64ValueParser::<usize>::new().range(1..))]
+    pub max_jobs: Option<usize>,
+
+    /// Show every carriage-return progress update (e.g. tqdm bars) as its own
+    /// line instead of only the latest one
+    #[arg(long)]
+    pub raw_progress: bool,
+
+    /// When a log file is truncated or replaced (e.g. a requeued job starts
+    /// over), keep its earlier output above a marker line instead of
+    /// clearing the panel
+    #[arg(long)]
+    pub keep_truncated_logs: bool,
+
+    /// List each job's steps (e.g. `123.0`, `123.batch`) under it with their
+    /// own state, elapsed time and peak memory
+    #[arg(long)]
+    pub steps: bool,
+
+    /// List the tasks of array jobs (e.g. `123_4`) under them, and switch the
+    /// log panels between the tasks' logs with `t` / `T`
+    #[arg(long)]
+    pub array_tasks: bool,
+
+    /// Ring the terminal bell when a job fails, times out or runs out of
+    /// memory (the default)
+    #[arg(long, overrides_with = "no_bell")]
+    pub bell: bool,
+
+    /// Don't ring the terminal bell when a job fails
+    #[arg(long, overrides_with = "bell")]
+    pub no_bell: bool,
+
+    /// Show the current memory use of running jobs and its history, read
+    /// with sstat on every status poll (one extra query per running job)
+    #[arg(long)]
+    pub live_memory: bool,
+
+    /// Flag running jobs whose logs haven't grown for this many seconds as
+    /// stalled; 0 turns it off [default: 600]
+    #[arg(long, value_name = "SECS", value_parser = parse_threshold)]
+    pub stall_after: Option<f64>,
+}
+
+impl MonitorArgs {
+    /// Override config file settings with the flags that were given.
+    pub fn apply(self, config: &mut Config) {
+        if let Some(interval) = self.interval {
+            config.status_interval = interval;
+        }
+        if let Some(interval) = self.discovery_interval {
+            config.discovery_interval = interval;
+        }
+        if self.auto_discover {
+            config.auto_discover = true;
+        }
+        if self.no_auto_discover {
+            config.auto_discover = false;
+        }
+        if self.gpu {
+            config.gpu = true;
+        }
+        if self.follow_new {
+            config.follow_new = true;
+        }
+        if !self.columns.is_empty() {
+            config.columns = self.columns;
+        }
+        if self.webhook.is_some() {
+            config.webhook = self.webhook;
+        }
+        if self.record.is_some() {
+            config.record = self.record;
+        }
+        if let Some(theme) = self.theme {
+            config.theme = theme;
+        }
+        if self.no_mouse {
+            config.mouse = false;
+        }
+        if self.max_jobs.is_some() {
+            config.max_jobs = self.max_jobs;
+        }
+        if self.raw_progress {
+            config.raw_progress = true;
+        }
+        if self.keep_truncated_logs {
+            config.keep_truncated_logs = true;
+        }
+        if self.steps {
+            config.steps = true;
+        }
+        if self.array_tasks {
+            config.array_tasks = true;
+        }
+        if self.live_memory {
+            config.live_memory = true;
+        }
+        if self.bell {
+            config.bell = true;
+        }
+        if self.no_bell {
+            config.bell = false;
+        }
+        if let Some(stall_after) = self.stall_after {
+            config.stall_after = stall_after;
+        }
+    }
+}
+
+/// Parse a positive number of seconds.
+fn parse_interval(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs > 0.0 => Ok(secs),
+        _ => Err(format!("'{}' is not a positive number of seconds", s)),
+    }
+}
+
+/// Parse a discovery interval, which has a floor to spare the controller.
+fn parse_discovery_interval(s: &str) -> Result<f64, String> {
+    let secs = parse_interval(s)?;
+    if secs < MIN_DISCOVERY_INTERVAL {
+        return Err(format!(
+            "checking for new jobs more often than every {} seconds would load the SLURM controller",
+            MIN_DISCOVERY_INTERVAL
+        ));
+    }
+    Ok(secs)
+}
+
+/// Parse a number of seconds that may be 0 (to turn a check off).
+fn parse_threshold(s: &str) -> Result<f64, String> {
+    match s.parse::<f64>() {
+        Ok(secs) if secs.is_finite() && secs >= 0.0 => Ok(secs),
+        _ => Err(format!("'{}' is not a number of seconds", s)),
+    }
 }
 
 /// Handle the submit command.
-pub fn handle_submit(script: &PathBuf, no_watch: bool) -> Result<()> {
-    let mut job_manager = JobManager::new();
-    let job_id = job_manager
-        .submit_job(script, &[])
-        .context("Failed to submit job")?;
+pub fn handle_submit(
+    script: &Path,
+    no_watch: bool,
+    verbose: bool,
+    interactive: bool,
+    monitor: MonitorArgs,
+    mut config: Config,
+    mut job_manager: JobManager,
+) -> Result<()> {
+    let plain = monitor.plain;
+    monitor.apply(&mut config);
+
+    let job_id = if interactive {
+        let theme = Theme::named(config.theme).with_status_colors(&config.colors);
+        match run_submit_form(&mut job_manager, script, &theme)? {
+            Some((job_id, args)) => {
+                if verbose {
+                    eprintln!("Ran: {}", shell_words(&job_manager.sbatch_command(script, &args)));
+                }
+                job_id
+            }
+            None => {
+                println!("Nothing submitted");
+                return Ok(());
+            }
+        }
+    } else {
+        if verbose {
+            eprintln!("Running: {}", shell_words(&job_manager.sbatch_command(script, &[])));
+        }
+        match job_manager.submit_job(script, &[]) {
+            Ok(job_id) => job_id,
+            Err(e) => {
+                let mut context = "Failed to submit job";
+                if let Some(sbatch) = e.downcast_ref::<SbatchError>() {
+                    if verbose {
+                        eprintln!("sbatch output (exit code {}):", sbatch.return_code);
+                        eprint!("{}", sbatch.stderr);
+                    } else if sbatch.has_more_output() {
+                        context = "Failed to submit job (--verbose shows all of sbatch's output)";
+                    }
+                }
+                return Err(e.context(context));
+            }
+        }
+    };
 
     println!("Submitted job {}", job_id);
 
     if !no_watch {
         println!("Starting monitor...");
-        run_monitor(vec![job_id], false)?;
+        let cluster = job_manager.cluster().map(String::from);
+        run_monitor(
+            job_manager,
+            vec![job_id],
+            MonitorOptions {
+                filter: JobFilter {
+                    cluster,
+                    ..Default::default()
+                },
+                plain,
+                config,
+                ..Default::default()
+            },
+        )?;
     } else {
         println!(
             "Job {} submitted. Use 'slurm-monitor watch {}' to monitor it.",
@@ -91,153 +530,1267 @@ pub fn handle_submit(script: &PathBuf, no_watch: bool) -> Result<()> {
     Ok(())
 }
 
+/// Show the submission form for `script` until a submission succeeds, giving
+/// the job ID and the sbatch options used, or the form is cancelled. When
+/// sbatch rejects the job its error is shown in the form to fix it there.
+fn run_submit_form(
+    job_manager: &mut JobManager,
+    script: &Path,
+    theme: &Theme,
+) -> Result<Option<(u64, Vec<String>)>> {
+    let content = std::fs::read_to_string(script)
+        .with_context(|| format!("Failed to read {}", script.display()))?;
+    let mut form = SubmitForm::new(script, &content);
+
+    let _terminal_guard = TerminalGuard::enter(stdout(), false)?;
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+    loop {
+        terminal.draw(|frame| ui::render_submit_form(frame, &form, theme))?;
+        let key = match event::read()? {
+            Event::Key(key) if key.kind == KeyEventKind::Press => key,
+            _ => continue,
+        };
+        let args = match form.handle_key(key) {
+            FormAction::None => continue,
+            FormAction::Cancel => return Ok(None),
+            FormAction::Submit(args) => args,
+        };
+        match job_manager.submit_job(script, &args) {
+            Ok(job_id) => return Ok(Some((job_id, args))),
+            Err(e) => {
+                form.sbatch_error = Some(match e.downcast_ref::<SbatchError>() {
+                    Some(sbatch) => sbatch.stderr.trim().to_string(),
+                    None => format!("{:#}", e),
+                });
+            }
+        }
+    }
+}
+
 /// Handle the watch command.
-pub fn handle_watch(job_ids: Vec<u64>) -> Result<()> {
-    let (job_ids, auto_discover) = if job_ids.is_empty() {
-        println!("No job IDs provided. Fetching all visible jobs from sacct...");
-        let all_jobs = get_all_job_ids_from_sacct();
-        if all_jobs.is_empty() {
-            println!("No jobs found in sacct. Will monitor for new jobs...");
-        } else {
+///
+/// With `ids_from`, the jobs are exactly those given, so there is nothing to
+/// discover; an empty list is an error rather than meaning all jobs.
+pub fn handle_watch(
+    mut job_ids: Vec<u64>,
+    ids_from: Option<PathBuf>,
+    filter: JobFilter,
+    mut monitor: MonitorArgs,
+    config: Config,
+    runner: SystemRunner,
+) -> Result<()> {
+    if let Some(path) = ids_from {
+        let listed = read_job_id_list(&path)?;
+        for job_id in listed {
+            if !job_ids.contains(&job_id) {
+                job_ids.push(job_id);
+            }
+        }
+        if job_ids.is_empty() {
+            anyhow::bail!("No job IDs found in {}", describe_id_source(&path));
+        }
+        monitor.auto_discover = false;
+        monitor.no_auto_discover = true;
+    }
+    start_watching(job_ids, filter, monitor, config, runner, MonitorOptions::default())
+}
+
+/// Read a job ID list for `--ids-from` from a file, or from stdin for `-`.
+fn read_job_id_list(path: &Path) -> Result<Vec<u64>> {
+    let content = if path == Path::new("-") {
+        io::read_to_string(io::stdin()).context("Cannot read job IDs from stdin")?
+    } else {
+        std::fs::read_to_string(path)
+            .with_context(|| format!("Cannot read job IDs from {}", path.display()))?
+    };
+    parse_job_id_list(&content)
+        .map_err(|e| anyhow::anyhow!("{}:{}", describe_id_source(path), e))
+}
+
+fn describe_id_source(path: &Path) -> String {
+    if path == Path::new("-") {
+        "stdin".to_string()
+    } else {
+        path.display().to_string()
+    }
+}
+
+/// Parse whitespace-separated job IDs, ignoring blank lines and `#` comments.
+///
+/// IDs may be written the way sacct prints them (`123.batch`, `123_4`,
+/// `123+0`), so its output can be piped in as is; each names its job once.
+/// Errors are prefixed with the line number.
+fn parse_job_id_list(content: &str) -> Result<Vec<u64>, String> {
+    let mut job_ids = Vec::new();
+    for (number, line) in content.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        for word in line.split_whitespace() {
+            let job_id = parse_job_id_field(word)
+                .ok_or_else(|| format!("{}: '{}' is not a job ID", number + 1, word))?;
+            if !job_ids.contains(&job_id) {
+                job_ids.push(job_id);
+            }
+        }
+    }
+    Ok(job_ids)
+}
+
+/// Handle the dashboard command.
+pub fn handle_dashboard(
+    job_ids: Vec<u64>,
+    sort: Option<SortMode>,
+    reverse: bool,
+    filter: JobFilter,
+    monitor: MonitorArgs,
+    config: Config,
+    runner: SystemRunner,
+) -> Result<()> {
+    let dashboard = Dashboard {
+        sort: sort.unwrap_or_default(),
+        reverse,
+    };
+    let options = MonitorOptions {
+        dashboard: Some(dashboard),
+        ..Default::default()
+    };
+    start_watching(job_ids, filter, monitor, config, runner, options)
+}
+
+/// Handle the daemon command.
+pub fn handle_daemon(
+    job_ids: Vec<u64>,
+    log_file: Option<PathBuf>,
+    filter: JobFilter,
+    monitor: MonitorArgs,
+    config: Config,
+    runner: SystemRunner,
+) -> Result<()> {
+    let options = MonitorOptions {
+        daemon: Some(Daemon { log_file }),
+        ..Default::default()
+    };
+    start_watching(job_ids, filter, monitor, config, runner, options)
+}
+
+/// Find the jobs to watch and open the monitor on them, as the job list and
+/// logs, as a dashboard or as a daemon, as set in `options`.
+fn start_watching(
+    job_ids: Vec<u64>,
+    filter: JobFilter,
+    monitor: MonitorArgs,
+    mut config: Config,
+    runner: SystemRunner,
+    options: MonitorOptions,
+) -> Result<()> {
+    let keep_unknown = monitor.keep_unknown;
+    let plain = monitor.plain;
+    monitor.apply(&mut config);
+    let job_manager = cluster_job_manager(runner, filter.cluster.clone())?;
+    let (job_ids, auto_discover) = if let Some(ref pattern) = filter.name_pattern {
+        println!("Fetching jobs named '{}' from sacct...", pattern);
+        let mut matched = match job_manager.discover_job_ids(&filter) {
+            Ok(matched) if matched.is_empty() => {
+                println!(
+                    "No jobs matching '{}' found. Will monitor for new jobs...",
+                    pattern
+                );
+                matched
+            }
+            Ok(matched) => {
+                print_found_jobs(&matched);
+                matched
+            }
+            Err(e) => {
+                eprintln!("sacct failed: {:#}. Will monitor for new jobs...", e);
+                Vec::new()
+            }
+        };
+        for job_id in job_ids {
+            if !matched.contains(&job_id) {
+                matched.push(job_id);
+            }
+        }
+        if config.auto_discover {
             println!(
-                "Found {} job(s): {}",
-                all_jobs.len(),
-                all_jobs
-                    .iter()
-                    .map(|id| id.to_string())
-                    .collect::<Vec<_>>()
-                    .join(", ")
+                "Auto-discovery enabled: new jobs matching '{}' will be added to monitoring.",
+                pattern
             );
         }
-        println!("Auto-discovery enabled: new jobs will be automatically added to monitoring.");
-        (all_jobs, true)
+        (matched, config.auto_discover)
+    } else if job_ids.is_empty() {
+        println!("No job IDs provided. Fetching all visible jobs from sacct...");
+        let all_jobs = match job_manager.discover_job_ids(&filter) {
+            Ok(all_jobs) if all_jobs.is_empty() => {
+                println!("No jobs found in sacct. Will monitor for new jobs...");
+                all_jobs
+            }
+            Ok(all_jobs) => {
+                print_found_jobs(&all_jobs);
+                all_jobs
+            }
+            Err(e) => {
+                eprintln!("sacct failed: {:#}. Will monitor for new jobs...", e);
+                Vec::new()
+            }
+        };
+        if config.auto_discover {
+            println!("Auto-discovery enabled: new jobs will be automatically added to monitoring.");
+        }
+        (all_jobs, config.auto_discover)
     } else {
         (job_ids, false)
     };
 
-    run_monitor(job_ids, auto_discover)?;
-    Ok(())
+    run_monitor(
+        job_manager,
+        job_ids,
+        MonitorOptions {
+            auto_discover,
+            filter,
+            keep_unknown,
+            plain,
+            config,
+            ..options
+        },
+    )?;
+    Ok(())
+}
+
+/// Keep at most `limit` of `job_ids`, preferring the newest (highest IDs).
+///
+/// Returns the kept IDs in their original order and how many were left out.
+fn apply_job_limit(job_ids: Vec<u64>, limit: Option<usize>) -> (Vec<u64>, usize) {
+    let limit = match limit {
+        Some(limit) if job_ids.len() > limit => limit,
+        _ => return (job_ids, 0),
+    };
+    let left_out = job_ids.len() - limit;
+    if limit == 0 {
+        return (Vec::new(), left_out);
+    }
+    let mut newest = job_ids.clone();
+    newest.sort_unstable_by(|a, b| b.cmp(a));
+    let cutoff = newest[limit - 1];
+    let kept = job_ids.into_iter().filter(|&job_id| job_id >= cutoff).collect();
+    (kept, left_out)
+}
+
+/// Get the status of each starting job, warning about IDs SLURM doesn't know.
+///
+/// Unknown IDs (usually typos) are dropped unless `keep_unknown` is set. IDs
+/// are kept when SLURM can't be queried, since nothing is known about them.
+fn check_initial_jobs(
+    job_manager: &JobManager,
+    job_ids: Vec<u64>,
+    keep_unknown: bool,
+) -> Vec<(u64, JobStatus)> {
+    let mut jobs = Vec::new();
+    for job_id in job_ids {
+        let status = job_manager.get_job_status(job_id);
+        if status == JobStatus::Unknown && job_manager.job_exists(job_id) == Some(false) {
+            if keep_unknown {
+                eprintln!("Warning: job {} was not found in SLURM", job_id);
+            } else {
+                eprintln!(
+                    "Warning: job {} was not found in SLURM; skipping it (use --keep-unknown to \
+                     monitor it anyway)",
+                    job_id
+                );
+                continue;
+            }
+        }
+        jobs.push((job_id, status));
+    }
+    jobs
+}
+
+/// Show a finished job, with its full logs if `read_logs` is set, without
+/// monitoring it further.
+fn load_replay(
+    app: &mut App,
+    job_id: u64,
+    status: JobStatus,
+    info: JobInfo,
+    summary: Option<JobSummary>,
+    read_logs: bool,
+) {
+    let stdout_path = info.stdout_path.clone();
+    let stderr_path = info.stderr_path.clone();
+    app.update_job_status(job_id, status, info);
+
+    for (log_type, path) in [("stdout", stdout_path), ("stderr", stderr_path)] {
+        if !read_logs || path.as_os_str().is_empty() {
+            continue;
+        }
+        match std::fs::read(&path) {
+            Ok(content) => app.update_log(job_id, log_type, &String::from_utf8_lossy(&content)),
+            Err(e) => diagnostics::warn(format!(
+                "Failed to read {} of finished job {} ({}): {}",
+                log_type,
+                job_id,
+                path.display(),
+                e
+            )),
+        }
+    }
+
+    app.set_replay(job_id, summary);
+}
+
+/// Print the job IDs found during initial discovery.
+fn print_found_jobs(job_ids: &[u64]) {
+    println!(
+        "Found {} job(s): {}",
+        job_ids.len(),
+        job_ids
+            .iter()
+            .map(|id| id.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+}
+
+/// Handle the tail command.
+pub fn handle_tail(paths: Vec<PathBuf>, config: Config) -> Result<()> {
+    run_monitor(
+        JobManager::new(),
+        Vec::new(),
+        MonitorOptions {
+            tail_files: paths,
+            config,
+            ..Default::default()
+        },
+    )
+}
+
+/// Create a job manager running SLURM commands through `runner` on
+/// `cluster`, checking that the cluster answers. Its copies for the UI
+/// aren't held to the runner's throttle.
+pub fn cluster_job_manager(runner: SystemRunner, cluster: Option<String>) -> Result<JobManager> {
+    let ui_runner = runner.unthrottled();
+    let mut job_manager = JobManager::with_runner(Arc::new(runner));
+    job_manager.set_ui_runner(Arc::new(ui_runner));
+    job_manager.set_cluster(cluster);
+    job_manager.check_cluster()?;
+    Ok(job_manager)
+}
+
+/// Handle the list command.
+pub fn handle_list(
+    filter: JobFilter,
+    format: Option<String>,
+    json: bool,
+    runner: SystemRunner,
+) -> Result<()> {
+    let template = format.as_deref().map(ListTemplate::parse).transpose()?;
+    let job_manager = cluster_job_manager(runner, filter.cluster.clone())?;
+    let all_jobs = job_manager
+        .discover_job_ids(&filter)
+        .map_err(|e| anyhow::anyhow!("sacct failed: {:#}", e))?;
+
+    if let Some(template) = template {
+        for job_id in all_jobs {
+            let status = job_manager.get_job_status(job_id);
+            let info = job_manager.get_job_info(job_id);
+            print!("{}", template.render(&info, status));
+        }
+        return Ok(());
+    }
+
+    if json {
+        let jobs: Vec<serde_json::Value> = all_jobs
+            .into_iter()
+            .map(|job_id| {
+                let status = job_manager.get_job_status(job_id);
+                let info = job_manager.get_job_info(job_id);
+                serde_json::json!({
+                    "job_id": job_id,
+                    "name": info.job_name,
+                    "status": status.to_string(),
+                    "state": info.state,
+                    "elapsed": info.elapsed,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&jobs)?);
+        return Ok(());
+    }
+
+    if all_jobs.is_empty() {
+        println!("No jobs found");
+        return Ok(());
+    }
+
+    println!("Tracked jobs:");
+    for job_id in all_jobs {
+        let status = job_manager.get_job_status(job_id);
+        let info = job_manager.get_job_info(job_id);
+        let job_name = if info.job_name.is_empty() {
+            "N/A".to_string()
+        } else {
+            info.job_name
+        };
+        println!("  {}: {} - {}", job_id, status, job_name);
+    }
+
+    Ok(())
+}
+
+/// A `list --format` template, split into literal text and placeholders.
+#[derive(Debug, PartialEq)]
+struct ListTemplate {
+    pieces: Vec<TemplatePiece>,
+}
+
+#[derive(Debug, PartialEq)]
+enum TemplatePiece {
+    Text(String),
+    Field(ListField),
+}
+
+/// Job fields available as `list --format` placeholders.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum ListField {
+    JobId,
+    Status,
+    Name,
+    State,
+    Elapsed,
+    Partition,
+    Nodes,
+    Start,
+    End,
+}
+
+impl ListField {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "job_id" => Some(ListField::JobId),
+            "status" => Some(ListField::Status),
+            "name" => Some(ListField::Name),
+            "state" => Some(ListField::State),
+            "elapsed" => Some(ListField::Elapsed),
+            "partition" => Some(ListField::Partition),
+            "nodes" => Some(ListField::Nodes),
+            "start" => Some(ListField::Start),
+            "end" => Some(ListField::End),
+            _ => None,
+        }
+    }
+}
+
+impl ListTemplate {
+    /// Parse a template. `\t`, `\n` and `\\` are escapes, `{field}` is a
+    /// placeholder and `{{`/`}}` are literal braces. A newline is added
+    /// after each job unless the template ends with one.
+    fn parse(template: &str) -> Result<Self> {
+        let mut pieces = Vec::new();
+        let mut text = String::new();
+        let mut chars = template.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => match chars.next() {
+                    Some('t') => text.push('\t'),
+                    Some('n') => text.push('\n'),
+                    Some('\\') => text.push('\\'),
+                    Some(other) => anyhow::bail!("Unknown escape '\\{}' in --format", other),
+                    None => anyhow::bail!("--format ends with a lone '\\'"),
+                },
+                '{' if chars.as_str().starts_with('{') => {
+                    chars.next();
+                    text.push('{');
+                }
+                '}' if chars.as_str().starts_with('}') => {
+                    chars.next();
+                    text.push('}');
+                }
+                '{' => {
+                    let rest = chars.as_str();
+                    let end = rest
+                        .find('}')
+                        .with_context(|| format!("Unclosed '{{' in --format '{}'", template))?;
+                    let name = &rest[..end];
+                    let field = ListField::from_name(name).with_context(|| {
+                        format!(
+                            "Unknown placeholder '{{{}}}' in --format (expected job_id, status, \
+                             name, state, elapsed, partition, nodes, start or end)",
+                            name
+                        )
+                    })?;
+                    if !text.is_empty() {
+                        pieces.push(TemplatePiece::Text(std::mem::take(&mut text)));
+                    }
+                    pieces.push(TemplatePiece::Field(field));
+                    chars = rest[end + 1..].chars();
+                }
+                '}' => anyhow::bail!("Unmatched '}}' in --format (use '}}}}' for a brace)"),
+                c => text.push(c),
+            }
+        }
+        let is_empty = text.is_empty() && pieces.is_empty();
+        if !is_empty && !text.ends_with('\n') {
+            text.push('\n');
+        }
+        if !text.is_empty() {
+            pieces.push(TemplatePiece::Text(text));
+        }
+        Ok(Self { pieces })
+    }
+
+    /// Expand the template for one job.
+    fn render(&self, info: &JobInfo, status: JobStatus) -> String {
+        self.pieces
+            .iter()
+            .map(|piece| match piece {
+                TemplatePiece::Text(text) => text.clone(),
+                TemplatePiece::Field(field) => match field {
+                    ListField::JobId => info.job_id.to_string(),
+                    ListField::Status => status.to_string(),
+                    ListField::Name => info.job_name.clone(),
+                    ListField::State => info.state.clone(),
+                    ListField::Elapsed => info.elapsed.clone(),
+                    ListField::Partition => info.partition.clone(),
+                    ListField::Nodes => info.node_list.clone(),
+                    ListField::Start => info.start_time.clone(),
+                    ListField::End => info.end_time.clone(),
+                },
+            })
+            .collect()
+    }
+}
+
+/// Handle the stop command.
+pub fn handle_stop(job_id: u64) -> Result<()> {
+    println!("Stopped tracking job {}", job_id);
+    println!("Note: This command is informational only in the Rust version.");
+    println!("The job continues running on SLURM.");
+    Ok(())
+}
+
+/// Handle the doctor command, failing if any check failed.
+pub fn handle_doctor(runner: SystemRunner) -> Result<()> {
+    let checks = doctor::run_checks(&runner);
+    for check in &checks {
+        println!("{}", check);
+    }
+    let failed = checks.iter().filter(|check| check.outcome == Outcome::Failed).count();
+    if failed > 0 {
+        anyhow::bail!("{} of {} checks failed", failed, checks.len());
+    }
+    println!("All SLURM checks passed");
+    Ok(())
+}
+
+/// Options for a monitor session.
+#[derive(Debug, Clone, Default)]
+struct MonitorOptions {
+    /// Automatically add newly discovered jobs
+    auto_discover: bool,
+    /// Criteria used when discovering jobs
+    filter: JobFilter,
+    /// Plain files to tail instead of SLURM jobs
+    tail_files: Vec<PathBuf>,
+    /// Monitor job IDs even if SLURM doesn't know them
+    keep_unknown: bool,
+    /// Show only the job table, without tailing logs
+    dashboard: Option<Dashboard>,
+    /// Run without a terminal, reporting status changes only
+    daemon: Option<Daemon>,
+    /// Print plain text instead of running the interactive UI
+    plain: bool,
+    /// Settings from the config file, with command-line overrides applied
+    config: Config,
+}
+
+/// How the dashboard's job table starts out sorted.
+#[derive(Debug, Clone, Copy, Default)]
+struct Dashboard {
+    sort: SortMode,
+    reverse: bool,
+}
+
+/// Where the daemon reports status changes.
+#[derive(Debug, Clone, Default)]
+struct Daemon {
+    /// File appended to; stdout (e.g. the service's journal) if unset
+    log_file: Option<PathBuf>,
+}
+
+/// Percent of the log area the stdout/stderr divider moves per key press.
+const SPLIT_STEP: i16 = 10;
+
+/// How long `--plain` keeps reading logs after every job has finished, so
+/// their last lines are printed.
+const PLAIN_FINAL_LOG_WAIT: Duration = Duration::from_secs(2);
+
+/// Longest the monitor waits on exit for queued webhook events and status
+/// records to go out.
+const FLUSH_WAIT: Duration = Duration::from_secs(10);
+
+/// Longest the event loop spends applying log updates before redrawing.
+const LOG_UPDATE_BUDGET: Duration = Duration::from_millis(30);
+
+/// Background workers feeding the event loop, and the channels they report on.
+struct Backend {
+    job_manager: Arc<Mutex<JobManager>>,
+    status_monitor: StatusMonitor,
+    log_tailer: LogTailer,
+    gpu_monitor: GpuMonitor,
+    status_rx: Receiver<StatusUpdate>,
+    log_rx: Receiver<LogUpdate>,
+    log_status_rx: Receiver<LogStatus>,
+    gpu_rx: Receiver<GpuUpdate>,
+    shutdown: ShutdownSignals,
+}
+
+impl Backend {
+    /// Stop the workers, joining their threads. Whatever they sent before
+    /// stopping stays in the channels.
+    fn stop_workers(&mut self) {
+        self.stop_pollers();
+        self.log_tailer.stop_monitoring();
+    }
+
+    /// Stop the status and GPU monitors but leave the log tailer running,
+    /// e.g. to tail log paths their last updates brought in.
+    fn stop_pollers(&mut self) {
+        self.status_monitor.stop_monitoring();
+        self.gpu_monitor.stop_monitoring();
+    }
+}
+
+/// Signals that end the monitor like Ctrl+C does, e.g. from `kill`, a
+/// timeout wrapper or tmux closing the pane. In raw mode Ctrl+C itself
+/// arrives as a key, not as SIGINT.
+const SHUTDOWN_SIGNALS: [i32; 3] = [SIGTERM, SIGINT, SIGHUP];
+
+/// Records shutdown signals so that they end the monitor the way quitting
+/// does, instead of killing the process before the terminal is restored.
+///
+/// The handlers stay registered for the rest of the process, as signal-hook
+/// can't put the default action back. Once `finish` is called they run the
+/// default action instead, so signals arriving during cleanup or after the
+/// monitor has ended aren't swallowed.
+#[derive(Debug, Clone, Default)]
+struct ShutdownSignals {
+    /// Set when SIGTERM or SIGHUP arrives
+    terminated: Arc<AtomicBool>,
+    /// Set when SIGINT arrives, kept apart as it can be meant for a program
+    /// run in the foreground
+    interrupted: Arc<AtomicBool>,
+    /// Set once the monitor stops handling signals
+    finished: Arc<AtomicBool>,
+}
+
+impl ShutdownSignals {
+    fn register() -> Result<Self> {
+        let signals = Self::default();
+        for signal in SHUTDOWN_SIGNALS {
+            let flag = if signal == SIGINT { &signals.interrupted } else { &signals.terminated };
+            signal_hook::flag::register_conditional_default(signal, Arc::clone(&signals.finished))
+                .and_then(|_| signal_hook::flag::register(signal, Arc::clone(flag)))
+                .with_context(|| format!("Failed to handle signal {}", signal))?;
+        }
+        Ok(signals)
+    }
+
+    /// Whether a shutdown signal has arrived.
+    fn received(&self) -> bool {
+        self.terminated.load(Ordering::Relaxed) || self.interrupted.load(Ordering::Relaxed)
+    }
+
+    /// Forget a SIGINT, after Ctrl+C reached the monitor while it was
+    /// meant for a program in the foreground. Other signals still count.
+    fn ignore_interrupt(&self) {
+        self.interrupted.store(false, Ordering::Relaxed);
+    }
+
+    /// Give the signals their default action back.
+    fn finish(&self) {
+        self.finished.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Run the monitor UI.
+///
+/// Takes ownership of `job_manager` so metadata recorded at submission (such
+/// as the script path) stays available while monitoring.
+fn run_monitor(
+    job_manager: JobManager,
+    initial_job_ids: Vec<u64>,
+    options: MonitorOptions,
+) -> Result<()> {
+    let (initial_job_ids, over_limit) = apply_job_limit(initial_job_ids, options.config.max_jobs);
+    if over_limit > 0 {
+        eprintln!(
+            "Warning: job limit of {} reached, {} jobs not shown",
+            initial_job_ids.len(),
+            over_limit
+        );
+    }
+
+    // Look up starting states before taking over the terminal, so warnings
+    // about unknown job IDs stay visible
+    let requested = initial_job_ids.len();
+    let initial_jobs = check_initial_jobs(&job_manager, initial_job_ids, options.keep_unknown);
+    if requested > 0 && initial_jobs.is_empty() && !options.auto_discover {
+        anyhow::bail!("None of the requested jobs exist in SLURM");
+    }
+    let initial_job_ids: Vec<u64> = initial_jobs.iter().map(|&(job_id, _)| job_id).collect();
+
+    let config = options.config;
+    // Likewise for errors opening the record file and the daemon's log
+    let recorder = config.record.as_deref().map(StatusRecorder::create).transpose()?;
+    let daemon_log = options
+        .daemon
+        .as_ref()
+        .and_then(|daemon| daemon.log_file.as_deref())
+        .map(|path| {
+            std::fs::OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .with_context(|| format!("Cannot open log file {}", path.display()))
+        })
+        .transpose()?;
+    // Only the UI and --plain show logs; the dashboard and daemon don't
+    // read them
+    let shows_logs = options.dashboard.is_none() && options.daemon.is_none();
+
+    // Create app state
+    let mut app = App::new();
+    app.auto_discover = options.auto_discover;
+    app.discovery_filter = options.filter;
+    app.add_tail_files(options.tail_files);
+    app.gpu_enabled = config.gpu;
+    app.follow_new = config.follow_new;
+    if let Some(dashboard) = options.dashboard {
+        app.layout_mode = LayoutMode::Dashboard;
+        app.sort_mode = dashboard.sort;
+        app.sort_reverse = dashboard.reverse;
+        app.columns = DASHBOARD_COLUMNS.to_vec();
+    }
+    if !config.columns.is_empty() {
+        app.columns = config.columns;
+    }
+    app.discovery_interval = Duration::from_secs_f64(config.discovery_interval);
+    app.theme = Theme::named(config.theme).with_status_colors(&config.colors);
+    app.keys = config.keys;
+    app.max_jobs = config.max_jobs;
+    app.raw_progress = config.raw_progress;
+    app.keep_truncated_logs = config.keep_truncated_logs;
+    app.bell = config.bell && !options.plain && options.daemon.is_none();
+    // Without logs there is no telling a stalled job
+    if shows_logs && config.stall_after > 0.0 {
+        app.stall_after = Some(Duration::from_secs_f64(config.stall_after));
+    }
+    app.stdout_share = config.stdout_share;
+    // Moves the focus off a panel the config hides
+    app.adjust_stdout_share(0);
+    app.jobs_over_limit = over_limit;
+
+    // Initialize jobs
+    for &job_id in &initial_job_ids {
+        app.add_job(job_id);
+    }
+
+    // Create channels for updates
+    let (status_tx, status_rx) = mpsc::channel();
+    let (log_tx, log_rx) = mpsc::channel();
+    let (log_status_tx, log_status_rx) = mpsc::channel();
+    let (gpu_tx, gpu_rx) = mpsc::channel();
+
+    // Create job manager
+    let job_manager = Arc::new(Mutex::new(job_manager));
+    for &job_id in &initial_job_ids {
+        job_manager.lock().unwrap().add_tracked_job(job_id);
+    }
+
+    // Jobs that have already finished won't change: load their logs once and
+    // leave them out of polling and tailing
+    let mut live_job_ids = Vec::new();
+    for (job_id, status) in initial_jobs {
+        if !status.is_terminal() {
+            live_job_ids.push(job_id);
+            continue;
+        }
+
+        let manager = job_manager.lock().unwrap();
+        let mut info = manager.get_job_info(job_id);
+        if config.steps {
+            info.steps = manager.get_job_steps(job_id);
+        }
+        if config.array_tasks {
+            info.array_tasks = manager.get_array_tasks(job_id);
+        }
+        let summary = manager.get_job_summary(job_id);
+        drop(manager);
+        load_replay(&mut app, job_id, status, info, summary, shows_logs);
+    }
+
+    // Start status monitor (tailed files have no SLURM status to poll)
+    let mut status_monitor = StatusMonitor::new(Arc::clone(&job_manager), config.status_interval);
+    status_monitor.set_steps(config.steps);
+    status_monitor.set_array_tasks(config.array_tasks);
+    status_monitor.set_live_memory(config.live_memory);
+    let notifier = config.webhook.map(WebhookNotifier::new);
+    if let Some(ref notifier) = notifier {
+        status_monitor.set_event_sender(notifier.sender());
+    }
+    if let Some(ref recorder) = recorder {
+        status_monitor.set_record_sender(recorder.sender());
+    }
+    if !app.is_tail_mode() {
+        status_monitor.start_monitoring(live_job_ids.clone(), status_tx);
+    }
+
+    // Start GPU monitor if requested
+    let mut gpu_monitor = GpuMonitor::new(Arc::clone(&job_manager), config.gpu_interval);
+    if config.gpu && !app.is_tail_mode() && options.daemon.is_none() {
+        gpu_monitor.start_monitoring(live_job_ids.clone(), gpu_tx);
+    }
+
+    // Start log tailer, if logs are shown; files added to a tailer that isn't
+    // running are ignored
+    let mut log_tailer = LogTailer::new(config.log_interval);
+    log_tailer.set_status_sender(log_status_tx);
+    if shows_logs {
+        log_tailer.start_monitoring(log_tx.clone());
+    }
+
+    for (index, path) in app.tail_files.iter().enumerate() {
+        log_tailer.add_file(&format!("file_{}", index + 1), path);
+    }
+
+    // Add initial log files to monitor
+    for &job_id in &live_job_ids {
+        let info = job_manager.lock().unwrap().get_job_info(job_id);
+        tail_job_logs(&log_tailer, job_id, &info);
+    }
+
+    // Run event loop; signals end it the same way as quitting, so the
+    // cleanup below still runs
+    let shutdown = ShutdownSignals::register()?;
+    let mut backend = Backend {
+        job_manager,
+        status_monitor,
+        log_tailer,
+        gpu_monitor,
+        status_rx,
+        log_rx,
+        log_status_rx,
+        gpu_rx,
+        shutdown,
+    };
+    let result = if let Some(file) = daemon_log {
+        run_plain_loop(&mut app, &mut backend, &mut PlainOutput::new(file))
+    } else if options.plain || options.daemon.is_some() {
+        run_plain_loop(&mut app, &mut backend, &mut PlainOutput::new(stdout()))
+    } else {
+        run_tui(&mut app, &backend, config.mouse)
+    };
+    backend.shutdown.finish();
+
+    // Cleanup; the workers hold senders to the notifier and recorder, which
+    // finish once those are gone
+    backend.stop_workers();
+    drop(backend);
+    if notifier.is_some_and(|notifier| !notifier.finish(FLUSH_WAIT)) {
+        eprintln!("Warning: gave up waiting for webhook notifications to be sent");
+    }
+    if recorder.is_some_and(|recorder| !recorder.finish(FLUSH_WAIT)) {
+        eprintln!("Warning: gave up waiting for status records to be written");
+    }
+
+    result
+}
+
+/// Note whether the file behind a tailer label can be read.
+fn apply_log_status(app: &mut App, status: LogStatus) {
+    if let Some((job_id, log_type)) = parse_log_label(&status.label) {
+        app.set_log_problem(job_id, log_type, status.problem);
+    }
+}
+
+/// Apply a log update to the job (or tailed file) its label routes to.
+fn apply_log_update(app: &mut App, update: &LogUpdate) {
+    if let Some((job_id, log_type)) = parse_log_label(&update.label) {
+        if let Some(modified) = update.modified.filter(|_| !update.content.is_empty()) {
+            app.record_output(job_id, modified);
+        }
+        if update.reset {
+            app.reset_log(job_id, log_type, &update.content, update.reloaded);
+        } else {
+            app.update_log(job_id, log_type, &update.content);
+        }
+    }
+}
+
+/// Take over the terminal and run the interactive UI until it quits.
+fn run_tui(app: &mut App, backend: &Backend, mouse: bool) -> Result<()> {
+    // The guard restores the terminal however this function is left, and the
+    // hook does so before a panic message is printed, so the message lands on
+    // the normal screen instead of the discarded alternate one. The hook is
+    // process-wide; a worker thread panicking leaves the UI running, so only
+    // panics on this thread restore the terminal.
+    let _terminal_guard = TerminalGuard::enter(stdout(), mouse)?;
+    let previous_hook = set_restore_hook(stdout(), mouse);
+    let mut terminal = Terminal::new(CrosstermBackend::new(stdout()))?;
+
+    let result = run_event_loop(&mut terminal, app, backend, mouse);
+    unset_restore_hook(previous_hook);
+    result
+}
+
+/// A panic hook, as `panic::take_hook` returns it.
+type PanicHook = Box<dyn Fn(&panic::PanicHookInfo<'_>) + Send + Sync + 'static>;
+
+/// Install a panic hook that restores the terminal through `out`, then hands
+/// the panic to the hook it replaced. Panics on other threads than the
+/// calling one go straight to the replaced hook. Returns that hook for
+/// `unset_restore_hook`.
+fn set_restore_hook<W: Write + Send + 'static>(out: W, mouse: bool) -> Arc<PanicHook> {
+    let previous_hook = Arc::new(panic::take_hook());
+    let chained_hook = Arc::clone(&previous_hook);
+    let out = Mutex::new(out);
+    let ui_thread = thread::current().id();
+    panic::set_hook(Box::new(move |info| {
+        if thread::current().id() == ui_thread {
+            let mut out = out.lock().unwrap_or_else(PoisonError::into_inner);
+            restore_terminal(&mut *out, mouse);
+        }
+        chained_hook(info);
+    }));
+    previous_hook
+}
+
+/// Put back the hook `set_restore_hook` replaced.
+fn unset_restore_hook(previous_hook: Arc<PanicHook>) {
+    drop(panic::take_hook());
+    match Arc::try_unwrap(previous_hook) {
+        Ok(hook) => panic::set_hook(hook),
+        // Another thread is still running the hook
+        Err(hook) => panic::set_hook(Box::new(move |info| hook(info))),
+    }
+}
+
+/// Non-interactive counterpart of `run_event_loop` for `--plain` and the
+/// daemon.
+///
+/// Prints the jobs that had already finished at startup, then status changes
+/// and log lines (unless they aren't being tailed) as they come in. Returns
+/// on a shutdown signal, or once every job has finished and their last log
+/// lines have been read, unless auto-discovery may still bring in new jobs.
+fn run_plain_loop<W: Write>(
+    app: &mut App,
+    backend: &mut Backend,
+    output: &mut PlainOutput<W>,
+) -> Result<()> {
+    for job_id in app.get_sorted_job_ids().into_iter().rev() {
+        let job = &app.jobs[&job_id];
+        if job.replay {
+            output.status(job_id, job.status, &job.info)?;
+            output.log_lines(job_id, "stdout", &job.stdout_lines)?;
+            output.log_lines(job_id, "stderr", &job.stderr_lines)?;
+        }
+    }
+
+    let tick_rate = Duration::from_millis(100);
+    let mut last_discovery = Instant::now();
+    let mut all_finished_at: Option<Instant> = None;
+
+    while !backend.shutdown.received() {
+        print_updates(app, backend, output)?;
+
+        if app.auto_discover && last_discovery.elapsed() >= app.discovery_interval {
+            last_discovery = Instant::now();
+            for job_id in discover_new_jobs(app, backend) {
+                let job = &app.jobs[&job_id];
+                output.status(job_id, job.status, &job.info)?;
+            }
+        }
+
+        let all_finished = !app.auto_discover && app.jobs.values().all(|job| job.status.is_terminal());
+        match all_finished_at {
+            _ if !all_finished => all_finished_at = None,
+            None => all_finished_at = Some(Instant::now()),
+            Some(at) if at.elapsed() >= PLAIN_FINAL_LOG_WAIT => break,
+            Some(_) => {}
+        }
+
+        thread::sleep(tick_rate);
+    }
+
+    // Stopping joins the workers, so the last status changes and log lines
+    // they sent are printed too. The tailer is stopped last, once it has
+    // read any log files the last status changes named
+    backend.stop_pollers();
+    print_updates(app, backend, output)?;
+    backend.log_tailer.stop_monitoring();
+    print_updates(app, backend, output)?;
+    output.finish()?;
+    Ok(())
+}
+
+/// Print the status changes, log lines and log problems queued by the
+/// workers.
+fn print_updates<W: Write>(
+    app: &mut App,
+    backend: &Backend,
+    output: &mut PlainOutput<W>,
+) -> Result<()> {
+    while let Ok(update) = backend.status_rx.try_recv() {
+        output.status(update.job_id, update.status, &update.info)?;
+        tail_job_logs(&backend.log_tailer, update.job_id, &update.info);
+        app.update_job_status(update.job_id, update.status, update.info);
+    }
+
+    // GPU utilization has no plain-text form
+    while backend.gpu_rx.try_recv().is_ok() {}
+
+    while let Ok(update) = backend.log_rx.try_recv() {
+        if let Some((job_id, log_type)) = parse_log_label(&update.label) {
+            if update.reset {
+                output.reset(job_id, log_type)?;
+            }
+            output.log(job_id, log_type, &update.content)?;
+        }
+    }
+    // Without a panel to show it in, a missing file is just no output yet
+    while let Ok(status) = backend.log_status_rx.try_recv() {
+        match (parse_log_label(&status.label), status.problem) {
+            (_, None) | (_, Some(LogProblem::Missing(_))) | (None, _) => {}
+            (Some((job_id, log_type)), Some(problem)) => {
+                output.problem(job_id, log_type, &problem)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Start tailing a job's log files, for the paths that are known. A path
+/// that changed since the last update replaces the old file.
+fn tail_job_logs(log_tailer: &LogTailer, job_id: u64, info: &JobInfo) {
+    if !info.stdout_path.as_os_str().is_empty() {
+        log_tailer.set_file(&format!("stdout_{}", job_id), &info.stdout_path);
+    }
+    if !info.stderr_path.as_os_str().is_empty() {
+        log_tailer.set_file(&format!("stderr_{}", job_id), &info.stderr_path);
+    }
+}
+
+/// Show the logs of the next (or previous) started task of the current array
+/// job in its panels.
+fn switch_array_task(
+    app: &mut App,
+    job_manager: &Mutex<JobManager>,
+    log_tailer: &LogTailer,
+    forward: bool,
+) {
+    let (job_id, task_id, raw_job_id) = match app.next_array_task(forward) {
+        Some(task) => task,
+        None => return,
+    };
+    let manager = job_manager.lock().unwrap().for_ui();
+    let task = manager.get_job_info(raw_job_id);
+    app.show_array_task(job_id, &task_id, task.stdout_path, task.stderr_path);
+
+    let info = &app.jobs[&job_id].info;
+    for (log_type, path) in [("stdout", &info.stdout_path), ("stderr", &info.stderr_path)] {
+        // A task without one of the files mustn't keep showing the last one
+        if path.as_os_str().is_empty() {
+            log_tailer.remove_file(&format!("{}_{}", log_type, job_id));
+        }
+    }
+    tail_job_logs(log_tailer, job_id, info);
 }
 
-/// Handle the list command.
-pub fn handle_list() -> Result<()> {
-    let job_manager = JobManager::new();
-    let all_jobs = get_all_job_ids_from_sacct();
+/// Add jobs that discovery finds and aren't tracked or deleted yet, up to the
+/// job limit. Returns the IDs of the jobs added.
+fn discover_new_jobs(app: &mut App, backend: &Backend) -> Vec<u64> {
+    // Query a snapshot so the status monitor isn't blocked meanwhile, without
+    // waiting behind its throttled polls
+    let manager = backend.job_manager.lock().unwrap().for_ui();
+    // Skip jobs that are already tracked or were explicitly deleted by user
+    let found: Vec<u64> = manager
+        .discover_job_ids(&app.discovery_filter)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|job_id| !app.jobs.contains_key(job_id) && !app.deleted_jobs.contains(job_id))
+        .collect();
+    let room = app.max_jobs.map(|max| max.saturating_sub(app.jobs.len()));
+    let (new_jobs, over_limit) = apply_job_limit(found, room);
+    app.jobs_over_limit = over_limit;
 
-    if all_jobs.is_empty() {
-        println!("No tracked jobs");
-        return Ok(());
-    }
+    for &job_id in &new_jobs {
+        // Fetch status and info immediately instead of waiting for poll cycle
+        let status = manager.get_job_status(job_id);
+        let info = manager.get_job_info(job_id);
+        tail_job_logs(&backend.log_tailer, job_id, &info);
+        app.update_job_status(job_id, status, info);
 
-    println!("Tracked jobs:");
-    for job_id in all_jobs {
-        let status = job_manager.get_job_status(job_id);
-        let info = job_manager.get_job_info(job_id);
-        let job_name = if info.job_name.is_empty() {
-            "N/A".to_string()
-        } else {
-            info.job_name
-        };
-        println!("  {}: {} - {}", job_id, status, job_name);
+        backend.job_manager.lock().unwrap().add_tracked_job(job_id);
+        backend.status_monitor.add_job_to_monitor(job_id);
+        backend.gpu_monitor.add_job_to_monitor(job_id);
     }
+    new_jobs
+}
 
-    Ok(())
+/// Puts the terminal in raw mode on the alternate screen, and restores it
+/// when dropped, including while unwinding from a panic.
+struct TerminalGuard<W: Write> {
+    out: W,
+    mouse: bool,
 }
 
-/// Handle the stop command.
-pub fn handle_stop(job_id: u64) -> Result<()> {
-    println!("Stopped tracking job {}", job_id);
-    println!("Note: This command is informational only in the Rust version.");
-    println!("The job continues running on SLURM.");
-    Ok(())
+impl<W: Write> TerminalGuard<W> {
+    fn enter(out: W, mouse: bool) -> Result<Self> {
+        // A failure part way drops the guard and restores the terminal
+        let mut guard = TerminalGuard { out, mouse };
+        setup_terminal(&mut guard.out, mouse)?;
+        Ok(guard)
+    }
 }
 
-/// Run the monitor UI.
-fn run_monitor(initial_job_ids: Vec<u64>, auto_discover: bool) -> Result<()> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = stdout();
-    execute!(stdout, EnterAlternateScreen, EnableMouseCapture, Hide)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        restore_terminal(&mut self.out, self.mouse);
+    }
+}
 
-    // Create app state
-    let mut app = App::new();
-    app.auto_discover = auto_discover;
+/// Enter raw mode and the alternate screen, hiding the cursor.
+fn setup_terminal(out: &mut impl Write, mouse: bool) -> io::Result<()> {
+    enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen, Hide)?;
+    if mouse {
+        execute!(out, EnableMouseCapture)?;
+    }
+    Ok(())
+}
 
-    // Initialize jobs
-    for &job_id in &initial_job_ids {
-        app.add_job(job_id);
+/// Leave raw mode and the alternate screen and show the cursor again.
+///
+/// Errors are ignored: this runs on the way out, often while panicking.
+fn restore_terminal(out: &mut impl Write, mouse: bool) {
+    let _ = disable_raw_mode();
+    if mouse {
+        let _ = execute!(out, DisableMouseCapture);
     }
+    let _ = execute!(out, LeaveAlternateScreen, Show);
+}
 
-    // Create channels for updates
-    let (status_tx, status_rx) = mpsc::channel();
-    let (log_tx, log_rx) = mpsc::channel();
+/// Put `text` on the clipboard with the OSC 52 escape sequence, which the
+/// terminal handles itself, so it works over SSH from a login node too.
+fn copy_to_clipboard(out: &mut impl Write, text: &str) -> io::Result<()> {
+    write!(out, "\x1b]52;c;{}\x07", base64_encode(text.as_bytes()))?;
+    out.flush()
+}
 
-    // Create job manager
-    let job_manager = Arc::new(Mutex::new(JobManager::new()));
-    for &job_id in &initial_job_ids {
-        job_manager.lock().unwrap().add_tracked_job(job_id);
+/// Standard base64 with padding.
+fn base64_encode(bytes: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] =
+        b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+    let mut encoded = String::new();
+    for chunk in bytes.chunks(3) {
+        let n = chunk.iter().enumerate().fold(0u32, |n, (i, &b)| n | (b as u32) << (16 - 8 * i));
+        for i in 0..4 {
+            if i <= chunk.len() {
+                encoded.push(ALPHABET[(n >> (18 - 6 * i) & 63) as usize] as char);
+            } else {
+                encoded.push('=');
+            }
+        }
     }
+    encoded
+}
 
-    // Start status monitor
-    let mut status_monitor = StatusMonitor::new(Arc::clone(&job_manager), 3.0);
-    status_monitor.start_monitoring(initial_job_ids.clone(), status_tx);
+/// Suspend the UI and show `path` in `$PAGER` (`less` if unset) until the
+/// pager exits, then redraw the UI from scratch.
+fn open_in_pager(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    path: &Path,
+    mouse: bool,
+) -> Result<()> {
+    let pager = std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| "less".to_string());
+    // PAGER may include arguments, e.g. "less -R"
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or("less");
 
-    // Start log tailer
-    let mut log_tailer = LogTailer::new(1.0);
-    log_tailer.start_monitoring(log_tx.clone());
+    let mut command = Command::new(program);
+    command.args(words).arg(path);
+    let status = run_suspended(terminal, &mut command, mouse)
+        .with_context(|| format!("Failed to run {}", program))?;
+    if !status.success() {
+        anyhow::bail!("{} exited with {}", program, status);
+    }
+    Ok(())
+}
 
-    // Add initial log files to monitor
-    for &job_id in &initial_job_ids {
-        let info = job_manager.lock().unwrap().get_job_info(job_id);
-        if !info.stdout_path.as_os_str().is_empty() {
-            log_tailer.add_file(&format!("stdout_{}", job_id), &info.stdout_path);
-        }
-        if !info.stderr_path.as_os_str().is_empty() {
-            log_tailer.add_file(&format!("stderr_{}", job_id), &info.stderr_path);
+/// Suspend the UI and attach to a job step's I/O with `sattach` until it
+/// detaches or the step ends, then redraw the UI.
+fn attach_to_step(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    job_manager: &Mutex<JobManager>,
+    job_id: u64,
+    step: &str,
+    mouse: bool,
+) -> Result<()> {
+    let target = format!("{}{}", job_id, step_suffix(step));
+    let mut command = job_manager.lock().unwrap().interactive_command(&["sattach", &target])?;
+    match run_suspended(terminal, &mut command, mouse) {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => anyhow::bail!("sattach {} exited with {}", target, status),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            anyhow::bail!("sattach isn't available on this machine")
         }
+        Err(e) => Err(e).with_context(|| format!("Failed to run sattach {}", target)),
     }
+}
 
-    // Run event loop
-    let result = run_event_loop(
-        &mut terminal,
-        &mut app,
-        status_rx,
-        log_rx,
-        &job_manager,
-        &log_tailer,
-        &status_monitor,
-    );
-
-    // Cleanup
-    status_monitor.stop_monitoring();
-    log_tailer.stop_monitoring();
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture,
-        Show
-    )?;
-
-    result
+/// Run `command` in the terminal with the UI suspended, then set the UI up
+/// again and redraw it from scratch.
+fn run_suspended(
+    terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
+    command: &mut Command,
+    mouse: bool,
+) -> io::Result<std::process::ExitStatus> {
+    restore_terminal(&mut stdout(), mouse);
+    let status = command.status();
+    setup_terminal(&mut stdout(), mouse)?;
+    terminal.clear()?;
+    status
 }
 
 /// Main event loop.
+///
+/// `mouse` says whether mouse capture is on, to turn it back on after
+/// suspending the UI for a pager.
 fn run_event_loop(
     terminal: &mut Terminal<CrosstermBackend<io::Stdout>>,
     app: &mut App,
-    status_rx: Receiver<StatusUpdate>,
-    log_rx: Receiver<LogUpdate>,
-    job_manager: &Arc<Mutex<JobManager>>,
-    log_tailer: &LogTailer,
-    status_monitor: &StatusMonitor,
+    backend: &Backend,
+    mouse: bool,
 ) -> Result<()> {
+    let Backend {
+        job_manager,
+        status_monitor,
+        log_tailer,
+        gpu_monitor,
+        status_rx,
+        log_rx,
+        log_status_rx,
+        gpu_rx,
+        shutdown,
+    } = backend;
+
     let tick_rate = Duration::from_millis(100);
     let mut last_tick = Instant::now();
     let mut last_discovery = Instant::now();
-    let discovery_interval = Duration::from_secs(10);
+    // Set to run discovery on the next iteration instead of waiting
+    let mut discover_now = false;
 
     loop {
         // Update panel heights using actual terminal size and layout calculations
@@ -261,131 +1814,914 @@ fn run_event_loop(
 
         // Handle status updates (non-blocking)
         while let Ok(update) = status_rx.try_recv() {
-            app.update_job_status(update.job_id, update.status, update.info.clone());
-
-            // Add log files if we have paths now
-            if !update.info.stdout_path.as_os_str().is_empty() {
-                log_tailer.add_file(
-                    &format!("stdout_{}", update.job_id),
-                    &update.info.stdout_path,
-                );
+            // A poll already in flight when the job was deleted
+            if app.deleted_jobs.contains(&update.job_id) {
+                continue;
             }
-            if !update.info.stderr_path.as_os_str().is_empty() {
-                log_tailer.add_file(
-                    &format!("stderr_{}", update.job_id),
-                    &update.info.stderr_path,
-                );
+            // Add log files if we have paths now; the app keeps the paths of
+            // an array task it shows instead of the array job's
+            let job_id = update.job_id;
+            app.slurm_unreachable = update.unreachable;
+            app.update_job_status(job_id, update.status, update.info);
+            if let Some(job) = app.jobs.get(&job_id) {
+                tail_job_logs(log_tailer, job_id, &job.info);
             }
         }
+        if app.take_bell() {
+            let backend = terminal.backend_mut();
+            backend.write_all(b"\x07")?;
+            backend.flush()?;
+        }
+
+        // Handle GPU updates (non-blocking)
+        while let Ok(update) = gpu_rx.try_recv() {
+            app.update_gpu(update);
+        }
 
-        // Handle log updates (non-blocking)
+        // Handle log updates (non-blocking). Large logs arrive in many pieces;
+        // stop after a while so the UI redraws between them
+        let log_deadline = Instant::now() + LOG_UPDATE_BUDGET;
+        let mut log_backlog = false;
         while let Ok(update) = log_rx.try_recv() {
-            debug_log(&format!("cli: received LogUpdate label={} content_len={}", update.label, update.content.len()));
-            // Parse label to get job_id and log type
-            if let Some((log_type, job_id_str)) = update.label.split_once('_') {
-                if let Ok(job_id) = job_id_str.parse::<u64>() {
-                    debug_log(&format!("cli: updating log for job {} type {}", job_id, log_type));
-                    app.update_log(job_id, log_type, &update.content);
-                }
+            apply_log_update(app, &update);
+            if Instant::now() >= log_deadline {
+                log_backlog = true;
+                break;
             }
         }
+        while let Ok(status) = log_status_rx.try_recv() {
+            apply_log_status(app, status);
+        }
 
         // Auto-discover new jobs
-        if app.auto_discover && last_discovery.elapsed() >= discovery_interval {
+        if app.auto_discover && (discover_now || last_discovery.elapsed() >= app.discovery_interval) {
             last_discovery = Instant::now();
-            let current_jobs: Vec<u64> = app.jobs.keys().copied().collect();
-            let all_jobs = get_all_job_ids_from_sacct();
-
-            for job_id in all_jobs {
-                // Skip jobs that are already tracked or were explicitly deleted by user
-                if !current_jobs.contains(&job_id) && !app.deleted_jobs.contains(&job_id) {
-                    // Fetch status and info immediately instead of waiting for poll cycle
-                    let status = job_manager.lock().unwrap().get_job_status(job_id);
-                    let info = job_manager.lock().unwrap().get_job_info(job_id);
-                    app.update_job_status(job_id, status, info.clone());
-
-                    job_manager.lock().unwrap().add_tracked_job(job_id);
-                    status_monitor.add_job_to_monitor(job_id);
-
-                    // Add log files if paths are available
-                    if !info.stdout_path.as_os_str().is_empty() {
-                        log_tailer.add_file(&format!("stdout_{}", job_id), &info.stdout_path);
-                    }
-                    if !info.stderr_path.as_os_str().is_empty() {
-                        log_tailer.add_file(&format!("stderr_{}", job_id), &info.stderr_path);
-                    }
+            discover_now = false;
+            let new_jobs = discover_new_jobs(app, backend);
+
+            if app.follow_new_jobs(&new_jobs) {
+                if let Some(job_id) = app.current_job_id {
+                    app.set_message(format!("Following new job {}", job_id));
                 }
             }
         }
 
-        // Handle input events
-        let timeout = tick_rate
-            .checked_sub(last_tick.elapsed())
-            .unwrap_or_else(|| Duration::from_secs(0));
+        // Handle input events, without waiting if log updates are queued
+        let timeout = if log_backlog {
+            Duration::ZERO
+        } else {
+            tick_rate
+                .checked_sub(last_tick.elapsed())
+                .unwrap_or_else(|| Duration::from_secs(0))
+        };
 
         if crossterm::event::poll(timeout)? {
             if let Event::Key(key) = event::read()? {
-                if key.kind == KeyEventKind::Press {
-                    match key.code {
-                        KeyCode::Char('q') => {
+                if key.kind == KeyEventKind::Press && app.overlay.is_some() {
+                    handle_overlay_key(app, key);
+                } else if key.kind == KeyEventKind::Press && app.prompt.is_some() {
+                    handle_prompt_key(app, key);
+                } else if key.kind == KeyEventKind::Press {
+                    let action = if key.code == KeyCode::Char('c')
+                        && key.modifiers.contains(event::KeyModifiers::CONTROL)
+                    {
+                        app.should_quit = true;
+                        None
+                    } else {
+                        app.keys.action(key.code)
+                    };
+                    if action != Some(Action::Delete) {
+                        app.cancel_pending_delete();
+                    }
+                    match action {
+                        Some(Action::Quit) => {
                             if app.is_in_scroll_mode() {
                                 app.exit_scroll_mode();
                             } else {
                                 app.should_quit = true;
                             }
                         }
-                        KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
-                            app.should_quit = true;
-                        }
-                        KeyCode::Tab => {
+                        Some(Action::ToggleFocus) => {
                             app.switch_focus();
                         }
-                        KeyCode::Char('n') => {
+                        Some(Action::PrevJob) => {
                             app.prev_job();
                         }
-                        KeyCode::Char('p') => {
+                        Some(Action::NextJob) => {
                             app.next_job();
                         }
-                        KeyCode::Char('d') => {
-                            if let Some(job_id) = app.current_job_id {
+                        Some(Action::Delete) => {
+                            if let Some(job_id) = app.confirm_delete() {
                                 status_monitor.remove_job_from_monitor(job_id);
-                                log_tailer.remove_file(&format!("stdout_{}", job_id));
-                                log_tailer.remove_file(&format!("stderr_{}", job_id));
+                                gpu_monitor.remove_job_from_monitor(job_id);
+                                for label in log_labels(app, job_id) {
+                                    log_tailer.remove_file(&label);
+                                }
                                 app.remove_current_job();
+                                app.set_message(format!(
+                                    "Removed job {} ({} to undo)",
+                                    job_id, app.keys.undo
+                                ));
+                            }
+                        }
+                        Some(Action::Script) if !app.is_tail_mode() => {
+                            if let Some(job_id) = app.current_job_id {
+                                let manager = job_manager.lock().unwrap().for_ui();
+                                let script = manager.get_batch_script(job_id);
+                                match script {
+                                    Ok(content) => {
+                                        app.overlay = Some(TextOverlay::new(
+                                            format!("Batch script (Job {})", job_id),
+                                            &content,
+                                        ));
+                                    }
+                                    Err(e) => app.set_message(format!("{:#}", e)),
+                                }
+                            }
+                        }
+                        Some(Action::Sort) => {
+                            app.cycle_sort_mode();
+                        }
+                        Some(Action::SortReverse) => {
+                            app.toggle_sort_reverse();
+                        }
+                        Some(Action::Times) => {
+                            app.cycle_time_display_mode();
+                        }
+                        Some(Action::Filter) if !app.is_tail_mode() => {
+                            app.cycle_status_filter();
+                        }
+                        Some(Action::HideFinished) if !app.is_tail_mode() => {
+                            app.toggle_hide_finished();
+                        }
+                        Some(Action::Errors) if !app.is_tail_mode() => {
+                            app.toggle_errors_view();
+                        }
+                        Some(Action::Attach) if !app.is_tail_mode() => {
+                            if let Some(job_id) = app.current_job_id {
+                                if app.jobs[&job_id].status == JobStatus::Running {
+                                    let manager = job_manager.lock().unwrap().for_ui();
+                                    let steps = manager
+                                        .get_job_steps(job_id)
+                                        .into_iter()
+                                        .filter(JobStep::is_attachable)
+                                        .map(|step| step.step_id)
+                                        .collect();
+                                    app.start_attach(job_id, steps);
+                                } else {
+                                    app.set_message("Only running jobs can be attached to");
+                                }
+                            }
+                        }
+                        Some(Action::NextTask) if !app.is_tail_mode() => {
+                            switch_array_task(app, job_manager, log_tailer, true);
+                        }
+                        Some(Action::PrevTask) if !app.is_tail_mode() => {
+                            switch_array_task(app, job_manager, log_tailer, false);
+                        }
+                        Some(Action::FollowNew) if app.auto_discover => {
+                            app.toggle_follow_new();
+                            app.set_message(format!(
+                                "Follow new jobs {}",
+                                if app.follow_new { "on" } else { "off" }
+                            ));
+                        }
+                        Some(Action::AutoDiscover) if !app.is_tail_mode() => {
+                            app.toggle_auto_discover();
+                            discover_now = app.auto_discover;
+                            app.set_message(format!(
+                                "Auto-discover {}",
+                                if app.auto_discover { "on" } else { "off" }
+                            ));
+                        }
+                        Some(Action::Diagnostics) => {
+                            app.toggle_diagnostics();
+                        }
+                        Some(Action::Undo) => {
+                            if let Some(job_id) = app.undo_delete() {
+                                if let Some(path) = app.tail_file(job_id) {
+                                    log_tailer.add_file(&format!("file_{}", job_id), path);
+                                } else {
+                                    // Log files are re-registered when the next status update arrives
+                                    job_manager.lock().unwrap().add_tracked_job(job_id);
+                                    status_monitor.add_job_to_monitor(job_id);
+                                    gpu_monitor.add_job_to_monitor(job_id);
+                                }
+                                app.set_message(format!("Restored job {}", job_id));
                             }
                         }
-                        KeyCode::Up => {
+                        Some(Action::ColumnsLeft) => {
+                            app.scroll_columns_left();
+                        }
+                        Some(Action::ColumnsRight) => {
+                            app.scroll_columns_right();
+                        }
+                        Some(Action::ScrollUp) => {
                             app.scroll_up(1);
                         }
-                        KeyCode::Down => {
+                        Some(Action::ScrollDown) => {
                             app.scroll_down(1);
                         }
-                        KeyCode::PageUp => {
+                        Some(Action::PageUp) => {
                             app.scroll_up(10);
                         }
-                        KeyCode::PageDown => {
+                        Some(Action::PageDown) => {
                             app.scroll_down(10);
                         }
-                        KeyCode::Home => {
+                        Some(Action::ScrollTop) => {
                             app.scroll_to_top();
                         }
-                        KeyCode::End => {
+                        Some(Action::ScrollBottom) => {
                             app.scroll_to_bottom();
                         }
+                        Some(Action::Search) => {
+                            app.start_search();
+                        }
+                        Some(action @ (Action::NextMatch | Action::PrevMatch)) => {
+                            if app.search_query.is_none() {
+                                app.set_message(format!("Press {} to search", app.keys.search));
+                            } else if !app.next_match(action == Action::NextMatch) {
+                                app.set_message("No matches in this panel");
+                            }
+                        }
+                        Some(Action::Wrap) => {
+                            app.toggle_wrap();
+                        }
+                        Some(Action::Preview) => {
+                            app.toggle_preview();
+                        }
+                        Some(Action::Note) => {
+                            app.start_note();
+                        }
+                        Some(Action::Grep) => {
+                            app.start_filter();
+                        }
+                        Some(Action::Open) => match app.focused_log_path().map(Path::to_path_buf) {
+                            None => app.set_message("No log file for this panel yet"),
+                            Some(path) if !path.exists() => {
+                                app.set_message(format!("{} doesn't exist yet", path.display()));
+                            }
+                            Some(path) => {
+                                if let Err(e) = open_in_pager(terminal, &path, mouse) {
+                                    app.set_message(format!("{:#}", e));
+                                }
+                                // Ctrl+C in the pager reaches the monitor as
+                                // SIGINT too; it was meant for the pager
+                                shutdown.ignore_interrupt();
+                            }
+                        },
+                        Some(Action::CopyPath) => match app.focused_log_path() {
+                            None => app.set_message("No log file for this panel yet"),
+                            Some(path) => {
+                                let path = path.to_string_lossy().into_owned();
+                                match copy_to_clipboard(terminal.backend_mut(), &path) {
+                                    Ok(()) => app.set_message(format!("Copied {}", path)),
+                                    Err(e) => app.set_message(format!("Failed to copy: {}", e)),
+                                }
+                            }
+                        },
+                        Some(Action::Reload) => match app.clear_focused_log() {
+                            None => app.set_message("No log to reload"),
+                            Some(label) => {
+                                log_tailer.reload_file(&label);
+                                app.set_message("Reloading log from the start");
+                            }
+                        },
+                        Some(Action::Layout) => {
+                            app.toggle_layout();
+                        }
+                        Some(action @ (Action::GrowStdout | Action::ShrinkStdout))
+                            if !app.is_tail_mode() =>
+                        {
+                            let step = if action == Action::GrowStdout { SPLIT_STEP } else { -SPLIT_STEP };
+                            app.adjust_stdout_share(step);
+                            if let Err(e) = Config::save_stdout_share(app.stdout_share) {
+                                app.set_message(format!("Failed to save the split: {:#}", e));
+                            }
+                        }
                         _ => {}
                     }
                 }
             }
         }
 
+        if let Some((job_id, step)) = app.attach_request.take() {
+            if let Err(e) = attach_to_step(terminal, job_manager, job_id, &step, mouse) {
+                app.set_message(format!("{:#}", e));
+            }
+            // Ctrl+C was meant for sattach, as with the pager
+            shutdown.ignore_interrupt();
+        }
+
         if last_tick.elapsed() >= tick_rate {
             last_tick = Instant::now();
         }
 
-        if app.should_quit {
+        if app.should_quit || shutdown.received() {
             break;
         }
     }
 
     Ok(())
 }
+
+/// Parse a tailer label into the job ID and log type it routes to.
+///
+/// Job logs are labelled `<stdout|stderr>_<job_id>`. Files opened with the
+/// `tail` command are labelled `file_<n>` and shown in the stdout panel of
+/// pseudo-job `n`. The ID is taken from the last `_`-separated segment so
+/// log types may themselves contain underscores; unknown types are rejected.
+fn parse_log_label(label: &str) -> Option<(u64, &'static str)> {
+    let (log_type, id) = label.rsplit_once('_')?;
+    let id = id.parse::<u64>().ok()?;
+    match log_type {
+        "stdout" => Some((id, "stdout")),
+        "stderr" => Some((id, "stderr")),
+        "file" => Some((id, "stdout")),
+        _ => None,
+    }
+}
+
+/// Tailer labels registered for a job (or tailed file).
+fn log_labels(app: &App, job_id: u64) -> Vec<String> {
+    if app.is_tail_mode() {
+        vec![format!("file_{}", job_id)]
+    } else {
+        vec![format!("stdout_{}", job_id), format!("stderr_{}", job_id)]
+    }
+}
+
+/// Handle a key press while an overlay is open.
+fn handle_overlay_key(app: &mut App, key: event::KeyEvent) {
+    let overlay = match app.overlay {
+        Some(ref mut o) => o,
+        None => return,
+    };
+
+    match key.code {
+        KeyCode::Char('q') | KeyCode::Esc => {
+            app.overlay = None;
+        }
+        KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+            app.should_quit = true;
+        }
+        KeyCode::Up => overlay.scroll_up(1),
+        KeyCode::Down => overlay.scroll_down(1),
+        KeyCode::PageUp => overlay.scroll_up(10),
+        KeyCode::PageDown => overlay.scroll_down(10),
+        KeyCode::Home => overlay.scroll = 0,
+        KeyCode::End => overlay.scroll_down(overlay.lines.len()),
+        _ => {}
+    }
+}
+
+/// Handle a key press while a prompt is open.
+fn handle_prompt_key(app: &mut App, key: event::KeyEvent) {
+    let (kind, input) = match app.prompt {
+        Some(ref mut prompt) => (prompt.kind, &mut prompt.input),
+        None => return,
+    };
+
+    match key.code {
+        KeyCode::Char('c') if key.modifiers.contains(event::KeyModifiers::CONTROL) => {
+            app.should_quit = true;
+        }
+        KeyCode::Char(c) => {
+            input.push(c);
+            app.prompt_changed();
+        }
+        KeyCode::Backspace => {
+            input.pop();
+            app.prompt_changed();
+        }
+        KeyCode::Esc => app.cancel_prompt(),
+        KeyCode::Enter if kind == PromptKind::Search => {
+            let found = app.submit_search();
+            if !found {
+                let query = app.search_query.as_ref().map_or("", |q| q.text());
+                app.set_message(format!("No matches for '{}'", query));
+            }
+        }
+        KeyCode::Enter if kind == PromptKind::Filter => app.submit_filter(),
+        KeyCode::Enter if matches!(kind, PromptKind::Attach(_)) => app.submit_attach(),
+        KeyCode::Enter => app.submit_note(),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::FakeRunner;
+
+    #[test]
+    fn test_terminal_is_restored_on_panic() {
+        let mut out = Vec::new();
+        let result = panic::catch_unwind(panic::AssertUnwindSafe(|| {
+            let _guard = TerminalGuard {
+                out: &mut out,
+                mouse: true,
+            };
+            panic!("render failed");
+        }));
+        assert!(result.is_err());
+
+        let written = String::from_utf8(out).unwrap();
+        assert!(written.contains("\x1b[?1049l"), "alternate screen left: {:?}", written);
+        assert!(written.contains("\x1b[?25h"), "cursor shown: {:?}", written);
+        assert!(written.contains("\x1b[?1000l"), "mouse released: {:?}", written);
+    }
+
+    /// A writer whose output can be read while it's in use.
+    #[derive(Debug, Clone, Default)]
+    struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+    impl SharedBuffer {
+        fn contents(&self) -> String {
+            String::from_utf8_lossy(&self.0.lock().unwrap()).into_owned()
+        }
+    }
+
+    impl Write for SharedBuffer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_restore_hook_only_restores_for_the_ui_thread() {
+        // Panics from other tests may pass through the hooks meanwhile, so
+        // each is told apart by its message
+        let out = SharedBuffer::default();
+        let seen: Arc<Mutex<Vec<(String, String)>>> = Arc::default();
+        let original_hook = panic::take_hook();
+        let (recorded_out, recorded) = (out.clone(), Arc::clone(&seen));
+        panic::set_hook(Box::new(move |info| {
+            let message = info.payload().downcast_ref::<&str>().copied().unwrap_or_default();
+            recorded.lock().unwrap().push((message.to_string(), recorded_out.contents()));
+        }));
+
+        let previous_hook = set_restore_hook(out.clone(), true);
+        assert!(panic::catch_unwind(|| panic!("restore hook: ui")).is_err());
+        let after_ui_panic = out.contents();
+        assert!(thread::spawn(|| panic!("restore hook: worker")).join().is_err());
+        unset_restore_hook(previous_hook);
+        drop(panic::take_hook());
+        panic::set_hook(original_hook);
+
+        let seen = seen.lock().unwrap();
+        let output_seen = |message: &str| {
+            let (_, output) = seen.iter().find(|(m, _)| m == message).unwrap();
+            output.clone()
+        };
+        // The terminal was restored before the previous hook printed anything
+        let restored = output_seen("restore hook: ui");
+        assert!(restored.contains("\x1b[?1049l"), "alternate screen left: {:?}", restored);
+        assert!(restored.contains("\x1b[?25h"), "cursor shown: {:?}", restored);
+        assert!(restored.contains("\x1b[?1000l"), "mouse released: {:?}", restored);
+        // A worker panicking leaves the terminal alone
+        assert_eq!(output_seen("restore hook: worker"), after_ui_panic);
+        assert_eq!(out.contents(), after_ui_panic);
+    }
+
+    /// Set in the child process `test_shutdown_signals` runs.
+    const SIGNAL_CHILD_ENV: &str = "SLURM_MONITOR_TEST_SIGNAL_CHILD";
+
+    #[test]
+    fn test_ignoring_an_interrupt_keeps_other_signals() {
+        let signals = ShutdownSignals::default();
+        signals.interrupted.store(true, Ordering::Relaxed);
+        assert!(signals.received());
+        signals.ignore_interrupt();
+        assert!(!signals.received());
+
+        signals.terminated.store(true, Ordering::Relaxed);
+        signals.interrupted.store(true, Ordering::Relaxed);
+        signals.ignore_interrupt();
+        assert!(signals.received());
+    }
+
+    #[test]
+    fn test_shutdown_signals() {
+        use std::os::unix::process::ExitStatusExt;
+
+        if std::env::var_os(SIGNAL_CHILD_ENV).is_some() {
+            let signals = ShutdownSignals::register().unwrap();
+            signal_hook::low_level::raise(SIGTERM).unwrap();
+            assert!(signals.received());
+
+            // Only the default action ends the child with SIGTERM
+            signals.finish();
+            signal_hook::low_level::raise(SIGTERM).unwrap();
+            std::process::exit(0);
+        }
+
+        // Signal handlers are process-wide, so register them in a child
+        // rather than in the test process
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "cli::tests::test_shutdown_signals", "--test-threads=1"])
+            .env(SIGNAL_CHILD_ENV, "1")
+            .stdout(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert_eq!(status.signal(), Some(SIGTERM), "{}", status);
+    }
+
+    #[test]
+    fn test_list_template() {
+        let info = JobInfo {
+            job_id: 12345,
+            job_name: "train".to_string(),
+            state: "RUNNING".to_string(),
+            elapsed: "00:10:00".to_string(),
+            ..Default::default()
+        };
+
+        let template = ListTemplate::parse("{job_id}\\t{status}\\t{name}").unwrap();
+        assert_eq!(template.render(&info, JobStatus::Running), "12345\tRUNNING\ttrain\n");
+
+        let template = ListTemplate::parse("{{{state}}} {elapsed}\\n").unwrap();
+        assert_eq!(template.render(&info, JobStatus::Running), "{RUNNING} 00:10:00\n");
+        let template = ListTemplate::parse("C:\\\\{name}").unwrap();
+        assert_eq!(template.render(&info, JobStatus::Running), "C:\\train\n");
+
+        assert!(ListTemplate::parse("{job}").is_err());
+        assert!(ListTemplate::parse("{name").is_err());
+        assert!(ListTemplate::parse("name}").is_err());
+        assert!(ListTemplate::parse("\\x").is_err());
+        assert!(ListTemplate::parse("\\").is_err());
+    }
+
+    #[test]
+    fn test_list_format_conflicts_with_json() {
+        let cli = Cli::try_parse_from(["slurm-monitor", "list", "--format", "{name}"]).unwrap();
+        assert!(matches!(cli.command, Commands::List { format: Some(_), json: false, .. }));
+        assert!(Cli::try_parse_from(["slurm-monitor", "list", "--format", "{name}", "--json"])
+            .is_err());
+    }
+
+    #[test]
+    fn test_watch_output_flags() {
+        let cli = Cli::try_parse_from(["slurm-monitor", "watch", "--watch-output", "diag.log"]).unwrap();
+        assert_eq!(cli.watch_output, Some(PathBuf::from("diag.log")));
+        assert_eq!(cli.log_level, None);
+        let cli = Cli::try_parse_from([
+            "slurm-monitor", "--log-level", "warn", "list", "--watch-output", "diag.log"
+        ])
+        .unwrap();
+        assert_eq!(cli.log_level, Some(Level::Warn));
+        // A level alone has nowhere to go
+        let cli = Cli::try_parse_from(["slurm-monitor", "list", "--log-level", "warn"]).unwrap();
+        assert!(cli.start_diagnostics_log().is_err());
+    }
+
+    #[test]
+    fn test_slurm_throttle_flags() {
+        let config = Config::from_toml("command_delay = 0.5\nmax_slurm_commands = 2").unwrap();
+        let cli = Cli::try_parse_from(["slurm-monitor", "list", "--command-jitter", "0.25"]).unwrap();
+        let throttle = cli.slurm_throttle(&config);
+        assert_eq!(throttle.delay, Duration::from_millis(500));
+        assert_eq!(throttle.jitter, Duration::from_millis(250));
+        assert_eq!(throttle.max_concurrent, 2);
+
+        let cli = Cli::try_parse_from(["slurm-monitor", "--max-slurm-commands", "1", "list"]).unwrap();
+        assert_eq!(cli.slurm_throttle(&config).max_concurrent, 1);
+        assert!(Cli::try_parse_from(["slurm-monitor", "list", "--max-slurm-commands", "0"]).is_err());
+        assert!(Cli::try_parse_from(["slurm-monitor", "list", "--command-delay", "-1"]).is_err());
+    }
+
+    #[test]
+    fn test_slurm_conf_flags() {
+        let dir = tempfile::tempdir().unwrap();
+        let conf = dir.path().join("slurm.conf");
+        std::fs::write(&conf, "ClusterName=b\n").unwrap();
+        let dir_arg = dir.path().to_str().unwrap();
+
+        let cli = Cli::try_parse_from(["slurm-monitor", "list", "--config-dir", dir_arg]).unwrap();
+        assert_eq!(
+            cli.slurm_env().unwrap(),
+            [("SLURM_CONF".to_string(), conf.clone().into_os_string())]
+        );
+        let cli = Cli::try_parse_from(["slurm-monitor", "--slurm-conf", conf.to_str().unwrap(), "list"])
+            .unwrap();
+        assert_eq!(cli.slurm_env().unwrap()[0].1, conf.into_os_string());
+        let cli = Cli::try_parse_from(["slurm-monitor", "list"]).unwrap();
+        assert!(cli.slurm_env().unwrap().is_empty());
+
+        let missing = dir.path().join("missing.conf");
+        let cli = Cli::try_parse_from(["slurm-monitor", "list", "--slurm-conf", missing.to_str().unwrap()])
+            .unwrap();
+        assert!(cli.slurm_env().is_err());
+        assert!(Cli::try_parse_from([
+            "slurm-monitor", "list", "--slurm-conf", "a", "--config-dir", "b"
+        ])
+        .is_err());
+    }
+
+    #[test]
+    fn test_parse_job_id_list() {
+        let content = "\
+# jobs from the sweep
+12345
+12346 12347  # the retries
+
+12345.batch
+12348_2
+";
+        assert_eq!(parse_job_id_list(content).unwrap(), [12345, 12346, 12347, 12348]);
+        assert_eq!(parse_job_id_list("1\n\njob 2\n").unwrap_err(), "3: 'job' is not a job ID");
+        assert!(parse_job_id_list("# nothing\n\n").unwrap().is_empty());
+
+        let file = tempfile::NamedTempFile::new().unwrap();
+        std::fs::write(file.path(), "1\nx\n").unwrap();
+        let error = read_job_id_list(file.path()).unwrap_err().to_string();
+        assert_eq!(error, format!("{}:2: 'x' is not a job ID", file.path().display()));
+    }
+
+    #[test]
+    fn test_daemon_args() {
+        let cli = Cli::try_parse_from([
+            "slurm-monitor", "daemon", "12345", "--log-file", "jobs.log", "--webhook",
+            "https://example.com/hook",
+        ])
+        .unwrap();
+        match cli.command {
+            Commands::Daemon { job_ids, log_file, monitor, .. } => {
+                assert_eq!(job_ids, [12345]);
+                assert_eq!(log_file, Some(PathBuf::from("jobs.log")));
+                assert_eq!(monitor.webhook.as_deref(), Some("https://example.com/hook"));
+            }
+            _ => panic!("expected the daemon command"),
+        }
+        let cli = Cli::try_parse_from(["slurm-monitor", "daemon", "--name", "train_*"]).unwrap();
+        assert!(matches!(cli.command, Commands::Daemon { log_file: None, .. }));
+    }
+
+    #[test]
+    fn test_dashboard_sort_args() {
+        let cli = Cli::try_parse_from(["slurm-monitor", "dashboard", "--sort", "runtime", "--reverse"])
+            .unwrap();
+        assert!(matches!(
+            cli.command,
+            Commands::Dashboard { sort: Some(SortMode::Runtime), reverse: true, .. }
+        ));
+        assert!(Cli::try_parse_from(["slurm-monitor", "dashboard", "--sort", "size"]).is_err());
+    }
+
+    #[test]
+    fn test_copy_to_clipboard() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foob"), "Zm9vYg==");
+
+        let mut out = Vec::new();
+        copy_to_clipboard(&mut out, "/logs/train-77.out").unwrap();
+        assert_eq!(out, b"\x1b]52;c;L2xvZ3MvdHJhaW4tNzcub3V0\x07");
+    }
+
+    #[test]
+    fn test_parse_log_label() {
+        assert_eq!(parse_log_label("stdout_12345"), Some((12345, "stdout")));
+        assert_eq!(parse_log_label("stderr_12345"), Some((12345, "stderr")));
+        assert_eq!(parse_log_label("file_2"), Some((2, "stdout")));
+
+        // Unknown or malformed labels are not routed anywhere
+        assert_eq!(parse_log_label("std_out_123"), None);
+        assert_eq!(parse_log_label("stdout"), None);
+        assert_eq!(parse_log_label("stdout_"), None);
+        assert_eq!(parse_log_label("stdout_abc"), None);
+        assert_eq!(parse_log_label("test_label"), None);
+    }
+
+    #[test]
+    fn test_log_labels_round_trip() {
+        let mut app = App::new();
+        for label in log_labels(&app, 42) {
+            assert_eq!(parse_log_label(&label).map(|(id, _)| id), Some(42));
+        }
+
+        app.add_tail_files(vec![PathBuf::from("a.log")]);
+        for label in log_labels(&app, 1) {
+            assert_eq!(parse_log_label(&label), Some((1, "stdout")));
+        }
+    }
+
+    #[test]
+    fn test_plain_output_includes_updates_queued_at_shutdown() {
+        let mut app = App::new();
+        app.update_job_status(1, JobStatus::Running, JobInfo::default());
+        let (status_tx, status_rx) = mpsc::channel();
+        let (log_tx, log_rx) = mpsc::channel();
+        let (log_status_tx, log_status_rx) = mpsc::channel();
+        let (_gpu_tx, gpu_rx) = mpsc::channel();
+        status_tx
+            .send(StatusUpdate {
+                job_id: 1,
+                status: JobStatus::Completed,
+                info: JobInfo::default(),
+                unreachable: false,
+            })
+            .unwrap();
+        for content in ["epoch 9\n", "done"] {
+            log_tx
+                .send(LogUpdate {
+                    label: "stdout_1".to_string(),
+                    content: content.to_string(),
+                    reset: false,
+                    reloaded: false,
+                    modified: None,
+                })
+                .unwrap();
+        }
+        log_status_tx
+            .send(LogStatus {
+                label: "stderr_1".to_string(),
+                problem: Some(LogProblem::PermissionDenied(PathBuf::from("/logs/1.err"))),
+            })
+            .unwrap();
+
+        // Shutting down before the loop gets to run
+        let job_manager = Arc::new(Mutex::new(JobManager::with_runner(Arc::new(
+            FakeRunner::default(),
+        ))));
+        let mut backend = Backend {
+            status_monitor: StatusMonitor::new(Arc::clone(&job_manager), 3.0),
+            gpu_monitor: GpuMonitor::new(Arc::clone(&job_manager), 5.0),
+            job_manager,
+            log_tailer: LogTailer::new(1.0),
+            status_rx,
+            log_rx,
+            log_status_rx,
+            gpu_rx,
+            shutdown: ShutdownSignals {
+                terminated: Arc::new(AtomicBool::new(true)),
+                ..Default::default()
+            },
+        };
+        let mut printed = Vec::new();
+        run_plain_loop(&mut app, &mut backend, &mut PlainOutput::new(&mut printed)).unwrap();
+
+        let printed = String::from_utf8(printed).unwrap();
+        let lines: Vec<&str> = printed.lines().collect();
+        assert!(lines[0].ends_with("job 1: COMPLETED"), "{}", lines[0]);
+        assert_eq!(
+            lines[1..],
+            [
+                "[1 stdout] epoch 9",
+                "[1 stderr] (permission denied: /logs/1.err)",
+                "[1 stdout] done",
+            ]
+        );
+        assert_eq!(app.jobs[&1].status, JobStatus::Completed);
+    }
+
+    #[test]
+    fn test_plain_output_includes_logs_named_at_shutdown() {
+        let dir = tempfile::tempdir().unwrap();
+        let stdout_path = dir.path().join("2.out");
+        std::fs::write(&stdout_path, "last line\n").unwrap();
+
+        let mut app = App::new();
+        app.update_job_status(2, JobStatus::Running, JobInfo::default());
+        let (status_tx, status_rx) = mpsc::channel();
+        let (log_tx, log_rx) = mpsc::channel();
+        let (_log_status_tx, log_status_rx) = mpsc::channel();
+        let (_gpu_tx, gpu_rx) = mpsc::channel();
+        // The log path only arrives with the final status
+        status_tx
+            .send(StatusUpdate {
+                job_id: 2,
+                status: JobStatus::Completed,
+                info: JobInfo {
+                    stdout_path,
+                    ..Default::default()
+                },
+                unreachable: false,
+            })
+            .unwrap();
+
+        let job_manager = Arc::new(Mutex::new(JobManager::with_runner(Arc::new(
+            FakeRunner::default(),
+        ))));
+        let mut log_tailer = LogTailer::new(1.0);
+        log_tailer.start_monitoring(log_tx);
+        let mut backend = Backend {
+            status_monitor: StatusMonitor::new(Arc::clone(&job_manager), 3.0),
+            gpu_monitor: GpuMonitor::new(Arc::clone(&job_manager), 5.0),
+            job_manager,
+            log_tailer,
+            status_rx,
+            log_rx,
+            log_status_rx,
+            gpu_rx,
+            shutdown: ShutdownSignals {
+                terminated: Arc::new(AtomicBool::new(true)),
+                ..Default::default()
+            },
+        };
+        let mut printed = Vec::new();
+        run_plain_loop(&mut app, &mut backend, &mut PlainOutput::new(&mut printed)).unwrap();
+
+        let printed = String::from_utf8(printed).unwrap();
+        let lines: Vec<&str> = printed.lines().collect();
+        assert!(lines[0].ends_with("job 2: COMPLETED"), "{}", lines[0]);
+        assert_eq!(lines[1..], ["[2 stdout] last line"]);
+    }
+
+    #[test]
+    fn test_check_initial_jobs_drops_unknown_ids() {
+        let runner = FakeRunner::default()
+            .respond("squeue -j 1 ", "RUNNING\n")
+            .respond("sacct -j 2 ", "");
+        let manager = JobManager::with_runner(Arc::new(runner));
+
+        assert_eq!(
+            check_initial_jobs(&manager, vec![1, 2], false),
+            vec![(1, JobStatus::Running)]
+        );
+        assert_eq!(
+            check_initial_jobs(&manager, vec![1, 2], true),
+            vec![(1, JobStatus::Running), (2, JobStatus::Unknown)]
+        );
+        // Job 3's sacct query fails, so it may well exist
+        assert_eq!(check_initial_jobs(&manager, vec![3], false), vec![(3, JobStatus::Unknown)]);
+    }
+
+    #[test]
+    fn test_apply_job_limit() {
+        assert_eq!(apply_job_limit(vec![3, 1, 2], None), (vec![3, 1, 2], 0));
+        assert_eq!(apply_job_limit(vec![3, 1, 2], Some(5)), (vec![3, 1, 2], 0));
+        // Newest jobs are kept, in their original order
+        assert_eq!(apply_job_limit(vec![3, 1, 4, 2], Some(2)), (vec![3, 4], 2));
+        // No room left
+        assert_eq!(apply_job_limit(vec![7, 8], Some(0)), (vec![], 2));
+    }
+
+    #[test]
+    fn test_columns_flag() {
+        let cli = Cli::try_parse_from(["slurm-monitor", "watch", "--columns", "id,partition,nodes"])
+            .unwrap();
+        match cli.command {
+            Commands::Watch { monitor, .. } => assert_eq!(
+                monitor.columns,
+                vec![Column::Id, Column::Partition, Column::Nodes]
+            ),
+            _ => panic!("expected watch"),
+        }
+
+        let err = Cli::try_parse_from(["slurm-monitor", "watch", "--columns", "id,bogus"]);
+        assert!(err.is_err());
+    }
+    #[test]
+    fn test_flags_override_config() {
+        let monitor_args = |args: &[&str]| {
+            let cli = Cli::try_parse_from([&["slurm-monitor", "watch"], args].concat()).unwrap();
+            match cli.command {
+                Commands::Watch { monitor, .. } => monitor,
+                _ => panic!("expected watch"),
+            }
+        };
+        let file = Config::from_toml(
+            "status_interval = 5\nauto_discover = false\ngpu = true\ncolumns = [\"id\"]",
+        )
+        .unwrap();
+
+        // No flags: config file values win over built-in defaults
+        let mut config = file.clone();
+        monitor_args(&[]).apply(&mut config);
+        assert_eq!(config, file);
+
+        // Flags win over the config file
+        let mut config = file.clone();
+        monitor_args(&["--interval", "1.5", "--auto-discover", "--columns", "id,name", "--theme", "mono"])
+            .apply(&mut config);
+        assert_eq!(config.theme, ThemeName::Mono);
+        assert_eq!(config.status_interval, 1.5);
+        assert!(config.auto_discover);
+        assert_eq!(config.columns, vec![Column::Id, Column::Name]);
+        assert!(config.gpu);
+
+        let mut config = Config::default();
+        monitor_args(&["--auto-discover", "--no-auto-discover", "--no-mouse", "--max-jobs", "50"])
+            .apply(&mut config);
+        assert!(!config.auto_discover);
+        assert!(!config.mouse);
+        assert_eq!(config.max_jobs, Some(50));
+
+        let mut config = Config::default();
+        monitor_args(&["--record", "timeline.csv", "--discovery-interval", "30"]).apply(&mut config);
+        assert_eq!(config.record, Some(PathBuf::from("timeline.csv")));
+        assert_eq!(config.discovery_interval, 30.0);
+
+        assert!(Cli::try_parse_from(["slurm-monitor", "watch", "--interval", "0"]).is_err());
+        assert!(Cli::try_parse_from(["slurm-monitor", "watch", "--max-jobs", "0"]).is_err());
+        assert!(Cli::try_parse_from(["slurm-monitor", "watch", "--discovery-interval", "1"]).is_err());
+    }
+}
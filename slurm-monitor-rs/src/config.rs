@@ -0,0 +1,678 @@
+//! User configuration loaded from `~/.config/slurm-monitor/config.toml`.
+//!
+//! Every field is optional; anything left out keeps its built-in default.
+//! Command-line flags take precedence over the file.
+
+use crate::ui::{Column, ThemeName};
+use crate::utils::{JobStatus, DEFAULT_MAX_SLURM_COMMANDS};
+use crate::webhook::validate_webhook_url;
+use anyhow::{bail, Context, Result};
+use crossterm::event::KeyCode;
+use ratatui::style::Color;
+use serde::Deserialize;
+use std::path::{Path, PathBuf};
+
+/// Shortest allowed time between auto-discovery runs. Each run queries
+/// sacct, so polling faster mostly adds load on the SLURM controller.
+pub const MIN_DISCOVERY_INTERVAL: f64 = 2.0;
+
+/// Monitor defaults read from the config file.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Seconds between SLURM status polls
+    pub status_interval: f64,
+    /// Seconds between fallback checks of tailed log files
+    pub log_interval: f64,
+    /// Seconds between GPU utilization polls
+    pub gpu_interval: f64,
+    /// Seconds between auto-discovery runs
+    pub discovery_interval: f64,
+    /// Least seconds between the starts of two SLURM commands
+    pub command_delay: f64,
+    /// Up to this many seconds added to each command delay at random
+    pub command_jitter: f64,
+    /// Most SLURM commands running at once
+    pub max_slurm_commands: usize,
+    /// Add newly submitted jobs when watching without job IDs
+    pub auto_discover: bool,
+    /// Poll GPU utilization of running jobs
+    pub gpu: bool,
+    /// Select newly discovered jobs automatically
+    pub follow_new: bool,
+    /// Status table columns; empty keeps the defaults
+    pub columns: Vec<Column>,
+    /// URL notified when a job finishes
+    pub webhook: Option<String>,
+    /// File that status changes are appended to
+    pub record: Option<PathBuf>,
+    /// Most jobs to monitor at once; unlimited if unset
+    pub max_jobs: Option<usize>,
+    /// Keep every carriage-return progress update as its own log line
+    pub raw_progress: bool,
+    /// Keep a log's earlier output when its file is truncated or replaced,
+    /// e.g. by a requeued job
+    pub keep_truncated_logs: bool,
+    /// Show each job's steps under it in the status table
+    pub steps: bool,
+    /// List array jobs' tasks under them and allow switching between the
+    /// tasks' logs
+    pub array_tasks: bool,
+    /// Poll the memory use of running jobs with sstat
+    pub live_memory: bool,
+    /// Ring the terminal bell when a monitored job fails
+    pub bell: bool,
+    /// Percentage of the log area given to stdout (the rest goes to stderr)
+    pub stdout_share: u16,
+    /// Seconds without log output after which a running job is flagged as
+    /// stalled; 0 turns it off
+    pub stall_after: f64,
+    /// Capture the mouse; turn off to keep the terminal's own text selection
+    pub mouse: bool,
+    /// Color theme preset
+    pub theme: ThemeName,
+    /// Status color overrides, applied on top of the theme
+    pub colors: StatusColors,
+    /// Key bindings for single-letter commands
+    pub keys: KeyBindings,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            status_interval: 3.0,
+            log_interval: 1.0,
+            gpu_interval: 10.0,
+            discovery_interval: 10.0,
+            command_delay: 0.0,
+            command_jitter: 0.0,
+            max_slurm_commands: DEFAULT_MAX_SLURM_COMMANDS,
+            auto_discover: true,
+            gpu: false,
+            follow_new: false,
+            columns: Vec::new(),
+            webhook: None,
+            record: None,
+            max_jobs: None,
+            raw_progress: false,
+            keep_truncated_logs: false,
+            steps: false,
+            array_tasks: false,
+            live_memory: false,
+            bell: true,
+            stdout_share: 50,
+            stall_after: 600.0,
+            mouse: true,
+            theme: ThemeName::default(),
+            colors: StatusColors::default(),
+            keys: KeyBindings::default(),
+        }
+    }
+}
+
+impl Config {
+    /// Location of the config file: `$XDG_CONFIG_HOME/slurm-monitor/config.toml`,
+    /// falling back to `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(base.join("slurm-monitor").join("config.toml"))
+    }
+
+    /// Load the config file from its default location.
+    ///
+    /// A missing file is not an error and yields the built-in defaults.
+    pub fn load() -> Result<Self> {
+        match Self::default_path() {
+            Some(path) => Self::load_from(&path),
+            None => Ok(Self::default()),
+        }
+    }
+
+    /// Load a config file, returning the defaults if it doesn't exist.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()))
+            }
+        };
+        Self::from_toml(&content).with_context(|| format!("Invalid config file {}", path.display()))
+    }
+
+    /// Remember `stdout_share` in the config file at its default location.
+    pub fn save_stdout_share(share: u16) -> Result<()> {
+        match Self::default_path() {
+            Some(path) => Self::save_stdout_share_to(&path, share),
+            None => Ok(()),
+        }
+    }
+
+    /// Set `stdout_share` in a config file, creating it if needed. The rest
+    /// of the file, comments included, is left as it is.
+    pub fn save_stdout_share_to(path: &Path, share: u16) -> Result<()> {
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(e) => {
+                return Err(e).with_context(|| format!("Failed to read {}", path.display()))
+            }
+        };
+        let mut document: toml_edit::DocumentMut = content
+            .parse()
+            .with_context(|| format!("Invalid config file {}", path.display()))?;
+        document["stdout_share"] = toml_edit::value(i64::from(share));
+
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        std::fs::write(path, document.to_string())
+            .with_context(|| format!("Failed to write {}", path.display()))
+    }
+
+    /// Parse and validate config file content.
+    pub fn from_toml(content: &str) -> Result<Self> {
+        let config: Self = toml::from_str(content)?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    fn validate(&self) -> Result<()> {
+        for (name, value) in [
+            ("status_interval", self.status_interval),
+            ("log_interval", self.log_interval),
+            ("gpu_interval", self.gpu_interval),
+            ("discovery_interval", self.discovery_interval),
+        ] {
+            if !value.is_finite() || value <= 0.0 {
+                bail!("{} must be a positive number of seconds, got {}", name, value);
+            }
+        }
+        if self.discovery_interval < MIN_DISCOVERY_INTERVAL {
+            bail!(
+                "discovery_interval must be at least {} seconds, got {}",
+                MIN_DISCOVERY_INTERVAL,
+                self.discovery_interval
+            );
+        }
+        for (name, value) in [
+            ("stall_after", self.stall_after),
+            ("command_delay", self.command_delay),
+            ("command_jitter", self.command_jitter),
+        ] {
+            if !value.is_finite() || value < 0.0 {
+                bail!("{} must be a number of seconds, got {}", name, value);
+            }
+        }
+        if self.max_slurm_commands == 0 {
+            bail!("max_slurm_commands must be at least 1");
+        }
+        if self.stdout_share > 100 {
+            bail!("stdout_share must be a percentage from 0 to 100, got {}", self.stdout_share);
+        }
+        if self.max_jobs == Some(0) {
+            bail!("max_jobs must be at least 1");
+        }
+        if let Some(ref url) = self.webhook {
+            validate_webhook_url(url).map_err(anyhow::Error::msg)?;
+        }
+        self.keys.validate()
+    }
+}
+
+/// Colors used for each job status. Unset statuses keep the theme's colors.
+///
+/// Values are color names (`red`, `light-blue`), `#rrggbb` hex codes or
+/// terminal palette indices.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct StatusColors {
+    pub queued: Option<Color>,
+    pub running: Option<Color>,
+    pub suspended: Option<Color>,
+    pub completed: Option<Color>,
+    pub cancelled: Option<Color>,
+    pub timeout: Option<Color>,
+    pub oom: Option<Color>,
+    pub failed: Option<Color>,
+    pub unknown: Option<Color>,
+}
+
+impl StatusColors {
+    /// The configured color for a status, if any.
+    pub fn get(&self, status: JobStatus) -> Option<Color> {
+        match status {
+            JobStatus::Queued => self.queued,
+            JobStatus::Running => self.running,
+            JobStatus::Suspended => self.suspended,
+            JobStatus::Completed => self.completed,
+            JobStatus::Cancelled => self.cancelled,
+            JobStatus::Timeout => self.timeout,
+            JobStatus::OutOfMemory => self.oom,
+            JobStatus::Failed => self.failed,
+            JobStatus::Unknown => self.unknown,
+        }
+    }
+}
+
+/// A key that can be bound to an action.
+///
+/// Written in the config file as a single character (`"j"`, `"S"`) or a key
+/// name: `up`, `down`, `left`, `right`, `pageup`, `pagedown`, `home`, `end`,
+/// `tab`, `backtab`, `enter`, `esc`, `backspace`, `space` or `f1`-`f12`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Key(pub KeyCode);
+
+impl From<char> for Key {
+    fn from(c: char) -> Self {
+        Key(KeyCode::Char(c))
+    }
+}
+
+impl TryFrom<String> for Key {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let mut chars = value.chars();
+        if let (Some(c), None) = (chars.next(), chars.next()) {
+            return Ok(Key::from(c));
+        }
+
+        let code = match value.to_lowercase().as_str() {
+            "up" => KeyCode::Up,
+            "down" => KeyCode::Down,
+            "left" => KeyCode::Left,
+            "right" => KeyCode::Right,
+            "pageup" | "pgup" => KeyCode::PageUp,
+            "pagedown" | "pgdn" => KeyCode::PageDown,
+            "home" => KeyCode::Home,
+            "end" => KeyCode::End,
+            "tab" => KeyCode::Tab,
+            "backtab" => KeyCode::BackTab,
+            "enter" => KeyCode::Enter,
+            "esc" => KeyCode::Esc,
+            "backspace" => KeyCode::Backspace,
+            "space" => KeyCode::Char(' '),
+            name => match name.strip_prefix('f').and_then(|n| n.parse::<u8>().ok()) {
+                Some(n @ 1..=12) => KeyCode::F(n),
+                _ => return Err(format!("unknown key '{}'", value)),
+            },
+        };
+        Ok(Key(code))
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            KeyCode::Char(' ') => write!(f, "Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Up => write!(f, "↑"),
+            KeyCode::Down => write!(f, "↓"),
+            KeyCode::Left => write!(f, "←"),
+            KeyCode::Right => write!(f, "→"),
+            KeyCode::PageUp => write!(f, "PgUp"),
+            KeyCode::PageDown => write!(f, "PgDn"),
+            KeyCode::BackTab => write!(f, "Shift+Tab"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+/// A command that can be triggered from the keyboard.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Quit,
+    PrevJob,
+    NextJob,
+    Sort,
+    SortReverse,
+    Filter,
+    Script,
+    Delete,
+    Undo,
+    FollowNew,
+    AutoDiscover,
+    Diagnostics,
+    ToggleFocus,
+    ScrollUp,
+    ScrollDown,
+    PageUp,
+    PageDown,
+    ScrollTop,
+    ScrollBottom,
+    Search,
+    NextMatch,
+    PrevMatch,
+    Wrap,
+    Layout,
+    GrowStdout,
+    ShrinkStdout,
+    Note,
+    Grep,
+    Open,
+    Preview,
+    HideFinished,
+    Errors,
+    Attach,
+    ColumnsLeft,
+    ColumnsRight,
+    CopyPath,
+    NextTask,
+    PrevTask,
+    Times,
+    Reload,
+}
+
+/// Keys bound to each action in the monitor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct KeyBindings {
+    /// Exit scroll mode, or quit
+    pub quit: Key,
+    /// Select the previous job in the list
+    pub prev_job: Key,
+    /// Select the next job in the list
+    pub next_job: Key,
+    /// Cycle the sort mode
+    pub sort: Key,
+    /// Reverse the sort direction
+    pub sort_reverse: Key,
+    /// Cycle the status filter
+    pub filter: Key,
+    /// Show the current job's batch script
+    pub script: Key,
+    /// Remove the current job (press twice)
+    pub delete: Key,
+    /// Restore the last removed job
+    pub undo: Key,
+    /// Toggle following newly discovered jobs
+    pub follow_new: Key,
+    /// Toggle auto-discovery of new jobs
+    pub auto_discover: Key,
+    /// Toggle the diagnostics pane
+    pub diagnostics: Key,
+    /// Switch focus between the stdout and stderr panels
+    pub toggle_focus: Key,
+    /// Scroll the focused panel one line up
+    pub scroll_up: Key,
+    /// Scroll the focused panel one line down
+    pub scroll_down: Key,
+    /// Scroll the focused panel a page up
+    pub page_up: Key,
+    /// Scroll the focused panel a page down
+    pub page_down: Key,
+    /// Jump to the top of the focused panel
+    pub scroll_top: Key,
+    /// Jump to the bottom of the focused panel and resume auto-scroll
+    pub scroll_bottom: Key,
+    /// Search the logs
+    pub search: Key,
+    /// Jump to the next search match in the focused panel
+    pub next_match: Key,
+    /// Jump to the previous search match in the focused panel
+    pub prev_match: Key,
+    /// Toggle wrapping of long log lines
+    pub wrap: Key,
+    /// Switch between the job list layout and the compact single-job one
+    pub layout: Key,
+    /// Move the stdout/stderr divider down, giving stdout more room
+    pub grow_stdout: Key,
+    /// Move the stdout/stderr divider up, giving stderr more room
+    pub shrink_stdout: Key,
+    /// Add or edit a note on the current job
+    pub note: Key,
+    /// Show only log lines containing a pattern, updated as it is typed
+    pub grep: Key,
+    /// Open the focused panel's log file in `$PAGER`
+    pub open: Key,
+    /// Show each job's last stdout line in the job table
+    pub preview: Key,
+    /// Hide finished jobs from the job list
+    pub hide_finished: Key,
+    /// Show the stderr of every job in a single pane
+    pub errors: Key,
+    /// Attach to a running step of the current job with sattach
+    pub attach: Key,
+    /// Scroll the status table one column left, when it is too wide
+    pub columns_left: Key,
+    /// Scroll the status table one column right, when it is too wide
+    pub columns_right: Key,
+    /// Copy the path of the focused panel's log file to the clipboard
+    pub copy_path: Key,
+    /// Show the logs of the next started task of an array job
+    pub next_task: Key,
+    /// Show the logs of the previous started task of an array job
+    pub prev_task: Key,
+    /// Cycle how the job list shows times: live, raw and relative
+    pub times: Key,
+    /// Read the focused panel's log file again from the start
+    pub reload: Key,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            quit: Key::from('q'),
+            prev_job: Key::from('n'),
+            next_job: Key::from('p'),
+            sort: Key::from('s'),
+            sort_reverse: Key::from('S'),
+            filter: Key::from('f'),
+            script: Key::from('b'),
+            delete: Key::from('d'),
+            undo: Key::from('u'),
+            follow_new: Key::from('F'),
+            auto_discover: Key::from('a'),
+            diagnostics: Key::from('L'),
+            toggle_focus: Key(KeyCode::Tab),
+            scroll_up: Key(KeyCode::Up),
+            scroll_down: Key(KeyCode::Down),
+            page_up: Key(KeyCode::PageUp),
+            page_down: Key(KeyCode::PageDown),
+            scroll_top: Key(KeyCode::Home),
+            scroll_bottom: Key(KeyCode::End),
+            search: Key::from('/'),
+            next_match: Key::from('m'),
+            prev_match: Key::from('M'),
+            wrap: Key::from('w'),
+            layout: Key::from('z'),
+            grow_stdout: Key::from(']'),
+            shrink_stdout: Key::from('['),
+            note: Key::from('e'),
+            grep: Key::from('g'),
+            open: Key::from('o'),
+            preview: Key::from('v'),
+            hide_finished: Key::from('h'),
+            errors: Key::from('E'),
+            attach: Key::from('A'),
+            columns_left: Key(KeyCode::Left),
+            columns_right: Key(KeyCode::Right),
+            copy_path: Key::from('y'),
+            next_task: Key::from('t'),
+            prev_task: Key::from('T'),
+            times: Key::from('r'),
+            reload: Key::from('R'),
+        }
+    }
+}
+
+impl KeyBindings {
+    fn all(&self) -> [(&'static str, Action, Key); 40] {
+        [
+            ("quit", Action::Quit, self.quit),
+            ("prev_job", Action::PrevJob, self.prev_job),
+            ("next_job", Action::NextJob, self.next_job),
+            ("sort", Action::Sort, self.sort),
+            ("sort_reverse", Action::SortReverse, self.sort_reverse),
+            ("filter", Action::Filter, self.filter),
+            ("script", Action::Script, self.script),
+            ("delete", Action::Delete, self.delete),
+            ("undo", Action::Undo, self.undo),
+            ("follow_new", Action::FollowNew, self.follow_new),
+            ("auto_discover", Action::AutoDiscover, self.auto_discover),
+            ("diagnostics", Action::Diagnostics, self.diagnostics),
+            ("toggle_focus", Action::ToggleFocus, self.toggle_focus),
+            ("scroll_up", Action::ScrollUp, self.scroll_up),
+            ("scroll_down", Action::ScrollDown, self.scroll_down),
+            ("page_up", Action::PageUp, self.page_up),
+            ("page_down", Action::PageDown, self.page_down),
+            ("scroll_top", Action::ScrollTop, self.scroll_top),
+            ("scroll_bottom", Action::ScrollBottom, self.scroll_bottom),
+            ("search", Action::Search, self.search),
+            ("next_match", Action::NextMatch, self.next_match),
+            ("prev_match", Action::PrevMatch, self.prev_match),
+            ("wrap", Action::Wrap, self.wrap),
+            ("layout", Action::Layout, self.layout),
+            ("grow_stdout", Action::GrowStdout, self.grow_stdout),
+            ("shrink_stdout", Action::ShrinkStdout, self.shrink_stdout),
+            ("note", Action::Note, self.note),
+            ("grep", Action::Grep, self.grep),
+            ("open", Action::Open, self.open),
+            ("preview", Action::Preview, self.preview),
+            ("hide_finished", Action::HideFinished, self.hide_finished),
+            ("errors", Action::Errors, self.errors),
+            ("attach", Action::Attach, self.attach),
+            ("columns_left", Action::ColumnsLeft, self.columns_left),
+            ("columns_right", Action::ColumnsRight, self.columns_right),
+            ("copy_path", Action::CopyPath, self.copy_path),
+            ("next_task", Action::NextTask, self.next_task),
+            ("prev_task", Action::PrevTask, self.prev_task),
+            ("times", Action::Times, self.times),
+            ("reload", Action::Reload, self.reload),
+        ]
+    }
+
+    /// The action bound to a key, if any.
+    pub fn action(&self, code: KeyCode) -> Option<Action> {
+        self.all()
+            .into_iter()
+            .find(|&(_, _, key)| key.0 == code)
+            .map(|(_, action, _)| action)
+    }
+
+    /// Reject bindings that would shadow each other.
+    fn validate(&self) -> Result<()> {
+        let all = self.all();
+        for (i, (name, _, key)) in all.iter().enumerate() {
+            if let Some((other, _, _)) = all[..i].iter().find(|(_, _, k)| k == key) {
+                bail!("keys.{} and keys.{} are both bound to '{}'", other, name, key);
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() {
+        assert_eq!(Config::from_toml("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_partial_config() {
+        let config = Config::from_toml(
+            r##"
+status_interval = 5
+auto_discover = false
+columns = ["id", "status", "partition"]
+theme = "light"
+
+[colors]
+running = "light-green"
+failed = "#ff0000"
+
+[keys]
+next_job = "j"
+prev_job = "k"
+"##,
+        )
+        .unwrap();
+
+        assert_eq!(config.status_interval, 5.0);
+        assert!(!config.auto_discover);
+        assert_eq!(config.columns, vec![Column::Id, Column::Status, Column::Partition]);
+        assert_eq!(config.theme, ThemeName::Light);
+        assert_eq!(config.colors.get(JobStatus::Running), Some(Color::LightGreen));
+        assert_eq!(config.colors.get(JobStatus::Failed), Some(Color::Rgb(255, 0, 0)));
+        assert_eq!(config.colors.get(JobStatus::Queued), None);
+        assert_eq!(config.keys.next_job, Key::from('j'));
+        assert_eq!(config.keys.sort, Key::from('s'));
+
+        // Untouched fields keep their defaults
+        assert_eq!(config.log_interval, 1.0);
+        assert!(!config.gpu);
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Config::from_toml("status_interval = 0").is_err());
+        assert!(Config::from_toml("refresh = 5").is_err());
+        assert!(Config::from_toml("theme = \"solarized\"").is_err());
+        assert!(Config::from_toml("[colors]\nrunning = \"not-a-color\"").is_err());
+        assert!(Config::from_toml("webhook = \"example.com\"").is_err());
+        assert!(Config::from_toml("max_jobs = 0").is_err());
+        assert!(Config::from_toml("command_delay = -1").is_err());
+        assert!(Config::from_toml("max_slurm_commands = 0").is_err());
+        assert!(Config::from_toml("stdout_share = 101").is_err());
+        assert!(Config::from_toml("stall_after = -1").is_err());
+
+        let err = Config::from_toml("[keys]\nundo = \"d\"").unwrap_err();
+        assert!(err.to_string().contains("keys.delete and keys.undo"));
+        assert!(Config::from_toml("[keys]\nquit = \"qq\"").is_err());
+    }
+
+    #[test]
+    fn test_key_bindings() {
+        let config = Config::from_toml(
+            "[keys]\nnext_job = \"down\"\nprev_job = \"Up\"\nscroll_down = \"j\"\nscroll_up = \"k\"\n\
+             toggle_focus = \"space\"\nscroll_bottom = \"F5\"",
+        )
+        .unwrap();
+        let keys = config.keys;
+        assert_eq!(keys.action(KeyCode::Down), Some(Action::NextJob));
+        assert_eq!(keys.action(KeyCode::Up), Some(Action::PrevJob));
+        assert_eq!(keys.action(KeyCode::Char('j')), Some(Action::ScrollDown));
+        assert_eq!(keys.action(KeyCode::Char(' ')), Some(Action::ToggleFocus));
+        assert_eq!(keys.action(KeyCode::F(5)), Some(Action::ScrollBottom));
+        assert_eq!(keys.action(KeyCode::Char('n')), None);
+        assert_eq!(keys.action(KeyCode::Char('q')), Some(Action::Quit));
+        assert_eq!(keys.toggle_focus.to_string(), "Space");
+
+        // Moving a default key without freeing it is a conflict
+        let err = Config::from_toml("[keys]\nnext_job = \"down\"").unwrap_err();
+        assert!(err.to_string().contains("keys.next_job and keys.scroll_down"));
+    }
+
+    #[test]
+    fn test_save_stdout_share() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("slurm-monitor").join("config.toml");
+        Config::save_stdout_share_to(&path, 80).unwrap();
+        assert_eq!(Config::load_from(&path).unwrap().stdout_share, 80);
+
+        let content = "# my settings\nbell = false  # quiet\nstdout_share = 80\n\n[keys]\nquit = \"x\"\n";
+        std::fs::write(&path, content).unwrap();
+        Config::save_stdout_share_to(&path, 30).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(&path).unwrap(),
+            content.replace("stdout_share = 80", "stdout_share = 30")
+        );
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = Config::load_from(&dir.path().join("config.toml")).unwrap();
+        assert_eq!(config, Config::default());
+    }
+}
@@ -0,0 +1,174 @@
+//! In-memory ring buffer of monitor diagnostics, shown in the UI.
+//!
+//! Background threads record command failures, unreadable files and other
+//! internal events here instead of printing them, since stdout belongs to the
+//! TUI. Only the most recent `CAPACITY` entries are kept, unless they are
+//! also written to a file with `log_to_file`.
+
+use clap::ValueEnum;
+use std::collections::VecDeque;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+/// Maximum number of entries kept.
+pub const CAPACITY: usize = 500;
+
+static ENTRIES: Mutex<VecDeque<Entry>> = Mutex::new(VecDeque::new());
+static START: OnceLock<Instant> = OnceLock::new();
+
+/// File that entries are also appended to, with the least severe level
+/// written.
+static LOG_FILE: Mutex<Option<(File, Level)>> = Mutex::new(None);
+
+/// Severity of a diagnostic entry, least severe first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum Level {
+    /// Routine tracing, e.g. which files are being tailed
+    Debug,
+    /// Something the user may need to act on, e.g. a failed command
+    Warn,
+}
+
+/// A recorded diagnostic message.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub level: Level,
+    pub message: String,
+    /// Time since the first diagnostic was recorded
+    pub at: Duration,
+    /// How many times in a row this message was recorded
+    pub count: usize,
+}
+
+impl Entry {
+    /// Format as a single display line.
+    pub fn to_line(&self) -> String {
+        let level = match self.level {
+            Level::Debug => "debug",
+            Level::Warn => "warn ",
+        };
+        let repeats = if self.count > 1 {
+            format!(" (x{})", self.count)
+        } else {
+            String::new()
+        };
+        format!("[{:>8.1}s] {} {}{}", self.at.as_secs_f64(), level, self.message, repeats)
+    }
+}
+
+/// Record a message. Consecutive duplicates are folded into one entry.
+pub fn record(level: Level, message: impl Into<String>) {
+    let message = message.into();
+    let at = START.get_or_init(Instant::now).elapsed();
+
+    // The file gets every occurrence; folding only saves room in the buffer
+    if let Some((ref mut file, min_level)) = *LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) {
+        if level >= min_level {
+            let entry = Entry {
+                level,
+                message: message.clone(),
+                at,
+                count: 1,
+            };
+            let _ = writeln!(file, "{}", entry.to_line());
+        }
+    }
+
+    let mut entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+
+    if let Some(last) = entries.back_mut() {
+        if last.level == level && last.message == message {
+            last.count += 1;
+            last.at = at;
+            return;
+        }
+    }
+
+    if entries.len() == CAPACITY {
+        entries.pop_front();
+    }
+    entries.push_back(Entry {
+        level,
+        message,
+        at,
+        count: 1,
+    });
+}
+
+/// Record a routine tracing message.
+pub fn debug(message: impl Into<String>) {
+    record(Level::Debug, message);
+}
+
+/// Record a problem worth surfacing to the user.
+pub fn warn(message: impl Into<String>) {
+    record(Level::Warn, message);
+}
+
+/// Also append entries of `level` and above to the file at `path`, so they
+/// can be read while the UI is running or after it exits.
+pub fn log_to_file(path: &Path, level: Level) -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(path)?;
+    *LOG_FILE.lock().unwrap_or_else(|e| e.into_inner()) = Some((file, level));
+    Ok(())
+}
+
+/// Snapshot of the most recent entries, oldest first.
+pub fn recent(limit: usize) -> Vec<Entry> {
+    let entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    let skip = entries.len().saturating_sub(limit);
+    entries.iter().skip(skip).cloned().collect()
+}
+
+/// Number of warnings currently in the buffer.
+pub fn warning_count() -> usize {
+    let entries = ENTRIES.lock().unwrap_or_else(|e| e.into_inner());
+    entries.iter().filter(|e| e.level == Level::Warn).count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_buffer_is_bounded_and_folds_repeats() {
+        // The buffer is global, so only assert on messages unique to this test
+        warn("diagnostics-test: sacct failed");
+        warn("diagnostics-test: sacct failed");
+        let last = recent(CAPACITY)
+            .into_iter()
+            .rev()
+            .find(|e| e.message == "diagnostics-test: sacct failed")
+            .unwrap();
+        assert_eq!(last.count, 2);
+        assert!(last.to_line().ends_with("warn  diagnostics-test: sacct failed (x2)"));
+
+        for i in 0..CAPACITY + 10 {
+            debug(format!("diagnostics-test: filler {}", i));
+        }
+        assert!(recent(usize::MAX).len() <= CAPACITY);
+        assert_eq!(recent(1).len(), 1);
+    }
+
+    #[test]
+    fn test_log_file_gets_entries_at_its_level() {
+        let file = tempfile::NamedTempFile::new().unwrap();
+        log_to_file(file.path(), Level::Warn).unwrap();
+        debug("diagnostics-test: file debug");
+        warn("diagnostics-test: file warn");
+        warn("diagnostics-test: file warn");
+        *LOG_FILE.lock().unwrap() = None;
+
+        // Other tests may log meanwhile, so only look at this test's lines
+        let content = std::fs::read_to_string(file.path()).unwrap();
+        let lines: Vec<&str> = content
+            .lines()
+            .filter(|l| l.contains("diagnostics-test: file"))
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(lines.iter().all(|l| l.ends_with("warn  diagnostics-test: file warn")));
+    }
+}
@@ -0,0 +1,271 @@
+//! Environment self-check (`slurm-monitor doctor`), to find out why the
+//! monitor can't see jobs or shows them all as UNKNOWN.
+
+use crate::utils::{CommandResult, CommandRunner};
+use std::fmt;
+use std::io;
+
+/// SLURM tools the monitor runs.
+const TOOLS: [&str; 4] = ["sbatch", "squeue", "sacct", "scontrol"];
+
+/// How a check turned out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    Ok,
+    /// Works, but may not behave as expected
+    Warning,
+    Failed,
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            Outcome::Ok => "ok",
+            Outcome::Warning => "warn",
+            Outcome::Failed => "FAIL",
+        };
+        f.pad(label)
+    }
+}
+
+/// Result of one probe of the environment.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Check {
+    /// What was checked, e.g. `sacct on PATH`
+    pub name: String,
+    pub outcome: Outcome,
+    /// What was found, or what went wrong
+    pub detail: String,
+}
+
+impl Check {
+    fn new(name: impl Into<String>, outcome: Outcome, detail: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            outcome,
+            detail: detail.into(),
+        }
+    }
+}
+
+impl fmt::Display for Check {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:<5} {}: {}", self.outcome, self.name, self.detail)
+    }
+}
+
+/// Run every check with `runner`.
+///
+/// Checks that need a tool which couldn't be run are skipped, so a missing
+/// `sacct` is only reported once.
+pub fn run_checks(runner: &dyn CommandRunner) -> Vec<Check> {
+    let mut checks = Vec::new();
+    let mut found = Vec::new();
+    for tool in TOOLS {
+        let check = check_version(runner, tool);
+        if check.outcome == Outcome::Ok {
+            found.push(tool);
+        }
+        checks.push(check);
+    }
+
+    if found.contains(&"squeue") {
+        checks.push(check_squeue(runner));
+    }
+    if found.contains(&"sacct") {
+        checks.push(check_sacct(runner));
+    }
+    if found.contains(&"scontrol") {
+        checks.push(check_accounting_storage(runner));
+    }
+    if found.contains(&"sbatch") {
+        checks.push(check_submit(runner));
+    }
+    checks
+}
+
+/// `tool --version`, which shows whether it's on PATH at all.
+fn check_version(runner: &dyn CommandRunner, tool: &str) -> Check {
+    let name = format!("{} on PATH", tool);
+    match runner.run(&[tool, "--version"], false) {
+        Ok(result) if result.return_code == 0 => {
+            Check::new(name, Outcome::Ok, first_line(&result.stdout, "found"))
+        }
+        Ok(result) => Check::new(name, Outcome::Failed, failure(&result)),
+        Err(e) => {
+            let not_found = e
+                .root_cause()
+                .downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::NotFound);
+            let detail = if not_found {
+                "not found; load the SLURM module or add its bin directory to PATH".to_string()
+            } else {
+                format!("{:#}", e)
+            };
+            Check::new(name, Outcome::Failed, detail)
+        }
+    }
+}
+
+/// Whether squeue can reach the controller, which status polling relies on.
+fn check_squeue(runner: &dyn CommandRunner) -> Check {
+    let name = "squeue can list jobs";
+    match runner.run(&["squeue", "-h", "-o", "%i"], false) {
+        Ok(result) if result.return_code == 0 => {
+            let jobs = result.stdout.lines().filter(|line| !line.trim().is_empty()).count();
+            Check::new(name, Outcome::Ok, format!("{} jobs in the queue", jobs))
+        }
+        Ok(result) => Check::new(name, Outcome::Failed, failure(&result)),
+        Err(e) => Check::new(name, Outcome::Failed, format!("{:#}", e)),
+    }
+}
+
+/// Whether sacct can query the accounting database, which finished jobs and
+/// job discovery rely on.
+fn check_sacct(runner: &dyn CommandRunner) -> Check {
+    let name = "sacct can query accounting";
+    match runner.run(&["sacct", "-n", "-X", "--starttime=now"], false) {
+        Ok(result) if is_accounting_disabled(&result) => Check::new(
+            name,
+            Outcome::Failed,
+            "accounting storage is disabled, so finished jobs show as UNKNOWN \
+             and jobs can't be discovered",
+        ),
+        Ok(result) if result.return_code == 0 && !result.stderr.contains("error") => {
+            Check::new(name, Outcome::Ok, "sacct answered")
+        }
+        Ok(result) => Check::new(name, Outcome::Failed, failure(&result)),
+        Err(e) => Check::new(name, Outcome::Failed, format!("{:#}", e)),
+    }
+}
+
+/// Whether the cluster has job accounting configured at all.
+fn check_accounting_storage(runner: &dyn CommandRunner) -> Check {
+    let name = "accounting enabled";
+    let result = match runner.run(&["scontrol", "show", "config"], false) {
+        Ok(result) if result.return_code == 0 => result,
+        Ok(result) => return Check::new(name, Outcome::Warning, failure(&result)),
+        Err(e) => return Check::new(name, Outcome::Warning, format!("{:#}", e)),
+    };
+    let storage = result.stdout.lines().find_map(|line| {
+        let (key, value) = line.split_once('=')?;
+        (key.trim() == "AccountingStorageType").then(|| value.trim().to_string())
+    });
+    match storage {
+        Some(storage) if storage.ends_with("/none") => Check::new(
+            name,
+            Outcome::Failed,
+            format!("AccountingStorageType is {}", storage),
+        ),
+        Some(storage) => Check::new(name, Outcome::Ok, storage),
+        None => Check::new(name, Outcome::Warning, "AccountingStorageType not in scontrol output"),
+    }
+}
+
+/// Whether the user may submit jobs, using `sbatch --test-only` so nothing
+/// is actually queued.
+fn check_submit(runner: &dyn CommandRunner) -> Check {
+    let name = "sbatch can submit";
+    match runner.run(&["sbatch", "--test-only", "--wrap=true"], false) {
+        // sbatch reports the would-be start time on stderr
+        Ok(result) if result.return_code == 0 => Check::new(
+            name,
+            Outcome::Ok,
+            first_line(&result.stderr, "a test job would be accepted"),
+        ),
+        Ok(result) => Check::new(name, Outcome::Failed, failure(&result)),
+        Err(e) => Check::new(name, Outcome::Failed, format!("{:#}", e)),
+    }
+}
+
+fn is_accounting_disabled(result: &CommandResult) -> bool {
+    result.stderr.to_lowercase().contains("accounting storage is disabled")
+}
+
+/// First non-blank line of `text`, or `default` if there is none.
+fn first_line(text: &str, default: &str) -> String {
+    text.lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .unwrap_or(default)
+        .to_string()
+}
+
+/// Why a command that ran exited with an error.
+fn failure(result: &CommandResult) -> String {
+    let reason = first_line(&result.stderr, "no error output");
+    format!("exited with code {}: {}", result.return_code, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::FakeRunner;
+
+    #[test]
+    fn test_checks_report_each_problem() {
+        let runner = FakeRunner::default()
+            .respond("sbatch --version", "slurm 23.02.6\n")
+            .respond("squeue --version", "slurm 23.02.6\n")
+            .respond("sacct --version", "slurm 23.02.6\n")
+            .respond("scontrol --version", "slurm 23.02.6\n")
+            .respond("squeue -h", "101\n102\n")
+            .fail("sacct -n -X", "sacct: error: Slurm accounting storage is disabled\n")
+            .respond(
+                "scontrol show config",
+                "ClusterName = test\nAccountingStorageType   = accounting_storage/none\n",
+            )
+            .fail("sbatch --test-only", "sbatch: error: Invalid account or account/partition\n");
+        let checks = run_checks(&runner);
+
+        let outcomes: Vec<(&str, Outcome)> =
+            checks.iter().map(|check| (check.name.as_str(), check.outcome)).collect();
+        assert_eq!(
+            outcomes,
+            [
+                ("sbatch on PATH", Outcome::Ok),
+                ("squeue on PATH", Outcome::Ok),
+                ("sacct on PATH", Outcome::Ok),
+                ("scontrol on PATH", Outcome::Ok),
+                ("squeue can list jobs", Outcome::Ok),
+                ("sacct can query accounting", Outcome::Failed),
+                ("accounting enabled", Outcome::Failed),
+                ("sbatch can submit", Outcome::Failed),
+            ]
+        );
+        assert_eq!(checks[0].to_string(), "ok    sbatch on PATH: slurm 23.02.6");
+        assert_eq!(checks[4].detail, "2 jobs in the queue");
+        assert!(checks[5].detail.starts_with("accounting storage is disabled"));
+        assert_eq!(checks[6].detail, "AccountingStorageType is accounting_storage/none");
+        assert_eq!(
+            checks[7].detail,
+            "exited with code 1: sbatch: error: Invalid account or account/partition"
+        );
+    }
+
+    #[test]
+    fn test_checks_needing_a_failed_tool_are_skipped() {
+        let runner = FakeRunner::default()
+            .respond("sbatch --version", "slurm 23.02.6\n")
+            .respond("squeue --version", "slurm 23.02.6\n")
+            .respond("sbatch --test-only", "")
+            .respond("squeue -h", "");
+        let checks = run_checks(&runner);
+
+        let names: Vec<&str> = checks.iter().map(|check| check.name.as_str()).collect();
+        assert_eq!(
+            names,
+            [
+                "sbatch on PATH",
+                "squeue on PATH",
+                "sacct on PATH",
+                "scontrol on PATH",
+                "squeue can list jobs",
+                "sbatch can submit",
+            ]
+        );
+        assert_eq!(checks[2].outcome, Outcome::Failed);
+        assert_eq!(checks[5].detail, "a test job would be accepted");
+        assert!(!runner.calls().iter().any(|call| call.starts_with("sacct -n")));
+    }
+}
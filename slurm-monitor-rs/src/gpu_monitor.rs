@@ -0,0 +1,336 @@
+//! GPU Monitor for polling GPU utilization of running jobs.
+
+use crate::job_manager::JobManager;
+use crate::utils::parse_scontrol_output;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Utilization of a single GPU.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GpuStats {
+    /// srun task (one per node) that reported the GPU
+    pub task: u32,
+    /// GPU index on its node
+    pub index: u32,
+    /// GPU utilization in percent
+    pub utilization: f64,
+    /// Memory in use, in MiB
+    pub memory_used_mib: u64,
+    /// Total memory, in MiB
+    pub memory_total_mib: u64,
+}
+
+/// GPU update message sent from the monitor thread to the UI.
+#[derive(Debug, Clone, Default)]
+pub struct GpuUpdate {
+    pub job_id: u64,
+    /// Nodes allocated to the job, as reported by scontrol
+    pub nodes: String,
+    pub gpus: Vec<GpuStats>,
+    /// Why stats could not be collected, if they couldn't
+    pub error: Option<String>,
+}
+
+/// Command sent to the monitor thread.
+#[derive(Debug)]
+pub enum GpuCommand {
+    /// Add a job to monitor
+    AddJob(u64),
+    /// Remove a job from monitoring
+    RemoveJob(u64),
+    /// Stop the monitor
+    Stop,
+}
+
+/// Monitors GPU utilization of running jobs with periodic polling.
+///
+/// Each poll runs `scontrol show job` to find out whether the job is running
+/// and where, then `srun --jobid=<id> --overlap nvidia-smi` inside the job's
+/// allocation. Jobs that aren't running are skipped; failures (no srun, no
+/// nvidia-smi, no GPUs) are reported in `GpuUpdate::error` rather than
+/// stopping the monitor. Commands go through the shared `JobManager`, so
+/// they reach the same cluster as the status polls.
+pub struct GpuMonitor {
+    /// Shared JobManager that runs the SLURM commands
+    job_manager: Arc<Mutex<JobManager>>,
+    /// Polling interval
+    poll_interval: Duration,
+    /// Sender for commands to the monitor thread
+    command_tx: Option<Sender<GpuCommand>>,
+    /// Monitor thread handle
+    thread_handle: Option<JoinHandle<()>>,
+}
+
+impl GpuMonitor {
+    /// Create a new GpuMonitor.
+    ///
+    /// # Arguments
+    /// * `job_manager` - Shared JobManager instance
+    /// * `poll_interval_secs` - Polling interval in seconds (default: 10.0)
+    pub fn new(job_manager: Arc<Mutex<JobManager>>, poll_interval_secs: f64) -> Self {
+        Self {
+            job_manager,
+            poll_interval: Duration::from_secs_f64(poll_interval_secs),
+            command_tx: None,
+            thread_handle: None,
+        }
+    }
+
+    /// Start monitoring jobs.
+    ///
+    /// # Arguments
+    /// * `job_ids` - List of job IDs to monitor
+    /// * `update_tx` - Channel to send GPU updates
+    pub fn start_monitoring(&mut self, job_ids: Vec<u64>, update_tx: Sender<GpuUpdate>) {
+        // Stop any existing monitoring
+        self.stop_monitoring();
+
+        let (command_tx, command_rx) = mpsc::channel();
+        self.command_tx = Some(command_tx);
+
+        let poll_interval = self.poll_interval;
+        let job_manager = Arc::clone(&self.job_manager);
+
+        let handle = thread::spawn(move || {
+            Self::monitor_loop(job_manager, command_rx, update_tx, poll_interval, job_ids);
+        });
+
+        self.thread_handle = Some(handle);
+    }
+
+    /// Process a single command. Returns true if the loop should stop.
+    fn process_command(cmd: GpuCommand, monitored_jobs: &mut Vec<u64>) -> bool {
+        match cmd {
+            GpuCommand::AddJob(job_id) => {
+                if !monitored_jobs.contains(&job_id) {
+                    monitored_jobs.push(job_id);
+                }
+                false
+            }
+            GpuCommand::RemoveJob(job_id) => {
+                monitored_jobs.retain(|&id| id != job_id);
+                false
+            }
+            GpuCommand::Stop => true,
+        }
+    }
+
+    /// Monitor loop running in a separate thread.
+    fn monitor_loop(
+        job_manager: Arc<Mutex<JobManager>>,
+        command_rx: Receiver<GpuCommand>,
+        update_tx: Sender<GpuUpdate>,
+        poll_interval: Duration,
+        initial_jobs: Vec<u64>,
+    ) {
+        let mut monitored_jobs: Vec<u64> = initial_jobs;
+
+        loop {
+            while let Ok(cmd) = command_rx.try_recv() {
+                if Self::process_command(cmd, &mut monitored_jobs) {
+                    return;
+                }
+            }
+
+            // Query a snapshot so the lock isn't held while srun runs
+            let manager = job_manager.lock().unwrap().clone();
+            for &job_id in &monitored_jobs {
+                if let Some(update) = Self::query_job(&manager, job_id) {
+                    if update_tx.send(update).is_err() {
+                        // Receiver dropped, stop monitoring
+                        return;
+                    }
+                }
+            }
+
+            // Wait for poll interval, processing commands periodically
+            let check_interval = Duration::from_millis(100);
+            let mut elapsed = Duration::ZERO;
+            while elapsed < poll_interval {
+                while let Ok(cmd) = command_rx.try_recv() {
+                    if Self::process_command(cmd, &mut monitored_jobs) {
+                        return;
+                    }
+                }
+                thread::sleep(check_interval);
+                elapsed += check_interval;
+            }
+        }
+    }
+
+    /// Query GPU stats for a job. Returns `None` if the job isn't running.
+    fn query_job(manager: &JobManager, job_id: u64) -> Option<GpuUpdate> {
+        let job = manager
+            .run_slurm(&["scontrol", "show", "job", &job_id.to_string()], false)
+            .ok()
+            .filter(|r| r.return_code == 0)?;
+        let fields = parse_scontrol_output(&job.stdout);
+        if fields.get("JobState").map(|s| s.as_str()) != Some("RUNNING") {
+            return None;
+        }
+
+        let nodes = fields.get("NodeList").cloned().unwrap_or_default();
+        let num_nodes = fields
+            .get("NumNodes")
+            .and_then(|n| n.split('-').next())
+            .and_then(|n| n.parse::<u32>().ok())
+            .unwrap_or(1)
+            .to_string();
+
+        let mut update = GpuUpdate {
+            job_id,
+            nodes,
+            ..Default::default()
+        };
+
+        let result = manager.run_slurm(
+            &[
+                "srun",
+                &format!("--jobid={}", job_id),
+                "--overlap",
+                "--label",
+                "--nodes",
+                &num_nodes,
+                "--ntasks",
+                &num_nodes,
+                "nvidia-smi",
+                "--query-gpu=index,utilization.gpu,memory.used,memory.total",
+                "--format=csv,noheader,nounits",
+            ],
+            false,
+        );
+
+        match result {
+            Ok(r) if r.return_code == 0 => {
+                update.gpus = parse_nvidia_smi_output(&r.stdout);
+                if update.gpus.is_empty() {
+                    update.error = Some("no GPUs reported".to_string());
+                }
+            }
+            Ok(r) => {
+                let stderr = r.stderr.trim();
+                update.error = Some(if stderr.is_empty() {
+                    format!("srun exited with code {}", r.return_code)
+                } else {
+                    stderr.lines().last().unwrap_or(stderr).to_string()
+                });
+            }
+            Err(e) => {
+                update.error = Some(format!("GPU stats unavailable: {}", e));
+            }
+        }
+
+        Some(update)
+    }
+
+    /// Stop monitoring all jobs.
+    pub fn stop_monitoring(&mut self) {
+        if let Some(tx) = self.command_tx.take() {
+            let _ = tx.send(GpuCommand::Stop);
+        }
+
+        if let Some(handle) = self.thread_handle.take() {
+            let _ = handle.join();
+        }
+    }
+
+    /// Add a job to monitoring.
+    pub fn add_job_to_monitor(&self, job_id: u64) {
+        if let Some(ref tx) = self.command_tx {
+            let _ = tx.send(GpuCommand::AddJob(job_id));
+        }
+    }
+
+    /// Remove a job from monitoring.
+    pub fn remove_job_from_monitor(&self, job_id: u64) {
+        if let Some(ref tx) = self.command_tx {
+            let _ = tx.send(GpuCommand::RemoveJob(job_id));
+        }
+    }
+}
+
+impl Drop for GpuMonitor {
+    fn drop(&mut self) {
+        self.stop_monitoring();
+    }
+}
+
+/// Parse `nvidia-smi --query-gpu=index,utilization.gpu,memory.used,memory.total`
+/// CSV output, optionally prefixed with srun's `--label` task number.
+fn parse_nvidia_smi_output(output: &str) -> Vec<GpuStats> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let line = line.trim();
+            let (task, rest) = match line.split_once(':') {
+                Some((task, rest)) => (task.trim().parse::<u32>().ok()?, rest),
+                None => (0, line),
+            };
+
+            let fields: Vec<&str> = rest.split(',').map(|f| f.trim()).collect();
+            if fields.len() < 4 {
+                return None;
+            }
+
+            Some(GpuStats {
+                task,
+                index: fields[0].parse().ok()?,
+                utilization: fields[1].parse().ok()?,
+                memory_used_mib: fields[2].parse().ok()?,
+                memory_total_mib: fields[3].parse().ok()?,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::utils::FakeRunner;
+
+    #[test]
+    fn test_query_job_uses_the_cluster() {
+        let runner = Arc::new(
+            FakeRunner::default()
+                .respond(
+                    "scontrol --clusters=beta show job 7",
+                    "JobId=7 JobState=RUNNING NodeList=gpu01 NumNodes=1",
+                )
+                .respond("srun --clusters=beta --jobid=7 ", "0: 0, 87, 30210, 81920\n"),
+        );
+        let mut manager = JobManager::with_runner(runner.clone());
+        manager.set_cluster(Some("beta".to_string()));
+
+        let update = GpuMonitor::query_job(&manager, 7).unwrap();
+        assert_eq!(update.nodes, "gpu01");
+        assert_eq!(update.gpus.len(), 1);
+        assert_eq!(update.error, None);
+        assert_eq!(runner.calls().len(), 2);
+        assert!(runner.calls().iter().all(|call| call.contains("--clusters=beta")));
+    }
+
+    #[test]
+    fn test_parse_nvidia_smi_output() {
+        let output = "0: 0, 87, 30210, 81920\n0: 1, 12, 1024, 81920\n1: 0, 100, 40000, 81920\n";
+        let gpus = parse_nvidia_smi_output(output);
+        assert_eq!(gpus.len(), 3);
+        assert_eq!(
+            gpus[0],
+            GpuStats {
+                task: 0,
+                index: 0,
+                utilization: 87.0,
+                memory_used_mib: 30210,
+                memory_total_mib: 81920,
+            }
+        );
+        assert_eq!(gpus[2].task, 1);
+
+        // Unlabelled output and junk lines
+        let gpus = parse_nvidia_smi_output("0, 5, 10, 20\n[N/A]\nsrun: error\n");
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].utilization, 5.0);
+    }
+}
@@ -1,68 +1,421 @@
 //! Job Manager for SLURM job lifecycle management.
 
-use crate::utils::{parse_job_id, parse_sacct_output, run_slurm_command, JobStatus};
+use crate::diagnostics;
+use crate::utils::{
+    format_mib, format_slurm_duration, parse_job_id, parse_sacct_multiple_output, parse_sacct_output,
+    parse_scontrol_output, parse_slurm_duration, parse_slurm_memory, parse_tres, sacct_delimiter,
+    try_discover_job_ids, CommandResult, CommandRunner, JobFilter, JobStatus, SystemRunner, Tres,
+    SACCT_DELIMITER_ARG,
+};
 
-/// Write debug message to file
-fn debug_log(msg: &str) {
-    use std::io::Write;
-    if let Ok(mut f) = std::fs::OpenOptions::new()
-        .create(true)
-        .append(true)
-        .open("/tmp/slurm_monitor_debug.log")
-    {
-        let _ = writeln!(f, "{}", msg);
-    }
-}
 use anyhow::{Context, Result};
 use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::fs::File;
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
 
 /// Information about a SLURM job
 #[derive(Debug, Clone, Default)]
 pub struct JobInfo {
-    #[allow(dead_code)]
     pub job_id: u64,
     pub job_name: String,
     pub state: String,
     pub start_time: String,
     pub end_time: String,
     pub elapsed: String,
+    /// Time limit as SLURM prints it, e.g. `01:00:00` or `UNLIMITED`
+    pub time_limit: String,
     pub work_dir: PathBuf,
     pub stdout_path: PathBuf,
     pub stderr_path: PathBuf,
+    pub partition: String,
+    /// Allocated nodes in SLURM's compressed form, e.g. `gpu[01-02]`
+    pub node_list: String,
+    /// Why a pending job is waiting (e.g. `Priority`), empty otherwise
+    pub reason: String,
+    /// Scheduler's estimated start time for a pending job, if it has one
+    pub estimated_start: String,
+    /// Dependencies a pending job is waiting on, e.g. `afterok:123(unfulfilled)`
+    pub dependencies: Vec<String>,
+    /// `exit:signal` code of the batch script, e.g. `1:0`; unset for
+    /// jobs still in the queue
+    pub exit_code: Option<String>,
+    /// Steps of the job, when steps are monitored (`--steps`)
+    pub steps: Vec<JobStep>,
+    /// Tasks of an array job, when they are monitored (`--array-tasks`)
+    pub array_tasks: Vec<ArrayTask>,
+    /// Memory use of a running job from sstat, when live memory is polled
+    /// (`--live-memory`)
+    pub live_memory: Option<LiveMemory>,
+    /// Free-text comment set with `sbatch --comment`
+    pub comment: String,
+    /// Workload characterization key, e.g. `*proj` (`*` marks the user's
+    /// default)
+    pub wckey: String,
+    /// CPUs allocated to the job (AllocCPUS); unset until it starts
+    pub alloc_cpus: Option<u64>,
+    /// Resources allocated to the job (AllocTRES); unset until it starts
+    pub alloc_tres: Option<Tres>,
+}
+
+/// Memory use of a running job's batch step, from sstat.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct LiveMemory {
+    /// Current resident memory, in MiB
+    pub rss_mib: f64,
+    /// Peak resident memory so far, in MiB
+    pub max_rss_mib: Option<f64>,
+}
+
+impl fmt::Display for LiveMemory {
+    /// e.g. `RSS 1.2 GiB (max 1.5 GiB)`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "RSS {}", format_mib(self.rss_mib))?;
+        if let Some(max) = self.max_rss_mib {
+            write!(f, " (max {})", format_mib(max))?;
+        }
+        Ok(())
+    }
+}
+
+impl JobInfo {
+    /// Whether the job exited with a nonzero code or was killed by a signal.
+    pub fn exit_code_failed(&self) -> bool {
+        self.exit_code
+            .as_deref()
+            .is_some_and(|code| code.split(':').any(|part| part.trim() != "0"))
+    }
+}
+
+/// One step of a job (an `srun` call, or the batch script itself), from sacct.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobStep {
+    /// Part of the ID after the dot, e.g. `0` or `batch` for `123.batch`. Steps
+    /// of a heterogeneous job start with their component, e.g. `1.0` for
+    /// `123+1.0`.
+    pub step_id: String,
+    pub name: String,
+    pub state: String,
+    pub elapsed: String,
+    /// Peak resident memory, in MiB; sacct only knows it once the step ends
+    pub max_rss_mib: Option<f64>,
+}
+
+impl JobStep {
+    /// Whether `sattach` can attach to the step: a running `srun` step, not
+    /// the batch script or the extern step.
+    pub fn is_attachable(&self) -> bool {
+        let step = self.step_id.rsplit('.').next().unwrap_or("");
+        self.state.starts_with("RUNNING") && !matches!(step, "batch" | "extern")
+    }
+}
+
+/// One task of an array job (`123_4`), from sacct.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArrayTask {
+    /// Part of the ID after the underscore, e.g. `4`; tasks still pending
+    /// together show as the range SLURM prints, e.g. `[5-9%2]`
+    pub task_id: String,
+    /// The task's own job ID (`JobIDRaw`); unset for a pending range
+    pub raw_job_id: Option<u64>,
+    pub state: String,
+    pub elapsed: String,
+}
+
+impl ArrayTask {
+    pub fn status(&self) -> JobStatus {
+        JobStatus::from_slurm_state(&self.state)
+    }
+
+    /// How many tasks the entry stands for: 1, or the size of a pending
+    /// range such as `[5-9,12%2]`.
+    pub fn count(&self) -> usize {
+        let range = match self.task_id.strip_prefix('[') {
+            Some(range) => range.trim_end_matches(']'),
+            None => return 1,
+        };
+        // `%2` limits how many run at once, `:2` steps through a range
+        let range = range.split('%').next().unwrap_or("");
+        let count: usize = range
+            .split(',')
+            .map(|part| {
+                let (span, step) = part.split_once(':').unwrap_or((part, "1"));
+                let step = step.parse::<usize>().unwrap_or(1).max(1);
+                match span.split_once('-') {
+                    Some((first, last)) => match (first.parse::<usize>(), last.parse::<usize>()) {
+                        (Ok(first), Ok(last)) if last >= first => (last - first) / step + 1,
+                        _ => 1,
+                    },
+                    None => 1,
+                }
+            })
+            .sum();
+        count.max(1)
+    }
+}
+
+/// How many of an array job's tasks are in each status, in `JobStatus::rank`
+/// order, e.g. `10 tasks: 2 running, 5 queued, 3 completed`.
+pub fn array_task_summary(tasks: &[ArrayTask]) -> String {
+    let mut counts: HashMap<JobStatus, usize> = HashMap::new();
+    for task in tasks {
+        *counts.entry(task.status()).or_default() += task.count();
+    }
+    let total: usize = counts.values().sum();
+    let mut counts: Vec<(JobStatus, usize)> = counts.into_iter().collect();
+    counts.sort_by_key(|(status, _)| status.rank());
+    let parts: Vec<String> = counts
+        .iter()
+        .map(|(status, count)| format!("{} {}", count, status.as_str().to_lowercase()))
+        .collect();
+    format!("{} tasks: {}", total, parts.join(", "))
+}
+
+/// How a step ID follows its job's ID in SLURM: `.0`, or `+1.0` for a step of
+/// a heterogeneous job's component.
+pub fn step_suffix(step_id: &str) -> String {
+    if step_id.contains('.') {
+        format!("+{}", step_id)
+    } else {
+        format!(".{}", step_id)
+    }
+}
+
+/// The sbatch command line for `sbatch_script`, before the cluster is added.
+fn sbatch_args(sbatch_script: &Path, extra_args: &[String]) -> Vec<OsString> {
+    let mut cmd = vec![OsString::from("sbatch")];
+    cmd.extend(extra_args.iter().map(OsString::from));
+    cmd.push(sbatch_script.as_os_str().to_owned());
+    cmd
+}
+
+/// sbatch exited with an error. Only the first line of its stderr is part of
+/// the message; `stderr` has all of it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SbatchError {
+    pub return_code: i32,
+    pub stderr: String,
+}
+
+impl SbatchError {
+    fn stderr_lines(&self) -> impl Iterator<Item = &str> {
+        self.stderr.lines().map(str::trim).filter(|line| !line.is_empty())
+    }
+
+    /// Whether stderr has more than the line shown in the message.
+    pub fn has_more_output(&self) -> bool {
+        self.stderr_lines().nth(1).is_some()
+    }
+}
+
+impl fmt::Display for SbatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.stderr_lines().next() {
+            Some(first) => write!(f, "sbatch exited with code {}: {}", self.return_code, first),
+            None => write!(f, "sbatch exited with code {}", self.return_code),
+        }
+    }
+}
+
+impl std::error::Error for SbatchError {}
+
+/// Scheduling details of a pending job, from squeue.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PendingDetails {
+    /// Why the job is waiting, e.g. `Priority` or `Dependency`
+    pub reason: String,
+    /// Estimated start time; empty when the scheduler hasn't estimated one
+    pub estimated_start: String,
+    /// Remaining dependencies, one entry per `type:job_id[:job_id...]` term
+    pub dependencies: Vec<String>,
+}
+
+/// Resource usage of a finished job, from sacct.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JobSummary {
+    /// `exit code:signal`, e.g. `0:0`
+    pub exit_code: String,
+    pub elapsed: String,
+    /// CPU time used as a percentage of CPU time allocated
+    pub cpu_efficiency: Option<f64>,
+    /// Peak resident memory of any step, in MiB
+    pub max_rss_mib: Option<f64>,
+    /// Memory requested for the whole job, in MiB
+    pub req_mem_mib: Option<f64>,
+}
+
+impl JobSummary {
+    /// Peak memory as a percentage of the memory requested.
+    pub fn memory_efficiency(&self) -> Option<f64> {
+        match (self.max_rss_mib, self.req_mem_mib) {
+            (Some(used), Some(requested)) if requested > 0.0 => Some(used / requested * 100.0),
+            _ => None,
+        }
+    }
 }
 
 /// Manages SLURM job submission, tracking, and status retrieval.
-#[derive(Debug, Default)]
+///
+/// Cloning is cheap apart from the tracked job metadata, and a clone runs
+/// commands the same way. Threads sharing one behind a mutex clone it and
+/// query the copy, so a slow SLURM command doesn't hold the lock.
+#[derive(Debug, Clone)]
 pub struct JobManager {
     tracked_jobs: HashMap<u64, HashMap<String, String>>,
+    /// Runs the SLURM commands
+    runner: Arc<dyn CommandRunner>,
+    /// Runs them for `for_ui` copies
+    ui_runner: Arc<dyn CommandRunner>,
+    /// Cluster every SLURM command is sent to (`--clusters`), if not the local one
+    cluster: Option<String>,
+}
+
+impl Default for JobManager {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl JobManager {
     /// Create a new JobManager instance.
     pub fn new() -> Self {
+        Self::with_runner(Arc::new(SystemRunner::default()))
+    }
+
+    /// Create a JobManager that runs SLURM commands through `runner`.
+    pub fn with_runner(runner: Arc<dyn CommandRunner>) -> Self {
         Self {
             tracked_jobs: HashMap::new(),
+            ui_runner: Arc::clone(&runner),
+            runner,
+            cluster: None,
         }
     }
 
+    /// Run the SLURM commands of `for_ui` copies through `runner`, e.g. one
+    /// that isn't throttled like background polling is.
+    pub fn set_ui_runner(&mut self, runner: Arc<dyn CommandRunner>) {
+        self.ui_runner = runner;
+    }
+
+    /// A copy for queries the UI thread waits on, running commands through
+    /// the UI runner so they don't queue behind background polling.
+    pub fn for_ui(&self) -> Self {
+        Self {
+            runner: Arc::clone(&self.ui_runner),
+            ..self.clone()
+        }
+    }
+
+    /// Send SLURM commands to `cluster` instead of the local cluster.
+    pub fn set_cluster(&mut self, cluster: Option<String>) {
+        self.cluster = cluster;
+    }
+
+    /// Cluster SLURM commands are sent to, if not the local one.
+    pub fn cluster(&self) -> Option<&str> {
+        self.cluster.as_deref()
+    }
+
+    /// Check that the configured cluster can be queried.
+    pub fn check_cluster(&self) -> Result<()> {
+        let cluster = match self.cluster {
+            Some(ref cluster) => cluster,
+            None => return Ok(()),
+        };
+        let result = self.run_slurm(
+            &[
+                "sacct",
+                "--noheader",
+                "--parsable2",
+                SACCT_DELIMITER_ARG,
+                "--format=JobID",
+                "--starttime=now",
+            ],
+            false,
+        )?;
+        if result.return_code != 0 {
+            anyhow::bail!("Cannot query cluster '{}': {}", cluster, result.stderr.trim());
+        }
+        Ok(())
+    }
+
+    /// Job IDs from sacct matching `filter`, newest first. The filter says
+    /// which cluster to ask.
+    pub fn discover_job_ids(&self, filter: &JobFilter) -> Result<Vec<u64>> {
+        try_discover_job_ids(self.runner.as_ref(), filter)
+    }
+
+    /// Build the process for a SLURM command to run in the foreground, e.g.
+    /// attached to the terminal, with the runner's environment.
+    ///
+    /// Such commands (sattach) only reach the local cluster, so this fails
+    /// when another cluster is configured.
+    pub fn interactive_command(&self, cmd: &[&str]) -> Result<Command> {
+        if let Some(ref cluster) = self.cluster {
+            anyhow::bail!(
+                "{} can't reach jobs on cluster '{}'",
+                cmd.first().copied().unwrap_or_default(),
+                cluster
+            );
+        }
+        Ok(self.runner.interactive_command(cmd))
+    }
+
+    /// Run a SLURM command (`cmd[0]`) against the configured cluster.
+    pub(crate) fn run_slurm(&self, cmd: &[&str], check: bool) -> Result<CommandResult> {
+        let cmd: Vec<&OsStr> = cmd.iter().map(OsStr::new).collect();
+        self.run_slurm_os(&cmd, check)
+    }
+
+    /// Like `run_slurm`, for arguments that may not be valid UTF-8.
+    fn run_slurm_os(&self, cmd: &[&OsStr], check: bool) -> Result<CommandResult> {
+        let cmd = self.with_cluster(cmd);
+        let cmd: Vec<&OsStr> = cmd.iter().map(OsString::as_os_str).collect();
+        let mut result = self.runner.run_os(&cmd, check)?;
+        if self.cluster.is_some() {
+            result.stdout = strip_cluster_banners(&result.stdout);
+        }
+        Ok(result)
+    }
+
+    /// `cmd` with the configured cluster passed to it, as `run_slurm` runs it.
+    fn with_cluster(&self, cmd: &[&OsStr]) -> Vec<OsString> {
+        let mut args: Vec<OsString> = cmd.iter().map(|&arg| arg.to_owned()).collect();
+        match self.cluster {
+            Some(ref cluster) if !cmd.is_empty() => {
+                args.insert(1, format!("--clusters={}", cluster).into());
+            }
+            _ => {}
+        }
+        args
+    }
+
     /// Submit a job using sbatch and return the job ID.
     ///
     /// # Arguments
     /// * `sbatch_script` - Path to the SLURM batch script
     /// * `extra_args` - Additional arguments to pass to sbatch
+    ///
+    /// A failing sbatch is reported as an `SbatchError`.
     pub fn submit_job(&mut self, sbatch_script: &Path, extra_args: &[String]) -> Result<u64> {
-        if !sbatch_script.exists() {
-            anyhow::bail!("Script not found: {}", sbatch_script.display());
-        }
-
-        let mut cmd_args = vec!["sbatch"];
-        let extra_args_refs: Vec<&str> = extra_args.iter().map(|s| s.as_str()).collect();
-        cmd_args.extend(extra_args_refs);
-        cmd_args.push(sbatch_script.to_str().unwrap_or(""));
+        check_script(sbatch_script)?;
 
-        let result = run_slurm_command(&cmd_args, true)
+        let cmd = sbatch_args(sbatch_script, extra_args);
+        let cmd: Vec<&OsStr> = cmd.iter().map(OsString::as_os_str).collect();
+        let result = self.run_slurm_os(&cmd, false)
             .with_context(|| format!("Failed to submit job: {}", sbatch_script.display()))?;
+        if result.return_code != 0 {
+            return Err(anyhow::Error::new(SbatchError {
+                return_code: result.return_code,
+                stderr: result.stderr,
+            }))
+            .with_context(|| format!("Failed to submit job: {}", sbatch_script.display()));
+        }
 
         let job_id = parse_job_id(&result.stdout)
             .ok_or_else(|| anyhow::anyhow!("Could not parse job ID from sbatch output"))?;
@@ -79,23 +432,30 @@ impl JobManager {
         Ok(job_id)
     }
 
+    /// The sbatch command line `submit_job` runs, cluster option included.
+    pub fn sbatch_command(&self, sbatch_script: &Path, extra_args: &[String]) -> Vec<OsString> {
+        let cmd = sbatch_args(sbatch_script, extra_args);
+        self.with_cluster(&cmd.iter().map(OsString::as_os_str).collect::<Vec<_>>())
+    }
+
     /// Get the current status of a job.
     pub fn get_job_status(&self, job_id: u64) -> JobStatus {
         // First try squeue for active jobs
-        let result = run_slurm_command(
+        let result = self.run_slurm(
             &["squeue", "-j", &job_id.to_string(), "-h", "-o", "%T"],
             false,
         );
 
         if let Ok(cmd_result) = result {
-            if cmd_result.return_code == 0 && !cmd_result.stdout.trim().is_empty() {
-                let state = cmd_result.stdout.trim().to_uppercase();
-                return JobStatus::from_slurm_state(&state);
+            if cmd_result.return_code == 0 {
+                if let Some(status) = parse_squeue_states(&cmd_result.stdout) {
+                    return status;
+                }
             }
         }
 
         // If not in squeue, check sacct for completed/failed jobs
-        let result = run_slurm_command(
+        let result = self.run_slurm(
             &[
                 "sacct",
                 "-j",
@@ -103,16 +463,16 @@ impl JobManager {
                 "--format=State",
                 "--noheader",
                 "--parsable2",
+                SACCT_DELIMITER_ARG,
             ],
             false,
         );
 
         if let Ok(cmd_result) = result {
             if cmd_result.return_code == 0 && !cmd_result.stdout.trim().is_empty() {
-                let state = cmd_result
-                    .stdout
-                    .trim()
-                    .split('|')
+                let stdout = cmd_result.stdout.trim();
+                let state = stdout
+                    .split(sacct_delimiter(stdout))
                     .next()
                     .unwrap_or("")
                     .trim()
@@ -121,24 +481,75 @@ impl JobManager {
             }
         }
 
+        // The status monitor warns once a job stays unknown
+        diagnostics::debug(format!("Job {} not found in squeue or sacct", job_id));
         JobStatus::Unknown
     }
 
+    /// Check whether SLURM knows about a job at all.
+    ///
+    /// Unlike `get_job_status` returning `Unknown`, `Some(false)` means sacct
+    /// ran successfully and neither it nor squeue reported the job, e.g. for a
+    /// mistyped ID. Returns `None` if SLURM couldn't be queried.
+    pub fn job_exists(&self, job_id: u64) -> Option<bool> {
+        let id = job_id.to_string();
+        let squeue = self.run_slurm(&["squeue", "-j", &id, "-h", "-o", "%i"], false);
+        if let Ok(r) = squeue {
+            if r.return_code == 0 && !r.stdout.trim().is_empty() {
+                return Some(true);
+            }
+        }
+
+        let sacct = self.run_slurm(
+            &[
+                "sacct",
+                "-j",
+                &id,
+                "--format=JobID",
+                "--noheader",
+                "--parsable2",
+                SACCT_DELIMITER_ARG,
+            ],
+            false,
+        );
+        match sacct {
+            Ok(r) if r.return_code == 0 => Some(!r.stdout.trim().is_empty()),
+            _ => None,
+        }
+    }
+
     /// Get detailed information about a job including output paths.
+    ///
+    /// When SLURM has nothing on the job, or can't be reached, the log paths
+    /// are guessed from the usual `slurm-<id>.out` names instead.
     pub fn get_job_info(&self, job_id: u64) -> JobInfo {
+        match self.query_job_info(job_id) {
+            Ok(Some(info)) => info,
+            _ => self.guess_job_info(job_id),
+        }
+    }
+
+    /// Ask sacct, then scontrol, about a job.
+    ///
+    /// `Ok(None)` means scontrol answered that it doesn't know the job. An
+    /// error means neither command could give an answer, e.g. while the
+    /// controller restarts, so nothing is known about the job either way.
+    pub fn query_job_info(&self, job_id: u64) -> Result<Option<JobInfo>> {
         let mut info = JobInfo {
             job_id,
             ..Default::default()
         };
 
         // Use sacct to get comprehensive job information
-        let result = run_slurm_command(
+        let result = self.run_slurm(
             &[
                 "sacct",
                 "-j",
                 &job_id.to_string(),
-                "--format=JobID,JobName,State,Start,End,Elapsed,WorkDir,StdOut,StdErr",
+                // Comment last: it can hold anything, `|` included
+                "--format=JobID,JobName,State,Start,End,Elapsed,Timelimit,Partition,WorkDir,NodeList,ExitCode,StdOut,StdErr,AllocCPUS,AllocTRES,WCKey,Comment",
                 "--parsable2",
+                SACCT_DELIMITER_ARG,
             ],
             false,
         );
@@ -152,44 +563,326 @@ impl JobManager {
                 info.start_time = parsed.get("Start").cloned().unwrap_or_default();
                 info.end_time = parsed.get("End").cloned().unwrap_or_default();
                 info.elapsed = parsed.get("Elapsed").cloned().unwrap_or_default();
+                info.time_limit = parsed.get("Timelimit").cloned().unwrap_or_default();
+                info.partition = parsed.get("Partition").cloned().unwrap_or_default();
                 
                 let work_dir = parsed.get("WorkDir").cloned().unwrap_or_default();
                 info.work_dir = PathBuf::from(&work_dir);
 
-                // Get and process stdout path
-                let stdout_path = parsed.get("StdOut").cloned().unwrap_or_default();
-                info.stdout_path = self.resolve_output_path(&stdout_path, job_id, &work_dir);
+                info.node_list = parsed.get("NodeList").cloned().unwrap_or_default();
+                info.exit_code = parsed.get("ExitCode").filter(|code| !code.is_empty()).cloned();
+                info.wckey = parsed.get("WCKey").cloned().unwrap_or_default();
+                info.comment = parsed.get("Comment").cloned().unwrap_or_default();
+                info.alloc_cpus = parsed.get("AllocCPUS").and_then(|cpus| parse_alloc_cpus(cpus));
+                info.alloc_tres = parsed.get("AllocTRES").and_then(|tres| parse_tres(tres));
+                self.fill_output_paths(&mut info, &parsed, &work_dir);
+                return Ok(Some(info));
+            }
+            diagnostics::warn(format!(
+                "sacct returned no info for job {} (code {}): {}",
+                job_id,
+                cmd_result.return_code,
+                cmd_result.stderr.trim()
+            ));
+        }
 
-                // Get and process stderr path
-                let stderr_path = parsed.get("StdErr").cloned().unwrap_or_default();
-                info.stderr_path = self.resolve_output_path(&stderr_path, job_id, &work_dir);
+        // Sites without accounting still have scontrol for jobs the controller
+        // remembers (running, pending or recently finished)
+        if let Some(fields) = self.scontrol_job_fields(job_id)? {
+            let field = |key: &str| {
+                fields
+                    .get(key)
+                    .filter(|value| value.as_str() != "(null)")
+                    .cloned()
+                    .unwrap_or_default()
+            };
+            info.job_name = field("JobName");
+            info.state = fields
+                .get("JobState")
+                .cloned()
+                .unwrap_or_else(|| "UNKNOWN".to_string());
+            info.start_time = field("StartTime");
+            info.end_time = field("EndTime");
+            info.elapsed = field("RunTime");
+            info.time_limit = field("TimeLimit");
+            info.partition = field("Partition");
+            info.node_list = field("NodeList");
+            info.exit_code = Some(field("ExitCode")).filter(|code| !code.is_empty());
+            info.wckey = field("WCKey");
+            info.comment = field("Comment");
+            // scontrol shows what a pending job asks for; only count it once
+            // it has been allocated
+            if JobStatus::from_slurm_state(&info.state) != JobStatus::Queued {
+                info.alloc_cpus = parse_alloc_cpus(&field("NumCPUs"));
+                info.alloc_tres = parse_tres(&field("TRES"));
+            }
 
-                debug_log(&format!("get_job_info: job_id={} stdout={} stderr={}", job_id, info.stdout_path.display(), info.stderr_path.display()));
+            let work_dir = field("WorkDir");
+            info.work_dir = PathBuf::from(&work_dir);
+            self.fill_output_paths(&mut info, &fields, &work_dir);
+            return Ok(Some(info));
+        }
+        Ok(None)
+    }
+
+    /// A job's info with only its log paths, guessed from common patterns
+    /// in the current directory.
+    pub fn guess_job_info(&self, job_id: u64) -> JobInfo {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        JobInfo {
+            job_id,
+            stdout_path: self.find_output_file(&cwd, job_id, "out"),
+            stderr_path: self.find_output_file(&cwd, job_id, "err"),
+            work_dir: cwd,
+            ..Default::default()
+        }
+    }
 
-                return info;
+    /// Get the status and details of a job that is still in the queue, with a
+    /// single squeue call.
+    ///
+    /// Cheaper and more current than `get_job_info` for active jobs, but
+    /// squeue doesn't report log paths, so `stdout_path`/`stderr_path` are
+    /// left empty. Returns `None` once the job has left the queue; sacct then
+    /// has the final state.
+    pub fn get_queue_info(&self, job_id: u64) -> Option<(JobStatus, JobInfo)> {
+        let result = self
+            .run_slurm(
+                &["squeue", "-j", &job_id.to_string(), "-h", "-o", "%T|%j|%S|%M|%P|%Z|%N|%l"],
+                false,
+            )
+            .ok()?;
+        if result.return_code != 0 {
+            return None;
+        }
+        parse_squeue_info(job_id, &result.stdout)
+    }
+
+    /// `key=value` fields of `scontrol show job`, or None if the controller
+    /// doesn't know the job. An error if it couldn't say.
+    fn scontrol_job_fields(&self, job_id: u64) -> Result<Option<HashMap<String, String>>> {
+        let result = self.run_slurm(&["scontrol", "show", "job", &job_id.to_string()], false)?;
+        if result.return_code != 0 {
+            if result.stderr.contains("Invalid job id") {
+                diagnostics::debug(format!("scontrol doesn't know job {}", job_id));
+                return Ok(None);
             }
+            anyhow::bail!("scontrol show job {} failed: {}", job_id, result.stderr.trim());
+        }
+        let fields = parse_scontrol_output(&result.stdout);
+        if fields.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(fields))
         }
+    }
 
-        // Fallback: try to construct paths from common patterns
-        let cwd = std::env::current_dir().unwrap_or_default();
-        info.work_dir = cwd.clone();
-        info.stdout_path = self.find_output_file(&cwd, job_id, "out");
-        info.stderr_path = self.find_output_file(&cwd, job_id, "err");
+    /// Resolve the StdOut/StdErr fields of sacct or scontrol output into
+    /// absolute log paths.
+    fn fill_output_paths(
+        &self,
+        info: &mut JobInfo,
+        fields: &HashMap<String, String>,
+        work_dir: &str,
+    ) {
+        let placeholders = OutputPlaceholders {
+            job_id: info.job_id,
+            job_name: &info.job_name,
+            user: current_user(self.runner.as_ref()),
+            node: first_node(&info.node_list),
+            array: array_ids(fields),
+        };
+
+        let stdout_path = fields.get("StdOut").cloned().unwrap_or_default();
+        let stderr_path = fields.get("StdErr").cloned().unwrap_or_default();
+        let stdout_path = self.resolve_output_path(&stdout_path, &placeholders, work_dir);
+        let stderr_path = self.resolve_output_path(&stderr_path, &placeholders, work_dir);
+        info.stdout_path = stdout_path;
+        info.stderr_path = stderr_path;
+
+        diagnostics::debug(format!(
+            "get_job_info: job_id={} stdout={} stderr={}",
+            info.job_id,
+            info.stdout_path.display(),
+            info.stderr_path.display()
+        ));
+    }
+
+    /// Get the resource usage summary of a job.
+    pub fn get_job_summary(&self, job_id: u64) -> Option<JobSummary> {
+        let result = self.run_slurm(
+            &[
+                "sacct",
+                "-j",
+                &job_id.to_string(),
+                "--format=JobID,ExitCode,Elapsed,TotalCPU,AllocCPUS,NNodes,ReqMem,MaxRSS",
+                "--noheader",
+                "--parsable2",
+                SACCT_DELIMITER_ARG,
+            ],
+            false,
+        )
+        .ok()?;
+
+        if result.return_code != 0 {
+            return None;
+        }
+        parse_job_summary(&result.stdout)
+    }
+
+    /// Get the steps of a job with their own state, elapsed time and peak
+    /// memory.
+    ///
+    /// Returns an empty list if the job has no steps yet or sacct fails.
+    pub fn get_job_steps(&self, job_id: u64) -> Vec<JobStep> {
+        let result = self.run_slurm(
+            &[
+                "sacct",
+                "-j",
+                &job_id.to_string(),
+                "--format=JobID,JobName,State,Elapsed,MaxRSS",
+                "--parsable2",
+                SACCT_DELIMITER_ARG,
+            ],
+            false,
+        );
 
-        info
+        match result {
+            Ok(result) if result.return_code == 0 => parse_job_steps(&result.stdout),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the tasks of an array job, in task order with pending ranges last.
+    /// Empty for jobs that aren't arrays.
+    pub fn get_array_tasks(&self, job_id: u64) -> Vec<ArrayTask> {
+        let result = self.run_slurm(
+            &[
+                "sacct",
+                "-j",
+                &job_id.to_string(),
+                "-X",
+                "--format=JobID,JobIDRaw,State,Elapsed",
+                "--parsable2",
+                SACCT_DELIMITER_ARG,
+            ],
+            false,
+        );
+
+        match result {
+            Ok(result) if result.return_code == 0 => parse_array_tasks(&result.stdout),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Get the current memory use of a running job's batch step from sstat.
+    ///
+    /// sstat only knows about running jobs, so `None` covers finished and
+    /// queued jobs as well as errors.
+    pub fn get_live_memory(&self, job_id: u64) -> Option<LiveMemory> {
+        let step = format!("{}.batch", job_id);
+        let result = self.run_slurm(
+            &["sstat", "-j", &step, "--format=MaxRSS,AveRSS", "--noheader", "--parsable2"],
+            false,
+        )
+        .ok()?;
+
+        if result.return_code != 0 {
+            return None;
+        }
+        parse_live_memory(&result.stdout)
+    }
+
+    /// Get the CPUs and other resources allocated to a job, e.g. once a job
+    /// followed with squeue has started.
+    pub fn get_allocation(&self, job_id: u64) -> (Option<u64>, Option<Tres>) {
+        let result = self.run_slurm(
+            &[
+                "sacct",
+                "-j",
+                &job_id.to_string(),
+                "-X",
+                "--noheader",
+                "--parsable2",
+                SACCT_DELIMITER_ARG,
+                "--format=AllocCPUS,AllocTRES",
+            ],
+            false,
+        );
+        let stdout = match result {
+            Ok(result) if result.return_code == 0 => result.stdout,
+            _ => return (None, None),
+        };
+        match stdout.lines().next().and_then(|line| line.split_once(sacct_delimiter(line))) {
+            Some((cpus, tres)) => (parse_alloc_cpus(cpus), parse_tres(tres)),
+            None => (None, None),
+        }
+    }
+
+    /// Get the pending reason, estimated start time and dependencies of a
+    /// queued job.
+    ///
+    /// Returns `None` if the job is no longer in squeue.
+    pub fn get_pending_details(&self, job_id: u64) -> Option<PendingDetails> {
+        let result = self.run_slurm(
+            &["squeue", "-j", &job_id.to_string(), "-h", "-o", "%r|%S|%E"],
+            false,
+        )
+        .ok()?;
+
+        if result.return_code != 0 {
+            return None;
+        }
+        parse_pending_details(&result.stdout)
+    }
+
+    /// Get the batch script a job was submitted with.
+    ///
+    /// Asks `scontrol write batch_script <id> -` first. scontrol usually drops
+    /// the script once a job has finished, so fall back to the script path
+    /// recorded at submission when this manager submitted the job.
+    pub fn get_batch_script(&self, job_id: u64) -> Result<String> {
+        let result = self.run_slurm(
+            &["scontrol", "write", "batch_script", &job_id.to_string(), "-"],
+            false,
+        );
+
+        if let Ok(cmd_result) = result {
+            if cmd_result.return_code == 0 && !cmd_result.stdout.trim().is_empty() {
+                return Ok(cmd_result.stdout);
+            }
+            diagnostics::warn(format!(
+                "get_batch_script: scontrol failed for job {}: {}",
+                job_id,
+                cmd_result.stderr.trim()
+            ));
+        }
+
+        if let Some(script) = self
+            .tracked_jobs
+            .get(&job_id)
+            .and_then(|metadata| metadata.get("script"))
+        {
+            return std::fs::read_to_string(script)
+                .with_context(|| format!("Failed to read batch script {}", script));
+        }
+
+        anyhow::bail!("No batch script available for job {}", job_id)
     }
 
     /// Resolve output path, replacing SLURM placeholders.
-    fn resolve_output_path(&self, path: &str, job_id: u64, work_dir: &str) -> PathBuf {
+    fn resolve_output_path(
+        &self,
+        path: &str,
+        placeholders: &OutputPlaceholders,
+        work_dir: &str,
+    ) -> PathBuf {
         if path.is_empty() {
             return PathBuf::new();
         }
 
         // Replace SLURM placeholders
-        let resolved = path
-            .replace("%j", &job_id.to_string())
-            .replace("%A", &job_id.to_string())
-            .replace("%a", "0"); // Default to 0 for non-array jobs
+        let resolved = expand_output_pattern(path, placeholders);
 
         let path = PathBuf::from(&resolved);
 
@@ -224,32 +917,392 @@ impl JobManager {
     }
 
     /// List all currently tracked job IDs.
-    #[allow(dead_code)]
     pub fn list_tracked_jobs(&self) -> Vec<u64> {
         self.tracked_jobs.keys().copied().collect()
     }
 
     /// Add a job to the tracking list.
     pub fn add_tracked_job(&mut self, job_id: u64) {
-        self.tracked_jobs.entry(job_id).or_insert_with(HashMap::new);
+        self.tracked_jobs.entry(job_id).or_default();
     }
 
     /// Remove a job from the tracking list.
-    #[allow(dead_code)]
     pub fn remove_tracked_job(&mut self, job_id: u64) {
         self.tracked_jobs.remove(&job_id);
     }
 
     /// Check if a job is being tracked.
-    #[allow(dead_code)]
     pub fn is_tracking(&self, job_id: u64) -> bool {
         self.tracked_jobs.contains_key(&job_id)
     }
 }
 
+/// Drop the `CLUSTER: <name>` lines squeue and friends print before each
+/// cluster's output when given `--clusters`, even with `-h`.
+fn strip_cluster_banners(stdout: &str) -> String {
+    stdout
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with("CLUSTER: "))
+        .collect()
+}
+
+/// Check that `script` is a regular file we can read, so sbatch isn't handed
+/// a path it would reject with a less helpful message.
+fn check_script(script: &Path) -> Result<()> {
+    let describe = |e: std::io::Error| match e.kind() {
+        ErrorKind::NotFound => anyhow::anyhow!("Script not found: {}", script.display()),
+        ErrorKind::PermissionDenied => {
+            anyhow::anyhow!("Permission denied reading script {}", script.display())
+        }
+        _ => anyhow::Error::new(e).context(format!("Cannot read script {}", script.display())),
+    };
+
+    let metadata = std::fs::metadata(script).map_err(describe)?;
+    if metadata.is_dir() {
+        anyhow::bail!("Script {} is a directory, not a batch script", script.display());
+    }
+    if !metadata.is_file() {
+        anyhow::bail!("Script {} is not a regular file", script.display());
+    }
+    File::open(script).map_err(describe)?;
+    Ok(())
+}
+
+/// Parse `sacct --format=JobID,ExitCode,Elapsed,TotalCPU,AllocCPUS,NNodes,ReqMem,MaxRSS`
+/// output. The first row is the allocation; MaxRSS is only set on step rows.
+fn parse_job_summary(output: &str) -> Option<JobSummary> {
+    let mut rows = output.lines().filter(|l| !l.trim().is_empty()).map(|line| {
+        line.split(sacct_delimiter(line)).map(|f| f.trim()).collect::<Vec<_>>()
+    });
+
+    let job = rows.next()?;
+    if job.len() < 8 {
+        return None;
+    }
+
+    let alloc_cpus: f64 = job[4].parse().unwrap_or(0.0);
+    let nodes: f64 = job[5].parse().unwrap_or(1.0);
+    let elapsed = parse_slurm_duration(job[2]).map(|d| d.as_secs_f64());
+    let total_cpu = parse_slurm_duration(job[3]).map(|d| d.as_secs_f64());
+    let cpu_efficiency = match (total_cpu, elapsed) {
+        (Some(used), Some(wall)) if wall > 0.0 && alloc_cpus > 0.0 => {
+            Some(used / (wall * alloc_cpus) * 100.0)
+        }
+        _ => None,
+    };
+
+    // Older SLURM versions suffix ReqMem with c (per CPU) or n (per node)
+    let req_mem_mib = match job[6].strip_suffix('c') {
+        Some(per_cpu) => parse_slurm_memory(per_cpu, 'M').map(|m| m * alloc_cpus),
+        None => match job[6].strip_suffix('n') {
+            Some(per_node) => parse_slurm_memory(per_node, 'M').map(|m| m * nodes),
+            None => parse_slurm_memory(job[6], 'M'),
+        },
+    };
+
+    let max_rss_mib = std::iter::once(job.clone())
+        .chain(rows)
+        .filter_map(|row| row.get(7).and_then(|rss| parse_slurm_memory(rss, 'K')))
+        .reduce(f64::max);
+
+    Some(JobSummary {
+        exit_code: job[1].to_string(),
+        elapsed: job[2].to_string(),
+        cpu_efficiency,
+        max_rss_mib,
+        req_mem_mib,
+    })
+}
+
+/// Parse `sacct --format=JobID,JobName,State,Elapsed,MaxRSS --parsable2` output
+/// into the job's steps, skipping the allocation row (or, for a heterogeneous
+/// job, the row of each component).
+fn parse_job_steps(output: &str) -> Vec<JobStep> {
+    parse_sacct_multiple_output(output)
+        .into_iter()
+        .filter_map(|row| {
+            let field = |name: &str| row.get(name).cloned().unwrap_or_default();
+            let job_id = row.get("JobID")?;
+            let step_id = match job_id.split_once('+') {
+                Some((_, step_id)) if step_id.contains('.') => step_id,
+                Some(_) => return None,
+                None => job_id.split_once('.')?.1,
+            };
+            Some(JobStep {
+                step_id: step_id.to_string(),
+                name: field("JobName"),
+                state: field("State"),
+                elapsed: field("Elapsed"),
+                max_rss_mib: row.get("MaxRSS").and_then(|rss| parse_slurm_memory(rss, 'K')),
+            })
+        })
+        .collect()
+}
+
+/// Parse `sacct -X --format=JobID,JobIDRaw,State,Elapsed --parsable2` output
+/// into an array job's tasks, skipping rows that aren't array tasks.
+fn parse_array_tasks(output: &str) -> Vec<ArrayTask> {
+    let mut tasks: Vec<ArrayTask> = parse_sacct_multiple_output(output)
+        .into_iter()
+        .filter_map(|row| {
+            let field = |name: &str| row.get(name).cloned().unwrap_or_default();
+            let (_, task_id) = row.get("JobID")?.split_once('_')?;
+            let raw_job_id = row
+                .get("JobIDRaw")
+                .filter(|_| !task_id.starts_with('['))
+                .and_then(|id| id.parse().ok());
+            Some(ArrayTask {
+                task_id: task_id.to_string(),
+                raw_job_id,
+                state: field("State"),
+                elapsed: field("Elapsed"),
+            })
+        })
+        .collect();
+    tasks.sort_by_key(|task| task.task_id.parse::<u64>().map_or((1, 0), |index| (0, index)));
+    tasks
+}
+
+/// Parse an AllocCPUS or NumCPUs value; a job that hasn't started has 0.
+fn parse_alloc_cpus(value: &str) -> Option<u64> {
+    value.trim().parse().ok().filter(|&cpus| cpus > 0)
+}
+
+/// Array job ID and task index of an array task (`123_4` in sacct's JobID,
+/// or scontrol's ArrayJobId and ArrayTaskId), for `%A` and `%a`.
+fn array_ids(fields: &HashMap<String, String>) -> Option<(u64, u64)> {
+    let (array_job_id, task_id) = match (fields.get("ArrayJobId"), fields.get("ArrayTaskId")) {
+        (Some(array_job_id), Some(task_id)) => (array_job_id.as_str(), task_id.as_str()),
+        _ => fields.get("JobID")?.split_once('_')?,
+    };
+    Some((array_job_id.parse().ok()?, task_id.parse().ok()?))
+}
+
+/// Parse `sstat --format=MaxRSS,AveRSS --noheader --parsable2` output.
+fn parse_live_memory(output: &str) -> Option<LiveMemory> {
+    let line = output.lines().find(|l| !l.trim().is_empty())?;
+    let mut fields = line.split('|').map(|f| parse_slurm_memory(f, 'K'));
+    let max_rss = fields.next().flatten();
+    let ave_rss = fields.next().flatten();
+    match (ave_rss, max_rss) {
+        (Some(ave), max) => Some(LiveMemory {
+            rss_mib: ave,
+            max_rss_mib: max,
+        }),
+        (None, Some(max)) => Some(LiveMemory {
+            rss_mib: max,
+            max_rss_mib: None,
+        }),
+        (None, None) => None,
+    }
+}
+
+/// Combine `squeue -o %T` output into one status.
+///
+/// Array and heterogeneous jobs print one line per task or component, each
+/// with its own state. The job takes the first of them by `JobStatus::rank`:
+/// running if any line is, then suspended, then queued; if every line has
+/// finished, the worst outcome wins.
+fn parse_squeue_states(output: &str) -> Option<JobStatus> {
+    output
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(JobStatus::from_slurm_state)
+        .min_by_key(JobStatus::rank)
+}
+
+/// Parse `squeue -o "%T|%j|%S|%M|%P|%Z|%N|%l"` output.
+///
+/// The status combines every line as in `parse_squeue_states`; the other
+/// fields come from the first line. Values are normalized to match sacct:
+/// elapsed time as `[D-]HH:MM:SS`, and no start time before the job starts.
+fn parse_squeue_info(job_id: u64, output: &str) -> Option<(JobStatus, JobInfo)> {
+    let lines: Vec<&str> = output.lines().map(str::trim).filter(|l| !l.is_empty()).collect();
+    let states: Vec<&str> = lines
+        .iter()
+        .map(|line| line.split('|').next().unwrap_or(""))
+        .collect();
+    let status = parse_squeue_states(&states.join("\n"))?;
+
+    let fields: Vec<&str> = lines[0].splitn(8, '|').collect();
+    if fields.len() < 7 {
+        return None;
+    }
+    let known = |value: &str| match value.trim() {
+        "N/A" | "(null)" => String::new(),
+        value => value.to_string(),
+    };
+
+    let start_time = if status == JobStatus::Queued {
+        "Unknown".to_string()
+    } else {
+        known(fields[2])
+    };
+    let elapsed = parse_slurm_duration(fields[3])
+        .map(format_slurm_duration)
+        .unwrap_or_default();
+    let work_dir = known(fields[5]);
+
+    let info = JobInfo {
+        job_id,
+        job_name: known(fields[1]),
+        state: fields[0].trim().to_string(),
+        start_time,
+        end_time: "Unknown".to_string(),
+        elapsed,
+        partition: known(fields[4]),
+        work_dir: PathBuf::from(work_dir),
+        node_list: known(fields[6]),
+        time_limit: fields.get(7).map(|limit| known(limit)).unwrap_or_default(),
+        ..Default::default()
+    };
+    Some((status, info))
+}
+
+/// Parse `squeue -o "%r|%S|%E"` output.
+fn parse_pending_details(output: &str) -> Option<PendingDetails> {
+    let line = output.lines().map(|l| l.trim()).find(|l| !l.is_empty())?;
+    let mut fields = line.splitn(3, '|');
+
+    let mut known = || match fields.next().unwrap_or("").trim() {
+        "" | "N/A" | "None" | "Unknown" | "(null)" => String::new(),
+        value => value.to_string(),
+    };
+    let reason = known();
+    let estimated_start = known();
+    // Terms are separated by `,` (all must hold) or `?` (any may hold)
+    let dependencies = known()
+        .split([',', '?'])
+        .map(|term| term.trim().to_string())
+        .filter(|term| !term.is_empty())
+        .collect();
+
+    Some(PendingDetails {
+        reason,
+        estimated_start,
+        dependencies,
+    })
+}
+
+/// Job IDs referenced by a dependency term such as `afterok:123:124(unfulfilled)`.
+///
+/// Array task suffixes (`123_*`) and `after` delays (`123+10`) are ignored;
+/// terms without job IDs, like `singleton`, yield nothing.
+pub fn dependency_job_ids(dependency: &str) -> Vec<u64> {
+    let spec = dependency.split('(').next().unwrap_or("");
+    spec.split(':')
+        .skip(1)
+        .filter_map(|id| {
+            let digits: String = id.chars().take_while(|c| c.is_ascii_digit()).collect();
+            digits.parse().ok()
+        })
+        .collect()
+}
+
+/// Values substituted into `--output`/`--error` filename patterns.
+struct OutputPlaceholders<'a> {
+    job_id: u64,
+    job_name: &'a str,
+    user: Option<String>,
+    /// Node the batch step runs on
+    node: Option<String>,
+    /// Array job ID and task index, for an array task
+    array: Option<(u64, u64)>,
+}
+
+/// Expand SLURM filename pattern placeholders (`%j`, `%x`, `%N`, ...).
+///
+/// Zero-padding widths such as `%4j` are honoured. Placeholders that can't
+/// be resolved are logged and dropped instead of being left in the path.
+fn expand_output_pattern(pattern: &str, values: &OutputPlaceholders) -> String {
+    let mut result = String::with_capacity(pattern.len());
+    let mut chars = pattern.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            result.push(c);
+            continue;
+        }
+
+        let mut width = String::new();
+        while let Some(d) = chars.peek().filter(|d| d.is_ascii_digit()) {
+            width.push(*d);
+            chars.next();
+        }
+        let width: usize = width.parse().unwrap_or(0);
+
+        let Some(spec) = chars.next() else {
+            // Trailing '%' is taken literally
+            result.push('%');
+            break;
+        };
+
+        let value = match spec {
+            '%' => Some("%".to_string()),
+            'j' => Some(format!("{:0width$}", values.job_id, width = width)),
+            'A' => {
+                let array_job_id = values.array.map_or(values.job_id, |(id, _)| id);
+                Some(format!("{:0width$}", array_job_id, width = width))
+            }
+            'a' => {
+                let task_id = values.array.map_or(0, |(_, task_id)| task_id);
+                Some(format!("{:0width$}", task_id, width = width))
+            }
+            // Node and task offsets are 0 for the batch step
+            'n' | 't' => Some(format!("{:0width$}", 0, width = width)),
+            'x' if !values.job_name.is_empty() => Some(values.job_name.to_string()),
+            'u' => values.user.clone(),
+            'N' => values.node.clone(),
+            _ => None,
+        };
+
+        match value {
+            Some(value) => result.push_str(&value),
+            None => diagnostics::warn(format!(
+                "expand_output_pattern: cannot resolve %{} in {} for job {}",
+                spec, pattern, values.job_id
+            )),
+        }
+    }
+
+    result
+}
+
+/// Name of the user running the monitor, from `$USER` or `whoami`.
+fn current_user(runner: &dyn CommandRunner) -> Option<String> {
+    if let Some(user) = std::env::var("USER").ok().filter(|u| !u.is_empty()) {
+        return Some(user);
+    }
+
+    runner
+        .run(&["whoami"], false)
+        .ok()
+        .filter(|r| r.return_code == 0)
+        .map(|r| r.stdout.trim().to_string())
+        .filter(|u| !u.is_empty())
+}
+
+/// First hostname in a SLURM node list such as `gpu[01-04,07],cpu3`.
+fn first_node(node_list: &str) -> Option<String> {
+    let node_list = node_list.trim();
+    if node_list.is_empty() || node_list.starts_with("None") {
+        return None;
+    }
+
+    match node_list.split_once('[') {
+        Some((prefix, range)) if !prefix.contains(',') => {
+            let first = range.split([',', '-', ']']).next()?;
+            Some(format!("{}{}", prefix, first))
+        }
+        _ => node_list.split(',').next().map(|n| n.to_string()),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::FakeRunner;
 
     #[test]
     fn test_job_manager_new() {
@@ -270,12 +1323,660 @@ mod tests {
         assert!(manager.list_tracked_jobs().is_empty());
     }
 
+    #[test]
+    fn test_get_batch_script_falls_back_to_submitted_script() {
+        let mut script = tempfile::NamedTempFile::new().unwrap();
+        std::io::Write::write_all(&mut script, b"#!/bin/bash\necho hi\n").unwrap();
+
+        let mut manager = JobManager::new();
+        let mut metadata = HashMap::new();
+        metadata.insert(
+            "script".to_string(),
+            script.path().to_string_lossy().to_string(),
+        );
+        manager.tracked_jobs.insert(424242, metadata);
+
+        let content = manager.get_batch_script(424242).unwrap();
+        assert!(content.contains("echo hi"));
+        assert!(manager.get_batch_script(424243).is_err());
+    }
+
+    fn fake_manager(runner: FakeRunner) -> (JobManager, Arc<FakeRunner>) {
+        let runner = Arc::new(runner);
+        (JobManager::with_runner(runner.clone()), runner)
+    }
+
+    #[test]
+    fn test_get_job_status_prefers_squeue_then_sacct() {
+        let (manager, _) = fake_manager(
+            FakeRunner::default()
+                .respond("squeue -j 1 ", "RUNNING\n")
+                .respond("sacct -j 2 ", "CANCELLED by 1000|\n")
+                .respond("sacct -j 3 ", "OUT_OF_MEMORY|\n"),
+        );
+
+        assert_eq!(manager.get_job_status(1), JobStatus::Running);
+        assert_eq!(manager.get_job_status(2), JobStatus::Cancelled);
+        assert_eq!(manager.get_job_status(3), JobStatus::OutOfMemory);
+        assert_eq!(manager.get_job_status(4), JobStatus::Unknown);
+    }
+
+    #[test]
+    fn test_parse_squeue_states() {
+        assert_eq!(parse_squeue_states(""), None);
+        assert_eq!(parse_squeue_states("RUNNING\n"), Some(JobStatus::Running));
+        // Array job with some tasks still pending
+        assert_eq!(
+            parse_squeue_states("PENDING\nRUNNING\nRUNNING\nCOMPLETING\n"),
+            Some(JobStatus::Running)
+        );
+        assert_eq!(parse_squeue_states("PENDING\nSUSPENDED\n"), Some(JobStatus::Suspended));
+        assert_eq!(parse_squeue_states("PENDING\nBOGUS\n"), Some(JobStatus::Queued));
+        assert_eq!(
+            parse_squeue_states("COMPLETED\nFAILED\nCANCELLED\n"),
+            Some(JobStatus::Failed)
+        );
+    }
+
+    #[test]
+    fn test_job_exists() {
+        let (manager, _) = fake_manager(
+            FakeRunner::default()
+                .respond("squeue -j 1 ", "1\n")
+                .respond("sacct -j 2 ", "2\n2.batch\n")
+                .respond("sacct -j 3 ", ""),
+        );
+
+        assert_eq!(manager.job_exists(1), Some(true));
+        assert_eq!(manager.job_exists(2), Some(true));
+        assert_eq!(manager.job_exists(3), Some(false));
+        // sacct failing says nothing about the job
+        assert_eq!(manager.job_exists(4), None);
+    }
+
+    #[test]
+    fn test_get_job_info_from_sacct() {
+        let sacct = "\
+JobID|JobName|State|Start|End|Elapsed|Partition|WorkDir|NodeList|ExitCode|StdOut|StdErr
+77|train|FAILED|2024-01-31T08:00:00|Unknown|00:05:00|gpu|/home/alice/run|gpu[01-02]|1:0||
+77.batch|batch|FAILED|2024-01-31T08:00:00|Unknown|00:05:00||/home/alice/run|gpu01|1:0|%x-%j.out|/logs/%x-%j.err
+";
+        let (manager, runner) = fake_manager(FakeRunner::default().respond("sacct -j 77 ", sacct));
+
+        let info = manager.get_job_info(77);
+        assert_eq!(info.job_name, "train");
+        assert_eq!(info.state, "FAILED");
+        assert_eq!(info.elapsed, "00:05:00");
+        assert_eq!(info.exit_code.as_deref(), Some("1:0"));
+        assert!(info.exit_code_failed());
+        assert_eq!(info.partition, "gpu");
+        assert_eq!(info.node_list, "gpu[01-02]");
+        assert_eq!(info.work_dir, PathBuf::from("/home/alice/run"));
+        assert_eq!(info.stdout_path, PathBuf::from("/home/alice/run/train-77.out"));
+        assert_eq!(info.stderr_path, PathBuf::from("/logs/train-77.err"));
+        assert!(runner.calls()[0].contains("--parsable2"));
+    }
+
+    #[test]
+    fn test_get_job_info_keeps_comment_with_pipes() {
+        let sacct = "\
+JobID|JobName|State|Start|End|Elapsed|Timelimit|Partition|WorkDir|NodeList|ExitCode|StdOut|StdErr|WCKey|Comment
+78|train|RUNNING|2024-01-31T08:00:00|Unknown|00:05:00|01:00:00|gpu|/home/alice/run|gpu01|0:0||||project=x|owner=y
+78.batch|batch|RUNNING|2024-01-31T08:00:00|Unknown|00:05:00||||gpu01|0:0|/logs/78.out|/logs/78.err||
+";
+        let (manager, _) = fake_manager(FakeRunner::default().respond("sacct -j 78 ", sacct));
+
+        let info = manager.get_job_info(78);
+        assert_eq!(info.comment, "project=x|owner=y");
+        assert_eq!(info.wckey, "");
+        assert_eq!(info.work_dir, PathBuf::from("/home/alice/run"));
+        assert_eq!(info.node_list, "gpu01");
+        assert_eq!(info.exit_code.as_deref(), Some("0:0"));
+        assert_eq!(info.stdout_path, PathBuf::from("/logs/78.out"));
+
+        let sacct = sacct.replace("||||project", "|||*ml|project");
+        let (manager, _) = fake_manager(FakeRunner::default().respond("sacct -j 78 ", &sacct));
+        let info = manager.get_job_info(78);
+        assert_eq!(info.comment, "project=x|owner=y");
+        assert_eq!(info.wckey, "*ml");
+
+        // Paths and allocation set on the job row itself
+        let sacct = "\
+JobID|JobName|State|WorkDir|ExitCode|StdOut|StdErr|AllocCPUS|AllocTRES|WCKey|Comment
+78|train|RUNNING|/home/alice/run|0:0|/logs/78.out|/logs/78.err|16|cpu=16,mem=64G|*ml|project=x|owner=y
+";
+        let (manager, _) = fake_manager(FakeRunner::default().respond("sacct -j 78 ", sacct));
+        let info = manager.get_job_info(78);
+        assert_eq!(info.stdout_path, PathBuf::from("/logs/78.out"));
+        assert_eq!(info.stderr_path, PathBuf::from("/logs/78.err"));
+        assert_eq!(info.alloc_cpus, Some(16));
+        assert_eq!(info.wckey, "*ml");
+        assert_eq!(info.comment, "project=x|owner=y");
+    }
+
+    #[test]
+    fn test_get_job_info_with_pipes_in_name_and_work_dir() {
+        let sacct = "\
+JobID\x1fJobName\x1fState\x1fWorkDir\x1fExitCode\x1fStdOut\x1fStdErr\x1fComment
+79\x1fa|b\x1fFAILED\x1f/home/alice/x|y\x1f1:0\x1f\x1f\x1fnote
+79.batch\x1fbatch\x1fFAILED\x1f/home/alice/x|y\x1f1:0\x1f%x.out\x1f/logs/%j.err\x1f
+";
+        let (manager, runner) = fake_manager(FakeRunner::default().respond("sacct -j 79 ", sacct));
+
+        let info = manager.get_job_info(79);
+        assert!(runner.calls()[0].contains("--delimiter=\x1f"));
+        assert_eq!(info.job_name, "a|b");
+        assert_eq!(info.state, "FAILED");
+        assert_eq!(info.work_dir, PathBuf::from("/home/alice/x|y"));
+        assert_eq!(info.exit_code.as_deref(), Some("1:0"));
+        assert_eq!(info.stdout_path, PathBuf::from("/home/alice/x|y/a|b.out"));
+        assert_eq!(info.stderr_path, PathBuf::from("/logs/79.err"));
+        assert_eq!(info.comment, "note");
+    }
+
+    #[test]
+    fn test_get_job_info_allocation() {
+        let sacct = "\
+JobID|JobName|State|WorkDir|StdOut|StdErr|AllocCPUS|AllocTRES|WCKey|Comment
+80|train|RUNNING|/home/alice|%j.out|%j.err|32|billing=32,cpu=32,gres/gpu=4,mem=256G,node=2||
+";
+        let (manager, _) = fake_manager(FakeRunner::default().respond("sacct -j 80 ", sacct));
+        let info = manager.get_job_info(80);
+        assert_eq!(info.alloc_cpus, Some(32));
+        assert_eq!(info.alloc_tres.map(|tres| (tres.gpus, tres.mem_mib)), Some((4, 262144.0)));
+
+        // Pending jobs have nothing allocated yet
+        let pending = sacct
+            .replace("RUNNING", "PENDING")
+            .replace("|32|billing=32,cpu=32,gres/gpu=4,mem=256G,node=2", "|0|");
+        let (manager, _) = fake_manager(FakeRunner::default().respond("sacct -j 80 ", &pending));
+        let info = manager.get_job_info(80);
+        assert_eq!((info.alloc_cpus, info.alloc_tres), (None, None));
+
+        let (manager, _) = fake_manager(
+            FakeRunner::default().respond("sacct -j 81 -X", "8|cpu=8,mem=16G,node=1\n"),
+        );
+        let (cpus, tres) = manager.get_allocation(81);
+        assert_eq!(cpus, Some(8));
+        assert_eq!(tres.unwrap().mem_mib, 16384.0);
+    }
+
+    #[test]
+    fn test_get_queue_info() {
+        let (manager, runner) = fake_manager(
+            FakeRunner::default()
+                .respond("squeue -j 77 ", "RUNNING|train|2024-01-31T08:00:00|5:03|gpu|/home/alice/run|gpu01|1:00:00\n")
+                .respond("squeue -j 78 ", "PENDING|eval|2024-02-01T00:00:00|0:00|gpu|/home/alice|\n")
+                .respond("squeue -j 79 ", "RUNNING|sweep|N/A|1-02:03:04|cpu|/tmp|n[1-2]\nPENDING|sweep|N/A|0:00|cpu|/tmp|\n"),
+        );
+
+        let (status, info) = manager.get_queue_info(77).unwrap();
+        assert_eq!(status, JobStatus::Running);
+        assert_eq!(info.job_name, "train");
+        assert_eq!(info.state, "RUNNING");
+        assert_eq!(info.start_time, "2024-01-31T08:00:00");
+        assert_eq!(info.elapsed, "00:05:03");
+        assert_eq!(info.partition, "gpu");
+        assert_eq!(info.work_dir, PathBuf::from("/home/alice/run"));
+        assert_eq!(info.node_list, "gpu01");
+        assert_eq!(info.time_limit, "1:00:00");
+        assert!(info.stdout_path.as_os_str().is_empty());
+        assert_eq!(runner.calls(), ["squeue -j 77 -h -o %T|%j|%S|%M|%P|%Z|%N|%l"]);
+
+        // The start time of a pending job is only an estimate
+        let (status, info) = manager.get_queue_info(78).unwrap();
+        assert_eq!(status, JobStatus::Queued);
+        assert_eq!(info.start_time, "Unknown");
+
+        // Array tasks: any running task makes the job running
+        let (status, info) = manager.get_queue_info(79).unwrap();
+        assert_eq!(status, JobStatus::Running);
+        assert_eq!(info.elapsed, "1-02:03:04");
+        assert_eq!(info.start_time, "");
+
+        // Gone from the queue
+        assert!(manager.get_queue_info(80).is_none());
+    }
+
+    #[test]
+    fn test_exit_code_failed() {
+        let info = |code: Option<&str>| JobInfo {
+            exit_code: code.map(String::from),
+            ..Default::default()
+        };
+        assert!(!info(None).exit_code_failed());
+        assert!(!info(Some("0:0")).exit_code_failed());
+        assert!(info(Some("1:0")).exit_code_failed());
+        // Killed by a signal
+        assert!(info(Some("0:9")).exit_code_failed());
+    }
+
+    #[test]
+    fn test_get_job_info_falls_back_to_scontrol() {
+        let scontrol = "\
+JobId=88 JobName=eval
+   UserId=alice(1000) GroupId=alice(1000)
+   JobState=RUNNING Reason=None Dependency=(null)
+   RunTime=00:02:00 TimeLimit=01:00:00 ExitCode=0:0
+   StartTime=2024-01-31T08:00:00 EndTime=2024-01-31T09:00:00
+   Partition=gpu AllocNode:Sid=login01:1234
+   NodeList=gpu03
+   WorkDir=/home/alice/eval
+   StdErr=/home/alice/eval/eval-88.err
+   StdOut=/home/alice/eval/eval-88.out
+";
+        let (manager, _) = fake_manager(
+            FakeRunner::default()
+                .respond("sacct -j 88 ", "")
+                .respond("scontrol show job 88", scontrol),
+        );
+
+        let info = manager.get_job_info(88);
+        assert_eq!(info.job_name, "eval");
+        assert_eq!(info.state, "RUNNING");
+        assert_eq!(info.elapsed, "00:02:00");
+        assert_eq!(info.node_list, "gpu03");
+        assert_eq!(info.exit_code.as_deref(), Some("0:0"));
+        assert_eq!(info.work_dir, PathBuf::from("/home/alice/eval"));
+        assert_eq!(info.stdout_path, PathBuf::from("/home/alice/eval/eval-88.out"));
+        assert_eq!(info.stderr_path, PathBuf::from("/home/alice/eval/eval-88.err"));
+    }
+
+    #[test]
+    fn test_query_job_info_tells_unknown_jobs_from_unreachable_slurm() {
+        let (manager, _) = fake_manager(
+            FakeRunner::default()
+                .respond("sacct -j 88 ", "")
+                .fail("scontrol show job 88", "slurm_load_jobs error: Invalid job id specified"),
+        );
+        assert!(manager.query_job_info(88).unwrap().is_none());
+
+        let down = "slurm_load_jobs error: Unable to contact slurm controller (connect failure)";
+        let (manager, _) = fake_manager(
+            FakeRunner::default()
+                .fail("sacct -j 88 ", "sacct: error: Problem talking to the database")
+                .fail("scontrol show job 88", down),
+        );
+        let error = manager.query_job_info(88).unwrap_err();
+        assert!(format!("{:#}", error).contains("Unable to contact slurm controller"));
+        // Without a previous answer to keep, the guess is all there is
+        assert_eq!(manager.get_job_info(88).job_id, 88);
+    }
+
+    #[test]
+    fn test_cluster_is_passed_to_slurm_commands() {
+        let runner = FakeRunner::default()
+            .respond("squeue --clusters=beta -j 5 ", "RUNNING\n")
+            .respond("sacct --clusters=beta --noheader", "");
+        let (mut manager, runner) = fake_manager(runner);
+        manager.set_cluster(Some("beta".to_string()));
+
+        assert_eq!(manager.get_job_status(5), JobStatus::Running);
+        assert!(manager.check_cluster().is_ok());
+        assert!(runner.calls().iter().all(|call| call.contains("--clusters=beta")));
+
+        manager.set_cluster(Some("nosuch".to_string()));
+        assert!(manager.check_cluster().is_err());
+    }
+
+    #[test]
+    fn test_for_ui_uses_the_ui_runner() {
+        let (mut manager, runner) = fake_manager(FakeRunner::default());
+        let ui_runner = Arc::new(FakeRunner::default().respond("squeue -j 5 ", "RUNNING\n"));
+        manager.set_ui_runner(ui_runner.clone());
+        manager.add_tracked_job(5);
+
+        let ui_manager = manager.for_ui();
+        assert_eq!(ui_manager.get_job_status(5), JobStatus::Running);
+        assert_eq!(ui_manager.list_tracked_jobs(), [5]);
+        assert_eq!(ui_runner.calls().len(), 1);
+        assert!(runner.calls().is_empty());
+
+        assert_eq!(manager.get_job_status(5), JobStatus::Unknown);
+        assert_eq!(ui_runner.calls().len(), 1);
+    }
+
+    #[test]
+    fn test_cluster_banners_are_ignored() {
+        let runner = FakeRunner::default()
+            .respond("squeue --clusters=beta -j 5 -h -o %T", "CLUSTER: beta\n")
+            .respond("squeue --clusters=beta -j 5 -h -o %i", "CLUSTER: beta\n")
+            .respond("squeue --clusters=beta -j 5 -h -o %T|", "CLUSTER: beta\n")
+            .respond("sacct --clusters=beta -j 5 ", "")
+            .respond("squeue --clusters=beta -j 6 -h -o %T", "CLUSTER: beta\nRUNNING\n")
+            .respond(
+                "squeue --clusters=beta -j 6 -h -o %T|",
+                "CLUSTER: beta\nRUNNING|train|N/A|5:03|gpu|/home/alice|gpu01|1:00:00\n",
+            )
+            .respond("sacct --clusters=beta --noheader", "");
+        let (mut manager, _) = fake_manager(runner);
+        manager.set_cluster(Some("beta".to_string()));
+
+        // Only the banner: the job isn't queued, and sacct doesn't know it
+        assert_eq!(manager.get_job_status(5), JobStatus::Unknown);
+        assert_eq!(manager.job_exists(5), Some(false));
+        assert!(manager.get_queue_info(5).is_none());
+
+        assert_eq!(manager.get_job_status(6), JobStatus::Running);
+        let (status, info) = manager.get_queue_info(6).unwrap();
+        assert_eq!(status, JobStatus::Running);
+        assert_eq!(info.job_name, "train");
+
+        assert!(manager.check_cluster().is_ok());
+        // sattach has no --clusters option
+        assert!(manager.interactive_command(&["sattach", "6.0"]).is_err());
+        manager.set_cluster(None);
+        assert!(manager.interactive_command(&["sattach", "6.0"]).is_ok());
+    }
+
+    #[test]
+    fn test_submit_job_parses_sbatch_output() {
+        let script = tempfile::NamedTempFile::new().unwrap();
+        let (mut manager, runner) =
+            fake_manager(FakeRunner::default().respond("sbatch", "Submitted batch job 4242\n"));
+
+        let job_id = manager.submit_job(script.path(), &["--partition=gpu".to_string()]).unwrap();
+        assert_eq!(job_id, 4242);
+        assert!(manager.is_tracking(4242));
+        assert!(runner.calls()[0].starts_with("sbatch --partition=gpu "));
+
+        // sbatch failing is an error
+        let (mut manager, _) = fake_manager(FakeRunner::default());
+        assert!(manager.submit_job(script.path(), &[]).is_err());
+    }
+
+    #[test]
+    fn test_sbatch_errors_keep_all_of_stderr() {
+        let script = tempfile::NamedTempFile::new().unwrap();
+        let stderr = "sbatch: error: Batch job submission failed: Invalid account\n\
+                      sbatch: error: Check --account\n";
+        let (mut manager, runner) = fake_manager(FakeRunner::default().fail("sbatch", stderr));
+        manager.set_cluster(Some("beta".to_string()));
+
+        let error = manager.submit_job(script.path(), &["--account=x".to_string()]).unwrap_err();
+        let sbatch = error.downcast_ref::<SbatchError>().unwrap();
+        assert_eq!(sbatch.stderr, stderr);
+        assert_eq!(
+            sbatch.to_string(),
+            "sbatch exited with code 1: sbatch: error: Batch job submission failed: Invalid account"
+        );
+        assert!(sbatch.has_more_output());
+        let command = manager.sbatch_command(script.path(), &["--account=x".to_string()]);
+        let command: Vec<_> = command.iter().map(|arg| arg.to_string_lossy()).collect();
+        assert_eq!(runner.calls(), [command.join(" ")]);
+        assert_eq!(command[..3], ["sbatch", "--clusters=beta", "--account=x"]);
+
+        let silent = SbatchError {
+            return_code: 2,
+            stderr: String::new(),
+        };
+        assert_eq!(silent.to_string(), "sbatch exited with code 2");
+        assert!(!silent.has_more_output());
+    }
+
+    #[test]
+    fn test_submit_job_checks_the_script() {
+        let dir = tempfile::tempdir().unwrap();
+        let (mut manager, runner) =
+            fake_manager(FakeRunner::default().respond("sbatch", "Submitted batch job 1\n"));
+
+        let missing = manager.submit_job(&dir.path().join("missing.sh"), &[]).unwrap_err();
+        assert!(missing.to_string().starts_with("Script not found: "));
+        let directory = manager.submit_job(dir.path(), &[]).unwrap_err();
+        assert!(directory.to_string().contains("is a directory"));
+        assert!(runner.calls().is_empty());
+
+        // Names that aren't valid UTF-8 reach sbatch intact
+        #[cfg(unix)]
+        {
+            use std::os::unix::ffi::OsStrExt;
+            let script = dir.path().join(OsStr::from_bytes(b"job-\xff.sh"));
+            std::fs::write(&script, "#!/bin/bash\n").unwrap();
+            assert_eq!(manager.submit_job(&script, &[]).unwrap(), 1);
+            assert!(runner.calls()[0].ends_with("job-\u{fffd}.sh"));
+        }
+    }
+
     #[test]
     fn test_resolve_output_path() {
         let manager = JobManager::new();
         
         // Test placeholder replacement
-        let resolved = manager.resolve_output_path("slurm-%j.out", 12345, "/home/user");
+        let placeholders = OutputPlaceholders {
+            job_id: 12345,
+            job_name: "train",
+            user: None,
+            node: None,
+            array: None,
+        };
+        let resolved = manager.resolve_output_path("slurm-%j.out", &placeholders, "/home/user");
         assert!(resolved.to_string_lossy().contains("slurm-12345.out"));
+
+        let resolved = manager.resolve_output_path("%x-%j.out", &placeholders, "/home/user");
+        assert_eq!(resolved, PathBuf::from("/home/user/train-12345.out"));
+    }
+
+    #[test]
+    fn test_expand_output_pattern() {
+        let placeholders = OutputPlaceholders {
+            job_id: 42,
+            job_name: "train",
+            user: Some("alice".to_string()),
+            node: Some("gpu01".to_string()),
+            array: None,
+        };
+        assert_eq!(
+            expand_output_pattern("/scratch/%u/%x-%j.%N.out", &placeholders),
+            "/scratch/alice/train-42.gpu01.out"
+        );
+        assert_eq!(expand_output_pattern("%6j_%a.log", &placeholders), "000042_0.log");
+        assert_eq!(expand_output_pattern("100%%-%j%", &placeholders), "100%-42%");
+
+        // Unresolvable placeholders are dropped rather than kept literally
+        let placeholders = OutputPlaceholders {
+            job_id: 42,
+            job_name: "",
+            user: None,
+            node: None,
+            array: None,
+        };
+        assert_eq!(expand_output_pattern("%x-%N-%j.out", &placeholders), "--42.out");
+    }
+
+    #[test]
+    fn test_parse_pending_details() {
+        let details = |reason: &str, start: &str, dependencies: &[&str]| PendingDetails {
+            reason: reason.to_string(),
+            estimated_start: start.to_string(),
+            dependencies: dependencies.iter().map(|d| d.to_string()).collect(),
+        };
+        assert_eq!(
+            parse_pending_details("Resources|2026-10-14T12:30:00|(null)\n"),
+            Some(details("Resources", "2026-10-14T12:30:00", &[]))
+        );
+        assert_eq!(
+            parse_pending_details("QOSMaxJobsPerUserLimit|N/A"),
+            Some(details("QOSMaxJobsPerUserLimit", "", &[]))
+        );
+        assert_eq!(parse_pending_details("None|N/A"), Some(details("", "", &[])));
+        assert_eq!(
+            parse_pending_details("Dependency|N/A|afterok:123(unfulfilled),afterany:124_*(unfulfilled)"),
+            Some(details(
+                "Dependency",
+                "",
+                &["afterok:123(unfulfilled)", "afterany:124_*(unfulfilled)"]
+            ))
+        );
+        assert_eq!(
+            parse_pending_details("Dependency|N/A|afterok:1?afternotok:2").unwrap().dependencies,
+            vec!["afterok:1", "afternotok:2"]
+        );
+        assert_eq!(parse_pending_details("\n"), None);
+    }
+
+    #[test]
+    fn test_dependency_job_ids() {
+        assert_eq!(dependency_job_ids("afterok:123(unfulfilled)"), vec![123]);
+        assert_eq!(dependency_job_ids("afterok:123:124"), vec![123, 124]);
+        assert_eq!(dependency_job_ids("afterany:124_*"), vec![124]);
+        assert_eq!(dependency_job_ids("after:125+10"), vec![125]);
+        assert!(dependency_job_ids("singleton").is_empty());
+    }
+
+    #[test]
+    fn test_parse_job_summary() {
+        let output = "\
+123|0:0|00:10:00|00:30:00|4|1|8G|
+123.batch|0:0|00:10:00|00:29:50|4|1||2097152K
+123.extern|0:0|00:10:00|00:00:00|4|1||1024K
+";
+        let summary = parse_job_summary(output).unwrap();
+        assert_eq!(summary.exit_code, "0:0");
+        assert_eq!(summary.elapsed, "00:10:00");
+        assert_eq!(summary.cpu_efficiency, Some(75.0));
+        assert_eq!(summary.max_rss_mib, Some(2048.0));
+        assert_eq!(summary.req_mem_mib, Some(8192.0));
+        assert_eq!(summary.memory_efficiency(), Some(25.0));
+
+        // Per-CPU memory request and no step usage recorded yet
+        let summary = parse_job_summary("7|1:0|00:00:00|00:00.500|2|1|1000Mc|\n").unwrap();
+        assert_eq!(summary.req_mem_mib, Some(2000.0));
+        assert_eq!(summary.cpu_efficiency, None);
+        assert_eq!(summary.max_rss_mib, None);
+        assert_eq!(summary.memory_efficiency(), None);
+
+        assert_eq!(parse_job_summary(""), None);
+    }
+
+    #[test]
+    fn test_get_job_steps() {
+        let sacct = "\
+JobID|JobName|State|Elapsed|MaxRSS
+123|pipeline|RUNNING|00:20:00|
+123.batch|batch|RUNNING|00:20:00|
+123.0|preprocess|COMPLETED|00:05:00|2097152K
+123.1|train|RUNNING|00:15:00|
+";
+        let (manager, runner) = fake_manager(FakeRunner::default().respond("sacct -j 123 ", sacct));
+
+        let steps = manager.get_job_steps(123);
+        assert_eq!(
+            runner.calls(),
+            ["sacct -j 123 --format=JobID,JobName,State,Elapsed,MaxRSS --parsable2 --delimiter=\x1f"]
+        );
+        let ids: Vec<&str> = steps.iter().map(|s| s.step_id.as_str()).collect();
+        assert_eq!(ids, ["batch", "0", "1"]);
+        assert_eq!(steps[1].name, "preprocess");
+        assert_eq!(steps[1].state, "COMPLETED");
+        assert_eq!(steps[1].elapsed, "00:05:00");
+        assert_eq!(steps[1].max_rss_mib, Some(2048.0));
+        assert_eq!(steps[2].max_rss_mib, None);
+
+        let (manager, _) = fake_manager(FakeRunner::default().fail("sacct", "error"));
+        assert!(manager.get_job_steps(123).is_empty());
+    }
+
+    #[test]
+    fn test_heterogeneous_job_steps() {
+        let output = "\
+JobID|JobName|State|Elapsed|MaxRSS
+124+0|het|RUNNING|00:10:00|
+124+0.batch|batch|RUNNING|00:10:00|
+124+0.0|server|RUNNING|00:10:00|
+124+1|het|RUNNING|00:10:00|
+124+1.0|client|RUNNING|00:09:00|
+";
+        let steps = parse_job_steps(output);
+        let ids: Vec<&str> = steps.iter().map(|s| s.step_id.as_str()).collect();
+        assert_eq!(ids, ["0.batch", "0.0", "1.0"]);
+        assert!(!steps[0].is_attachable());
+        assert!(steps[2].is_attachable());
+        assert_eq!(step_suffix(&steps[2].step_id), "+1.0");
+        assert_eq!(step_suffix("batch"), ".batch");
+    }
+
+    #[test]
+    fn test_array_tasks() {
+        let sacct = "\
+JobID|JobIDRaw|State|Elapsed
+50_[6-9%2]|50|PENDING|00:00:00
+50_10|61|RUNNING|00:01:00
+50_2|53|COMPLETED|00:04:00
+50_3|54|FAILED|00:02:00
+";
+        let (manager, runner) = fake_manager(FakeRunner::default().respond("sacct -j 50 ", sacct));
+        let tasks = manager.get_array_tasks(50);
+        assert_eq!(
+            runner.calls(),
+            ["sacct -j 50 -X --format=JobID,JobIDRaw,State,Elapsed --parsable2 --delimiter=\x1f"]
+        );
+
+        let ids: Vec<(&str, Option<u64>)> =
+            tasks.iter().map(|task| (task.task_id.as_str(), task.raw_job_id)).collect();
+        assert_eq!(ids, [("2", Some(53)), ("3", Some(54)), ("10", Some(61)), ("[6-9%2]", None)]);
+        assert_eq!(tasks[3].count(), 4);
+        assert_eq!(
+            array_task_summary(&tasks),
+            "7 tasks: 1 running, 4 queued, 1 failed, 1 completed"
+        );
+
+        let range = |task_id: &str| ArrayTask {
+            task_id: task_id.to_string(),
+            ..Default::default()
+        };
+        assert_eq!(range("[0-9:2,20,30-31]").count(), 8);
+        assert_eq!(range("[oops]").count(), 1);
+
+        // Not an array job
+        let (manager, _) = fake_manager(
+            FakeRunner::default().respond("sacct -j 51 ", "JobID|JobIDRaw|State|Elapsed\n51|51|RUNNING|00:01:00\n"),
+        );
+        assert!(manager.get_array_tasks(51).is_empty());
+    }
+
+    #[test]
+    fn test_array_task_log_paths() {
+        let sacct = "\
+JobID|JobName|State|WorkDir|StdOut|StdErr
+50_3|sweep|RUNNING|/home/alice|%x-%A_%a.out|%x-%j.err
+";
+        let (manager, _) = fake_manager(FakeRunner::default().respond("sacct -j 54 ", sacct));
+        let info = manager.get_job_info(54);
+        assert_eq!(info.stdout_path, PathBuf::from("/home/alice/sweep-50_3.out"));
+        assert_eq!(info.stderr_path, PathBuf::from("/home/alice/sweep-54.err"));
+    }
+
+    #[test]
+    fn test_get_live_memory() {
+        let (manager, runner) = fake_manager(
+            FakeRunner::default().respond("sstat -j 77.batch", "1572864K|1258291K\n"),
+        );
+        let memory = manager.get_live_memory(77).unwrap();
+        assert_eq!(memory.to_string(), "RSS 1.2 GiB (max 1.5 GiB)");
+        assert_eq!(memory.rss_mib, 1258291.0 / 1024.0);
+        assert_eq!(
+            runner.calls(),
+            ["sstat -j 77.batch --format=MaxRSS,AveRSS --noheader --parsable2"]
+        );
+
+        assert_eq!(
+            parse_live_memory("|512M\n").map(|m| m.to_string()).as_deref(),
+            Some("RSS 512 MiB")
+        );
+        assert_eq!(parse_live_memory("|\n"), None);
+        assert_eq!(parse_live_memory(""), None);
+        // sstat fails for jobs that aren't running
+        let (manager, _) = fake_manager(FakeRunner::default().fail("sstat", "no steps running"));
+        assert_eq!(manager.get_live_memory(77), None);
+    }
+
+    #[test]
+    fn test_first_node() {
+        assert_eq!(first_node("gpu[01-04,07],cpu3").as_deref(), Some("gpu01"));
+        assert_eq!(first_node("node[3,5]").as_deref(), Some("node3"));
+        assert_eq!(first_node("cpu3,gpu[01-02]").as_deref(), Some("cpu3"));
+        assert_eq!(first_node("n001").as_deref(), Some("n001"));
+        assert_eq!(first_node("None assigned"), None);
+        assert_eq!(first_node(""), None);
     }
 }
@@ -0,0 +1,40 @@
+//! SLURM Monitor library.
+//!
+//! The monitoring backend is usable without the TUI: construct a
+//! [`StatusMonitor`] around a shared [`JobManager`], start it with the job IDs
+//! to watch, and consume [`StatusUpdate`]s from the channel. A [`LogTailer`]
+//! does the same for stdout/stderr files, emitting [`LogUpdate`]s.
+//!
+//! ```no_run
+//! use slurm_monitor::{JobManager, StatusMonitor};
+//! use std::sync::{mpsc, Arc, Mutex};
+//!
+//! let job_manager = Arc::new(Mutex::new(JobManager::new()));
+//! let mut monitor = StatusMonitor::new(job_manager, 3.0);
+//! let (tx, rx) = mpsc::channel();
+//! monitor.start_monitoring(vec![12345], tx);
+//!
+//! for update in rx.iter().take(1) {
+//!     println!("{}: {}", update.job_id, update.status);
+//! }
+//! monitor.stop_monitoring();
+//! ```
+
+pub mod cli;
+pub mod config;
+pub mod diagnostics;
+pub mod doctor;
+pub mod gpu_monitor;
+pub mod job_manager;
+pub mod log_tailer;
+pub mod plain;
+pub mod recorder;
+pub mod status_monitor;
+mod ui;
+pub mod utils;
+pub mod webhook;
+
+pub use job_manager::{JobInfo, JobManager};
+pub use log_tailer::{LogStatus, LogTailer, LogUpdate};
+pub use status_monitor::{StatusMonitor, StatusUpdate};
+pub use utils::{CommandRunner, JobStatus};
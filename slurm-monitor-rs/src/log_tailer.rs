@@ -1,28 +1,112 @@
 //! Log Tailer for real-time monitoring of stdout/stderr files.
 
+use crate::diagnostics;
 use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
 use std::collections::HashMap;
-use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::fmt;
+use std::fs::{File, Metadata};
+use std::io::{self, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
-use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::sync::Mutex;
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime};
+
+/// Quiet period after a file event before the file is read.
+const DEBOUNCE_WINDOW: Duration = Duration::from_millis(50);
+
+/// Longest a continuously written file waits before being read anyway.
+const DEBOUNCE_MAX_DELAY: Duration = Duration::from_millis(250);
+
+/// How often the tailer checks for commands while idle.
+const CHECK_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Largest piece of content sent in one update. Big reads, like the initial
+/// snapshot of a long log, are split so the UI can render between pieces.
+const MAX_UPDATE_BYTES: usize = 64 * 1024;
+
+/// How much of the start of a file is kept to notice it being rewritten.
+const HEAD_BYTES: usize = 256;
 
 /// Log update message sent from the tailer thread to the UI.
 #[derive(Debug, Clone)]
 pub struct LogUpdate {
     pub label: String,
     pub content: String,
+    /// The file was truncated or replaced; `content` supersedes everything
+    /// sent before for this label
+    pub reset: bool,
+    /// The reset was asked for with `reload_file` rather than the file
+    /// changing, so there is no old content worth keeping
+    pub reloaded: bool,
+    /// Modification time of the file when it was read, if available
+    pub modified: Option<SystemTime>,
+}
+
+/// Whether a monitored file can be read, sent from the tailer thread when
+/// that changes, and once after the file is added.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LogStatus {
+    pub label: String,
+    /// Why the file can't be read, or `None` if it can
+    pub problem: Option<LogProblem>,
+}
+
+/// Why a monitored file can't be read.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogProblem {
+    /// The file doesn't exist, e.g. because the job hasn't started yet
+    Missing(PathBuf),
+    PermissionDenied(PathBuf),
+    /// Any other error opening or reading the file
+    Unreadable(PathBuf, String),
+}
+
+impl LogProblem {
+    fn from_error(path: &Path, error: &io::Error) -> Self {
+        let path = path.to_path_buf();
+        match error.kind() {
+            io::ErrorKind::NotFound => LogProblem::Missing(path),
+            io::ErrorKind::PermissionDenied => LogProblem::PermissionDenied(path),
+            _ => LogProblem::Unreadable(path, error.to_string()),
+        }
+    }
+
+    /// A few words for a panel title, e.g. `permission denied`.
+    pub fn summary(&self) -> &'static str {
+        match self {
+            LogProblem::Missing(_) => "file not found",
+            LogProblem::PermissionDenied(_) => "permission denied",
+            LogProblem::Unreadable(..) => "unreadable",
+        }
+    }
+}
+
+impl fmt::Display for LogProblem {
+    /// e.g. `file not found: /home/alice/job-12.out`
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LogProblem::Unreadable(path, error) => {
+                write!(f, "can't read {}: {}", path.display(), error)
+            }
+            LogProblem::Missing(path) | LogProblem::PermissionDenied(path) => {
+                write!(f, "{}: {}", self.summary(), path.display())
+            }
+        }
+    }
 }
 
 /// Command sent to the log tailer thread.
 #[derive(Debug)]
 pub enum TailerCommand {
-    /// Add a file to monitor
-    AddFile { label: String, path: PathBuf },
+    /// Add a file to monitor. With `reset`, its content supersedes
+    /// everything sent before for the label, e.g. from a previous path.
+    AddFile { label: String, path: PathBuf, reset: bool },
     /// Remove a file from monitoring
     RemoveFile { label: String },
+    /// Read a monitored file again from the start; its content supersedes
+    /// everything sent before for the label
+    Reload { label: String },
     /// Stop the tailer
     Stop,
 }
@@ -32,6 +116,23 @@ struct FileState {
     path: PathBuf,
     last_position: u64,
     initial_read_done: bool,
+    /// Set when the file shrank, disappeared or was rewritten, until the
+    /// next read is sent
+    reset_pending: bool,
+    /// Set by `reload` along with `reset_pending`
+    reload_pending: bool,
+    /// First bytes read from the file, up to `HEAD_BYTES`; if they change, the
+    /// file was truncated and written again (e.g. by a requeued job) even if
+    /// it has grown past the old size since the last read
+    head: Vec<u8>,
+    /// Device and inode of the file read, to notice it being replaced
+    identity: Option<(u64, u64)>,
+    /// Modification time seen at the last read
+    modified: Option<SystemTime>,
+    /// What kept the last read from succeeding, if anything
+    problem: Option<LogProblem>,
+    /// Problem last sent as a `LogStatus`; None until the first is sent
+    reported: Option<Option<LogProblem>>,
 }
 
 impl FileState {
@@ -40,31 +141,110 @@ impl FileState {
             path,
             last_position: 0,
             initial_read_done: false,
+            reset_pending: false,
+            reload_pending: false,
+            head: Vec::new(),
+            identity: None,
+            modified: None,
+            problem: None,
+            reported: None,
+        }
+    }
+
+    /// Send the file's status if it changed since it was last sent.
+    fn report_status(&mut self, label: &str, status_tx: Option<&Sender<LogStatus>>) {
+        let status_tx = match status_tx {
+            Some(status_tx) if self.reported.as_ref() != Some(&self.problem) => status_tx,
+            _ => return,
+        };
+        self.reported = Some(self.problem.clone());
+        // Updates keep flowing if nobody listens for statuses any more
+        let _ = status_tx.send(LogStatus {
+            label: label.to_string(),
+            problem: self.problem.clone(),
+        });
+    }
+
+    /// Build an update for `content`, consuming any pending reset.
+    fn make_update(&mut self, label: &str, content: String) -> LogUpdate {
+        LogUpdate {
+            label: label.to_string(),
+            content,
+            reset: std::mem::take(&mut self.reset_pending),
+            reloaded: std::mem::take(&mut self.reload_pending),
+            modified: self.modified,
+        }
+    }
+
+    /// Send `content` as one or more updates of at most `MAX_UPDATE_BYTES`.
+    /// Only the first carries a pending reset. Returns false if the receiver
+    /// is gone.
+    fn send_content(&mut self, label: &str, content: &str, update_tx: &Sender<LogUpdate>) -> bool {
+        split_chunks(content, MAX_UPDATE_BYTES)
+            .into_iter()
+            .all(|chunk| update_tx.send(self.make_update(label, chunk.to_string())).is_ok())
+    }
+
+    /// Start reading the file over from the beginning, superseding what was
+    /// sent so far.
+    fn restart(&mut self) {
+        diagnostics::debug(format!("{} was rewritten, reading it from the start", self.path.display()));
+        self.last_position = 0;
+        self.head.clear();
+        self.reset_pending = true;
+    }
+
+    /// Note content read from the file, after what was read before.
+    fn remember(&mut self, content: &str) {
+        let room = HEAD_BYTES.saturating_sub(self.head.len());
+        self.head.extend(content.as_bytes().iter().take(room));
+        self.last_position += content.len() as u64;
+    }
+
+    /// Whether the file no longer starts with what was read from it.
+    fn was_rewritten(&self, file: &mut File) -> bool {
+        if self.head.is_empty() {
+            return false;
         }
+        let mut head = vec![0; self.head.len()];
+        let read = file.seek(SeekFrom::Start(0)).and_then(|_| file.read_exact(&mut head));
+        read.is_ok() && head != self.head
     }
 
-    /// Write debug message to file
-    fn debug_log(msg: &str) {
-        use std::io::Write;
-        if let Ok(mut f) = std::fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("/tmp/slurm_monitor_debug.log")
-        {
-            let _ = writeln!(f, "{}", msg);
+    /// Send the file's existing content, or an empty update if a reset is
+    /// pending and there is nothing to send, so the old content is cleared.
+    fn send_existing_content(&mut self, label: &str, update_tx: &Sender<LogUpdate>) {
+        if let Some(content) = self.read_existing_content() {
+            diagnostics::debug(format!("read_existing_content returned {} bytes for {}", content.len(), label));
+            self.send_content(label, &content, update_tx);
+        } else {
+            diagnostics::debug(format!("read_existing_content returned None for {}", label));
+            if self.reset_pending {
+                let _ = update_tx.send(self.make_update(label, String::new()));
+            }
         }
     }
 
+    /// Forget what was read so the next read starts from the beginning, as
+    /// for a newly added file.
+    fn reload(&mut self) {
+        self.last_position = 0;
+        self.initial_read_done = false;
+        self.head.clear();
+        self.reset_pending = true;
+        self.reload_pending = true;
+    }
+
     /// Read existing content from file.
     fn read_existing_content(&mut self) -> Option<String> {
-        Self::debug_log(&format!("read_existing_content: path={} initial_read_done={}", self.path.display(), self.initial_read_done));
+        diagnostics::debug(format!("read_existing_content: path={} initial_read_done={}", self.path.display(), self.initial_read_done));
         if self.initial_read_done {
             return None;
         }
 
-        let exists = self.path.exists();
-        Self::debug_log(&format!("read_existing_content: path exists={}", exists));
-        if !exists {
+        if let Err(e) = std::fs::metadata(&self.path) {
+            diagnostics::debug(format!("read_existing_content: path exists=false ({})", e));
+            self.problem = Some(LogProblem::from_error(&self.path, &e));
             self.initial_read_done = true;
             return None;
         }
@@ -74,22 +254,28 @@ impl FileState {
                 let mut content = String::new();
                 match file.read_to_string(&mut content) {
                     Ok(_) => {
-                        Self::debug_log(&format!("read_existing_content: read {} bytes", content.len()));
+                        diagnostics::debug(format!("read_existing_content: read {} bytes", content.len()));
+                        let metadata = file.metadata().ok();
+                        self.modified = metadata.as_ref().and_then(|m| m.modified().ok());
+                        self.identity = metadata.as_ref().and_then(file_identity);
+                        self.problem = None;
                         if !content.is_empty() {
-                            self.last_position = content.len() as u64;
+                            self.remember(&content);
                             self.initial_read_done = true;
                             return Some(content);
                         }
                     }
                     Err(e) => {
-                        Self::debug_log(&format!("read_existing_content: read error: {}", e));
+                        diagnostics::warn(format!("Failed to read {}: {}", self.path.display(), e));
+                        self.problem = Some(LogProblem::from_error(&self.path, &e));
                     }
                 }
                 self.initial_read_done = true;
                 None
             }
             Err(e) => {
-                Self::debug_log(&format!("read_existing_content: open error: {}", e));
+                diagnostics::warn(format!("Failed to open {}: {}", self.path.display(), e));
+                self.problem = Some(LogProblem::from_error(&self.path, &e));
                 self.initial_read_done = true;
                 None
             }
@@ -98,55 +284,177 @@ impl FileState {
 
     /// Read new content from the file since last read.
     fn read_new_content(&mut self) -> Option<String> {
-        if !self.path.exists() {
-            // Reset position if file was deleted
-            self.last_position = 0;
-            return None;
-        }
-
         let metadata = match std::fs::metadata(&self.path) {
             Ok(m) => m,
-            Err(_) => return None,
+            Err(e) => {
+                // Reset position if file was deleted; a recreated file starts over
+                if self.last_position > 0 {
+                    self.reset_pending = true;
+                }
+                self.last_position = 0;
+                self.head.clear();
+                self.problem = Some(LogProblem::from_error(&self.path, &e));
+                return None;
+            }
         };
 
         let current_size = metadata.len();
+        self.modified = metadata.modified().ok();
+
+        // A different file at the path: rotated away, or recreated by a
+        // requeued job
+        let identity = file_identity(&metadata);
+        if self.last_position > 0 && self.identity.is_some() && identity != self.identity {
+            self.restart();
+        }
+        self.identity = identity;
 
         // If file was truncated, reset position
         if current_size < self.last_position {
-            self.last_position = 0;
+            self.restart();
         }
 
-        // No new content
+        // No new content; still report a truncation to empty so the view clears
         if current_size == self.last_position {
-            return None;
+            self.problem = None;
+            return if self.reset_pending && current_size == 0 {
+                Some(String::new())
+            } else {
+                None
+            };
         }
 
         match File::open(&self.path) {
             Ok(mut file) => {
+                // Truncated and written past the old size between two reads
+                if self.last_position > 0 && self.was_rewritten(&mut file) {
+                    self.restart();
+                }
                 if file.seek(SeekFrom::Start(self.last_position)).is_err() {
                     return None;
                 }
 
                 let mut content = String::new();
-                if file.read_to_string(&mut content).is_ok() && !content.is_empty() {
-                    self.last_position += content.len() as u64;
+                match file.read_to_string(&mut content) {
+                    Ok(_) => self.problem = None,
+                    Err(e) => self.problem = Some(LogProblem::from_error(&self.path, &e)),
+                }
+                if self.problem.is_none() && !content.is_empty() {
+                    self.remember(&content);
                     return Some(content);
                 }
                 None
             }
-            Err(_) => None,
+            Err(e) => {
+                self.problem = Some(LogProblem::from_error(&self.path, &e));
+                None
+            }
         }
     }
 }
 
+/// Device and inode of a file, which change when it is replaced.
+#[cfg(unix)]
+fn file_identity(metadata: &Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    Some((metadata.dev(), metadata.ino()))
+}
+
+#[cfg(not(unix))]
+fn file_identity(_metadata: &Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+/// Split `content` into pieces of at most about `max_bytes`, breaking after a
+/// newline where possible and never inside a UTF-8 character. Empty content
+/// yields a single empty piece.
+fn split_chunks(content: &str, max_bytes: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut rest = content;
+    while rest.len() > max_bytes {
+        let end = match rest.as_bytes()[..max_bytes].iter().rposition(|&b| b == b'\n') {
+            Some(newline) => newline + 1,
+            None => (max_bytes..rest.len())
+                .find(|&i| rest.is_char_boundary(i))
+                .unwrap_or(rest.len()),
+        };
+        let (chunk, tail) = rest.split_at(end);
+        chunks.push(chunk);
+        rest = tail;
+    }
+    if !rest.is_empty() || chunks.is_empty() {
+        chunks.push(rest);
+    }
+    chunks
+}
+
+/// Coalesces bursts of file events so each burst results in a single read.
+///
+/// A label becomes due once no event has arrived for `window`, or once it has
+/// been pending for `max_delay` so a file that never goes quiet is still read.
+struct Debouncer {
+    window: Duration,
+    max_delay: Duration,
+    /// Label -> (first event, latest event)
+    pending: HashMap<String, (Instant, Instant)>,
+}
+
+impl Debouncer {
+    fn new(window: Duration, max_delay: Duration) -> Self {
+        Self {
+            window,
+            max_delay,
+            pending: HashMap::new(),
+        }
+    }
+
+    /// Record an event for `label`.
+    fn touch(&mut self, label: &str, now: Instant) {
+        self.pending
+            .entry(label.to_string())
+            .and_modify(|(_, last)| *last = now)
+            .or_insert((now, now));
+    }
+
+    fn deadline(&self, first: Instant, last: Instant) -> Instant {
+        (last + self.window).min(first + self.max_delay)
+    }
+
+    /// Earliest time a pending label becomes due.
+    fn next_deadline(&self) -> Option<Instant> {
+        self.pending
+            .values()
+            .map(|&(first, last)| self.deadline(first, last))
+            .min()
+    }
+
+    /// Remove and return the labels that are due at `now`.
+    fn take_due(&mut self, now: Instant) -> Vec<String> {
+        let due: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, &(first, last))| self.deadline(first, last) <= now)
+            .map(|(label, _)| label.clone())
+            .collect();
+        for label in &due {
+            self.pending.remove(label);
+        }
+        due
+    }
+}
+
 /// Monitors stdout/stderr files for real-time updates.
 pub struct LogTailer {
     /// Polling interval for fallback mode
     poll_interval: Duration,
     /// Sender for commands to the tailer thread
     command_tx: Option<Sender<TailerCommand>>,
+    /// Where to report whether files can be read
+    status_tx: Option<Sender<LogStatus>>,
     /// Tailer thread handle
     thread_handle: Option<JoinHandle<()>>,
+    /// Path last sent to the tailer thread for each label
+    paths: Mutex<HashMap<String, PathBuf>>,
 }
 
 impl LogTailer {
@@ -158,10 +466,19 @@ impl LogTailer {
         Self {
             poll_interval: Duration::from_secs_f64(poll_interval_secs),
             command_tx: None,
+            status_tx: None,
             thread_handle: None,
+            paths: Mutex::new(HashMap::new()),
         }
     }
 
+    /// Report on `status_tx` when a file can't be read (missing, permission
+    /// denied, ...) and when it can again. Takes effect on the next
+    /// `start_monitoring`.
+    pub fn set_status_sender(&mut self, status_tx: Sender<LogStatus>) {
+        self.status_tx = Some(status_tx);
+    }
+
     /// Start monitoring files.
     ///
     /// # Arguments
@@ -174,10 +491,11 @@ impl LogTailer {
         self.command_tx = Some(command_tx);
 
         let poll_interval = self.poll_interval;
+        let status_tx = self.status_tx.clone();
 
         // Start tailer thread
         let handle = thread::spawn(move || {
-            Self::tailer_loop(command_rx, update_tx, poll_interval);
+            Self::tailer_loop(command_rx, update_tx, status_tx, poll_interval);
         });
 
         self.thread_handle = Some(handle);
@@ -189,29 +507,24 @@ impl LogTailer {
         files: &mut HashMap<String, FileState>,
         watcher: &mut Option<RecommendedWatcher>,
         update_tx: &Sender<LogUpdate>,
+        status_tx: Option<&Sender<LogStatus>>,
     ) -> bool {
         match cmd {
-            TailerCommand::AddFile { label, path } => {
+            TailerCommand::AddFile { label, path, reset } => {
                 // Skip if already monitoring this label to prevent duplicate reads
                 if files.contains_key(&label) {
-                    FileState::debug_log(&format!("process_command: AddFile label={} already monitored, skipping", label));
+                    diagnostics::debug(format!("process_command: AddFile label={} already monitored, skipping", label));
                     return false;
                 }
 
-                FileState::debug_log(&format!("process_command: AddFile label={} path={}", label, path.display()));
+                diagnostics::debug(format!("process_command: AddFile label={} path={}", label, path.display()));
                 let mut state = FileState::new(path.clone());
+                state.reset_pending = reset;
 
-                // Read existing content
-                if let Some(content) = state.read_existing_content() {
-                    FileState::debug_log(&format!("read_existing_content returned {} bytes for {}", content.len(), label));
-                    let send_result = update_tx.send(LogUpdate {
-                        label: label.clone(),
-                        content,
-                    });
-                    FileState::debug_log(&format!("update_tx.send result: {:?}", send_result.is_ok()));
-                } else {
-                    FileState::debug_log(&format!("read_existing_content returned None for {}", label));
-                }
+                // Read existing content, clearing what was shown for an old
+                // path if there is none
+                state.send_existing_content(&label, update_tx);
+                state.report_status(&label, status_tx);
 
                 // Set up watcher for the directory if possible
                 if let Some(ref mut w) = watcher {
@@ -227,16 +540,30 @@ impl LogTailer {
                 files.remove(&label);
                 false
             }
+            TailerCommand::Reload { label } => {
+                if let Some(state) = files.get_mut(&label) {
+                    diagnostics::debug(format!("process_command: Reload label={}", label));
+                    state.reload();
+                    state.send_existing_content(&label, update_tx);
+                    state.report_status(&label, status_tx);
+                }
+                false
+            }
             TailerCommand::Stop => true,
         }
     }
 
     /// Tailer loop running in a separate thread.
+    ///
+    /// File events are debounced per file before reading. Every file is also
+    /// polled each `poll_interval`, in case events are missed or unsupported.
     fn tailer_loop(
         command_rx: Receiver<TailerCommand>,
         update_tx: Sender<LogUpdate>,
+        status_tx: Option<Sender<LogStatus>>,
         poll_interval: Duration,
     ) {
+        let status_tx = status_tx.as_ref();
         let mut files: HashMap<String, FileState> = HashMap::new();
         let mut watcher: Option<RecommendedWatcher> = None;
         let (notify_tx, notify_rx) = mpsc::channel();
@@ -253,56 +580,71 @@ impl LogTailer {
             watcher = Some(w);
         }
 
+        let mut debouncer = Debouncer::new(DEBOUNCE_WINDOW, DEBOUNCE_MAX_DELAY);
+        let mut last_poll: Option<Instant> = None;
+
         loop {
             // Process all pending commands
             while let Ok(cmd) = command_rx.try_recv() {
-                if Self::process_command(cmd, &mut files, &mut watcher, &update_tx) {
+                if Self::process_command(cmd, &mut files, &mut watcher, &update_tx, status_tx) {
                     return;
                 }
             }
 
-            // Check for file events from watcher
-            while let Ok(event) = notify_rx.try_recv() {
+            // Fallback: poll all files for changes
+            if last_poll.map(|at| at.elapsed() >= poll_interval).unwrap_or(true) {
+                last_poll = Some(Instant::now());
                 for (label, state) in files.iter_mut() {
-                    if event.paths.iter().any(|p| p == &state.path) {
-                        if let Some(content) = state.read_new_content() {
-                            let _ = update_tx.send(LogUpdate {
-                                label: label.clone(),
-                                content,
-                            });
+                    if let Some(content) = state.read_new_content() {
+                        if !state.send_content(label, &content, &update_tx) {
+                            // Receiver dropped
+                            return;
                         }
                     }
+                    state.report_status(label, status_tx);
                 }
             }
 
-            // Fallback: poll all files for changes
-            for (label, state) in files.iter_mut() {
-                if let Some(content) = state.read_new_content() {
-                    if update_tx
-                        .send(LogUpdate {
-                            label: label.clone(),
-                            content,
-                        })
-                        .is_err()
-                    {
-                        // Receiver dropped
-                        return;
+            // Wait for file events until the next command check, poll or
+            // debounce deadline
+            let now = Instant::now();
+            let until_poll = last_poll
+                .map_or(Duration::ZERO, |at| poll_interval.saturating_sub(at.elapsed()));
+            let mut timeout = CHECK_INTERVAL.min(until_poll);
+            if let Some(deadline) = debouncer.next_deadline() {
+                timeout = timeout.min(deadline.saturating_duration_since(now));
+            }
+
+            let mut mark = |event: Event| {
+                let now = Instant::now();
+                for (label, state) in files.iter() {
+                    if event.paths.iter().any(|p| p == &state.path) {
+                        debouncer.touch(label, now);
                     }
                 }
+            };
+            match notify_rx.recv_timeout(timeout) {
+                Ok(event) => {
+                    mark(event);
+                    while let Ok(event) = notify_rx.try_recv() {
+                        mark(event);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => {}
+                // No watcher; rely on polling
+                Err(RecvTimeoutError::Disconnected) => thread::sleep(timeout),
             }
 
-            // Wait for poll interval, processing any commands that arrive
-            let check_interval = Duration::from_millis(100);
-            let mut elapsed = Duration::ZERO;
-            while elapsed < poll_interval {
-                // Process ALL pending commands during wait period
-                while let Ok(cmd) = command_rx.try_recv() {
-                    if Self::process_command(cmd, &mut files, &mut watcher, &update_tx) {
-                        return;
+            // Read files whose burst of events has settled
+            for label in debouncer.take_due(Instant::now()) {
+                if let Some(state) = files.get_mut(&label) {
+                    if let Some(content) = state.read_new_content() {
+                        if !state.send_content(&label, &content, &update_tx) {
+                            return;
+                        }
                     }
+                    state.report_status(&label, status_tx);
                 }
-                thread::sleep(check_interval);
-                elapsed += check_interval;
             }
         }
     }
@@ -316,22 +658,62 @@ impl LogTailer {
         if let Some(handle) = self.thread_handle.take() {
             let _ = handle.join();
         }
+        self.paths.lock().unwrap().clear();
     }
 
     /// Add a file to monitor.
     pub fn add_file(&self, label: &str, path: &Path) {
-        FileState::debug_log(&format!("add_file called: label={} path={} has_tx={}", label, path.display(), self.command_tx.is_some()));
+        self.send_add(label, path, false);
+    }
+
+    /// Monitor `path` under `label`, replacing the file the label had if
+    /// the path changed. Nothing is sent if the label already has this path,
+    /// so it can be called on every status update of a job.
+    ///
+    /// The content of a replacing file supersedes what the old one sent.
+    pub fn set_file(&self, label: &str, path: &Path) {
+        let current = self.paths.lock().unwrap().get(label).cloned();
+        match current {
+            Some(ref current) if current == path => {}
+            Some(current) => {
+                diagnostics::debug(format!(
+                    "{} moved from {} to {}",
+                    label,
+                    current.display(),
+                    path.display()
+                ));
+                self.remove_file(label);
+                self.send_add(label, path, true);
+            }
+            None => self.send_add(label, path, false),
+        }
+    }
+
+    fn send_add(&self, label: &str, path: &Path, reset: bool) {
         if let Some(ref tx) = self.command_tx {
-            let result = tx.send(TailerCommand::AddFile {
+            self.paths.lock().unwrap().insert(label.to_string(), path.to_path_buf());
+            let _ = tx.send(TailerCommand::AddFile {
                 label: label.to_string(),
                 path: path.to_path_buf(),
+                reset,
+            });
+        }
+    }
+
+    /// Read the file monitored under `label` again from the start, e.g. after
+    /// a rotation was missed. The content sent supersedes what was sent
+    /// before, so it isn't shown twice.
+    pub fn reload_file(&self, label: &str) {
+        if let Some(ref tx) = self.command_tx {
+            let _ = tx.send(TailerCommand::Reload {
+                label: label.to_string(),
             });
-            FileState::debug_log(&format!("add_file send result: {:?}", result.is_ok()));
         }
     }
 
     /// Remove a file from monitoring.
     pub fn remove_file(&self, label: &str) {
+        self.paths.lock().unwrap().remove(label);
         if let Some(ref tx) = self.command_tx {
             let _ = tx.send(TailerCommand::RemoveFile {
                 label: label.to_string(),
@@ -366,6 +748,120 @@ mod tests {
         assert!(content.is_some());
         assert!(content.unwrap().contains("Hello, World!"));
         assert!(state.initial_read_done);
+
+        // Updates carry the file's modification time, for stall detection
+        let modified = temp_file.as_file().metadata().unwrap().modified().unwrap();
+        assert_eq!(state.make_update("stdout_1", String::new()).modified, Some(modified));
+    }
+
+    #[test]
+    fn test_file_state_reports_truncation() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "Before truncation").unwrap();
+        temp_file.flush().unwrap();
+
+        let mut state = FileState::new(temp_file.path().to_path_buf());
+        let content = state.read_existing_content().unwrap();
+        assert!(!state.make_update("stdout_1", content).reset);
+
+        // Truncated to empty: an empty reset update clears the view
+        temp_file.as_file().set_len(0).unwrap();
+        temp_file.seek(SeekFrom::Start(0)).unwrap();
+        let content = state.read_new_content().unwrap();
+        assert!(content.is_empty());
+        assert!(state.make_update("stdout_1", content).reset);
+
+        writeln!(temp_file, "After").unwrap();
+        temp_file.flush().unwrap();
+        let content = state.read_new_content().unwrap();
+        let update = state.make_update("stdout_1", content);
+        assert_eq!(update.content, "After\n");
+        assert!(!update.reset);
+    }
+
+    #[test]
+    fn test_file_state_notices_truncate_and_regrow() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "run 1: step 1").unwrap();
+        temp_file.flush().unwrap();
+
+        let mut state = FileState::new(temp_file.path().to_path_buf());
+        let content = state.read_existing_content().unwrap();
+        assert!(!state.make_update("stdout_1", content).reset);
+
+        // Requeued: truncated and already longer than before at the next read
+        temp_file.as_file().set_len(0).unwrap();
+        temp_file.seek(SeekFrom::Start(0)).unwrap();
+        write!(temp_file, "run 2: step 1\nrun 2: step 2\n").unwrap();
+        temp_file.flush().unwrap();
+        let content = state.read_new_content().unwrap();
+        let update = state.make_update("stdout_1", content);
+        assert_eq!(update.content, "run 2: step 1\nrun 2: step 2\n");
+        assert!(update.reset);
+
+        // Then plain growth again
+        writeln!(temp_file, "run 2: step 3").unwrap();
+        temp_file.flush().unwrap();
+        let content = state.read_new_content().unwrap();
+        let update = state.make_update("stdout_1", content);
+        assert_eq!(update.content, "run 2: step 3\n");
+        assert!(!update.reset);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_file_state_notices_a_replaced_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("job.out");
+        std::fs::write(&path, "first\n").unwrap();
+        let mut state = FileState::new(path.clone());
+        assert_eq!(state.read_existing_content().unwrap(), "first\n");
+
+        // Rotated: a new file that starts the same way takes its place
+        std::fs::rename(&path, dir.path().join("job.out.1")).unwrap();
+        std::fs::write(&path, "first\nsecond\n").unwrap();
+        let content = state.read_new_content().unwrap();
+        assert_eq!(content, "first\nsecond\n");
+        assert!(state.make_update("stdout_1", content).reset);
+    }
+
+    #[test]
+    fn test_split_chunks() {
+        assert_eq!(split_chunks("", 4), vec![""]);
+        assert_eq!(split_chunks("abc", 4), vec!["abc"]);
+        // Breaks after the last newline that fits
+        assert_eq!(split_chunks("ab\ncd\nef\n", 7), vec!["ab\ncd\n", "ef\n"]);
+        // A line longer than the limit is cut on a char boundary
+        assert_eq!(split_chunks("ééé", 3), vec!["éé", "é"]);
+
+        let content: String = (0..1000).map(|i| format!("line {}\n", i)).collect();
+        let chunks = split_chunks(&content, 100);
+        assert!(chunks.iter().all(|c| c.len() <= 100 && c.ends_with('\n')));
+        assert_eq!(chunks.concat(), content);
+    }
+
+    #[test]
+    fn test_debouncer_coalesces_bursts() {
+        let start = Instant::now();
+        let ms = |n| start + Duration::from_millis(n);
+        let mut debouncer = Debouncer::new(Duration::from_millis(50), Duration::from_millis(250));
+
+        // A burst of events within the window is read once, after it settles
+        for t in [0, 10, 20, 30] {
+            debouncer.touch("stdout_1", ms(t));
+        }
+        assert_eq!(debouncer.next_deadline(), Some(ms(80)));
+        assert!(debouncer.take_due(ms(79)).is_empty());
+        assert_eq!(debouncer.take_due(ms(80)), vec!["stdout_1".to_string()]);
+        assert!(debouncer.take_due(ms(200)).is_empty());
+        assert_eq!(debouncer.next_deadline(), None);
+
+        // A file that never goes quiet is still read after max_delay
+        for t in (300..600).step_by(20) {
+            debouncer.touch("stderr_1", ms(t));
+        }
+        assert_eq!(debouncer.next_deadline(), Some(ms(550)));
+        assert_eq!(debouncer.take_due(ms(550)), vec!["stderr_1".to_string()]);
     }
 
     #[test]
@@ -421,4 +917,119 @@ mod tests {
         assert!(updates[0].content.contains("Test content line 1"));
         assert!(updates[0].content.contains("Test content line 2"));
     }
+
+    #[test]
+    fn test_set_file_replaces_a_changed_path() {
+        let mut old_file = NamedTempFile::new().unwrap();
+        writeln!(old_file, "old").unwrap();
+        let mut new_file = NamedTempFile::new().unwrap();
+        writeln!(new_file, "new").unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let mut tailer = LogTailer::new(0.1);
+        tailer.start_monitoring(tx);
+
+        tailer.set_file("stdout_1", old_file.path());
+        tailer.set_file("stdout_1", old_file.path());
+        thread::sleep(Duration::from_millis(300));
+        tailer.set_file("stdout_1", new_file.path());
+        thread::sleep(Duration::from_millis(300));
+
+        // Only the new file is tailed now
+        writeln!(old_file, "old again").unwrap();
+        old_file.flush().unwrap();
+        thread::sleep(Duration::from_millis(300));
+        tailer.stop_monitoring();
+
+        let updates: Vec<(String, bool)> = rx.try_iter().map(|u| (u.content, u.reset)).collect();
+        assert_eq!(
+            updates,
+            [("old\n".to_string(), false), ("new\n".to_string(), true)]
+        );
+    }
+
+    #[test]
+    fn test_reload_reads_the_file_again_from_the_start() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "first").unwrap();
+        temp_file.flush().unwrap();
+
+        let (tx, rx) = mpsc::channel();
+        let mut tailer = LogTailer::new(0.1);
+        tailer.start_monitoring(tx);
+
+        tailer.add_file("stdout_1", temp_file.path());
+        thread::sleep(Duration::from_millis(300));
+        writeln!(temp_file, "second").unwrap();
+        temp_file.flush().unwrap();
+        thread::sleep(Duration::from_millis(300));
+        tailer.reload_file("stdout_1");
+        tailer.reload_file("stdout_2");
+        thread::sleep(Duration::from_millis(300));
+        tailer.stop_monitoring();
+
+        let updates: Vec<(String, bool, bool)> =
+            rx.try_iter().map(|u| (u.content, u.reset, u.reloaded)).collect();
+        assert_eq!(
+            updates,
+            [
+                ("first\n".to_string(), false, false),
+                ("second\n".to_string(), false, false),
+                ("first\nsecond\n".to_string(), true, true),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_file_status_is_reported_until_it_appears() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("job-1.out");
+
+        let (tx, rx) = mpsc::channel();
+        let (status_tx, status_rx) = mpsc::channel();
+        let mut tailer = LogTailer::new(0.1);
+        tailer.set_status_sender(status_tx);
+        tailer.start_monitoring(tx);
+
+        tailer.add_file("stdout_1", &path);
+        thread::sleep(Duration::from_millis(300));
+        std::fs::write(&path, "started\n").unwrap();
+        thread::sleep(Duration::from_millis(300));
+        tailer.stop_monitoring();
+
+        // Reported once while missing, not at every poll
+        let statuses: Vec<LogStatus> = status_rx.try_iter().collect();
+        assert_eq!(
+            statuses,
+            [
+                LogStatus {
+                    label: "stdout_1".to_string(),
+                    problem: Some(LogProblem::Missing(path.clone())),
+                },
+                LogStatus {
+                    label: "stdout_1".to_string(),
+                    problem: None,
+                },
+            ]
+        );
+        assert_eq!(rx.try_iter().map(|u| u.content).collect::<String>(), "started\n");
+    }
+
+    #[test]
+    fn test_log_problem_messages() {
+        let path = Path::new("/logs/job-1.out");
+        let problem = |kind| LogProblem::from_error(path, &io::Error::from(kind));
+        assert_eq!(
+            problem(io::ErrorKind::NotFound).to_string(),
+            "file not found: /logs/job-1.out"
+        );
+        let denied = problem(io::ErrorKind::PermissionDenied);
+        assert_eq!(denied.to_string(), "permission denied: /logs/job-1.out");
+        assert_eq!(denied.summary(), "permission denied");
+        let binary = io::Error::new(io::ErrorKind::InvalidData, "stream did not contain valid UTF-8");
+        assert_eq!(
+            LogProblem::from_error(path, &binary).to_string(),
+            "can't read /logs/job-1.out: stream did not contain valid UTF-8"
+        );
+    }
 }
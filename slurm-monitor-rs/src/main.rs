@@ -1,30 +1,88 @@
-mod cli;
-mod job_manager;
-mod log_tailer;
-mod status_monitor;
-mod ui;
-mod utils;
-
 use anyhow::Result;
 use clap::Parser;
-use cli::{Cli, Commands};
+use slurm_monitor::cli::{self, Cli, Commands};
+use slurm_monitor::config::Config;
+use slurm_monitor::utils::SystemRunner;
 
 fn main() -> Result<()> {
     let cli = Cli::parse();
+    let env = cli.slurm_env()?;
+    cli.start_diagnostics_log()?;
+    // A broken config file only matters to the commands that use it
+    let uses_config = !matches!(
+        cli.command,
+        Commands::List { .. } | Commands::Stop { .. } | Commands::Doctor
+    );
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) if !uses_config => {
+            eprintln!("Warning: using the default settings. {:#}", e);
+            Config::default()
+        }
+        Err(e) => return Err(e),
+    };
+    let runner = SystemRunner::new(env, cli.slurm_throttle(&config));
 
     match cli.command {
-        Commands::Submit { script, no_watch } => {
-            cli::handle_submit(&script, no_watch)?;
+        Commands::Submit {
+            script,
+            no_watch,
+            verbose,
+            cluster,
+            interactive,
+            monitor,
+        } => {
+            cli::handle_submit(
+                &script,
+                no_watch,
+                verbose,
+                interactive,
+                monitor,
+                config,
+                cli::cluster_job_manager(runner, cluster)?,
+            )?;
+        }
+        Commands::Watch {
+            job_ids,
+            ids_from,
+            filter,
+            monitor,
+        } => {
+            cli::handle_watch(job_ids, ids_from, filter.into(), monitor, config, runner)?;
         }
-        Commands::Watch { job_ids } => {
-            cli::handle_watch(job_ids)?;
+        Commands::Dashboard {
+            job_ids,
+            sort,
+            reverse,
+            filter,
+            monitor,
+        } => {
+            cli::handle_dashboard(job_ids, sort, reverse, filter.into(), monitor, config, runner)?;
         }
-        Commands::List => {
-            cli::handle_list()?;
+        Commands::Daemon {
+            job_ids,
+            log_file,
+            filter,
+            monitor,
+        } => {
+            cli::handle_daemon(job_ids, log_file, filter.into(), monitor, config, runner)?;
+        }
+        Commands::Tail { paths } => {
+            cli::handle_tail(paths, config)?;
+        }
+        Commands::List {
+            filter,
+            format,
+            json,
+        } => {
+            cli::handle_list(filter.into(), format, json, runner)?;
         }
         Commands::Stop { job_id } => {
             cli::handle_stop(job_id)?;
         }
+        Commands::Doctor => {
+            cli::handle_doctor(runner)?;
+        }
     }
 
     Ok(())
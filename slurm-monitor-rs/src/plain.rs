@@ -0,0 +1,186 @@
+//! Plain-text monitor output (`--plain`), for CI logs and redirected output
+//! where the interactive UI can't run.
+
+use crate::job_manager::JobInfo;
+use crate::log_tailer::LogProblem;
+use crate::utils::JobStatus;
+use chrono::Local;
+use std::collections::HashMap;
+use std::io::{self, Write};
+
+/// Writes status changes and new log lines as plain lines of text, with no
+/// cursor control.
+///
+/// Status lines look like `[12:00:05] job 123 (train): RUNNING`; log lines
+/// are prefixed with where they came from, e.g. `[123 stdout] epoch 1`. Log
+/// content arrives in chunks that can end mid-line, so the unterminated rest
+/// of each log is held back until its line ends or `finish` is called. As in
+/// the UI, only the last carriage-return update of a progress bar is kept.
+pub struct PlainOutput<W: Write> {
+    out: W,
+    /// Status and raw state last printed for each job
+    statuses: HashMap<u64, (JobStatus, String)>,
+    /// Unterminated last line of each log, keyed by job and log type
+    partial: HashMap<(u64, &'static str), String>,
+}
+
+impl<W: Write> PlainOutput<W> {
+    pub fn new(out: W) -> Self {
+        Self {
+            out,
+            statuses: HashMap::new(),
+            partial: HashMap::new(),
+        }
+    }
+
+    /// Print a job's status if it differs from the one printed last.
+    pub fn status(&mut self, job_id: u64, status: JobStatus, info: &JobInfo) -> io::Result<()> {
+        let seen = (status, info.state.clone());
+        if self.statuses.get(&job_id) == Some(&seen) {
+            return Ok(());
+        }
+        self.statuses.insert(job_id, seen);
+
+        let mut line = format!("[{}] job {}", Local::now().format("%H:%M:%S"), job_id);
+        if !info.job_name.is_empty() {
+            line.push_str(&format!(" ({})", info.job_name));
+        }
+        line.push_str(&format!(": {}", status));
+        // The raw state can say more, e.g. `CANCELLED by 1000`
+        if !info.state.is_empty() && info.state != status.as_str() {
+            line.push_str(&format!(" [{}]", info.state));
+        }
+        if status == JobStatus::Queued && !info.reason.is_empty() {
+            line.push_str(&format!(", waiting for {}", info.reason));
+        }
+        if status.is_terminal() {
+            if let Some(ref code) = info.exit_code {
+                line.push_str(&format!(", exit {}", code));
+            }
+            if !info.elapsed.is_empty() {
+                line.push_str(&format!(", elapsed {}", info.elapsed));
+            }
+        }
+        writeln!(self.out, "{}", line)?;
+        self.out.flush()
+    }
+
+    /// Print the complete lines of new log content.
+    pub fn log(&mut self, job_id: u64, log_type: &'static str, content: &str) -> io::Result<()> {
+        let key = (job_id, log_type);
+        let mut text = self.partial.remove(&key).unwrap_or_default();
+        text.push_str(content);
+
+        let mut lines: Vec<&str> = text.split('\n').collect();
+        let rest = lines.pop().unwrap_or("");
+        for line in lines {
+            self.write_log_line(job_id, log_type, line)?;
+        }
+        if !rest.is_empty() {
+            // Only the last progress update will be printed, so don't let
+            // the earlier ones pile up
+            let last_update = rest.trim_end_matches('\r').rfind('\r');
+            let rest = last_update.map_or(rest, |cr| &rest[cr..]);
+            self.partial.insert(key, rest.to_string());
+        }
+        self.out.flush()
+    }
+
+    /// Print lines that have already been split up, e.g. the logs of a
+    /// finished job read at startup.
+    pub fn log_lines(&mut self, job_id: u64, log_type: &str, lines: &[String]) -> io::Result<()> {
+        for line in lines {
+            self.write_log_line(job_id, log_type, line)?;
+        }
+        self.out.flush()
+    }
+
+    /// Note that a log file was truncated or replaced and is being read from
+    /// the start.
+    pub fn reset(&mut self, job_id: u64, log_type: &'static str) -> io::Result<()> {
+        self.partial.remove(&(job_id, log_type));
+        writeln!(self.out, "[{} {}] (log truncated or replaced, reading it from the start)", job_id, log_type)
+    }
+
+    /// Note that a log file can't be read.
+    pub fn problem(&mut self, job_id: u64, log_type: &str, problem: &LogProblem) -> io::Result<()> {
+        writeln!(self.out, "[{} {}] ({})", job_id, log_type, problem)?;
+        self.out.flush()
+    }
+
+    /// Print what is left of unterminated lines.
+    pub fn finish(&mut self) -> io::Result<()> {
+        let mut partial: Vec<_> = self.partial.drain().collect();
+        partial.sort();
+        for ((job_id, log_type), line) in partial {
+            self.write_log_line(job_id, log_type, &line)?;
+        }
+        self.out.flush()
+    }
+
+    fn write_log_line(&mut self, job_id: u64, log_type: &str, line: &str) -> io::Result<()> {
+        let line = line.trim_end_matches('\r');
+        let line = line.rsplit('\r').next().unwrap_or(line);
+        writeln!(self.out, "[{} {}] {}", job_id, log_type, line)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn printed(output: &PlainOutput<Vec<u8>>) -> Vec<String> {
+        String::from_utf8(output.out.clone())
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_status_is_printed_on_change() {
+        let mut output = PlainOutput::new(Vec::new());
+        let mut info = JobInfo {
+            job_id: 7,
+            job_name: "train".to_string(),
+            state: "PENDING".to_string(),
+            reason: "Priority".to_string(),
+            ..Default::default()
+        };
+        output.status(7, JobStatus::Queued, &info).unwrap();
+        output.status(7, JobStatus::Queued, &info).unwrap();
+        info.state = "CANCELLED by 1000".to_string();
+        info.exit_code = Some("0:15".to_string());
+        info.elapsed = "00:00:00".to_string();
+        output.status(7, JobStatus::Cancelled, &info).unwrap();
+
+        let lines = printed(&output);
+        assert_eq!(lines.len(), 2);
+        assert!(lines[0].ends_with("] job 7 (train): QUEUED [PENDING], waiting for Priority"));
+        assert!(lines[1].ends_with(
+            "] job 7 (train): CANCELLED [CANCELLED by 1000], exit 0:15, elapsed 00:00:00"
+        ));
+    }
+
+    #[test]
+    fn test_log_lines_are_split_across_chunks() {
+        let mut output = PlainOutput::new(Vec::new());
+        output.log(7, "stdout", "epoch 1\nepo").unwrap();
+        output.log(7, "stderr", "warning\n").unwrap();
+        output.log(7, "stdout", "ch 2\n10%\r50%\r").unwrap();
+        assert_eq!(output.partial[&(7, "stdout")], "\r50%\r");
+        output.log(7, "stdout", "100%\nlast").unwrap();
+        output.finish().unwrap();
+
+        assert_eq!(
+            printed(&output),
+            [
+                "[7 stdout] epoch 1",
+                "[7 stderr] warning",
+                "[7 stdout] epoch 2",
+                "[7 stdout] 100%",
+                "[7 stdout] last",
+            ]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+//! Append-only log of job status changes, for plotting job timelines.
+
+use crate::diagnostics;
+use crate::status_monitor::StatusUpdate;
+use crate::utils::{join_with_timeout, JobStatus};
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local};
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Header line of CSV recordings.
+const CSV_HEADER: &str = "timestamp,job_id,status,state,elapsed";
+
+/// One status change of a job.
+#[derive(Debug, Clone)]
+pub struct StatusRecord {
+    /// When the change was seen, not when it was written
+    pub timestamp: DateTime<Local>,
+    pub job_id: u64,
+    pub status: JobStatus,
+    /// Raw SLURM state, e.g. `CANCELLED by 1000`
+    pub state: String,
+    pub elapsed: String,
+}
+
+impl StatusRecord {
+    /// Record `update` as seen now.
+    pub fn from_update(update: &StatusUpdate) -> Self {
+        Self {
+            timestamp: Local::now(),
+            job_id: update.job_id,
+            status: update.status,
+            state: update.info.state.clone(),
+            elapsed: update.info.elapsed.clone(),
+        }
+    }
+
+    /// The record as one line of `format`, without the newline.
+    fn to_line(&self, format: RecordFormat) -> String {
+        let timestamp = self.timestamp.to_rfc3339();
+        match format {
+            RecordFormat::JsonLines => serde_json::json!({
+                "timestamp": timestamp,
+                "job_id": self.job_id,
+                "status": self.status.as_str(),
+                "state": self.state,
+                "elapsed": self.elapsed,
+            })
+            .to_string(),
+            RecordFormat::Csv => [
+                timestamp.as_str(),
+                &self.job_id.to_string(),
+                self.status.as_str(),
+                &self.state,
+                &self.elapsed,
+            ]
+            .iter()
+            .map(|field| csv_field(field))
+            .collect::<Vec<_>>()
+            .join(","),
+        }
+    }
+}
+
+/// File format of a recording, chosen by its extension.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RecordFormat {
+    /// One JSON object per line (any extension but `.csv`)
+    JsonLines,
+    /// Comma-separated values with a header line
+    Csv,
+}
+
+impl RecordFormat {
+    fn for_path(path: &Path) -> Self {
+        let is_csv = path
+            .extension()
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            RecordFormat::Csv
+        } else {
+            RecordFormat::JsonLines
+        }
+    }
+}
+
+/// Quote a CSV field if it contains a separator, quote or newline.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Appends `StatusRecord`s to a file from a dedicated thread.
+///
+/// The file is opened when the recorder is created so a bad path is reported
+/// at startup. Sending a record only enqueues it, so a slow (e.g. network)
+/// file system never holds up status polling or rendering. Every record is
+/// written as one line and flushed right away; write errors are reported as
+/// diagnostics. The thread exits once every sender is gone; call `finish`
+/// before the process exits so queued records are still written.
+pub struct StatusRecorder {
+    record_tx: Sender<StatusRecord>,
+    handle: JoinHandle<()>,
+}
+
+impl StatusRecorder {
+    /// Start appending to `path`, creating it if needed.
+    pub fn create(path: &Path) -> Result<Self> {
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .with_context(|| format!("Failed to open record file {}", path.display()))?;
+
+        let format = RecordFormat::for_path(path);
+        let is_empty = file.metadata().map(|m| m.len() == 0).unwrap_or(false);
+        if format == RecordFormat::Csv && is_empty {
+            writeln!(file, "{}", CSV_HEADER)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+
+        let (record_tx, record_rx) = mpsc::channel();
+        let path = path.to_path_buf();
+        let handle = thread::spawn(move || Self::write_loop(record_rx, file, path, format));
+        Ok(Self { record_tx, handle })
+    }
+
+    /// Channel to send records on.
+    pub fn sender(&self) -> Sender<StatusRecord> {
+        self.record_tx.clone()
+    }
+
+    /// Wait up to `timeout` for the records already sent to be written, once
+    /// every other sender is dropped. Returns whether they were.
+    pub fn finish(self, timeout: Duration) -> bool {
+        drop(self.record_tx);
+        join_with_timeout(self.handle, timeout)
+    }
+
+    /// Write records until all senders are dropped.
+    fn write_loop(record_rx: Receiver<StatusRecord>, mut file: File, path: PathBuf, format: RecordFormat) {
+        for record in record_rx {
+            let line = record.to_line(format) + "\n";
+            if let Err(e) = file.write_all(line.as_bytes()).and_then(|_| file.flush()) {
+                diagnostics::warn(format!("record: failed to write {}: {}", path.display(), e));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job_manager::JobInfo;
+
+    fn record(job_id: u64, status: JobStatus, state: &str) -> StatusRecord {
+        StatusRecord::from_update(&StatusUpdate {
+            job_id,
+            status,
+            info: JobInfo {
+                job_id,
+                state: state.to_string(),
+                elapsed: "00:01:00".to_string(),
+                ..Default::default()
+            },
+            unreachable: false,
+        })
+    }
+
+    /// Wait for the writer thread to get `path` to `expected_lines` lines.
+    fn read_after_writes(path: &Path, expected_lines: usize) -> Vec<String> {
+        for _ in 0..100 {
+            let content = std::fs::read_to_string(path).unwrap();
+            if content.lines().count() >= expected_lines {
+                return content.lines().map(String::from).collect();
+            }
+            thread::sleep(std::time::Duration::from_millis(20));
+        }
+        panic!("records were not written to {}", path.display());
+    }
+
+    #[test]
+    fn test_records_are_appended_as_json_lines() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("timeline.jsonl");
+        std::fs::write(&path, "{\"earlier\": true}\n").unwrap();
+
+        let recorder = StatusRecorder::create(&path).unwrap();
+        recorder.sender().send(record(7, JobStatus::Running, "RUNNING")).unwrap();
+        drop(recorder);
+
+        let lines = read_after_writes(&path, 2);
+        assert_eq!(lines[0], "{\"earlier\": true}");
+        let value: serde_json::Value = serde_json::from_str(&lines[1]).unwrap();
+        assert_eq!(value["job_id"], 7);
+        assert_eq!(value["status"], "RUNNING");
+        assert_eq!(value["elapsed"], "00:01:00");
+        assert!(value["timestamp"].as_str().unwrap().contains('T'));
+    }
+
+    #[test]
+    fn test_finish_writes_queued_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("timeline.jsonl");
+
+        let recorder = StatusRecorder::create(&path).unwrap();
+        let record_tx = recorder.sender();
+        for job_id in 0..100 {
+            record_tx.send(record(job_id, JobStatus::Running, "RUNNING")).unwrap();
+        }
+        drop(record_tx);
+        assert!(recorder.finish(Duration::from_secs(5)));
+        assert_eq!(std::fs::read_to_string(&path).unwrap().lines().count(), 100);
+    }
+
+    #[test]
+    fn test_csv_records_get_one_header() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("timeline.CSV");
+
+        for (run, state) in ["PENDING", "CANCELLED by 1000, \"admin\""].iter().enumerate() {
+            let recorder = StatusRecorder::create(&path).unwrap();
+            recorder.sender().send(record(7, JobStatus::Queued, state)).unwrap();
+            read_after_writes(&path, run + 2);
+        }
+
+        let lines = read_after_writes(&path, 3);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0], CSV_HEADER);
+        assert!(lines[1].ends_with(",7,QUEUED,PENDING,00:01:00"));
+        assert!(lines[2].ends_with(",7,QUEUED,\"CANCELLED by 1000, \"\"admin\"\"\",00:01:00"));
+    }
+
+    #[test]
+    fn test_unwritable_path_is_an_error() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(StatusRecorder::create(&dir.path().join("missing").join("x.jsonl")).is_err());
+    }
+}
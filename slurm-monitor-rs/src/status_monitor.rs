@@ -1,12 +1,23 @@
 //! Status Monitor for polling SLURM job status.
 
+use crate::diagnostics;
 use crate::job_manager::{JobInfo, JobManager};
+use crate::recorder::StatusRecord;
 use crate::utils::JobStatus;
-use std::collections::HashMap;
+use crate::webhook::JobEvent;
+use std::collections::{HashMap, HashSet};
 use std::sync::mpsc::{self, Receiver, Sender};
 use std::sync::{Arc, Mutex};
 use std::thread::{self, JoinHandle};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+/// How often jobs in a terminal state are re-polled to catch accounting lag.
+const TERMINAL_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Polls an active job may come back Unknown before that is reported. A job
+/// that just left squeue can be missing from sacct until accounting has
+/// recorded its final state.
+const UNKNOWN_GRACE_POLLS: u32 = 3;
 
 /// Status update message sent from the monitor thread to the UI.
 #[derive(Debug, Clone)]
@@ -14,6 +25,8 @@ pub struct StatusUpdate {
     pub job_id: u64,
     pub status: JobStatus,
     pub info: JobInfo,
+    /// SLURM couldn't be reached, so this is the last update that got through
+    pub unreachable: bool,
 }
 
 /// Command sent to the monitor thread.
@@ -28,9 +41,39 @@ pub enum MonitorCommand {
 }
 
 /// Monitors SLURM job status with periodic polling.
+///
+/// # Threading model
+///
+/// `start_monitoring` spawns a single background thread that owns the list of
+/// monitored job IDs and polls each one every `poll_interval`. The caller keeps
+/// the receiving end of the update channel; the thread exits when `Stop` is
+/// received or when that receiver is dropped.
+///
+/// The `JobManager` is shared behind an `Arc<Mutex<_>>` so the caller can keep
+/// using it (e.g. `add_tracked_job`) while the thread is polling. The thread
+/// only locks it to take a snapshot at the start of each cycle and runs the
+/// SLURM commands on that copy.
+/// `add_job_to_monitor` / `remove_job_from_monitor` only enqueue commands and
+/// never block on the poll. Dropping the monitor calls `stop_monitoring`, which
+/// joins the thread.
+///
+/// With `set_steps`, the steps of started jobs are queried too and sent in
+/// `JobInfo::steps`; with `set_array_tasks`, array jobs' tasks are sent in
+/// `JobInfo::array_tasks`; with `set_live_memory`, running jobs' memory use is
+/// read with sstat into `JobInfo::live_memory`.
+///
+/// An active job that briefly shows up in neither squeue nor sacct keeps its
+/// last status for a few polls before it is reported as Unknown. While SLURM
+/// can't be reached at all, e.g. during a controller restart, jobs keep their
+/// last status and info for as long as it takes, sent with `unreachable` set.
+///
+/// If an event sender is set with `set_event_sender`, a `JobEvent` is sent
+/// whenever a job moves from an active state to a terminal one. A record
+/// sender set with `set_record_sender` gets a `StatusRecord` whenever a job's
+/// status or raw state changes.
 pub struct StatusMonitor {
-    /// Polling interval in seconds
-    poll_interval: Duration,
+    /// How to poll
+    settings: PollSettings,
     /// Sender for commands to the monitor thread
     command_tx: Option<Sender<MonitorCommand>>,
     /// Monitor thread handle
@@ -39,6 +82,29 @@ pub struct StatusMonitor {
     job_manager: Arc<Mutex<JobManager>>,
     /// Current status cache
     current_statuses: Arc<Mutex<HashMap<u64, StatusUpdate>>>,
+    /// Where to report jobs that finish, and status changes
+    listeners: Listeners,
+}
+
+/// What the monitor thread polls, and how often.
+#[derive(Debug, Clone, Copy)]
+struct PollSettings {
+    interval: Duration,
+    /// Also query the steps of started jobs
+    steps: bool,
+    /// Also query the tasks of array jobs
+    array_tasks: bool,
+    /// Also query the memory use of running jobs
+    live_memory: bool,
+}
+
+/// Optional receivers of job events, besides the update channel.
+#[derive(Debug, Clone, Default)]
+struct Listeners {
+    /// Where to report jobs that finish while monitored
+    event_tx: Option<Sender<JobEvent>>,
+    /// Where to record status changes
+    record_tx: Option<Sender<StatusRecord>>,
 }
 
 impl StatusMonitor {
@@ -49,14 +115,50 @@ impl StatusMonitor {
     /// * `poll_interval_secs` - Polling interval in seconds (default: 3.0)
     pub fn new(job_manager: Arc<Mutex<JobManager>>, poll_interval_secs: f64) -> Self {
         Self {
-            poll_interval: Duration::from_secs_f64(poll_interval_secs),
+            settings: PollSettings {
+                interval: Duration::from_secs_f64(poll_interval_secs),
+                steps: false,
+                array_tasks: false,
+                live_memory: false,
+            },
             command_tx: None,
             thread_handle: None,
             job_manager,
             current_statuses: Arc::new(Mutex::new(HashMap::new())),
+            listeners: Listeners::default(),
         }
     }
 
+    /// Report jobs that finish on `event_tx`. Takes effect on the next
+    /// `start_monitoring`.
+    pub fn set_event_sender(&mut self, event_tx: Sender<JobEvent>) {
+        self.listeners.event_tx = Some(event_tx);
+    }
+
+    /// Query the steps of started jobs as well. Takes effect on the next
+    /// `start_monitoring`.
+    pub fn set_steps(&mut self, steps: bool) {
+        self.settings.steps = steps;
+    }
+
+    /// Query the tasks of array jobs as well. Takes effect on the next
+    /// `start_monitoring`.
+    pub fn set_array_tasks(&mut self, array_tasks: bool) {
+        self.settings.array_tasks = array_tasks;
+    }
+
+    /// Query the memory use of running jobs with sstat each poll. Takes
+    /// effect on the next `start_monitoring`.
+    pub fn set_live_memory(&mut self, live_memory: bool) {
+        self.settings.live_memory = live_memory;
+    }
+
+    /// Record status changes on `record_tx`. Takes effect on the next
+    /// `start_monitoring`.
+    pub fn set_record_sender(&mut self, record_tx: Sender<StatusRecord>) {
+        self.listeners.record_tx = Some(record_tx);
+    }
+
     /// Start monitoring jobs.
     ///
     /// # Arguments
@@ -82,6 +184,7 @@ impl StatusMonitor {
                             job_id,
                             ..Default::default()
                         },
+                        unreachable: false,
                     },
                 );
             }
@@ -89,8 +192,9 @@ impl StatusMonitor {
 
         let job_manager = Arc::clone(&self.job_manager);
         let current_statuses = Arc::clone(&self.current_statuses);
-        let poll_interval = self.poll_interval;
+        let settings = self.settings;
         let initial_jobs = job_ids.clone();
+        let listeners = self.listeners.clone();
 
         // Start monitor thread
         let handle = thread::spawn(move || {
@@ -99,63 +203,193 @@ impl StatusMonitor {
                 update_tx,
                 job_manager,
                 current_statuses,
-                poll_interval,
+                settings,
                 initial_jobs,
+                listeners,
             );
         });
 
         self.thread_handle = Some(handle);
     }
 
+    /// Process a single command. Returns true if the loop should stop.
+    fn process_command(
+        cmd: MonitorCommand,
+        monitored_jobs: &mut Vec<u64>,
+        last_polled: &mut HashMap<u64, Instant>,
+        current_statuses: &Mutex<HashMap<u64, StatusUpdate>>,
+    ) -> bool {
+        match cmd {
+            MonitorCommand::AddJob(job_id) => {
+                if !monitored_jobs.contains(&job_id) {
+                    monitored_jobs.push(job_id);
+                }
+                false
+            }
+            MonitorCommand::RemoveJob(job_id) => {
+                monitored_jobs.retain(|&id| id != job_id);
+                last_polled.remove(&job_id);
+                current_statuses.lock().unwrap().remove(&job_id);
+                false
+            }
+            MonitorCommand::Stop => true,
+        }
+    }
+
     /// Monitor loop running in a separate thread.
     fn monitor_loop(
         command_rx: Receiver<MonitorCommand>,
         update_tx: Sender<StatusUpdate>,
         job_manager: Arc<Mutex<JobManager>>,
         current_statuses: Arc<Mutex<HashMap<u64, StatusUpdate>>>,
-        poll_interval: Duration,
+        settings: PollSettings,
         initial_jobs: Vec<u64>,
+        listeners: Listeners,
     ) {
         let mut monitored_jobs: Vec<u64> = initial_jobs;
+        let mut last_polled: HashMap<u64, Instant> = HashMap::new();
+        // Consecutive Unknown polls of jobs last seen active
+        let mut unknown_polls: HashMap<u64, u32> = HashMap::new();
+        // Jobs last reported as Unknown, warned about once until they're found
+        let mut unknown_jobs: HashSet<u64> = HashSet::new();
 
         loop {
             // Check for commands (non-blocking)
             while let Ok(cmd) = command_rx.try_recv() {
-                match cmd {
-                    MonitorCommand::AddJob(job_id) => {
-                        if !monitored_jobs.contains(&job_id) {
-                            monitored_jobs.push(job_id);
-                        }
-                    }
-                    MonitorCommand::RemoveJob(job_id) => {
-                        monitored_jobs.retain(|&id| id != job_id);
-                        current_statuses.lock().unwrap().remove(&job_id);
-                    }
-                    MonitorCommand::Stop => {
-                        return;
-                    }
+                if Self::process_command(
+                    cmd,
+                    &mut monitored_jobs,
+                    &mut last_polled,
+                    &current_statuses,
+                ) {
+                    return;
                 }
             }
 
-            // Poll each job's status
+            // Query a snapshot so the SLURM commands run without the lock
+            let manager = job_manager.lock().unwrap().clone();
+
+            // Poll each job's status; finished jobs are only re-polled occasionally
             for &job_id in &monitored_jobs {
-                let (status, info) = {
-                    let manager = job_manager.lock().unwrap();
-                    let status = manager.get_job_status(job_id);
-                    let info = manager.get_job_info(job_id);
-                    (status, info)
+                let due = {
+                    let statuses = current_statuses.lock().unwrap();
+                    Self::is_poll_due(&statuses, last_polled.get(&job_id).copied(), job_id)
+                };
+                if !due {
+                    continue;
+                }
+                last_polled.insert(job_id, Instant::now());
+
+                // While a job is queued or running, squeue alone is enough once
+                // its log paths are known; sacct is only asked after it leaves
+                // the queue, for the final state.
+                let previous = {
+                    let statuses = current_statuses.lock().unwrap();
+                    statuses
+                        .get(&job_id)
+                        .filter(|s| !s.status.is_terminal())
+                        .map(|s| s.info.clone())
+                        .filter(|info| !info.stdout_path.as_os_str().is_empty())
                 };
+                let queued = previous.and_then(|previous| {
+                    let (status, mut info) = manager.get_queue_info(job_id)?;
+                    info.stdout_path = previous.stdout_path;
+                    info.stderr_path = previous.stderr_path;
+                    info.comment = previous.comment;
+                    info.wckey = previous.wckey;
+                    info.alloc_cpus = previous.alloc_cpus;
+                    info.alloc_tres = previous.alloc_tres;
+                    if info.work_dir.as_os_str().is_empty() {
+                        info.work_dir = previous.work_dir;
+                    }
+                    Some((status, info))
+                });
 
-                let update = StatusUpdate {
-                    job_id,
-                    status,
-                    info,
+                // Guessed paths could replace good ones, so what was known is
+                // kept until SLURM answers again
+                let polled = match queued {
+                    Some(polled) => Ok(polled),
+                    None => {
+                        let status = manager.get_job_status(job_id);
+                        match manager.query_job_info(job_id) {
+                            Ok(info) => {
+                                Ok((status, info.unwrap_or_else(|| manager.guess_job_info(job_id))))
+                            }
+                            // squeue or sacct still gave the status
+                            Err(e) if status != JobStatus::Unknown => {
+                                diagnostics::debug(format!("No info on job {}: {:#}", job_id, e));
+                                let statuses = current_statuses.lock().unwrap();
+                                let info = match Self::last_known(statuses.get(&job_id)) {
+                                    Some(last) => last.info.clone(),
+                                    None => manager.guess_job_info(job_id),
+                                };
+                                Ok((status, info))
+                            }
+                            Err(e) => Err(e),
+                        }
+                    }
+                };
+
+                let update = match polled {
+                    Ok((status, mut info)) => {
+                        Self::add_details(&manager, settings, status, &mut info);
+                        let statuses = current_statuses.lock().unwrap();
+                        Self::hold_last_known(
+                            statuses.get(&job_id),
+                            StatusUpdate {
+                                job_id,
+                                status,
+                                info,
+                                unreachable: false,
+                            },
+                            unknown_polls.entry(job_id).or_default(),
+                        )
+                    }
+                    Err(e) => {
+                        diagnostics::debug(format!("Could not query job {}: {:#}", job_id, e));
+                        let statuses = current_statuses.lock().unwrap();
+                        match Self::last_known(statuses.get(&job_id)) {
+                            Some(last) => StatusUpdate {
+                                unreachable: true,
+                                ..last.clone()
+                            },
+                            None => StatusUpdate {
+                                job_id,
+                                status: JobStatus::Unknown,
+                                info: manager.guess_job_info(job_id),
+                                unreachable: true,
+                            },
+                        }
+                    }
                 };
 
                 // Update cache
-                {
+                let previous = {
                     let mut statuses = current_statuses.lock().unwrap();
-                    statuses.insert(job_id, update.clone());
+                    statuses.insert(job_id, update.clone())
+                };
+                if let Some(warning) =
+                    Self::problem_report(previous.as_ref(), &update, &mut unknown_jobs)
+                {
+                    diagnostics::warn(warning);
+                }
+
+                if let Some(ref tx) = listeners.record_tx {
+                    if Self::changed(previous.as_ref(), &update) {
+                        let _ = tx.send(StatusRecord::from_update(&update));
+                    }
+                }
+
+                if let Some(ref tx) = listeners.event_tx {
+                    if Self::just_finished(previous.as_ref(), &update.status) {
+                        let _ = tx.send(JobEvent {
+                            job_id,
+                            name: update.info.job_name.clone(),
+                            status: update.status,
+                            state: update.info.state.clone(),
+                            elapsed: update.info.elapsed.clone(),
+                        });
+                    }
                 }
 
                 // Send update to UI
@@ -168,22 +402,16 @@ impl StatusMonitor {
             // Wait for poll interval, processing all commands periodically
             let check_interval = Duration::from_millis(100);
             let mut elapsed = Duration::ZERO;
-            while elapsed < poll_interval {
+            while elapsed < settings.interval {
                 // Process ALL pending commands during wait period
                 while let Ok(cmd) = command_rx.try_recv() {
-                    match cmd {
-                        MonitorCommand::AddJob(job_id) => {
-                            if !monitored_jobs.contains(&job_id) {
-                                monitored_jobs.push(job_id);
-                            }
-                        }
-                        MonitorCommand::RemoveJob(job_id) => {
-                            monitored_jobs.retain(|&id| id != job_id);
-                            current_statuses.lock().unwrap().remove(&job_id);
-                        }
-                        MonitorCommand::Stop => {
-                            return;
-                        }
+                    if Self::process_command(
+                        cmd,
+                        &mut monitored_jobs,
+                        &mut last_polled,
+                        &current_statuses,
+                    ) {
+                        return;
                     }
                 }
                 thread::sleep(check_interval);
@@ -192,6 +420,137 @@ impl StatusMonitor {
         }
     }
 
+    /// Whether a job has reached a terminal state according to `statuses`.
+    fn is_finished_in(statuses: &HashMap<u64, StatusUpdate>, job_id: u64) -> bool {
+        statuses
+            .get(&job_id)
+            .map(|s| s.status.is_terminal())
+            .unwrap_or(false)
+    }
+
+    /// Whether `status` is a transition from an active state to a terminal one.
+    ///
+    /// Jobs first seen already finished (e.g. discovered from sacct history)
+    /// don't count, so starting the monitor doesn't report old jobs.
+    fn just_finished(previous: Option<&StatusUpdate>, status: &JobStatus) -> bool {
+        let was_active = previous
+            .map(|p| p.status != JobStatus::Unknown && !p.status.is_terminal())
+            .unwrap_or(false);
+        was_active && status.is_terminal()
+    }
+
+    /// Fill in what `settings` asks for beyond the basic info of a job.
+    fn add_details(
+        manager: &JobManager,
+        settings: PollSettings,
+        status: JobStatus,
+        info: &mut JobInfo,
+    ) {
+        let job_id = info.job_id;
+        if status == JobStatus::Queued {
+            if let Some(details) = manager.get_pending_details(job_id) {
+                info.reason = details.reason;
+                info.estimated_start = details.estimated_start;
+                info.dependencies = details.dependencies;
+            }
+        }
+        // Jobs followed with squeue since they were queued
+        if status == JobStatus::Running && info.alloc_tres.is_none() {
+            (info.alloc_cpus, info.alloc_tres) = manager.get_allocation(job_id);
+        }
+        if settings.steps && !matches!(status, JobStatus::Queued | JobStatus::Unknown) {
+            info.steps = manager.get_job_steps(job_id);
+        }
+        if settings.array_tasks && status != JobStatus::Unknown {
+            info.array_tasks = manager.get_array_tasks(job_id);
+        }
+        if settings.live_memory && status == JobStatus::Running {
+            info.live_memory = manager.get_live_memory(job_id);
+        }
+    }
+
+    /// The last update, to keep while SLURM can't be reached, unless it is
+    /// the placeholder from before the job was first polled.
+    fn last_known(previous: Option<&StatusUpdate>) -> Option<&StatusUpdate> {
+        previous.filter(|p| {
+            p.status != JobStatus::Unknown || !p.info.stdout_path.as_os_str().is_empty()
+        })
+    }
+
+    /// The update to report for a poll, given the last one reported.
+    ///
+    /// Jobs last seen active that come back Unknown keep their previous
+    /// update for up to `UNKNOWN_GRACE_POLLS` polls in a row, counted in
+    /// `misses`, rather than flickering to UNKNOWN between squeue and sacct.
+    fn hold_last_known(
+        previous: Option<&StatusUpdate>,
+        update: StatusUpdate,
+        misses: &mut u32,
+    ) -> StatusUpdate {
+        if update.status != JobStatus::Unknown {
+            *misses = 0;
+            return update;
+        }
+        match previous {
+            Some(p)
+                if p.status != JobStatus::Unknown
+                    && !p.status.is_terminal()
+                    && *misses < UNKNOWN_GRACE_POLLS =>
+            {
+                *misses += 1;
+                p.clone()
+            }
+            _ => update,
+        }
+    }
+
+    /// A warning for a job that has just become unreachable or Unknown, so
+    /// each problem is reported once rather than on every poll.
+    /// `unknown_jobs` holds the jobs last reported as Unknown.
+    fn problem_report(
+        previous: Option<&StatusUpdate>,
+        update: &StatusUpdate,
+        unknown_jobs: &mut HashSet<u64>,
+    ) -> Option<String> {
+        if update.unreachable {
+            return (!previous.is_some_and(|p| p.unreachable)).then(|| {
+                format!(
+                    "Could not reach SLURM for job {}; showing its last known status",
+                    update.job_id
+                )
+            });
+        }
+        if update.status != JobStatus::Unknown {
+            unknown_jobs.remove(&update.job_id);
+            return None;
+        }
+        unknown_jobs.insert(update.job_id).then(|| {
+            format!("Job {} not found in squeue or sacct; status is UNKNOWN", update.job_id)
+        })
+    }
+
+    /// Whether `update` differs in status or raw state from the previous one.
+    fn changed(previous: Option<&StatusUpdate>, update: &StatusUpdate) -> bool {
+        !previous.is_some_and(|p| p.status == update.status && p.info.state == update.info.state)
+    }
+
+    /// Whether a job should be polled now.
+    ///
+    /// Active jobs are polled every cycle. Once the last delivered update was
+    /// terminal, the job is only re-polled every `TERMINAL_POLL_INTERVAL`.
+    fn is_poll_due(
+        statuses: &HashMap<u64, StatusUpdate>,
+        last_polled: Option<Instant>,
+        job_id: u64,
+    ) -> bool {
+        if !Self::is_finished_in(statuses, job_id) {
+            return true;
+        }
+        last_polled
+            .map(|at| at.elapsed() >= TERMINAL_POLL_INTERVAL)
+            .unwrap_or(true)
+    }
+
     /// Stop monitoring all jobs.
     pub fn stop_monitoring(&mut self) {
         if let Some(tx) = self.command_tx.take() {
@@ -220,20 +579,13 @@ impl StatusMonitor {
     }
 
     /// Get the current cached status for a job.
-    #[allow(dead_code)]
     pub fn get_status(&self, job_id: u64) -> Option<StatusUpdate> {
         self.current_statuses.lock().unwrap().get(&job_id).cloned()
     }
 
-    /// Check if a job has finished (completed or failed).
-    #[allow(dead_code)]
+    /// Check if a job has finished (reached any terminal state).
     pub fn is_finished(&self, job_id: u64) -> bool {
-        self.current_statuses
-            .lock()
-            .unwrap()
-            .get(&job_id)
-            .map(|s| matches!(s.status, JobStatus::Completed | JobStatus::Failed))
-            .unwrap_or(false)
+        Self::is_finished_in(&self.current_statuses.lock().unwrap(), job_id)
     }
 }
 
@@ -246,6 +598,9 @@ impl Drop for StatusMonitor {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::utils::{CommandResult, CommandRunner, FakeRunner};
+    use std::ffi::OsStr;
+    use std::path::PathBuf;
 
     #[test]
     fn test_status_monitor_new() {
@@ -254,4 +609,314 @@ mod tests {
         assert!(monitor.command_tx.is_none());
         assert!(monitor.thread_handle.is_none());
     }
+
+    #[test]
+    fn test_monitor_sends_polled_status() {
+        let runner = FakeRunner::default()
+            .respond("squeue -j 5 -h -o %T", "PENDING\n")
+            .respond("squeue -j 5 -h -o %r|%S", "Priority|N/A\n");
+        let job_manager = Arc::new(Mutex::new(JobManager::with_runner(Arc::new(runner))));
+        let mut monitor = StatusMonitor::new(job_manager, 3.0);
+
+        let (tx, rx) = mpsc::channel();
+        monitor.start_monitoring(vec![5], tx);
+        let update = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        monitor.stop_monitoring();
+
+        assert_eq!(update.job_id, 5);
+        assert_eq!(update.status, JobStatus::Queued);
+        assert_eq!(update.info.reason, "Priority");
+        assert_eq!(update.info.estimated_start, "");
+    }
+
+    #[test]
+    fn test_steps_are_polled_for_started_jobs() {
+        let runner = Arc::new(
+            FakeRunner::default()
+                .respond("squeue -j 5 -h -o %T", "RUNNING\n")
+                .respond("squeue -j 6 -h -o %T", "PENDING\n")
+                .respond(
+                    "sacct -j 5 --format=JobID,JobName,State,Elapsed,MaxRSS",
+                    "JobID|JobName|State|Elapsed|MaxRSS\n5|run|RUNNING|00:01:00|\n5.0|train|RUNNING|00:01:00|\n",
+                ),
+        );
+        let job_manager = JobManager::with_runner(Arc::clone(&runner) as Arc<dyn CommandRunner>);
+        let mut monitor = StatusMonitor::new(Arc::new(Mutex::new(job_manager)), 3.0);
+        monitor.set_steps(true);
+
+        let (tx, rx) = mpsc::channel();
+        monitor.start_monitoring(vec![5, 6], tx);
+        let running = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let queued = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        monitor.stop_monitoring();
+
+        assert_eq!(running.info.steps.len(), 1);
+        assert_eq!(running.info.steps[0].name, "train");
+        assert!(queued.info.steps.is_empty());
+        assert!(!runner
+            .calls()
+            .iter()
+            .any(|call| call.starts_with("sacct -j 6 --format=JobID,JobName,State,Elapsed,MaxRSS")));
+    }
+
+    #[test]
+    fn test_array_tasks_are_polled() {
+        let runner = FakeRunner::default().respond("squeue -j 5 -h -o %T", "RUNNING\n").respond(
+            "sacct -j 5 -X --format=JobID,JobIDRaw,State,Elapsed",
+            "JobID|JobIDRaw|State|Elapsed\n5_0|6|RUNNING|00:01:00\n5_[1-3]|5|PENDING|00:00:00\n",
+        );
+        let job_manager = Arc::new(Mutex::new(JobManager::with_runner(Arc::new(runner))));
+        let mut monitor = StatusMonitor::new(job_manager, 3.0);
+        monitor.set_array_tasks(true);
+
+        let (tx, rx) = mpsc::channel();
+        monitor.start_monitoring(vec![5], tx);
+        let update = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        monitor.stop_monitoring();
+
+        let tasks: Vec<&str> =
+            update.info.array_tasks.iter().map(|task| task.task_id.as_str()).collect();
+        assert_eq!(tasks, ["0", "[1-3]"]);
+    }
+
+    #[test]
+    fn test_active_jobs_are_polled_with_squeue_only() {
+        let sacct = "\
+JobID|JobName|State|Start|End|Elapsed|Partition|WorkDir|NodeList|StdOut|StdErr
+5|train|RUNNING|2024-01-31T08:00:00|Unknown|00:01:00|gpu|/run|gpu01|/run/5.out|/run/5.err
+";
+        let runner = Arc::new(
+            FakeRunner::default()
+                .respond("squeue -j 5 -h -o %T", "RUNNING\n")
+                .respond("squeue -j 5 -h -o %T|", "RUNNING|train|2024-01-31T08:00:00|2:00|gpu|/run|gpu01\n")
+                .respond("sacct -j 5 ", sacct),
+        );
+        let job_manager = JobManager::with_runner(Arc::clone(&runner) as Arc<dyn CommandRunner>);
+        let mut monitor = StatusMonitor::new(Arc::new(Mutex::new(job_manager)), 0.1);
+
+        let (tx, rx) = mpsc::channel();
+        monitor.start_monitoring(vec![5], tx);
+        let first = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        let calls_before = runner.calls().len();
+        let second = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        monitor.stop_monitoring();
+
+        assert_eq!(first.info.elapsed, "00:01:00");
+        assert_eq!(second.status, JobStatus::Running);
+        assert_eq!(second.info.elapsed, "00:02:00");
+        assert_eq!(second.info.stdout_path, first.info.stdout_path);
+        assert_eq!(second.info.stderr_path, PathBuf::from("/run/5.err"));
+        assert!(runner.calls()[calls_before..]
+            .iter()
+            .all(|call| call.starts_with("squeue -j 5 -h -o %T|")));
+    }
+
+    /// Blocks every command until the test lets it finish.
+    #[derive(Debug)]
+    struct BlockingRunner {
+        started: Mutex<Sender<()>>,
+        release: Mutex<Receiver<()>>,
+    }
+
+    impl CommandRunner for BlockingRunner {
+        fn run_os(&self, _cmd: &[&OsStr], _check: bool) -> anyhow::Result<CommandResult> {
+            let _ = self.started.lock().unwrap().send(());
+            let _ = self.release.lock().unwrap().recv();
+            Ok(CommandResult::default())
+        }
+    }
+
+    #[test]
+    fn test_poll_does_not_hold_job_manager_lock() {
+        let (started_tx, started_rx) = mpsc::channel();
+        let (release_tx, release_rx) = mpsc::channel();
+        let runner = BlockingRunner {
+            started: Mutex::new(started_tx),
+            release: Mutex::new(release_rx),
+        };
+        let job_manager = Arc::new(Mutex::new(JobManager::with_runner(Arc::new(runner))));
+        let mut monitor = StatusMonitor::new(Arc::clone(&job_manager), 3.0);
+
+        let (tx, _rx) = mpsc::channel();
+        monitor.start_monitoring(vec![5], tx);
+        started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+
+        // A command is in flight, yet the manager can still be used
+        job_manager.try_lock().unwrap().add_tracked_job(6);
+
+        drop(release_tx);
+        monitor.stop_monitoring();
+    }
+
+    #[test]
+    fn test_finished_jobs_back_off() {
+        let mut statuses = HashMap::new();
+        let update = |job_id, status| StatusUpdate {
+            job_id,
+            status,
+            info: JobInfo::default(),
+            unreachable: false,
+        };
+        statuses.insert(1, update(1, JobStatus::Running));
+        statuses.insert(2, update(2, JobStatus::Completed));
+
+        let just_now = Some(Instant::now());
+        let long_ago = Instant::now().checked_sub(TERMINAL_POLL_INTERVAL * 2);
+
+        // Active and never-seen jobs are always polled
+        assert!(StatusMonitor::is_poll_due(&statuses, just_now, 1));
+        assert!(StatusMonitor::is_poll_due(&statuses, just_now, 3));
+
+        // Finished jobs wait for the longer interval
+        assert!(!StatusMonitor::is_poll_due(&statuses, just_now, 2));
+        assert!(StatusMonitor::is_poll_due(&statuses, long_ago, 2));
+        assert!(StatusMonitor::is_poll_due(&statuses, None, 2));
+    }
+
+    #[test]
+    fn test_just_finished_requires_active_previous_state() {
+        let update = |status| StatusUpdate {
+            job_id: 1,
+            status,
+            info: JobInfo::default(),
+            unreachable: false,
+        };
+        let running = update(JobStatus::Running);
+        let queued = update(JobStatus::Queued);
+        let unknown = update(JobStatus::Unknown);
+        let completed = update(JobStatus::Completed);
+
+        assert!(StatusMonitor::just_finished(Some(&running), &JobStatus::Completed));
+        assert!(StatusMonitor::just_finished(Some(&queued), &JobStatus::Cancelled));
+        assert!(!StatusMonitor::just_finished(Some(&running), &JobStatus::Running));
+        // Already finished, or never seen active
+        assert!(!StatusMonitor::just_finished(Some(&completed), &JobStatus::Completed));
+        assert!(!StatusMonitor::just_finished(Some(&unknown), &JobStatus::Failed));
+        assert!(!StatusMonitor::just_finished(None, &JobStatus::Failed));
+    }
+
+    #[test]
+    fn test_unknown_is_held_off_for_active_jobs() {
+        let update = |status| StatusUpdate {
+            job_id: 1,
+            status,
+            info: JobInfo::default(),
+            unreachable: false,
+        };
+        let running = update(JobStatus::Running);
+        let mut misses = 0;
+        let mut reported = Vec::new();
+        let mut previous = running.clone();
+        for _ in 0..UNKNOWN_GRACE_POLLS + 1 {
+            previous = StatusMonitor::hold_last_known(
+                Some(&previous),
+                update(JobStatus::Unknown),
+                &mut misses,
+            );
+            reported.push(previous.status);
+        }
+        let mut expected = vec![JobStatus::Running; UNKNOWN_GRACE_POLLS as usize];
+        expected.push(JobStatus::Unknown);
+        assert_eq!(reported, expected);
+
+        // A confident status resets the count
+        let completed = StatusMonitor::hold_last_known(
+            Some(&running),
+            update(JobStatus::Completed),
+            &mut misses,
+        );
+        assert_eq!((completed.status, misses), (JobStatus::Completed, 0));
+
+        // Jobs never seen active, or already finished, are reported as is
+        let held = |previous: Option<&StatusUpdate>| {
+            StatusMonitor::hold_last_known(previous, update(JobStatus::Unknown), &mut 0).status
+        };
+        assert_eq!(held(None), JobStatus::Unknown);
+        assert_eq!(held(Some(&update(JobStatus::Completed))), JobStatus::Unknown);
+    }
+
+    #[test]
+    fn test_last_update_is_kept_while_slurm_is_unreachable() {
+        let scontrol = "JobId=5 JobName=train JobState=RUNNING StdOut=/scratch/train-5.out";
+        let runner = Arc::new(
+            FakeRunner::default()
+                .respond("squeue -j 5 -h -o %T", "RUNNING\n")
+                .respond("sacct -j 5 ", "")
+                .respond("scontrol show job 5", scontrol),
+        );
+        let job_manager = JobManager::with_runner(Arc::clone(&runner) as Arc<dyn CommandRunner>);
+        let job_manager = Arc::new(Mutex::new(job_manager));
+        let mut monitor = StatusMonitor::new(Arc::clone(&job_manager), 0.1);
+
+        let (tx, rx) = mpsc::channel();
+        monitor.start_monitoring(vec![5], tx);
+        let known = rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(known.info.stdout_path, PathBuf::from("/scratch/train-5.out"));
+
+        // The controller goes down
+        let down = "Unable to contact slurm controller (connect failure)";
+        let runner = FakeRunner::default()
+            .fail("squeue", down)
+            .fail("sacct", "Problem talking to the database")
+            .fail("scontrol", down);
+        *job_manager.lock().unwrap() = JobManager::with_runner(Arc::new(runner));
+        let held = rx.iter().find(|update| update.unreachable).unwrap();
+        monitor.stop_monitoring();
+
+        assert_eq!(held.status, JobStatus::Running);
+        assert_eq!(held.info.job_name, "train");
+        assert_eq!(held.info.stdout_path, PathBuf::from("/scratch/train-5.out"));
+    }
+
+    #[test]
+    fn test_problems_are_reported_once() {
+        let update = |status, unreachable| StatusUpdate {
+            job_id: 1,
+            status,
+            info: JobInfo::default(),
+            unreachable,
+        };
+        let running = update(JobStatus::Running, false);
+        let unknown = update(JobStatus::Unknown, false);
+        let unreachable = update(JobStatus::Running, true);
+        let mut unknown_jobs = HashSet::new();
+        let mut report = |previous: &StatusUpdate, update: &StatusUpdate| {
+            StatusMonitor::problem_report(Some(previous), update, &mut unknown_jobs)
+        };
+
+        assert!(report(&running, &running).is_none());
+        assert!(report(&running, &unknown).unwrap().contains("UNKNOWN"));
+        assert!(report(&unknown, &unknown).is_none());
+        assert!(report(&unknown, &running).is_none());
+        assert!(report(&running, &unknown).is_some());
+
+        assert!(report(&running, &unreachable).unwrap().contains("Could not reach"));
+        assert!(report(&unreachable, &unreachable).is_none());
+        assert!(report(&unreachable, &running).is_none());
+    }
+
+    #[test]
+    fn test_changed_compares_status_and_state() {
+        let update = |status, state: &str| StatusUpdate {
+            job_id: 1,
+            status,
+            info: JobInfo {
+                state: state.to_string(),
+                elapsed: "00:05:00".to_string(),
+                ..Default::default()
+            },
+            unreachable: false,
+        };
+        let running = update(JobStatus::Running, "RUNNING");
+        let mut later = running.clone();
+        later.info.elapsed = "00:06:00".to_string();
+
+        assert!(StatusMonitor::changed(None, &running));
+        assert!(!StatusMonitor::changed(Some(&running), &later));
+        assert!(StatusMonitor::changed(Some(&running), &update(JobStatus::Completed, "COMPLETED")));
+        assert!(StatusMonitor::changed(
+            Some(&update(JobStatus::Cancelled, "CANCELLED")),
+            &update(JobStatus::Cancelled, "CANCELLED by 1000"),
+        ));
+    }
 }
@@ -1,9 +1,49 @@
 //! Application state management for the TUI.
 
-use crate::job_manager::JobInfo;
-use crate::utils::JobStatus;
+use super::search::{shown_line_count, shown_lines, Pattern};
+use super::theme::Theme;
+use crate::config::KeyBindings;
+use crate::gpu_monitor::GpuUpdate;
+use crate::job_manager::{JobInfo, JobSummary};
+use crate::log_tailer::LogProblem;
+use crate::utils::{
+    format_slurm_duration, parse_slurm_duration, parse_slurm_timestamp, JobFilter, JobStatus, Tres,
+};
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
 use ratatui::layout::{Constraint, Direction, Layout, Rect};
-use std::collections::{HashMap, HashSet};
+use serde::Deserialize;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// How long a first `d` press waits for the confirming second press.
+const DELETE_CONFIRM_WINDOW: Duration = Duration::from_secs(2);
+
+/// How long a header message stays visible.
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Memory samples kept per job for the memory history, one per status poll.
+const MEMORY_HISTORY_LEN: usize = 300;
+
+/// Running jobs with less time than this left before their time limit are
+/// highlighted.
+pub const TIME_LEFT_WARNING: Duration = Duration::from_secs(5 * 60);
+
+/// Line put between a log's old and new content when its file restarts, with
+/// `keep_truncated_logs`.
+pub const LOG_RESET_MARKER: &str = "--- log file truncated or replaced, output restarts here ---";
+
+/// Longest log line kept in one piece; longer lines are hard-wrapped so a job
+/// printing megabytes without a newline can't slow down rendering.
+pub const MAX_LOG_LINE_CHARS: usize = 10_000;
+
+/// Appended to a log line that was hard-wrapped at `MAX_LOG_LINE_CHARS`.
+pub const LINE_CONTINUATION_MARKER: &str = " ↩";
+
+/// Height of the diagnostics pane, including borders.
+const DIAGNOSTICS_HEIGHT: u16 = 10;
 
 /// Which panel is currently focused
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -21,6 +61,285 @@ impl FocusedPanel {
     }
 }
 
+/// Ordering applied to the job list
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum SortMode {
+    /// Newest job first
+    #[default]
+    Id,
+    /// Active jobs first, then finished ones
+    Status,
+    /// Longest-running first
+    Runtime,
+    /// Alphabetical by job name
+    Name,
+}
+
+impl SortMode {
+    /// Cycle to the next sort mode.
+    pub fn next(self) -> Self {
+        match self {
+            SortMode::Id => SortMode::Status,
+            SortMode::Status => SortMode::Runtime,
+            SortMode::Runtime => SortMode::Name,
+            SortMode::Name => SortMode::Id,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortMode::Id => "ID",
+            SortMode::Status => "Status",
+            SortMode::Runtime => "Runtime",
+            SortMode::Name => "Name",
+        }
+    }
+}
+
+/// How the Runtime, Start and End columns show times
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeDisplayMode {
+    /// Running jobs' runtime counts up between polls; start and end as
+    /// SLURM reports them
+    #[default]
+    Live,
+    /// Everything exactly as sacct last reported it
+    Raw,
+    /// Start and end relative to now, e.g. `3m ago`
+    Relative,
+}
+
+impl TimeDisplayMode {
+    /// Cycle to the next mode.
+    pub fn next(self) -> Self {
+        match self {
+            TimeDisplayMode::Live => TimeDisplayMode::Raw,
+            TimeDisplayMode::Raw => TimeDisplayMode::Relative,
+            TimeDisplayMode::Relative => TimeDisplayMode::Live,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TimeDisplayMode::Live => "live",
+            TimeDisplayMode::Raw => "raw",
+            TimeDisplayMode::Relative => "relative",
+        }
+    }
+}
+
+/// What a prompt's input is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PromptKind {
+    /// Query for the log search
+    Search,
+    /// Note for the given job
+    Note(u64),
+    /// Log filter, applied as it is typed
+    Filter,
+    /// Step of the given job to attach to
+    Attach(u64),
+}
+
+/// A line of input typed in the header, e.g. a search query.
+#[derive(Debug, Clone)]
+pub struct Prompt {
+    pub kind: PromptKind,
+    pub input: String,
+    /// Input when the prompt was opened, restored by cancelling a filter
+    pub initial: String,
+    /// `input` compiled, for searches and filters; kept up to date by
+    /// `App::prompt_changed`
+    pub pattern: Option<Pattern>,
+    /// Valid inputs, listed in the header, e.g. the steps to attach to
+    pub choices: Vec<String>,
+}
+
+impl Prompt {
+    fn new(kind: PromptKind, input: String) -> Self {
+        let mut prompt = Self {
+            kind,
+            initial: input.clone(),
+            input,
+            pattern: None,
+            choices: Vec::new(),
+        };
+        prompt.compile();
+        prompt
+    }
+
+    fn compile(&mut self) {
+        if matches!(self.kind, PromptKind::Search | PromptKind::Filter) {
+            self.pattern = Some(Pattern::new(&self.input)).filter(|_| !self.input.is_empty());
+        }
+    }
+}
+
+/// How the body is divided between the job list and the log panels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LayoutMode {
+    /// Job list on the left, logs on the right
+    #[default]
+    Split,
+    /// The current job on a single line above full-width logs
+    Compact,
+    /// Only the job list, over the whole body; logs aren't read at all
+    Dashboard,
+}
+
+/// A field that can be shown in the job status table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Column {
+    /// Job ID
+    Id,
+    /// Job status
+    Status,
+    /// Elapsed run time
+    Runtime,
+    /// Job name
+    Name,
+    /// Pending reason and estimated start of queued jobs
+    Details,
+    /// Partition the job was submitted to
+    Partition,
+    /// Allocated nodes
+    Nodes,
+    /// Start time
+    Start,
+    /// End time
+    End,
+    /// Raw SLURM state, e.g. `CANCELLED by 1000`
+    State,
+    /// Time left before a running job reaches its time limit
+    Remaining,
+    /// Job comment (`sbatch --comment`)
+    Comment,
+    /// Workload characterization key (`sbatch --wckey`)
+    Wckey,
+}
+
+/// Columns shown when `--columns` isn't given.
+pub const DEFAULT_COLUMNS: &[Column] = &[
+    Column::Id,
+    Column::Status,
+    Column::Runtime,
+    Column::Name,
+    Column::Details,
+];
+
+/// Columns of the dashboard when `--columns` isn't given; it has the whole
+/// width to itself.
+pub const DASHBOARD_COLUMNS: &[Column] = &[
+    Column::Id,
+    Column::Status,
+    Column::Runtime,
+    Column::Name,
+    Column::Partition,
+    Column::Nodes,
+    Column::Start,
+    Column::Remaining,
+    Column::State,
+    Column::Details,
+];
+
+impl Column {
+    /// Table header text.
+    pub fn title(&self) -> &'static str {
+        match self {
+            Column::Id => "Job ID",
+            Column::Status => "Status",
+            Column::Runtime => "Runtime",
+            Column::Name => "Name",
+            Column::Details => "Details",
+            Column::Partition => "Partition",
+            Column::Nodes => "Nodes",
+            Column::Start => "Start",
+            Column::End => "End",
+            Column::State => "State",
+            Column::Remaining => "Remaining",
+            Column::Comment => "Comment",
+            Column::Wckey => "WCKey",
+        }
+    }
+
+    /// Column width in the table.
+    pub fn width(&self) -> u16 {
+        match self {
+            Column::Id
+            | Column::Status
+            | Column::Runtime
+            | Column::Partition
+            | Column::Remaining
+            | Column::Wckey => 12,
+            Column::Name | Column::Comment => 22,
+            Column::Details | Column::Nodes => 16,
+            Column::Start | Column::End => 20,
+            Column::State => 14,
+        }
+    }
+}
+
+/// Rank used when sorting by status; jobs needing attention come first, and
+/// unknown ones last in either direction.
+fn status_rank(status: JobStatus) -> Option<u8> {
+    Some(status.rank()).filter(|_| status != JobStatus::Unknown)
+}
+
+/// Compare optional sort keys, keeping missing values last in either direction.
+fn cmp_missing_last<T: Ord>(a: Option<T>, b: Option<T>, reverse: bool) -> Ordering {
+    match (a, b) {
+        (Some(a), Some(b)) if reverse => b.cmp(&a),
+        (Some(a), Some(b)) => a.cmp(&b),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+/// Scrollable text shown on top of the main view
+#[derive(Debug, Clone, Default)]
+pub struct TextOverlay {
+    pub title: String,
+    pub lines: Vec<String>,
+    pub scroll: usize,
+}
+
+impl TextOverlay {
+    pub fn new(title: impl Into<String>, content: &str) -> Self {
+        Self {
+            title: title.into(),
+            lines: content.lines().map(|l| l.to_string()).collect(),
+            scroll: 0,
+        }
+    }
+
+    pub fn scroll_up(&mut self, lines: usize) {
+        self.scroll = self.scroll.saturating_sub(lines);
+    }
+
+    pub fn scroll_down(&mut self, lines: usize) {
+        self.scroll = (self.scroll + lines).min(self.lines.len().saturating_sub(1));
+    }
+}
+
+/// How long a running job may still run before SLURM ends it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeLeft {
+    /// The job has no time limit
+    Unlimited,
+    Left(Duration),
+}
+
+/// Task of an array job whose logs are shown in place of the job's own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShownTask {
+    /// Index of the task, e.g. `4` for `123_4`
+    pub task_id: String,
+    pub stdout_path: PathBuf,
+    pub stderr_path: PathBuf,
+}
+
 /// Data for a single job
 #[derive(Debug, Clone, Default)]
 pub struct JobData {
@@ -34,6 +353,43 @@ pub struct JobData {
     pub stderr_scroll: usize,
     pub stdout_scroll_mode: bool,
     pub stderr_scroll_mode: bool,
+    /// Latest GPU utilization sample, when GPU monitoring is enabled
+    pub gpu: Option<GpuUpdate>,
+    /// Job had already finished at startup; its logs were loaded once and
+    /// it isn't polled or tailed
+    pub replay: bool,
+    /// Resource usage summary, for replayed jobs
+    pub summary: Option<JobSummary>,
+    /// Stderr received output that hasn't been looked at yet
+    pub unread_stderr: bool,
+    /// When the monitor saw the job finish; unset for jobs that had already
+    /// finished when they were added
+    pub finished_at: Option<Instant>,
+    /// Free-form note entered by the user, shown next to the job's name
+    pub note: Option<String>,
+    /// When the job's logs last grew (file modification time), or when it
+    /// was first seen running if they haven't yet
+    pub last_output: Option<DateTime<Local>>,
+    /// Resident memory in MiB at each status poll while running, oldest
+    /// first, up to `MEMORY_HISTORY_LEN` samples (`--live-memory`)
+    pub memory_history: VecDeque<f64>,
+    /// `info.start_time`, parsed when it changes; None until the job starts
+    /// or if SLURM didn't report it
+    pub started_at: Option<DateTime<Local>>,
+    /// Why the stdout/stderr file can't be read, as last reported by the
+    /// log tailer
+    pub stdout_problem: Option<LogProblem>,
+    pub stderr_problem: Option<LogProblem>,
+    /// Array task whose logs the panels show, once one was picked
+    pub shown_task: Option<ShownTask>,
+    /// The last stdout/stderr line has no trailing newline yet and is
+    /// continued by the next append, from this column
+    stdout_partial: Option<usize>,
+    stderr_partial: Option<usize>,
+    /// With raw progress, the last stdout/stderr line was ended by a \r, so
+    /// a \n starting the next append isn't a blank line
+    stdout_ended_by_cr: bool,
+    stderr_ended_by_cr: bool,
 }
 
 impl JobData {
@@ -48,56 +404,246 @@ impl JobData {
         }
     }
 
-    /// Process log content to handle carriage returns (progress bars).
-    /// Simulates terminal behavior: \r returns to line start, overwriting previous content.
-    fn process_log_content(content: &str) -> Vec<String> {
-        let mut lines: Vec<String> = Vec::new();
-        let mut current_line = String::new();
+    /// Time left before a running job reaches its time limit. None for jobs
+    /// that aren't running or whose limit or elapsed time is unknown.
+    pub fn time_left(&self) -> Option<TimeLeft> {
+        if self.status != JobStatus::Running {
+            return None;
+        }
+        if self.info.time_limit == "UNLIMITED" {
+            return Some(TimeLeft::Unlimited);
+        }
+        let limit = parse_slurm_duration(&self.info.time_limit)?;
+        let elapsed = self.elapsed(Local::now())?;
+        Some(TimeLeft::Left(limit.saturating_sub(elapsed)))
+    }
+
+    /// How long the job has run at `now`. While it runs this counts up from
+    /// its start time, so it keeps moving between status polls; otherwise,
+    /// or without a start time, it is the elapsed time of the last poll.
+    pub fn elapsed(&self, now: DateTime<Local>) -> Option<Duration> {
+        let polled = parse_slurm_duration(&self.info.elapsed);
+        let since_start = self
+            .started_at
+            .filter(|_| self.status == JobStatus::Running)
+            .and_then(|started_at| now.signed_duration_since(started_at).to_std().ok());
+        match (since_start, polled) {
+            // Never behind the poll, e.g. if this clock is behind SLURM's
+            (Some(since_start), Some(polled)) => Some(since_start.max(polled)),
+            (since_start, polled) => since_start.or(polled),
+        }
+    }
+
+    /// The Runtime column: `elapsed` formatted like sacct, or the polled
+    /// text as-is for jobs that aren't running.
+    pub fn runtime(&self, now: DateTime<Local>) -> String {
+        if self.status != JobStatus::Running || self.started_at.is_none() {
+            return self.info.elapsed.clone();
+        }
+        self.elapsed(now).map(format_slurm_duration).unwrap_or_default()
+    }
+
+    /// Why the log shown in `panel` can't be read, if that's worth showing.
+    /// SLURM creates log files when a job starts, so a queued job's missing
+    /// file only means there is no output yet.
+    pub fn log_problem(&self, panel: FocusedPanel) -> Option<&LogProblem> {
+        let problem = match panel {
+            FocusedPanel::Stdout => self.stdout_problem.as_ref(),
+            FocusedPanel::Stderr => self.stderr_problem.as_ref(),
+        }?;
+        if matches!(problem, LogProblem::Missing(_)) && self.status == JobStatus::Queued {
+            return None;
+        }
+        Some(problem)
+    }
+
+    /// ID the log panels are titled with: the job's, or the shown array
+    /// task's, e.g. `123_4`.
+    pub fn log_id(&self) -> String {
+        match self.shown_task {
+            Some(ref task) => format!("{}_{}", self.info.job_id, task.task_id),
+            None => self.info.job_id.to_string(),
+        }
+    }
+
+    /// Whether there are enough memory samples to show a trend.
+    pub fn has_memory_trend(&self) -> bool {
+        self.status == JobStatus::Running && self.memory_history.len() >= 2
+    }
+
+    /// Record a memory sample, dropping the oldest beyond the history length.
+    fn record_memory(&mut self, rss_mib: f64) {
+        if self.memory_history.len() == MEMORY_HISTORY_LEN {
+            self.memory_history.pop_front();
+        }
+        self.memory_history.push_back(rss_mib);
+    }
+
+    /// Last stdout line with something other than whitespace on it.
+    pub fn last_stdout_line(&self) -> Option<&str> {
+        self.stdout_lines
+            .iter()
+            .rev()
+            .map(|line| line.trim())
+            .find(|line| !line.is_empty())
+    }
+
+    /// Append log content to `lines`, handling carriage returns (progress bars).
+    /// Simulates terminal behavior: \r returns to line start, and what follows
+    /// overwrites the line from there. The line stays as it was until then, so
+    /// a trailing \r or a `\r\n` line ending doesn't blank it.
+    /// With `raw_progress`, \r ends the line instead, so every progress update
+    /// is kept as its own line.
+    /// Lines longer than `MAX_LOG_LINE_CHARS` are cut there and continue on
+    /// the next line, the cut piece ending in `LINE_CONTINUATION_MARKER`; a
+    /// later \r only returns to the start of the continuation.
+    ///
+    /// Only the new content is processed, continuing the unterminated last
+    /// line at its column if `partial` is set, so appends cost the size of the
+    /// new content rather than the whole log. Likewise `ended_by_cr` carries
+    /// over that the last line was ended by a \r in raw mode, so a \n
+    /// starting the next append isn't a blank line.
+    fn append_log_lines(
+        lines: &mut Vec<String>,
+        partial: &mut Option<usize>,
+        ended_by_cr: &mut bool,
+        content: &str,
+        raw_progress: bool,
+    ) {
+        let (mut current_line, mut cursor): (Vec<char>, usize) = match partial.take() {
+            Some(cursor) => (lines.pop().unwrap_or_default().chars().collect(), cursor),
+            None => (Vec::new(), 0),
+        };
 
         for ch in content.chars() {
             match ch {
+                '\r' if raw_progress => {
+                    if !current_line.is_empty() {
+                        lines.push(current_line.drain(..).collect());
+                        *ended_by_cr = true;
+                    }
+                    cursor = 0;
+                    continue;
+                }
                 '\r' => {
-                    // Carriage return: reset to beginning of current line (don't push yet)
-                    current_line.clear();
+                    // Carriage return: back to the start, overwriting from there
+                    cursor = 0;
                 }
+                '\n' if *ended_by_cr => {}
                 '\n' => {
                     // Newline: push current line and start fresh
-                    lines.push(current_line.clone());
-                    current_line.clear();
+                    lines.push(current_line.drain(..).collect());
+                    cursor = 0;
                 }
                 _ => {
-                    current_line.push(ch);
+                    if cursor == MAX_LOG_LINE_CHARS {
+                        let mut piece: String = current_line.drain(..).collect();
+                        piece.push_str(LINE_CONTINUATION_MARKER);
+                        lines.push(piece);
+                        cursor = 0;
+                    }
+                    match current_line.get_mut(cursor) {
+                        Some(overwritten) => *overwritten = ch,
+                        None => current_line.push(ch),
+                    }
+                    cursor += 1;
                 }
             }
+            *ended_by_cr = false;
         }
 
         // Don't forget any trailing content without a newline
         if !current_line.is_empty() {
-            lines.push(current_line);
+            lines.push(current_line.into_iter().collect());
+            *partial = Some(cursor);
         }
-
-        lines
     }
 
-    /// Update stdout content
-    pub fn append_stdout(&mut self, content: &str, max_visible_lines: usize) {
+    /// Update stdout content.
+    ///
+    /// New content only moves the panel if it is following the end of the
+    /// log: in scroll mode it stays where it was scrolled to, whichever panel
+    /// has the focus, until the user scrolls back down or everything fits.
+    pub fn append_stdout(&mut self, content: &str, max_visible_lines: usize, raw_progress: bool) {
         self.stdout.push_str(content);
-        self.stdout_lines = Self::process_log_content(&self.stdout);
+        Self::append_log_lines(
+            &mut self.stdout_lines,
+            &mut self.stdout_partial,
+            &mut self.stdout_ended_by_cr,
+            content,
+            raw_progress,
+        );
 
         // Auto-scroll to bottom if not in scroll mode
         if !self.stdout_scroll_mode {
             self.scroll_stdout_to_bottom(max_visible_lines);
+        } else {
+            Self::clamp_scroll(
+                &mut self.stdout_scroll,
+                &mut self.stdout_scroll_mode,
+                self.stdout_lines.len(),
+                max_visible_lines,
+            );
         }
     }
 
-    /// Update stderr content
-    pub fn append_stderr(&mut self, content: &str, max_visible_lines: usize) {
+    /// Update stderr content, scrolling like `append_stdout`.
+    pub fn append_stderr(&mut self, content: &str, max_visible_lines: usize, raw_progress: bool) {
+        if !content.is_empty() {
+            self.unread_stderr = true;
+        }
         self.stderr.push_str(content);
-        self.stderr_lines = Self::process_log_content(&self.stderr);
+        Self::append_log_lines(
+            &mut self.stderr_lines,
+            &mut self.stderr_partial,
+            &mut self.stderr_ended_by_cr,
+            content,
+            raw_progress,
+        );
 
         // Auto-scroll to bottom if not in scroll mode
         if !self.stderr_scroll_mode {
             self.scroll_stderr_to_bottom(max_visible_lines);
+        } else {
+            Self::clamp_scroll(
+                &mut self.stderr_scroll,
+                &mut self.stderr_scroll_mode,
+                self.stderr_lines.len(),
+                max_visible_lines,
+            );
+        }
+    }
+
+    /// Discard stdout, e.g. after the file was truncated.
+    pub fn clear_stdout(&mut self) {
+        self.stdout.clear();
+        self.stdout_lines.clear();
+        self.stdout_partial = None;
+        self.stdout_ended_by_cr = false;
+    }
+
+    /// Discard stderr, e.g. after the file was truncated.
+    pub fn clear_stderr(&mut self) {
+        self.stderr.clear();
+        self.stderr_lines.clear();
+        self.stderr_partial = None;
+        self.stderr_ended_by_cr = false;
+    }
+
+    /// Keep a scroll offset within `total` lines after the content changed.
+    ///
+    /// Leaves scroll mode once everything fits in the panel, since there is
+    /// nothing left to scroll.
+    fn clamp_scroll(
+        scroll: &mut usize,
+        scroll_mode: &mut bool,
+        total: usize,
+        max_visible_lines: usize,
+    ) {
+        let max_scroll = total.saturating_sub(max_visible_lines);
+        *scroll = (*scroll).min(max_scroll);
+        if total <= max_visible_lines {
+            *scroll_mode = false;
         }
     }
 
@@ -116,6 +662,17 @@ impl JobData {
     }
 }
 
+/// Completion of all monitored jobs, shown below the job list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BatchProgress {
+    /// Jobs in a terminal state
+    pub complete: usize,
+    pub total: usize,
+    /// Rough time until the remaining jobs finish, once any have finished
+    /// while monitoring
+    pub eta: Option<Duration>,
+}
+
 /// Main application state
 pub struct App {
     /// All job data
@@ -132,10 +689,101 @@ pub struct App {
     pub stdout_panel_height: usize,
     /// Actual stderr panel inner height (set from render layout)
     pub stderr_panel_height: usize,
+    /// Inner height of the errors view (calculated from terminal size)
+    pub errors_panel_height: usize,
+    /// Inner width of the status table (set from render layout)
+    pub status_table_width: u16,
     /// Auto-discover new jobs
     pub auto_discover: bool,
+    /// Criteria used when auto-discovering jobs
+    pub discovery_filter: JobFilter,
     /// Jobs that have been explicitly deleted by the user (to prevent re-adding via auto-discovery)
     pub deleted_jobs: HashSet<u64>,
+    /// Most recently deleted job, restorable with undo
+    pub last_deleted: Option<u64>,
+    /// Job awaiting delete confirmation, with the time of the first press
+    pub pending_delete: Option<(u64, Instant)>,
+    /// Transient message shown in the header, with the time it was set
+    pub message: Option<(String, Instant)>,
+    /// Ordering of the job list
+    pub sort_mode: SortMode,
+    /// Reverse the natural direction of `sort_mode`
+    pub sort_reverse: bool,
+    /// How the job list shows runtimes and start and end times
+    pub time_display_mode: TimeDisplayMode,
+    /// Only list jobs with this status
+    pub status_filter: Option<JobStatus>,
+    /// Leave finished jobs out of the job list, apart from the current one
+    pub hide_finished: bool,
+    /// Overlay drawn above the main view (e.g. a job's batch script)
+    pub overlay: Option<TextOverlay>,
+    /// Files tailed directly, without a SLURM job. Entry `i` is shown as
+    /// pseudo-job `i + 1` with its content in the stdout panel.
+    pub tail_files: Vec<PathBuf>,
+    /// Whether GPU utilization is being polled
+    pub gpu_enabled: bool,
+    /// Jump to newly discovered jobs
+    pub follow_new: bool,
+    /// Set once the user switches jobs themselves; suppresses `follow_new`
+    pub user_navigated: bool,
+    /// Show the diagnostics pane below the main view
+    pub show_diagnostics: bool,
+    /// How the job list and the logs share the screen
+    pub layout_mode: LayoutMode,
+    /// Percentage of the output area given to stdout; 0 or 100 hides a panel
+    pub stdout_share: u16,
+    /// Fields shown in the job status table, in order
+    pub columns: Vec<Column>,
+    /// Columns after the first scrolled out of a status table too wide to
+    /// show them all
+    pub column_offset: usize,
+    /// How often to look for new jobs when auto-discovering
+    pub discovery_interval: Duration,
+    /// Colors used when rendering
+    pub theme: Theme,
+    /// Key bindings for single-letter commands
+    pub keys: KeyBindings,
+    /// Most jobs to monitor at once, if limited
+    pub max_jobs: Option<usize>,
+    /// Keep every carriage-return progress update as its own log line
+    pub raw_progress: bool,
+    /// Keep a log's earlier output above `LOG_RESET_MARKER` when its file
+    /// is truncated or replaced, instead of clearing the panel
+    pub keep_truncated_logs: bool,
+    /// Running jobs whose logs haven't grown for this long are flagged as
+    /// stalled; never if unset
+    pub stall_after: Option<Duration>,
+    /// Ring the terminal bell when a monitored job fails
+    pub bell: bool,
+    /// A job failed since the bell last rang
+    bell_pending: bool,
+    /// Jobs left out at the last discovery because of `max_jobs`
+    pub jobs_over_limit: usize,
+    /// The last status poll couldn't reach SLURM, so jobs show what was
+    /// known before
+    pub slurm_unreachable: bool,
+    /// When monitoring started, the baseline for completion estimates
+    pub started_at: Instant,
+    /// Input line being typed, if a prompt is open
+    pub prompt: Option<Prompt>,
+    /// Active log search, highlighted in both panels
+    pub search_query: Option<Pattern>,
+    /// Only lines matching this are shown in both panels
+    pub log_filter: Option<Pattern>,
+    /// Line of the last match jumped to, in the given job's panel
+    pub current_match: Option<(u64, FocusedPanel, usize)>,
+    /// Wrap long log lines instead of cutting them off at the panel edge
+    pub wrap_logs: bool,
+    /// Show the last stdout line of each job in the job table
+    pub show_preview: bool,
+    /// Job and step chosen to attach to, for the event loop to run sattach
+    pub attach_request: Option<(u64, String)>,
+    /// Show the stderr of every job in one pane instead of the job list
+    pub errors_view: bool,
+    /// First visible line of the errors view in scroll mode
+    pub errors_scroll: usize,
+    /// The errors view was scrolled and no longer follows new lines
+    pub errors_scroll_mode: bool,
 }
 
 impl App {
@@ -149,68 +797,906 @@ impl App {
             max_visible_lines: 20, // Default, will be updated based on terminal size
             stdout_panel_height: 20, // Default, will be updated from actual render layout
             stderr_panel_height: 20, // Default, will be updated from actual render layout
+            errors_panel_height: 20, // Default, will be updated from actual render layout
+            status_table_width: u16::MAX, // Default, will be updated from actual render layout
             auto_discover: false,
+            discovery_filter: JobFilter::default(),
             deleted_jobs: HashSet::new(),
+            last_deleted: None,
+            pending_delete: None,
+            message: None,
+            sort_mode: SortMode::default(),
+            sort_reverse: false,
+            time_display_mode: TimeDisplayMode::default(),
+            status_filter: None,
+            hide_finished: false,
+            overlay: None,
+            tail_files: Vec::new(),
+            gpu_enabled: false,
+            follow_new: false,
+            user_navigated: false,
+            show_diagnostics: false,
+            layout_mode: LayoutMode::default(),
+            stdout_share: 50,
+            columns: DEFAULT_COLUMNS.to_vec(),
+            column_offset: 0,
+            discovery_interval: Duration::from_secs(10),
+            theme: Theme::default(),
+            keys: KeyBindings::default(),
+            max_jobs: None,
+            raw_progress: false,
+            keep_truncated_logs: false,
+            stall_after: None,
+            bell: false,
+            bell_pending: false,
+            jobs_over_limit: 0,
+            slurm_unreachable: false,
+            started_at: Instant::now(),
+            prompt: None,
+            search_query: None,
+            log_filter: None,
+            current_match: None,
+            wrap_logs: false,
+            show_preview: false,
+            attach_request: None,
+            errors_view: false,
+            errors_scroll: 0,
+            errors_scroll_mode: false,
         }
     }
 
     /// Add a job to track.
     pub fn add_job(&mut self, job_id: u64) {
-        if !self.jobs.contains_key(&job_id) {
-            self.jobs.insert(job_id, JobData::new(job_id));
-        }
+        let tail_file = self.tail_file(job_id).cloned();
+        self.jobs.entry(job_id).or_insert_with(|| {
+            let mut job = JobData::new(job_id);
+            if let Some(path) = tail_file {
+                job.info.job_name = path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_else(|| path.display().to_string());
+                job.info.stdout_path = path;
+            }
+            job
+        });
         if self.current_job_id.is_none() {
             self.current_job_id = Some(job_id);
         }
     }
 
+    /// Set up tail mode for the given files, adding one entry per file.
+    pub fn add_tail_files(&mut self, paths: Vec<PathBuf>) {
+        self.tail_files = paths;
+        // Show files in the order given rather than newest ID first
+        self.sort_reverse = true;
+        for job_id in 1..=self.tail_files.len() as u64 {
+            self.add_job(job_id);
+        }
+    }
+
+    /// Whether the monitor is tailing plain files instead of SLURM jobs.
+    pub fn is_tail_mode(&self) -> bool {
+        !self.tail_files.is_empty()
+    }
+
+    /// Path of the tailed file shown as pseudo-job `job_id`.
+    pub fn tail_file(&self, job_id: u64) -> Option<&PathBuf> {
+        let index = (job_id as usize).checked_sub(1)?;
+        self.tail_files.get(index)
+    }
+
+    /// Clear the focused panel of the current job so its log can be read
+    /// again from the start, returning the tailer label to reload. Clearing
+    /// first keeps the old content from being shown twice, or kept above a
+    /// marker with `keep_truncated_logs`.
+    pub fn clear_focused_log(&mut self) -> Option<String> {
+        let job_id = self.current_job_id?;
+        let tail_mode = self.is_tail_mode();
+        let job = self.jobs.get_mut(&job_id)?;
+        if tail_mode || self.focused_panel == FocusedPanel::Stdout {
+            job.clear_stdout();
+            job.stdout_scroll = 0;
+            job.stdout_scroll_mode = false;
+        } else {
+            job.clear_stderr();
+            job.stderr_scroll = 0;
+            job.stderr_scroll_mode = false;
+        }
+        Some(match (tail_mode, self.focused_panel) {
+            (true, _) => format!("file_{}", job_id),
+            (false, FocusedPanel::Stdout) => format!("stdout_{}", job_id),
+            (false, FocusedPanel::Stderr) => format!("stderr_{}", job_id),
+        })
+    }
+
+    /// Log file shown in the focused panel of the current job, if it has one.
+    pub fn focused_log_path(&self) -> Option<&Path> {
+        let job_id = self.current_job_id?;
+        if self.is_tail_mode() {
+            return self.tail_file(job_id).map(PathBuf::as_path);
+        }
+        let info = &self.jobs.get(&job_id)?.info;
+        let path = match self.focused_panel {
+            FocusedPanel::Stdout => &info.stdout_path,
+            FocusedPanel::Stderr => &info.stderr_path,
+        };
+        Some(path.as_path()).filter(|p| !p.as_os_str().is_empty())
+    }
+
     /// Remove a job from tracking.
     pub fn remove_job(&mut self, job_id: u64) {
         self.jobs.remove(&job_id);
         // Track deleted jobs to prevent re-adding via auto-discovery
         self.deleted_jobs.insert(job_id);
+        self.last_deleted = Some(job_id);
         if self.current_job_id == Some(job_id) {
             self.current_job_id = self.get_sorted_job_ids().first().copied();
         }
     }
 
-    /// Get sorted job IDs (descending order).
+    /// Get the IDs of jobs passing the status filter, ordered by the current
+    /// sort mode.
+    ///
+    /// Jobs with an empty or unparseable sort key are always placed last, and
+    /// ties are broken by job ID (newest first).
     pub fn get_sorted_job_ids(&self) -> Vec<u64> {
-        let mut ids: Vec<u64> = self.jobs.keys().copied().collect();
-        ids.sort_unstable_by(|a, b| b.cmp(a));
+        let mut ids: Vec<u64> = self
+            .jobs
+            .keys()
+            .copied()
+            .filter(|&job_id| self.is_visible(job_id))
+            .collect();
+        ids.sort_unstable_by(|a, b| self.compare_jobs(*a, *b).then_with(|| b.cmp(a)));
         ids
     }
 
+    /// Compare two jobs under the current sort mode.
+    fn compare_jobs(&self, a: u64, b: u64) -> Ordering {
+        let (job_a, job_b) = match (self.jobs.get(&a), self.jobs.get(&b)) {
+            (Some(job_a), Some(job_b)) => (job_a, job_b),
+            _ => return Ordering::Equal,
+        };
+        let reverse = self.sort_reverse;
+
+        match self.sort_mode {
+            SortMode::Id => cmp_missing_last(Some(Reverse(a)), Some(Reverse(b)), reverse),
+            SortMode::Status => {
+                cmp_missing_last(status_rank(job_a.status), status_rank(job_b.status), reverse)
+            }
+            SortMode::Runtime => {
+                let runtime = |job: &JobData| parse_slurm_duration(&job.info.elapsed).map(Reverse);
+                cmp_missing_last(runtime(job_a), runtime(job_b), reverse)
+            }
+            SortMode::Name => {
+                let name = |job: &JobData| {
+                    Some(job.info.job_name.to_lowercase()).filter(|n| !n.is_empty())
+                };
+                cmp_missing_last(name(job_a), name(job_b), reverse)
+            }
+        }
+    }
+
+    /// Cycle to the next sort mode.
+    pub fn cycle_sort_mode(&mut self) {
+        self.sort_mode = self.sort_mode.next();
+    }
+
+    /// Flip the sort direction.
+    pub fn toggle_sort_reverse(&mut self) {
+        self.sort_reverse = !self.sort_reverse;
+    }
+
+    /// Cycle how times are shown: live, raw and relative.
+    pub fn cycle_time_display_mode(&mut self) {
+        self.time_display_mode = self.time_display_mode.next();
+        self.set_message(format!("Times: {}", self.time_display_mode.as_str()));
+    }
+
+    /// Cycle the status filter through All, Running, Failed and Queued.
+    pub fn cycle_status_filter(&mut self) {
+        self.status_filter = match self.status_filter {
+            None => Some(JobStatus::Running),
+            Some(JobStatus::Running) => Some(JobStatus::Failed),
+            Some(JobStatus::Failed) => Some(JobStatus::Queued),
+            Some(_) => None,
+        };
+        self.ensure_current_visible();
+    }
+
+    /// Toggle hiding finished jobs from the job list.
+    pub fn toggle_hide_finished(&mut self) {
+        self.hide_finished = !self.hide_finished;
+        self.ensure_current_visible();
+    }
+
+    /// Whether a job passes the status filter and isn't a hidden finished job.
+    fn is_visible(&self, job_id: u64) -> bool {
+        let Some(job) = self.jobs.get(&job_id) else {
+            return false;
+        };
+        if self.is_hidden_finished(job_id, job) {
+            return false;
+        }
+        match self.status_filter {
+            None => true,
+            // Timeouts and OOM kills are failures too
+            Some(JobStatus::Failed) => job.status.is_failure(),
+            Some(filter) => job.status == filter,
+        }
+    }
+
+    /// Whether `job` is left out of the list by `hide_finished`. The current
+    /// job stays listed so its final logs can still be read.
+    fn is_hidden_finished(&self, job_id: u64, job: &JobData) -> bool {
+        self.hide_finished && job.status.is_terminal() && Some(job_id) != self.current_job_id
+    }
+
+    /// Number of finished jobs left out of the list by `hide_finished`.
+    pub fn hidden_finished_count(&self) -> usize {
+        self.jobs
+            .iter()
+            .filter(|(&job_id, job)| self.is_hidden_finished(job_id, job))
+            .count()
+    }
+
+    /// Move the selection to the first listed job if the filter hides the
+    /// current one.
+    fn ensure_current_visible(&mut self) {
+        if self.current_job_id.is_some_and(|job_id| self.is_visible(job_id)) {
+            return;
+        }
+        let first = self.get_sorted_job_ids().first().copied();
+        if first.is_some() || self.status_filter.is_some() {
+            self.current_job_id = first;
+            self.mark_stderr_read();
+        }
+    }
+
     /// Update job status.
+    ///
+    /// Updates for jobs the user deleted are ignored, so a poll that was
+    /// already in flight can't bring the job back.
     pub fn update_job_status(&mut self, job_id: u64, status: JobStatus, info: JobInfo) {
+        if self.deleted_jobs.contains(&job_id) {
+            return;
+        }
         if let Some(job) = self.jobs.get_mut(&job_id) {
+            let was_active = !job.status.is_terminal() && job.status != JobStatus::Unknown;
+            if status.is_terminal() && was_active {
+                job.finished_at = Some(Instant::now());
+                // Only the transition rings, not later polls of the failed job
+                if self.bell && status.is_failure() {
+                    self.bell_pending = true;
+                    self.message = Some((format!("Job {} {}", job_id, status), Instant::now()));
+                }
+            }
+            if status == JobStatus::Running && job.last_output.is_none() {
+                job.last_output = Some(Local::now());
+            }
+            if let Some(memory) = info.live_memory.filter(|_| status == JobStatus::Running) {
+                job.record_memory(memory.rss_mib);
+            }
+            if info.start_time != job.info.start_time {
+                job.started_at = parse_slurm_timestamp(&info.start_time);
+            }
             job.status = status;
             job.info = info;
+            // Polls report the paths of the array job itself
+            if let Some(ref task) = job.shown_task {
+                job.info.stdout_path = task.stdout_path.clone();
+                job.info.stderr_path = task.stderr_path.clone();
+            }
         } else {
             let mut job_data = JobData::new(job_id);
             job_data.status = status;
+            job_data.started_at = parse_slurm_timestamp(&info.start_time);
             job_data.info = info;
             self.jobs.insert(job_id, job_data);
             if self.current_job_id.is_none() {
                 self.current_job_id = Some(job_id);
             }
         }
+        if self.status_filter.is_some() {
+            self.ensure_current_visible();
+        }
+    }
+
+    /// Replace log content, e.g. after the file was truncated. With
+    /// `keep_truncated_logs`, the old content stays above a marker line,
+    /// unless the reset comes from reloading the log.
+    pub fn reset_log(&mut self, job_id: u64, log_type: &str, content: &str, reloaded: bool) {
+        if let Some(job) = self.jobs.get_mut(&job_id) {
+            let (lines, partial) = match log_type {
+                "stdout" => (&job.stdout_lines, job.stdout_partial),
+                "stderr" => (&job.stderr_lines, job.stderr_partial),
+                _ => return,
+            };
+            if self.keep_truncated_logs && !reloaded && !lines.is_empty() {
+                let newline = if partial.is_some() { "\n" } else { "" };
+                let marker = format!("{}{}\n", newline, LOG_RESET_MARKER);
+                self.update_log(job_id, log_type, &marker);
+            } else if log_type == "stdout" {
+                job.clear_stdout();
+            } else {
+                job.clear_stderr();
+            }
+        }
+        self.update_log(job_id, log_type, content);
     }
 
     /// Update log content.
     pub fn update_log(&mut self, job_id: u64, log_type: &str, content: &str) {
         if let Some(job) = self.jobs.get_mut(&job_id) {
             match log_type {
-                "stdout" => job.append_stdout(content, self.stdout_panel_height),
-                "stderr" => job.append_stderr(content, self.stderr_panel_height),
+                "stdout" => {
+                    job.append_stdout(content, self.stdout_panel_height, self.raw_progress)
+                }
+                "stderr" => {
+                    job.append_stderr(content, self.stderr_panel_height, self.raw_progress)
+                }
+                _ => {}
+            }
+        }
+        self.mark_stderr_read();
+    }
+
+    /// Note why a job's log file can't be read, or that it can again.
+    pub fn set_log_problem(&mut self, job_id: u64, log_type: &str, problem: Option<LogProblem>) {
+        if let Some(job) = self.jobs.get_mut(&job_id) {
+            match log_type {
+                "stdout" => job.stdout_problem = problem,
+                "stderr" => job.stderr_problem = problem,
                 _ => {}
             }
         }
     }
 
-    /// Switch focus between panels.
-    pub fn switch_focus(&mut self) {
-        self.focused_panel.toggle();
+    /// Note that a job's log file was modified at `modified`.
+    pub fn record_output(&mut self, job_id: u64, modified: SystemTime) {
+        if let Some(job) = self.jobs.get_mut(&job_id) {
+            let modified = DateTime::<Local>::from(modified);
+            job.last_output = Some(job.last_output.map_or(modified, |last| last.max(modified)));
+        }
+    }
+
+    /// Whether `job` is running but its logs haven't grown for `stall_after`.
+    pub fn is_stalled(&self, job: &JobData, now: DateTime<Local>) -> bool {
+        let (threshold, last_output) = match (self.stall_after, job.last_output) {
+            (Some(threshold), Some(last_output)) => (threshold, last_output),
+            _ => return false,
+        };
+        let quiet = now.signed_duration_since(last_output).to_std().unwrap_or_default();
+        job.status == JobStatus::Running && quiet >= threshold
+    }
+
+    /// Clear the current job's unread stderr flag if its stderr panel is focused.
+    fn mark_stderr_read(&mut self) {
+        if self.focused_panel != FocusedPanel::Stderr {
+            return;
+        }
+        if let Some(job) = self.current_job_id.and_then(|id| self.jobs.get_mut(&id)) {
+            job.unread_stderr = false;
+        }
+    }
+
+    /// Count jobs per status.
+    pub fn status_counts(&self) -> HashMap<JobStatus, usize> {
+        let mut counts = HashMap::new();
+        for job in self.jobs.values() {
+            *counts.entry(job.status).or_insert(0) += 1;
+        }
+        counts
+    }
+
+    /// Resources allocated to the running jobs together, or None if no
+    /// running job's allocation is known.
+    pub fn resource_usage(&self) -> Option<Tres> {
+        let mut total: Option<Tres> = None;
+        for job in self.jobs.values().filter(|job| job.status == JobStatus::Running) {
+            let mut tres = job.info.alloc_tres.unwrap_or_default();
+            if let Some(cpus) = job.info.alloc_cpus {
+                tres.cpus = cpus;
+            } else if job.info.alloc_tres.is_none() {
+                continue;
+            }
+            *total.get_or_insert_with(Tres::default) += tres;
+        }
+        total
+    }
+
+    /// Overall completion of the monitored jobs, or None with fewer than two jobs.
+    ///
+    /// The ETA assumes the remaining jobs keep finishing at the average rate
+    /// seen since monitoring started.
+    pub fn batch_progress(&self, now: Instant) -> Option<BatchProgress> {
+        let total = self.jobs.len();
+        if total < 2 || self.is_tail_mode() {
+            return None;
+        }
+
+        let complete = self.jobs.values().filter(|job| job.status.is_terminal()).count();
+        let finished: Vec<Instant> = self.jobs.values().filter_map(|job| job.finished_at).collect();
+        let remaining = (total - complete) as u32;
+        let eta = match finished.iter().max() {
+            Some(&latest) if remaining > 0 => {
+                let per_job =
+                    latest.saturating_duration_since(self.started_at) / finished.len() as u32;
+                Some((per_job * remaining).saturating_sub(now.saturating_duration_since(latest)))
+            }
+            _ => None,
+        };
+
+        Some(BatchProgress {
+            complete,
+            total,
+            eta,
+        })
+    }
+
+    /// Mark a job as replayed, with its resource usage summary.
+    pub fn set_replay(&mut self, job_id: u64, summary: Option<JobSummary>) {
+        if let Some(job) = self.jobs.get_mut(&job_id) {
+            job.replay = true;
+            job.summary = summary;
+            // Old output of a finished job isn't a new error
+            job.unread_stderr = false;
+        }
+    }
+
+    /// Store the latest GPU sample for a job.
+    pub fn update_gpu(&mut self, update: GpuUpdate) {
+        if let Some(job) = self.jobs.get_mut(&update.job_id) {
+            job.gpu = Some(update);
+        }
+    }
+
+    /// Open the search prompt, starting from the active query.
+    pub fn start_search(&mut self) {
+        let query = self.search_query.as_ref().map(|q| q.text().to_string());
+        self.prompt = Some(Prompt::new(PromptKind::Search, query.unwrap_or_default()));
+    }
+
+    /// Open the note prompt for the current job, starting from its note.
+    pub fn start_note(&mut self) {
+        let job_id = match self.current_job_id {
+            Some(job_id) => job_id,
+            None => return,
+        };
+        let note = self.jobs.get(&job_id).and_then(|job| job.note.clone());
+        self.prompt = Some(Prompt::new(PromptKind::Note(job_id), note.unwrap_or_default()));
+    }
+
+    /// Open the filter prompt, starting from the active filter.
+    pub fn start_filter(&mut self) {
+        let filter = self.log_filter.as_ref().map(|f| f.text().to_string());
+        self.prompt = Some(Prompt::new(PromptKind::Filter, filter.unwrap_or_default()));
+    }
+
+    /// Attach to one of `steps`, the running steps of `job_id`: right away if
+    /// there is only one, otherwise after picking it in a prompt.
+    pub fn start_attach(&mut self, job_id: u64, steps: Vec<String>) {
+        match steps.len() {
+            0 => self.set_message(format!("Job {} has no running steps to attach to", job_id)),
+            1 => self.attach_request = steps.into_iter().next().map(|step| (job_id, step)),
+            _ => {
+                let mut prompt = Prompt::new(PromptKind::Attach(job_id), steps[0].clone());
+                prompt.choices = steps;
+                self.prompt = Some(prompt);
+            }
+        }
+    }
+
+    /// The array task to show after (or before, if not `forward`) the one the
+    /// current job shows, as the job ID, task index and the task's own job
+    /// ID. Only tasks that have started have logs to show. Sets a message and
+    /// returns None if there is nothing to switch to.
+    pub fn next_array_task(&mut self, forward: bool) -> Option<(u64, String, u64)> {
+        let job_id = self.current_job_id?;
+        let job = self.jobs.get(&job_id)?;
+        if job.replay {
+            self.set_message(format!("Job {} had finished; its logs were loaded at startup", job_id));
+            return None;
+        }
+        if job.info.array_tasks.is_empty() {
+            self.set_message(format!("Job {} has no array tasks (listed with --array-tasks)", job_id));
+            return None;
+        }
+        let started: Vec<(&str, u64)> = job
+            .info
+            .array_tasks
+            .iter()
+            .filter(|task| task.status() != JobStatus::Queued)
+            .filter_map(|task| Some((task.task_id.as_str(), task.raw_job_id?)))
+            .collect();
+        if started.is_empty() {
+            self.set_message(format!("None of job {}'s tasks have started yet", job_id));
+            return None;
+        }
+        let shown = job
+            .shown_task
+            .as_ref()
+            .and_then(|shown| started.iter().position(|&(task_id, _)| task_id == shown.task_id));
+        let index = match (shown, forward) {
+            (Some(index), true) => (index + 1) % started.len(),
+            (Some(index), false) => (index + started.len() - 1) % started.len(),
+            (None, true) => 0,
+            (None, false) => started.len() - 1,
+        };
+        let (task_id, raw_job_id) = started[index];
+        Some((job_id, task_id.to_string(), raw_job_id))
+    }
+
+    /// Show the logs of array task `task_id` in `job_id`'s panels, replacing
+    /// what they showed.
+    pub fn show_array_task(&mut self, job_id: u64, task_id: &str, stdout: PathBuf, stderr: PathBuf) {
+        let job = match self.jobs.get_mut(&job_id) {
+            Some(job) => job,
+            None => return,
+        };
+        if stdout.as_os_str().is_empty() && stderr.as_os_str().is_empty() {
+            self.set_message(format!("Task {}_{} has no log files yet", job_id, task_id));
+            return;
+        }
+        job.clear_stdout();
+        job.clear_stderr();
+        job.stdout_problem = None;
+        job.stderr_problem = None;
+        job.info.stdout_path = stdout.clone();
+        job.info.stderr_path = stderr.clone();
+        job.shown_task = Some(ShownTask {
+            task_id: task_id.to_string(),
+            stdout_path: stdout,
+            stderr_path: stderr,
+        });
+        self.set_message(format!("Showing task {}_{}", job_id, task_id));
+    }
+
+    /// Attach to the typed step, if it is one of the choices.
+    pub fn submit_attach(&mut self) {
+        let prompt = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        if let PromptKind::Attach(job_id) = prompt.kind {
+            let step = prompt.input.trim();
+            if prompt.choices.iter().any(|choice| choice == step) {
+                self.attach_request = Some((job_id, step.to_string()));
+            } else {
+                self.set_message(format!("Job {} has no running step '{}'", job_id, step));
+            }
+        }
+    }
+
+    /// Close the prompt without changing anything. A filter being typed is
+    /// reverted to the one active when the prompt was opened.
+    pub fn cancel_prompt(&mut self) {
+        if let Some(prompt) = self.prompt.take() {
+            if prompt.kind == PromptKind::Filter {
+                self.set_log_filter(&prompt.initial);
+            }
+        }
+    }
+
+    /// Update the prompt after its input was edited: recompile a search or
+    /// filter, and apply a filter so the panels update with each key.
+    pub fn prompt_changed(&mut self) {
+        let prompt = match self.prompt {
+            Some(ref mut prompt) => prompt,
+            None => return,
+        };
+        prompt.compile();
+        if prompt.kind == PromptKind::Filter {
+            let pattern = prompt.pattern.clone();
+            self.apply_log_filter(pattern);
+        }
+    }
+
+    /// Keep the typed filter and close the prompt.
+    pub fn submit_filter(&mut self) {
+        if let Some(prompt) = self.prompt.take() {
+            self.apply_log_filter(prompt.pattern);
+        }
+    }
+
+    /// Show only lines matching `filter`, or every line if it is empty.
+    pub fn set_log_filter(&mut self, filter: &str) {
+        self.apply_log_filter(Some(Pattern::new(filter)).filter(|_| !filter.is_empty()));
+    }
+
+    /// Scroll positions count shown lines, so changing the filter sends every
+    /// panel back to following the end of the log. The full log is kept.
+    fn apply_log_filter(&mut self, filter: Option<Pattern>) {
+        if filter.as_ref().map(Pattern::text) == self.log_filter.as_ref().map(Pattern::text) {
+            return;
+        }
+        self.log_filter = filter;
+        self.current_match = None;
+        for job in self.jobs.values_mut() {
+            job.stdout_scroll_mode = false;
+            job.stderr_scroll_mode = false;
+        }
+    }
+
+    /// Save the typed note on the job it was opened for. An empty note
+    /// removes it.
+    pub fn submit_note(&mut self) {
+        let prompt = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        if let PromptKind::Note(job_id) = prompt.kind {
+            if let Some(job) = self.jobs.get_mut(&job_id) {
+                let note = prompt.input.trim();
+                job.note = Some(note.to_string()).filter(|n| !n.is_empty());
+            }
+        }
+    }
+
+    /// Search for the typed query, jumping to the first match at or below the
+    /// top of the focused panel. An empty query clears the search.
+    ///
+    /// Returns false if the query has no matches.
+    pub fn submit_search(&mut self) -> bool {
+        self.search_query = self.prompt.take().and_then(|p| p.pattern);
+        self.current_match = None;
+        self.search_query.is_none() || self.jump_to_match(true, true)
+    }
+
+    /// Jump to the next (or previous) line matching the search in the focused
+    /// panel, wrapping around at the ends. Returns false if nothing matches.
+    pub fn next_match(&mut self, forward: bool) -> bool {
+        self.jump_to_match(forward, false)
+    }
+
+    fn jump_to_match(&mut self, forward: bool, inclusive: bool) -> bool {
+        let (query, job_id) = match (self.search_query.as_ref(), self.current_job_id) {
+            (Some(query), Some(job_id)) => (query, job_id),
+            _ => return false,
+        };
+        let panel = self.focused_panel;
+        let height = match panel {
+            FocusedPanel::Stdout => self.stdout_panel_height,
+            FocusedPanel::Stderr => self.stderr_panel_height,
+        };
+        let job = match self.jobs.get_mut(&job_id) {
+            Some(job) => job,
+            None => return false,
+        };
+        let (lines, scroll, scroll_mode) = match panel {
+            FocusedPanel::Stdout => {
+                (&job.stdout_lines, &mut job.stdout_scroll, &mut job.stdout_scroll_mode)
+            }
+            FocusedPanel::Stderr => {
+                (&job.stderr_lines, &mut job.stderr_scroll, &mut job.stderr_scroll_mode)
+            }
+        };
+        let lines = shown_lines(lines, self.log_filter.as_ref());
+
+        if lines.is_empty() {
+            return false;
+        }
+
+        // Continue from the last match, or from the top of the panel
+        let from = match self.current_match {
+            Some((id, p, line)) if id == job_id && p == panel && line < lines.len() => line,
+            _ => (*scroll).min(lines.len() - 1),
+        };
+        let total = lines.len();
+        let is_match = |&idx: &usize| query.is_match(lines[idx]);
+        let found = if forward {
+            let skip = if inclusive { 0 } else { 1 };
+            (from + skip..total).chain(0..from + skip).find(is_match)
+        } else {
+            (0..from).rev().chain((from..total).rev()).find(is_match)
+        };
+
+        let line = match found {
+            Some(line) => line,
+            None => return false,
+        };
+        let max_scroll = total.saturating_sub(height);
+        *scroll = line.min(max_scroll);
+        *scroll_mode = max_scroll > 0;
+        self.current_match = Some((job_id, panel, line));
+        true
+    }
+
+    /// Number of lines matching the search in a panel of the current job, and
+    /// the position of the current match among them.
+    pub fn match_position(&self, panel: FocusedPanel) -> Option<(Option<usize>, usize)> {
+        let query = self.search_query.as_ref()?;
+        let job_id = self.current_job_id?;
+        let job = self.jobs.get(&job_id)?;
+        let lines = match panel {
+            FocusedPanel::Stdout => &job.stdout_lines,
+            FocusedPanel::Stderr => &job.stderr_lines,
+        };
+        let lines = shown_lines(lines, self.log_filter.as_ref());
+
+        let current = match self.current_match {
+            Some((id, p, line)) if id == job_id && p == panel => Some(line),
+            _ => None,
+        };
+        let mut count = 0;
+        let mut position = None;
+        for (idx, line) in lines.iter().enumerate() {
+            if query.is_match(line) {
+                count += 1;
+                if current == Some(idx) {
+                    position = Some(count);
+                }
+            }
+        }
+        Some((position, count))
+    }
+
+    /// Toggle wrapping of long log lines.
+    pub fn toggle_wrap(&mut self) {
+        self.wrap_logs = !self.wrap_logs;
+    }
+
+    /// Toggle the column with each job's last stdout line.
+    pub fn toggle_preview(&mut self) {
+        self.show_preview = !self.show_preview;
+    }
+
+    /// The status table columns that fit its width, starting `column_offset`
+    /// columns after the first. The first column (usually the job ID) always
+    /// stays so each row can be told apart, and at least one more is shown
+    /// even if it has to be cut off.
+    pub fn shown_columns(&self) -> Vec<Column> {
+        self.columns_from(self.column_offset.min(self.max_column_offset()))
+    }
+
+    /// Whether some status table columns are scrolled or don't fit.
+    pub fn has_hidden_columns(&self) -> bool {
+        self.shown_columns().len() < self.columns.len()
+    }
+
+    /// Scroll the status table one column left.
+    pub fn scroll_columns_left(&mut self) {
+        self.column_offset = self.column_offset.min(self.max_column_offset()).saturating_sub(1);
+    }
+
+    /// Scroll the status table one column right, until the last column is shown.
+    pub fn scroll_columns_right(&mut self) {
+        self.column_offset = (self.column_offset + 1).min(self.max_column_offset());
+    }
+
+    fn columns_from(&self, offset: usize) -> Vec<Column> {
+        let (&first, rest) = match self.columns.split_first() {
+            Some(split) => split,
+            None => return Vec::new(),
+        };
+        // The preview column needs at least 10 characters and a space
+        let reserved = if self.show_preview { 11 } else { 0 };
+        let available = self.status_table_width.saturating_sub(reserved);
+        let mut shown = vec![first];
+        let mut used = first.width();
+        for &column in rest.iter().skip(offset) {
+            used = used.saturating_add(column.width() + 1);
+            if used > available && shown.len() > 1 {
+                break;
+            }
+            shown.push(column);
+        }
+        shown
+    }
+
+    /// Smallest offset that shows the last column; scrolling further would
+    /// only leave space empty.
+    fn max_column_offset(&self) -> usize {
+        let scrollable = self.columns.len().saturating_sub(1);
+        (0..scrollable)
+            .find(|&offset| self.columns_from(offset).len() == self.columns.len() - offset)
+            .unwrap_or(0)
+    }
+
+    /// Toggle the errors view. It opens following the newest lines.
+    pub fn toggle_errors_view(&mut self) {
+        self.errors_view = !self.errors_view;
+        self.errors_scroll_mode = false;
+    }
+
+    /// Stderr lines of every job, prefixed with the job ID. Jobs that wrote
+    /// output most recently come last, so the newest errors end up at the
+    /// bottom.
+    pub fn all_stderr_lines(&self) -> Vec<String> {
+        let mut job_ids: Vec<u64> = self.jobs.keys().copied().collect();
+        job_ids.sort_by_key(|id| (self.jobs[id].last_output, *id));
+        job_ids
+            .iter()
+            .flat_map(|id| {
+                self.jobs[id].stderr_lines.iter().map(move |line| format!("[{}] {}", id, line))
+            })
+            .collect()
+    }
+
+    /// Current position of the errors view and its largest possible value.
+    fn errors_position(&self) -> (usize, usize) {
+        let total = shown_line_count(&self.all_stderr_lines(), self.log_filter.as_ref());
+        let max_scroll = total.saturating_sub(self.errors_panel_height);
+        if self.errors_scroll_mode {
+            (self.errors_scroll.min(max_scroll), max_scroll)
+        } else {
+            (max_scroll, max_scroll)
+        }
+    }
+
+    /// Move the errors view to `scroll`, following new lines again at the
+    /// bottom.
+    fn scroll_errors_to(&mut self, scroll: usize, max_scroll: usize) {
+        self.errors_scroll = scroll.min(max_scroll);
+        self.errors_scroll_mode = self.errors_scroll < max_scroll;
+    }
+
+    /// Switch focus between panels.
+    pub fn switch_focus(&mut self) {
+        // Tailed files only use the stdout panel, and a hidden panel can't be focused
+        let both_shown = self.stdout_share > 0 && self.stdout_share < 100;
+        if !self.is_tail_mode() && both_shown {
+            self.focused_panel.toggle();
+        }
+        self.mark_stderr_read();
+    }
+
+    /// Move the stdout/stderr divider by `delta` percent of the output area.
+    ///
+    /// At 0 or 100 one panel is hidden; focus moves to the one still shown.
+    pub fn adjust_stdout_share(&mut self, delta: i16) {
+        self.stdout_share = (self.stdout_share as i16 + delta).clamp(0, 100) as u16;
+        let hidden = match self.stdout_share {
+            0 => Some(FocusedPanel::Stdout),
+            100 => Some(FocusedPanel::Stderr),
+            _ => None,
+        };
+        if hidden == Some(self.focused_panel) {
+            self.focused_panel.toggle();
+            self.mark_stderr_read();
+        }
+    }
+
+    /// Split the output area into the stdout and stderr panels by
+    /// `stdout_share`. A hidden panel gets zero height.
+    pub fn split_output(&self, area: Rect) -> (Rect, Rect) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Percentage(self.stdout_share),
+                Constraint::Percentage(100 - self.stdout_share),
+            ])
+            .split(area);
+        (chunks[0], chunks[1])
+    }
+
+    /// Select the newest of the newly discovered `job_ids` if following new jobs.
+    ///
+    /// Does nothing once the user has switched jobs by hand, so a job being
+    /// read isn't swapped out from under them. Returns true if selection moved.
+    pub fn follow_new_jobs(&mut self, job_ids: &[u64]) -> bool {
+        if !self.follow_new || self.user_navigated {
+            return false;
+        }
+
+        match job_ids.iter().copied().filter(|&id| self.is_visible(id)).max() {
+            Some(newest) if self.current_job_id != Some(newest) => {
+                self.current_job_id = Some(newest);
+                self.mark_stderr_read();
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Toggle auto-discovery of new jobs. Jobs already added are kept.
+    pub fn toggle_auto_discover(&mut self) {
+        self.auto_discover = !self.auto_discover;
+    }
+
+    /// Toggle following new jobs. Turning it on re-arms it after manual navigation.
+    pub fn toggle_follow_new(&mut self) {
+        self.follow_new = !self.follow_new;
+        self.user_navigated = false;
     }
 
     /// Switch to next job.
@@ -219,6 +1705,7 @@ impl App {
         if ids.is_empty() {
             return;
         }
+        self.user_navigated = true;
 
         self.current_job_id = match self.current_job_id {
             Some(current) => {
@@ -227,6 +1714,7 @@ impl App {
             }
             None => Some(ids[0]),
         };
+        self.mark_stderr_read();
     }
 
     /// Switch to previous job.
@@ -235,6 +1723,7 @@ impl App {
         if ids.is_empty() {
             return;
         }
+        self.user_navigated = true;
 
         self.current_job_id = match self.current_job_id {
             Some(current) => {
@@ -243,20 +1732,29 @@ impl App {
             }
             None => Some(ids[0]),
         };
+        self.mark_stderr_read();
     }
 
     /// Scroll the focused panel up.
     pub fn scroll_up(&mut self, lines: usize) {
+        if self.errors_view {
+            let (scroll, max_scroll) = self.errors_position();
+            self.scroll_errors_to(scroll.saturating_sub(lines), max_scroll);
+            return;
+        }
+        let filter = self.log_filter.as_ref();
         if let Some(job_id) = self.current_job_id {
             if let Some(job) = self.jobs.get_mut(&job_id) {
                 match self.focused_panel {
                     FocusedPanel::Stdout => {
                         let visible_lines = self.stdout_panel_height;
-                        let max_scroll = job.stdout_lines.len().saturating_sub(visible_lines);
+                        let total = shown_line_count(&job.stdout_lines, filter);
+                        let max_scroll = total.saturating_sub(visible_lines);
                         if max_scroll == 0 {
                             // Not enough content to scroll
                             return;
                         }
+                        job.stdout_scroll = job.stdout_scroll.min(max_scroll);
                         let old_scroll = job.stdout_scroll;
                         job.stdout_scroll = job.stdout_scroll.saturating_sub(lines);
                         if job.stdout_scroll != old_scroll {
@@ -265,11 +1763,13 @@ impl App {
                     }
                     FocusedPanel::Stderr => {
                         let visible_lines = self.stderr_panel_height;
-                        let max_scroll = job.stderr_lines.len().saturating_sub(visible_lines);
+                        let total = shown_line_count(&job.stderr_lines, filter);
+                        let max_scroll = total.saturating_sub(visible_lines);
                         if max_scroll == 0 {
                             // Not enough content to scroll
                             return;
                         }
+                        job.stderr_scroll = job.stderr_scroll.min(max_scroll);
                         let old_scroll = job.stderr_scroll;
                         job.stderr_scroll = job.stderr_scroll.saturating_sub(lines);
                         if job.stderr_scroll != old_scroll {
@@ -283,16 +1783,24 @@ impl App {
 
     /// Scroll the focused panel down.
     pub fn scroll_down(&mut self, lines: usize) {
+        if self.errors_view {
+            let (scroll, max_scroll) = self.errors_position();
+            self.scroll_errors_to(scroll + lines, max_scroll);
+            return;
+        }
+        let filter = self.log_filter.as_ref();
         if let Some(job_id) = self.current_job_id {
             if let Some(job) = self.jobs.get_mut(&job_id) {
                 match self.focused_panel {
                     FocusedPanel::Stdout => {
                         let visible_lines = self.stdout_panel_height;
-                        let max_scroll = job.stdout_lines.len().saturating_sub(visible_lines);
+                        let total = shown_line_count(&job.stdout_lines, filter);
+                        let max_scroll = total.saturating_sub(visible_lines);
                         if max_scroll == 0 {
                             // Not enough content to scroll
                             return;
                         }
+                        job.stdout_scroll = job.stdout_scroll.min(max_scroll);
                         let old_scroll = job.stdout_scroll;
                         job.stdout_scroll = (job.stdout_scroll + lines).min(max_scroll);
                         if job.stdout_scroll != old_scroll {
@@ -304,11 +1812,13 @@ impl App {
                     }
                     FocusedPanel::Stderr => {
                         let visible_lines = self.stderr_panel_height;
-                        let max_scroll = job.stderr_lines.len().saturating_sub(visible_lines);
+                        let total = shown_line_count(&job.stderr_lines, filter);
+                        let max_scroll = total.saturating_sub(visible_lines);
                         if max_scroll == 0 {
                             // Not enough content to scroll
                             return;
                         }
+                        job.stderr_scroll = job.stderr_scroll.min(max_scroll);
                         let old_scroll = job.stderr_scroll;
                         job.stderr_scroll = (job.stderr_scroll + lines).min(max_scroll);
                         if job.stderr_scroll != old_scroll {
@@ -325,6 +1835,11 @@ impl App {
 
     /// Scroll to top.
     pub fn scroll_to_top(&mut self) {
+        if self.errors_view {
+            let (_, max_scroll) = self.errors_position();
+            self.scroll_errors_to(0, max_scroll);
+            return;
+        }
         if let Some(job_id) = self.current_job_id {
             if let Some(job) = self.jobs.get_mut(&job_id) {
                 match self.focused_panel {
@@ -343,6 +1858,10 @@ impl App {
 
     /// Scroll to bottom (exit scroll mode).
     pub fn scroll_to_bottom(&mut self) {
+        if self.errors_view {
+            self.errors_scroll_mode = false;
+            return;
+        }
         if let Some(job_id) = self.current_job_id {
             if let Some(job) = self.jobs.get_mut(&job_id) {
                 match self.focused_panel {
@@ -369,6 +1888,122 @@ impl App {
         }
     }
 
+    /// Handle a delete key press for the current job.
+    ///
+    /// The first press arms the delete; a second press within
+    /// `DELETE_CONFIRM_WINDOW` confirms it and returns the job ID to remove.
+    pub fn confirm_delete(&mut self) -> Option<u64> {
+        let job_id = self.current_job_id?;
+        match self.pending_delete.take() {
+            Some((pending_id, armed_at))
+                if pending_id == job_id && armed_at.elapsed() <= DELETE_CONFIRM_WINDOW =>
+            {
+                Some(job_id)
+            }
+            _ => {
+                self.pending_delete = Some((job_id, Instant::now()));
+                None
+            }
+        }
+    }
+
+    /// Job awaiting delete confirmation, if the confirm window is still open.
+    pub fn pending_delete_job(&self) -> Option<u64> {
+        self.pending_delete
+            .filter(|(_, armed_at)| armed_at.elapsed() <= DELETE_CONFIRM_WINDOW)
+            .map(|(job_id, _)| job_id)
+    }
+
+    /// Cancel an armed delete.
+    pub fn cancel_pending_delete(&mut self) {
+        self.pending_delete = None;
+    }
+
+    /// Restore the most recently deleted job. Returns its ID.
+    pub fn undo_delete(&mut self) -> Option<u64> {
+        let job_id = self.last_deleted.take()?;
+        self.deleted_jobs.remove(&job_id);
+        self.add_job(job_id);
+        self.current_job_id = Some(job_id);
+        Some(job_id)
+    }
+
+    /// Show a transient message in the header.
+    pub fn set_message(&mut self, message: impl Into<String>) {
+        self.message = Some((message.into(), Instant::now()));
+    }
+
+    /// Whether the bell should ring for a job that failed since the last call.
+    pub fn take_bell(&mut self) -> bool {
+        std::mem::take(&mut self.bell_pending)
+    }
+
+    /// Current header message, if it hasn't timed out.
+    pub fn current_message(&self) -> Option<&str> {
+        self.message
+            .as_ref()
+            .filter(|(_, set_at)| set_at.elapsed() <= MESSAGE_TIMEOUT)
+            .map(|(message, _)| message.as_str())
+    }
+
+    /// Toggle the diagnostics pane.
+    pub fn toggle_diagnostics(&mut self) {
+        self.show_diagnostics = !self.show_diagnostics;
+    }
+
+    /// Split the body area into the main view and, if shown, the diagnostics pane.
+    pub fn split_diagnostics(&self, body: Rect) -> (Rect, Option<Rect>) {
+        if !self.show_diagnostics {
+            return (body, None);
+        }
+
+        let height = DIAGNOSTICS_HEIGHT.min(body.height / 2);
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(0), Constraint::Length(height)])
+            .split(body);
+        (chunks[0], Some(chunks[1]))
+    }
+
+    /// Switch between the split and compact layouts. The dashboard has no
+    /// logs to lay out and stays as it is.
+    pub fn toggle_layout(&mut self) {
+        self.layout_mode = match self.layout_mode {
+            LayoutMode::Split => LayoutMode::Compact,
+            LayoutMode::Compact => LayoutMode::Split,
+            LayoutMode::Dashboard => LayoutMode::Dashboard,
+        };
+    }
+
+    /// Split the main view into the status area and the output area,
+    /// according to `layout_mode`.
+    pub fn split_body(&self, body: Rect) -> (Rect, Rect) {
+        let chunks = match self.layout_mode {
+            LayoutMode::Split => Layout::default()
+                .direction(Direction::Horizontal)
+                .constraints([
+                    Constraint::Percentage(35), // Status panel
+                    Constraint::Percentage(65), // Output panel
+                ])
+                .split(body),
+            LayoutMode::Compact => Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(1), // Current job line
+                    Constraint::Min(0),    // Output panel
+                ])
+                .split(body),
+            LayoutMode::Dashboard => Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Min(0),    // Status panel
+                    Constraint::Length(0), // No output panel
+                ])
+                .split(body),
+        };
+        (chunks[0], chunks[1])
+    }
+
     /// Update panel heights based on terminal size using exact same Layout as render.rs.
     /// This ensures scroll calculations match what's actually rendered.
     pub fn update_panel_heights(&mut self, frame_area: Rect) {
@@ -382,28 +2017,28 @@ impl App {
             ])
             .split(frame_area);
 
-        let body_area = main_chunks[1];
+        let (body_area, _) = self.split_diagnostics(main_chunks[1]);
+        // The errors view replaces the whole body
+        self.errors_panel_height = body_area.height.saturating_sub(2).max(1) as usize;
 
-        // 2. Body horizontal split: 35% status + 65% output
-        let body_chunks = Layout::default()
-            .direction(Direction::Horizontal)
-            .constraints([
-                Constraint::Percentage(35), // Status panel
-                Constraint::Percentage(65), // Output panel
-            ])
-            .split(body_area);
+        // 2. Body split between status and output, as set by the layout mode
+        let (status_area, output_area) = self.split_body(body_area);
+        self.status_table_width = status_area.width.saturating_sub(2);
 
-        let output_area = body_chunks[1];
+        // Tail mode shows a single panel over the whole output area
+        if self.is_tail_mode() {
+            self.stdout_panel_height = output_area.height.saturating_sub(2).max(1) as usize;
+            self.stderr_panel_height = self.stdout_panel_height;
+            self.max_visible_lines = self.stdout_panel_height;
+            return;
+        }
 
-        // 3. Output vertical split: 50% stdout + 50% stderr
-        let output_chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-            .split(output_area);
+        // 3. Output vertical split between stdout and stderr by stdout_share
+        let (stdout_area, stderr_area) = self.split_output(output_area);
 
         // 4. Inner height = panel height - 2 (for borders)
-        self.stdout_panel_height = output_chunks[0].height.saturating_sub(2).max(1) as usize;
-        self.stderr_panel_height = output_chunks[1].height.saturating_sub(2).max(1) as usize;
+        self.stdout_panel_height = stdout_area.height.saturating_sub(2).max(1) as usize;
+        self.stderr_panel_height = stderr_area.height.saturating_sub(2).max(1) as usize;
 
         // Also update max_visible_lines for backwards compatibility
         self.max_visible_lines = self.stdout_panel_height;
@@ -411,6 +2046,9 @@ impl App {
 
     /// Check if current job is in scroll mode.
     pub fn is_in_scroll_mode(&self) -> bool {
+        if self.errors_view {
+            return self.errors_scroll_mode;
+        }
         if let Some(job_id) = self.current_job_id {
             if let Some(job) = self.jobs.get(&job_id) {
                 return match self.focused_panel {
@@ -429,8 +2067,1046 @@ impl Default for App {
     }
 }
 
-impl Default for JobStatus {
-    fn default() -> Self {
-        JobStatus::Unknown
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job_manager::{ArrayTask, LiveMemory};
+    use crate::ui::render;
+    use crate::utils::parse_tres;
+    use ratatui::{backend::TestBackend, Terminal};
+
+    #[test]
+    fn test_adjust_stdout_share() {
+        let mut app = App::new();
+        app.add_job(1);
+        let frame = Rect::new(0, 0, 100, 43);
+
+        app.adjust_stdout_share(20);
+        assert_eq!(app.stdout_share, 70);
+        app.update_panel_heights(frame);
+        // 40 body rows: 28 + 12, minus borders
+        assert_eq!((app.stdout_panel_height, app.stderr_panel_height), (26, 10));
+
+        // Collapsing stderr keeps the focus on stdout and pins it there
+        app.focused_panel = FocusedPanel::Stderr;
+        app.adjust_stdout_share(50);
+        assert_eq!(app.stdout_share, 100);
+        assert_eq!(app.focused_panel, FocusedPanel::Stdout);
+        app.switch_focus();
+        assert_eq!(app.focused_panel, FocusedPanel::Stdout);
+        app.update_panel_heights(frame);
+        assert_eq!(app.stdout_panel_height, 38);
+
+        app.adjust_stdout_share(-200);
+        assert_eq!(app.stdout_share, 0);
+        assert_eq!(app.focused_panel, FocusedPanel::Stderr);
+    }
+
+    #[test]
+    fn test_compact_layout_gives_logs_the_body() {
+        let mut app = App::new();
+        app.add_job(1);
+        let frame = Rect::new(0, 0, 100, 43);
+
+        app.update_panel_heights(frame);
+        assert_eq!(app.stdout_panel_height, 18);
+        let (status, output) = app.split_body(Rect::new(0, 3, 100, 40));
+        assert_eq!((status.width, output.width), (35, 65));
+
+        app.toggle_layout();
+        assert_eq!(app.layout_mode, LayoutMode::Compact);
+        let (status, output) = app.split_body(Rect::new(0, 3, 100, 40));
+        assert_eq!((status.height, status.width), (1, 100));
+        assert_eq!((output.y, output.height, output.width), (4, 39, 100));
+        // Heights follow the rendered layout: 39 rows split in two, minus borders
+        app.update_panel_heights(frame);
+        assert_eq!(app.stdout_panel_height + app.stderr_panel_height, 35);
+
+        app.toggle_layout();
+        assert_eq!(app.layout_mode, LayoutMode::Split);
+
+        // The dashboard gives everything to the job list, for good
+        app.layout_mode = LayoutMode::Dashboard;
+        let (status, output) = app.split_body(Rect::new(0, 3, 100, 40));
+        assert_eq!((status.height, status.width), (40, 100));
+        assert_eq!(output.height, 0);
+        app.toggle_layout();
+        assert_eq!(app.layout_mode, LayoutMode::Dashboard);
+    }
+
+    #[test]
+    fn test_delete_requires_confirmation_and_can_be_undone() {
+        let mut app = App::new();
+        app.add_job(100);
+        app.add_job(200);
+        app.current_job_id = Some(200);
+
+        // First press only arms the delete
+        assert_eq!(app.confirm_delete(), None);
+        assert_eq!(app.pending_delete_job(), Some(200));
+
+        // Second press confirms
+        assert_eq!(app.confirm_delete(), Some(200));
+        app.remove_job(200);
+        assert!(!app.jobs.contains_key(&200));
+        assert!(app.deleted_jobs.contains(&200));
+
+        assert_eq!(app.undo_delete(), Some(200));
+        assert!(app.jobs.contains_key(&200));
+        assert!(!app.deleted_jobs.contains(&200));
+        assert_eq!(app.current_job_id, Some(200));
+        assert_eq!(app.undo_delete(), None);
+    }
+
+    fn job_with(app: &mut App, job_id: u64, status: JobStatus, elapsed: &str, name: &str) {
+        let info = JobInfo {
+            job_id,
+            elapsed: elapsed.to_string(),
+            job_name: name.to_string(),
+            ..Default::default()
+        };
+        app.update_job_status(job_id, status, info);
+    }
+
+    #[test]
+    fn test_sort_modes_keep_missing_values_last() {
+        let mut app = App::new();
+        job_with(&mut app, 1, JobStatus::Completed, "00:10:00", "beta");
+        job_with(&mut app, 2, JobStatus::Running, "1-00:00:00", "");
+        job_with(&mut app, 3, JobStatus::Unknown, "", "alpha");
+        job_with(&mut app, 4, JobStatus::Queued, "00:00:00", "Gamma");
+
+        assert_eq!(app.get_sorted_job_ids(), vec![4, 3, 2, 1]);
+
+        app.cycle_sort_mode();
+        assert_eq!(app.sort_mode, SortMode::Status);
+        assert_eq!(app.get_sorted_job_ids(), vec![2, 4, 1, 3]);
+
+        app.cycle_sort_mode();
+        assert_eq!(app.sort_mode, SortMode::Runtime);
+        assert_eq!(app.get_sorted_job_ids(), vec![2, 1, 4, 3]);
+        app.toggle_sort_reverse();
+        assert_eq!(app.get_sorted_job_ids(), vec![4, 1, 2, 3]);
+        app.toggle_sort_reverse();
+
+        app.cycle_sort_mode();
+        assert_eq!(app.sort_mode, SortMode::Name);
+        assert_eq!(app.get_sorted_job_ids(), vec![3, 1, 4, 2]);
+        app.toggle_sort_reverse();
+        assert_eq!(app.get_sorted_job_ids(), vec![4, 1, 3, 2]);
+
+        app.cycle_sort_mode();
+        assert_eq!(app.sort_mode, SortMode::Id);
+    }
+
+    #[test]
+    fn test_bell_rings_once_per_failure() {
+        let mut app = App::new();
+        app.bell = true;
+        app.update_job_status(1, JobStatus::Failed, JobInfo::default());
+        // Jobs first seen already failed don't ring
+        assert!(!app.take_bell());
+
+        app.update_job_status(2, JobStatus::Running, JobInfo::default());
+        app.update_job_status(2, JobStatus::OutOfMemory, JobInfo::default());
+        assert!(app.take_bell());
+        assert_eq!(app.current_message(), Some("Job 2 OOM"));
+        app.update_job_status(2, JobStatus::OutOfMemory, JobInfo::default());
+        assert!(!app.take_bell());
+
+        app.update_job_status(3, JobStatus::Running, JobInfo::default());
+        app.update_job_status(3, JobStatus::Cancelled, JobInfo::default());
+        assert!(!app.take_bell());
+
+        app.bell = false;
+        app.update_job_status(4, JobStatus::Running, JobInfo::default());
+        app.update_job_status(4, JobStatus::Timeout, JobInfo::default());
+        assert!(!app.take_bell());
+    }
+
+    #[test]
+    fn test_errors_view() {
+        let mut app = App::new();
+        app.add_job(2);
+        app.add_job(1);
+        app.update_log(2, "stderr", "Traceback\nValueError\n");
+        app.update_log(1, "stdout", "fine\n");
+        app.update_log(1, "stderr", "warning\n");
+        assert_eq!(
+            app.all_stderr_lines(),
+            ["[1] warning", "[2] Traceback", "[2] ValueError"]
+        );
+
+        app.toggle_errors_view();
+        app.errors_panel_height = 2;
+        app.scroll_up(1);
+        assert!(app.is_in_scroll_mode());
+        assert_eq!(app.errors_scroll, 0);
+        app.scroll_down(5);
+        assert!(!app.is_in_scroll_mode());
+        app.scroll_to_top();
+        assert!(app.is_in_scroll_mode());
+
+        // Closing the view leaves the job panels as they were
+        app.toggle_errors_view();
+        assert!(!app.is_in_scroll_mode());
+    }
+
+    #[test]
+    fn test_hide_finished() {
+        let mut app = App::new();
+        job_with(&mut app, 1, JobStatus::Running, "00:10:00", "a");
+        job_with(&mut app, 2, JobStatus::Completed, "00:10:00", "b");
+        job_with(&mut app, 3, JobStatus::Failed, "00:10:00", "c");
+        app.current_job_id = Some(3);
+
+        app.toggle_hide_finished();
+        // The current job stays listed until the selection moves on
+        assert_eq!(app.get_sorted_job_ids(), vec![3, 1]);
+        assert_eq!(app.hidden_finished_count(), 1);
+        app.next_job();
+        assert_eq!(app.current_job_id, Some(1));
+        assert_eq!(app.get_sorted_job_ids(), vec![1]);
+        assert_eq!(app.hidden_finished_count(), 2);
+        app.next_job();
+        assert_eq!(app.current_job_id, Some(1));
+
+        // Jobs are still tracked and updated while hidden
+        job_with(&mut app, 2, JobStatus::Completed, "00:11:00", "b");
+        assert_eq!(app.jobs[&2].info.elapsed, "00:11:00");
+
+        app.toggle_hide_finished();
+        assert_eq!(app.get_sorted_job_ids(), vec![3, 2, 1]);
+        assert_eq!(app.hidden_finished_count(), 0);
+    }
+
+    #[test]
+    fn test_status_filter() {
+        let mut app = App::new();
+        job_with(&mut app, 1, JobStatus::Running, "00:10:00", "a");
+        job_with(&mut app, 2, JobStatus::Failed, "00:10:00", "b");
+        job_with(&mut app, 3, JobStatus::Running, "00:10:00", "c");
+        job_with(&mut app, 4, JobStatus::Queued, "", "d");
+        app.current_job_id = Some(4);
+
+        app.cycle_status_filter();
+        assert_eq!(app.status_filter, Some(JobStatus::Running));
+        assert_eq!(app.get_sorted_job_ids(), vec![3, 1]);
+        // The selected queued job is hidden, so selection moves to a listed one
+        assert_eq!(app.current_job_id, Some(3));
+        app.next_job();
+        assert_eq!(app.current_job_id, Some(1));
+        app.next_job();
+        assert_eq!(app.current_job_id, Some(3));
+
+        // A job leaving the filtered status hands the selection on
+        job_with(&mut app, 3, JobStatus::Completed, "00:20:00", "c");
+        assert_eq!(app.get_sorted_job_ids(), vec![1]);
+        assert_eq!(app.current_job_id, Some(1));
+
+        job_with(&mut app, 5, JobStatus::Timeout, "01:00:00", "e");
+        app.cycle_status_filter();
+        assert_eq!(app.get_sorted_job_ids(), vec![5, 2]);
+        assert_eq!(app.current_job_id, Some(5));
+
+        app.cycle_status_filter();
+        assert_eq!(app.get_sorted_job_ids(), vec![4]);
+
+        app.cycle_status_filter();
+        assert_eq!(app.status_filter, None);
+        assert_eq!(app.get_sorted_job_ids(), vec![5, 4, 3, 2, 1]);
+        assert_eq!(app.current_job_id, Some(4));
+    }
+
+    #[test]
+    fn test_tail_mode_entries_follow_file_order() {
+        let mut app = App::new();
+        app.add_tail_files(vec![PathBuf::from("/tmp/a.log"), PathBuf::from("b.log")]);
+
+        assert!(app.is_tail_mode());
+        assert_eq!(app.get_sorted_job_ids(), vec![1, 2]);
+        assert_eq!(app.current_job_id, Some(1));
+        assert_eq!(app.jobs[&1].info.job_name, "a.log");
+        assert_eq!(app.jobs[&2].info.stdout_path, PathBuf::from("b.log"));
+
+        app.switch_focus();
+        assert_eq!(app.focused_panel, FocusedPanel::Stdout);
+    }
+
+    #[test]
+    fn test_late_status_update_does_not_restore_deleted_job() {
+        let mut app = App::new();
+        app.update_job_status(1, JobStatus::Running, JobInfo::default());
+        app.update_job_status(2, JobStatus::Running, JobInfo::default());
+        app.current_job_id = Some(1);
+        app.remove_current_job();
+        assert!(!app.jobs.contains_key(&1));
+
+        // The status monitor polled job 1 before it was removed
+        app.update_job_status(1, JobStatus::Completed, JobInfo::default());
+        assert!(!app.jobs.contains_key(&1));
+        assert_eq!(app.current_job_id, Some(2));
+
+        // After undo, updates apply again
+        assert_eq!(app.undo_delete(), Some(1));
+        app.update_job_status(1, JobStatus::Completed, JobInfo::default());
+        assert_eq!(app.jobs[&1].status, JobStatus::Completed);
+    }
+
+    #[test]
+    fn test_cancelled_delete_needs_fresh_confirmation() {
+        let mut app = App::new();
+        app.add_job(100);
+
+        assert_eq!(app.confirm_delete(), None);
+        app.cancel_pending_delete();
+        assert_eq!(app.pending_delete_job(), None);
+        assert_eq!(app.confirm_delete(), None);
+    }
+
+    #[test]
+    fn test_follow_new_jobs_until_user_navigates() {
+        let mut app = App::new();
+        app.add_job(100);
+        app.add_job(200);
+        app.current_job_id = Some(100);
+
+        // Off by default
+        app.add_job(300);
+        assert!(!app.follow_new_jobs(&[300]));
+        assert_eq!(app.current_job_id, Some(100));
+
+        app.toggle_follow_new();
+        app.add_job(400);
+        app.add_job(500);
+        assert!(app.follow_new_jobs(&[500, 400]));
+        assert_eq!(app.current_job_id, Some(500));
+
+        // Manual navigation suppresses following
+        app.next_job();
+        let current = app.current_job_id;
+        app.add_job(600);
+        assert!(!app.follow_new_jobs(&[600]));
+        assert_eq!(app.current_job_id, current);
+
+        // Re-enabling re-arms it
+        app.toggle_follow_new();
+        app.toggle_follow_new();
+        assert!(app.follow_new_jobs(&[600]));
+        assert_eq!(app.current_job_id, Some(600));
+    }
+
+    #[test]
+    fn test_incremental_append_matches_whole_content() {
+        let content = "epoch 1\nprogress 10%\rprogress 50%\rprogress 100%\nlast line, no newline";
+        let mut whole = JobData::new(1);
+        whole.append_stdout(content, 20, false);
+
+        // Split at every position, including inside lines and right after \r
+        for split in (0..content.len()).filter(|&i| content.is_char_boundary(i)) {
+            let mut job = JobData::new(1);
+            job.append_stdout(&content[..split], 20, false);
+            job.append_stdout(&content[split..], 20, false);
+            assert_eq!(job.stdout_lines, whole.stdout_lines, "split at {}", split);
+        }
+        assert_eq!(
+            whole.stdout_lines,
+            vec!["epoch 1", "progress 100%", "last line, no newline"]
+        );
+    }
+
+    #[test]
+    fn test_long_lines_are_wrapped() {
+        let mut app = App::new();
+        app.add_job(1);
+        let content = "x".repeat(1024 * 1024);
+        app.update_log(1, "stdout", &content[..1000]);
+        app.update_log(1, "stdout", &content[1000..]);
+
+        let lines = &app.jobs[&1].stdout_lines;
+        let max_len = MAX_LOG_LINE_CHARS + LINE_CONTINUATION_MARKER.chars().count();
+        assert!(lines.iter().all(|line| line.chars().count() <= max_len));
+        assert_eq!(lines.len(), content.len().div_ceil(MAX_LOG_LINE_CHARS));
+        assert!(lines[0].ends_with(LINE_CONTINUATION_MARKER));
+        assert!(!lines.last().unwrap().ends_with(LINE_CONTINUATION_MARKER));
+
+        let mut terminal = Terminal::new(TestBackend::new(200, 50)).unwrap();
+        let started = Instant::now();
+        terminal.draw(|frame| render(frame, &app)).unwrap();
+        assert!(started.elapsed() < Duration::from_secs(2));
+    }
+
+    #[test]
+    fn test_clear_focused_log() {
+        let mut app = App::new();
+        app.keep_truncated_logs = true;
+        app.add_job(1);
+        app.update_log(1, "stdout", "a\nb\n");
+        app.update_log(1, "stderr", "oops\n");
+
+        assert_eq!(app.clear_focused_log().as_deref(), Some("stdout_1"));
+        assert!(app.jobs[&1].stdout_lines.is_empty());
+        assert_eq!(app.jobs[&1].stderr_lines, ["oops"]);
+
+        // The re-read content replaces the log without a marker
+        app.reset_log(1, "stdout", "a\nb\nc\n", false);
+        assert_eq!(app.jobs[&1].stdout_lines, ["a", "b", "c"]);
+
+        app.switch_focus();
+        assert_eq!(app.clear_focused_log().as_deref(), Some("stderr_1"));
+        assert!(app.jobs[&1].stderr_lines.is_empty());
+    }
+
+    #[test]
+    fn test_incremental_append_matches_whole_content_with_raw_progress() {
+        let content = "epoch 1\r\nprogress 10%\rprogress 50%\r\nlast\r";
+        let mut whole = JobData::new(1);
+        whole.append_stdout(content, 20, true);
+        for split in (0..content.len()).filter(|&i| content.is_char_boundary(i)) {
+            let mut job = JobData::new(1);
+            job.append_stdout(&content[..split], 20, true);
+            job.append_stdout(&content[split..], 20, true);
+            assert_eq!(job.stdout_lines, whole.stdout_lines, "split at {}", split);
+        }
+        assert_eq!(
+            whole.stdout_lines,
+            vec!["epoch 1", "progress 10%", "progress 50%", "last"]
+        );
+    }
+
+    #[test]
+    fn test_progress_modes() {
+        // tqdm rewrites its bar with a leading \r and ends it with \n
+        let tqdm = "Training\n\r  0%|          | 0/3\r 33%|###       | 1/3\r100%|##########| 3/3\ndone\n";
+
+        let mut job = JobData::new(1);
+        job.append_stdout(tqdm, 20, false);
+        assert_eq!(job.stdout_lines, vec!["Training", "100%|##########| 3/3", "done"]);
+
+        let mut job = JobData::new(1);
+        job.append_stdout(tqdm, 20, true);
+        assert_eq!(
+            job.stdout_lines,
+            vec![
+                "Training",
+                "  0%|          | 0/3",
+                " 33%|###       | 1/3",
+                "100%|##########| 3/3",
+                "done",
+            ]
+        );
+
+        // Windows line endings don't add blank lines
+        let mut job = JobData::new(1);
+        job.append_stdout("a\r\nb\r\n", 20, true);
+        assert_eq!(job.stdout_lines, vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_carriage_returns_overwrite_like_a_terminal() {
+        let lines = |chunks: &[&str]| {
+            let mut job = JobData::new(1);
+            for chunk in chunks {
+                job.append_stdout(chunk, 20, false);
+            }
+            job.stdout_lines
+        };
+
+        // Windows line endings keep the line
+        assert_eq!(lines(&["a\r\nb\r\n"]), ["a", "b"]);
+        assert_eq!(lines(&["a\r", "\nb\r", "\n"]), ["a", "b"]);
+
+        // A trailing \r leaves the line shown until it is overwritten
+        assert_eq!(lines(&["50%\r"]), ["50%"]);
+        assert_eq!(lines(&["50%\r", "60%"]), ["60%"]);
+        assert_eq!(lines(&["50%\r", "\n"]), ["50%"]);
+
+        // Mid-line, only as much as is written again is replaced
+        assert_eq!(lines(&["progress 100%\rdone"]), ["doneress 100%"]);
+        assert_eq!(lines(&["progress 100%\rdone         \n"]), ["done         "]);
+        assert_eq!(lines(&["abcdef\rXY", "Z\n"]), ["XYZdef"]);
+    }
+
+    #[test]
+    fn test_batch_progress() {
+        let mut app = App::new();
+        let start = app.started_at;
+        app.add_job(1);
+        assert_eq!(app.batch_progress(start), None);
+
+        for job_id in 1..=4 {
+            app.update_job_status(job_id, JobStatus::Running, JobInfo::default());
+        }
+        let progress = app.batch_progress(start).unwrap();
+        assert_eq!((progress.complete, progress.total, progress.eta), (0, 4, None));
+
+        // A job that was already finished when added counts, but gives no rate
+        app.update_job_status(5, JobStatus::Completed, JobInfo::default());
+        assert!(app.jobs[&5].finished_at.is_none());
+
+        app.update_job_status(1, JobStatus::Completed, JobInfo::default());
+        app.update_job_status(2, JobStatus::Failed, JobInfo::default());
+        assert!(app.jobs[&1].finished_at.is_some());
+        // Two jobs finished 10 minutes in: 5 minutes per job, 2 left
+        app.jobs.get_mut(&1).unwrap().finished_at = Some(start + Duration::from_secs(300));
+        app.jobs.get_mut(&2).unwrap().finished_at = Some(start + Duration::from_secs(600));
+        let progress = app.batch_progress(start + Duration::from_secs(660)).unwrap();
+        assert_eq!(progress.complete, 3);
+        assert_eq!(progress.total, 5);
+        assert_eq!(progress.eta, Some(Duration::from_secs(540)));
+    }
+
+    #[test]
+    fn test_search_jumps_between_matches() {
+        let mut app = App::new();
+        app.stdout_panel_height = 3;
+        app.add_job(1);
+        let log: String = (0..10)
+            .map(|i| if i % 4 == 1 { format!("step {} ERROR\n", i) } else { format!("step {}\n", i) })
+            .collect();
+        app.update_log(1, "stdout", &log);
+
+        app.start_search();
+        app.prompt.as_mut().unwrap().input.push_str("error");
+        app.prompt_changed();
+        assert!(app.submit_search());
+        assert!(app.prompt.is_none());
+        // Following the log, the panel's top line is 7; the next match is 9
+        assert_eq!(app.match_position(FocusedPanel::Stdout), Some((Some(3), 3)));
+        assert_eq!(app.jobs[&1].stdout_scroll, 7);
+        assert!(app.jobs[&1].stdout_scroll_mode);
+
+        // Wraps around to the first match
+        assert!(app.next_match(true));
+        assert_eq!(app.match_position(FocusedPanel::Stdout), Some((Some(1), 3)));
+        assert_eq!(app.jobs[&1].stdout_scroll, 1);
+        assert!(app.next_match(true));
+        assert_eq!(app.jobs[&1].stdout_scroll, 5);
+        assert!(app.next_match(false));
+        assert_eq!(app.jobs[&1].stdout_scroll, 1);
+
+        // Nothing in stderr
+        app.switch_focus();
+        assert!(!app.next_match(true));
+        assert_eq!(app.match_position(FocusedPanel::Stderr), Some((None, 0)));
+
+        app.start_search();
+        assert_eq!(app.prompt.as_ref().unwrap().input, "error");
+        app.prompt.as_mut().unwrap().input.clear();
+        app.prompt_changed();
+        assert!(app.submit_search());
+        assert!(app.search_query.is_none());
+        assert_eq!(app.match_position(FocusedPanel::Stdout), None);
+    }
+
+    fn filter_text(app: &App) -> Option<&str> {
+        app.log_filter.as_ref().map(Pattern::text)
+    }
+
+    #[test]
+    fn test_log_filter() {
+        let mut app = App::new();
+        app.stdout_panel_height = 2;
+        app.add_job(1);
+        let log: String = (0..10)
+            .map(|i| if i % 2 == 0 { format!("step {} loss\n", i) } else { format!("step {}\n", i) })
+            .collect();
+        app.update_log(1, "stdout", &log);
+
+        app.start_filter();
+        for c in "LOSS".chars() {
+            app.prompt.as_mut().unwrap().input.push(c);
+            app.prompt_changed();
+        }
+        assert_eq!(filter_text(&app), Some("LOSS"));
+        app.submit_filter();
+        assert!(app.prompt.is_none());
+
+        // Scrolling moves over the 5 matching lines, not the 10 in the log
+        app.scroll_up(1);
+        assert!(app.jobs[&1].stdout_scroll_mode);
+        assert_eq!(app.jobs[&1].stdout_scroll, 2);
+        app.scroll_down(5);
+        assert_eq!(app.jobs[&1].stdout_scroll, 3);
+
+        // Search positions count shown lines too
+        app.start_search();
+        app.prompt.as_mut().unwrap().input.push_str("step 4");
+        app.prompt_changed();
+        assert!(app.submit_search());
+        assert_eq!(app.jobs[&1].stdout_scroll, 2);
+        assert_eq!(app.match_position(FocusedPanel::Stdout), Some((Some(1), 1)));
+
+        // Cancelling an edit restores the filter; clearing it shows everything
+        app.start_filter();
+        app.prompt.as_mut().unwrap().input.push('x');
+        app.prompt_changed();
+        assert_eq!(filter_text(&app), Some("LOSSx"));
+        app.cancel_prompt();
+        assert_eq!(filter_text(&app), Some("LOSS"));
+        app.set_log_filter("");
+        assert_eq!(filter_text(&app), None);
+        assert!(!app.jobs[&1].stdout_scroll_mode);
+        assert_eq!(app.jobs[&1].stdout_lines.len(), 10);
+    }
+
+    #[test]
+    fn test_stalled_jobs() {
+        let mut app = App::new();
+        app.stall_after = Some(Duration::from_secs(600));
+        app.add_job(1);
+        let now = Local::now();
+        let minutes_ago = |m: u64| SystemTime::now() - Duration::from_secs(m * 60);
+
+        // Not running yet
+        app.record_output(1, minutes_ago(30));
+        assert!(!app.is_stalled(&app.jobs[&1], now));
+
+        app.update_job_status(1, JobStatus::Running, JobInfo::default());
+        assert!(app.is_stalled(&app.jobs[&1], now));
+        // An older modification time doesn't move the last output back
+        app.record_output(1, minutes_ago(5));
+        app.record_output(1, minutes_ago(20));
+        assert!(!app.is_stalled(&app.jobs[&1], now));
+        assert!(app.is_stalled(&app.jobs[&1], now + chrono::Duration::minutes(6)));
+
+        // A job without any output counts from when it was seen running
+        app.add_job(2);
+        app.update_job_status(2, JobStatus::Running, JobInfo::default());
+        assert!(!app.is_stalled(&app.jobs[&2], now));
+        assert!(app.is_stalled(&app.jobs[&2], now + chrono::Duration::minutes(11)));
+
+        app.stall_after = None;
+        assert!(!app.is_stalled(&app.jobs[&1], now + chrono::Duration::minutes(60)));
+    }
+
+    #[test]
+    fn test_focused_log_path() {
+        let mut app = App::new();
+        assert_eq!(app.focused_log_path(), None);
+        app.update_job_status(
+            1,
+            JobStatus::Running,
+            JobInfo {
+                job_id: 1,
+                stdout_path: PathBuf::from("/logs/1.out"),
+                ..Default::default()
+            },
+        );
+        assert_eq!(app.focused_log_path(), Some(Path::new("/logs/1.out")));
+        // Stderr isn't known yet
+        app.switch_focus();
+        assert_eq!(app.focused_log_path(), None);
+
+        let mut app = App::new();
+        app.add_tail_files(vec![PathBuf::from("a.log"), PathBuf::from("b.log")]);
+        app.current_job_id = Some(2);
+        assert_eq!(app.focused_log_path(), Some(Path::new("b.log")));
+    }
+
+    #[test]
+    fn test_last_stdout_line() {
+        let mut app = App::new();
+        app.add_job(1);
+        assert_eq!(app.jobs[&1].last_stdout_line(), None);
+        app.update_log(1, "stdout", "epoch 1\n  epoch 2 \n\n   \n");
+        assert_eq!(app.jobs[&1].last_stdout_line(), Some("epoch 2"));
+    }
+
+    #[test]
+    fn test_time_left() {
+        let mut job = JobData::new(1);
+        job.info.time_limit = "01:00:00".to_string();
+        job.info.elapsed = "00:56:30".to_string();
+        // Only running jobs are counting down
+        assert_eq!(job.time_left(), None);
+
+        job.status = JobStatus::Running;
+        assert_eq!(job.time_left(), Some(TimeLeft::Left(Duration::from_secs(210))));
+        job.info.elapsed = "01:00:05".to_string();
+        assert_eq!(job.time_left(), Some(TimeLeft::Left(Duration::ZERO)));
+        job.info.time_limit = "UNLIMITED".to_string();
+        assert_eq!(job.time_left(), Some(TimeLeft::Unlimited));
+        job.info.time_limit = "Partition_Limit".to_string();
+        assert_eq!(job.time_left(), None);
+    }
+
+    #[test]
+    fn test_wide_status_table_scrolls() {
+        let mut app = App::new();
+        app.columns = DASHBOARD_COLUMNS.to_vec();
+        assert_eq!(app.shown_columns(), DASHBOARD_COLUMNS);
+        assert!(!app.has_hidden_columns());
+
+        // Job ID (12) + Status, Runtime (13 each with the space); Name needs 23
+        app.status_table_width = 45;
+        assert_eq!(app.shown_columns(), [Column::Id, Column::Status, Column::Runtime]);
+        app.scroll_columns_left();
+        assert_eq!(app.column_offset, 0);
+        app.scroll_columns_right();
+        assert_eq!(app.shown_columns(), [Column::Id, Column::Runtime]);
+        for _ in 0..20 {
+            app.scroll_columns_right();
+        }
+        // Stops once State and Details are in view
+        assert_eq!(app.column_offset, 7);
+        assert_eq!(app.shown_columns(), [Column::Id, Column::State, Column::Details]);
+        app.scroll_columns_left();
+        assert_eq!(app.shown_columns(), [Column::Id, Column::Remaining, Column::State]);
+
+        // A column is shown, cut off, even if nothing else fits
+        app.status_table_width = 5;
+        assert_eq!(app.shown_columns(), [Column::Id, Column::Remaining]);
+        app.columns = vec![Column::Name];
+        assert_eq!(app.shown_columns(), [Column::Name]);
+        app.scroll_columns_right();
+        assert_eq!(app.column_offset, 0);
+    }
+
+    #[test]
+    fn test_elapsed_counts_up_between_polls() {
+        let mut app = App::new();
+        let info = |start_time: &str, elapsed: &str| JobInfo {
+            start_time: start_time.to_string(),
+            elapsed: elapsed.to_string(),
+            ..Default::default()
+        };
+        let start = parse_slurm_timestamp("2024-01-31T08:00:00").unwrap();
+        let later = |secs| start + chrono::Duration::seconds(secs);
+
+        app.update_job_status(1, JobStatus::Queued, info("Unknown", "00:00:00"));
+        assert_eq!(app.jobs[&1].started_at, None);
+        assert_eq!(app.jobs[&1].runtime(later(90)), "00:00:00");
+
+        app.update_job_status(1, JobStatus::Running, info("2024-01-31T08:00:00", "00:01:00"));
+        let job = &app.jobs[&1];
+        assert_eq!(job.started_at, Some(start));
+        assert_eq!(job.elapsed(later(90)), Some(Duration::from_secs(90)));
+        assert_eq!(job.runtime(later(90)), "00:01:30");
+        assert_eq!(job.runtime(later(86400 + 5)), "1-00:00:05");
+        // A clock behind SLURM's doesn't go back past the polled value
+        assert_eq!(job.runtime(later(-30)), "00:01:00");
+
+        app.update_job_status(1, JobStatus::Completed, info("2024-01-31T08:00:00", "00:02:10"));
+        assert_eq!(app.jobs[&1].runtime(later(600)), "00:02:10");
+
+        // Without a start time the polled value is shown
+        app.update_job_status(2, JobStatus::Running, info("", "00:05:00"));
+        assert_eq!(app.jobs[&2].runtime(later(600)), "00:05:00");
+    }
+
+    #[test]
+    fn test_scrolled_panels_stay_put_while_both_logs_grow() {
+        let mut app = App::new();
+        app.stdout_panel_height = 5;
+        app.stderr_panel_height = 5;
+        app.add_job(1);
+        let lines = |from: usize, to: usize| -> String {
+            (from..to).map(|i| format!("line {}\n", i)).collect()
+        };
+        app.update_log(1, "stdout", &lines(0, 20));
+        app.update_log(1, "stderr", &lines(0, 20));
+
+        // Freeze stdout, then stderr from the other panel
+        app.scroll_up(3);
+        app.switch_focus();
+        app.scroll_up(5);
+        assert_eq!(app.jobs[&1].stdout_scroll, 12);
+        assert_eq!(app.jobs[&1].stderr_scroll, 10);
+
+        // Both logs grow, with the focus going back and forth
+        for round in 0..6 {
+            let start = 20 + round * 10;
+            app.update_log(1, "stdout", &lines(start, start + 10));
+            app.update_log(1, "stderr", &lines(start, start + 10));
+            app.update_log(1, "stdout", "partial line");
+            app.update_log(1, "stdout", " ends\n");
+            if round % 2 == 0 {
+                app.switch_focus();
+            }
+        }
+        let job = &app.jobs[&1];
+        assert!(job.stdout_scroll_mode && job.stderr_scroll_mode);
+        assert_eq!((job.stdout_scroll, job.stderr_scroll), (12, 10));
+
+        // Resuming one panel's auto-scroll leaves the other frozen
+        app.focused_panel = FocusedPanel::Stdout;
+        app.scroll_to_bottom();
+        app.update_log(1, "stdout", &lines(80, 90));
+        app.update_log(1, "stderr", &lines(80, 90));
+        let job = &app.jobs[&1];
+        assert!(!job.stdout_scroll_mode);
+        assert_eq!(job.stdout_scroll, job.stdout_lines.len() - 5);
+        assert_eq!(job.stderr_scroll, 10);
+
+        // Scrolling the frozen panel carries on from where it was left
+        app.switch_focus();
+        app.scroll_down(1);
+        assert_eq!(app.jobs[&1].stderr_scroll, 11);
+        assert!(app.jobs[&1].stderr_scroll_mode);
+    }
+
+    #[test]
+    fn test_log_problems_are_shown_once_the_job_starts() {
+        let mut app = App::new();
+        app.update_job_status(1, JobStatus::Queued, JobInfo::default());
+        let missing = LogProblem::Missing(PathBuf::from("/logs/1.out"));
+        app.set_log_problem(1, "stdout", Some(missing.clone()));
+        let denied = LogProblem::PermissionDenied(PathBuf::from("/logs/1.err"));
+        app.set_log_problem(1, "stderr", Some(denied.clone()));
+
+        // Queued jobs have no log files yet
+        let job = &app.jobs[&1];
+        assert_eq!(job.log_problem(FocusedPanel::Stdout), None);
+        assert_eq!(job.log_problem(FocusedPanel::Stderr), Some(&denied));
+
+        app.update_job_status(1, JobStatus::Running, JobInfo::default());
+        assert_eq!(app.jobs[&1].log_problem(FocusedPanel::Stdout), Some(&missing));
+        app.set_log_problem(1, "stdout", None);
+        assert_eq!(app.jobs[&1].log_problem(FocusedPanel::Stdout), None);
+    }
+
+    #[test]
+    fn test_array_tasks_are_cycled_through() {
+        let task = |task_id: &str, raw_job_id, state: &str| ArrayTask {
+            task_id: task_id.to_string(),
+            raw_job_id,
+            state: state.to_string(),
+            ..Default::default()
+        };
+        let mut app = App::new();
+        app.update_job_status(50, JobStatus::Running, JobInfo::default());
+        assert_eq!(app.next_array_task(true), None);
+        assert!(app.current_message().unwrap().contains("--array-tasks"));
+
+        let info = JobInfo {
+            job_id: 50,
+            stdout_path: PathBuf::from("/logs/50.out"),
+            array_tasks: vec![
+                task("0", Some(51), "COMPLETED"),
+                task("1", Some(52), "RUNNING"),
+                task("[2-5]", None, "PENDING"),
+            ],
+            ..Default::default()
+        };
+        app.update_job_status(50, JobStatus::Running, info.clone());
+        assert_eq!(app.next_array_task(false), Some((50, "1".to_string(), 52)));
+        let (job_id, task_id, _) = app.next_array_task(true).unwrap();
+        assert_eq!(task_id, "0");
+
+        app.update_log(50, "stdout", "array job output\n");
+        app.show_array_task(job_id, &task_id, PathBuf::from("/logs/50_0.out"), PathBuf::new());
+        assert_eq!(app.current_message(), Some("Showing task 50_0"));
+        assert_eq!(app.next_array_task(true), Some((50, "1".to_string(), 52)));
+        assert_eq!(app.next_array_task(false), Some((50, "1".to_string(), 52)));
+
+        // Polls of the array job keep showing the task's logs
+        app.update_job_status(50, JobStatus::Running, info);
+        let job = &app.jobs[&50];
+        assert!(job.stdout_lines.is_empty());
+        assert_eq!(job.info.stdout_path, PathBuf::from("/logs/50_0.out"));
+        assert_eq!(job.log_id(), "50_0");
+    }
+
+    #[test]
+    fn test_resource_usage_adds_up_running_jobs() {
+        let allocated = |cpus, tres: &str| JobInfo {
+            alloc_cpus: cpus,
+            alloc_tres: parse_tres(tres),
+            ..Default::default()
+        };
+        let mut app = App::new();
+        assert_eq!(app.resource_usage(), None);
+
+        let tres = "cpu=64,mem=256G,gres/gpu=4";
+        app.update_job_status(1, JobStatus::Running, allocated(Some(64), tres));
+        app.update_job_status(2, JobStatus::Running, allocated(None, tres));
+        app.update_job_status(3, JobStatus::Running, allocated(Some(8), ""));
+        app.update_job_status(4, JobStatus::Completed, allocated(Some(16), "cpu=16,mem=1T"));
+        app.update_job_status(5, JobStatus::Running, JobInfo::default());
+        assert_eq!(
+            app.resource_usage(),
+            Some(Tres {
+                cpus: 136,
+                mem_mib: 512.0 * 1024.0,
+                gpus: 8,
+            })
+        );
+    }
+
+    #[test]
+    fn test_memory_history_is_bounded() {
+        let mut app = App::new();
+        let running = |rss_mib| JobInfo {
+            live_memory: Some(LiveMemory {
+                rss_mib,
+                max_rss_mib: None,
+            }),
+            ..Default::default()
+        };
+        app.update_job_status(1, JobStatus::Running, JobInfo::default());
+        app.update_job_status(1, JobStatus::Running, running(100.0));
+        assert!(!app.jobs[&1].has_memory_trend());
+        for i in 0..MEMORY_HISTORY_LEN {
+            app.update_job_status(1, JobStatus::Running, running(200.0 + i as f64));
+        }
+        let history = &app.jobs[&1].memory_history;
+        assert_eq!(history.len(), MEMORY_HISTORY_LEN);
+        assert_eq!(history.front(), Some(&200.0));
+        assert!(app.jobs[&1].has_memory_trend());
+
+        app.update_job_status(1, JobStatus::Completed, running(50.0));
+        assert_eq!(app.jobs[&1].memory_history.len(), MEMORY_HISTORY_LEN);
+        assert!(!app.jobs[&1].has_memory_trend());
+    }
+
+    #[test]
+    fn test_attach_picks_a_step() {
+        let mut app = App::new();
+        app.start_attach(1, Vec::new());
+        assert!(app.attach_request.is_none());
+        assert_eq!(app.current_message(), Some("Job 1 has no running steps to attach to"));
+
+        app.start_attach(1, vec!["0".to_string()]);
+        assert!(app.prompt.is_none());
+        assert_eq!(app.attach_request.take(), Some((1, "0".to_string())));
+
+        app.start_attach(2, vec!["0".to_string(), "1".to_string()]);
+        let prompt = app.prompt.as_mut().unwrap();
+        assert_eq!((prompt.kind, prompt.input.as_str()), (PromptKind::Attach(2), "0"));
+        prompt.input = "1".to_string();
+        app.submit_attach();
+        assert_eq!(app.attach_request.take(), Some((2, "1".to_string())));
+
+        app.start_attach(2, vec!["0".to_string(), "1".to_string()]);
+        app.prompt.as_mut().unwrap().input = "7".to_string();
+        app.submit_attach();
+        assert!(app.prompt.is_none() && app.attach_request.is_none());
+        assert_eq!(app.current_message(), Some("Job 2 has no running step '7'"));
+    }
+
+    #[test]
+    fn test_job_notes() {
+        let mut app = App::new();
+        app.add_job(1);
+        app.add_job(2);
+        app.current_job_id = Some(1);
+
+        app.start_note();
+        assert_eq!(app.prompt.as_ref().unwrap().kind, PromptKind::Note(1));
+        app.prompt.as_mut().unwrap().input.push_str("  LR=0.01 run ");
+        // The note stays with the job the prompt was opened for
+        app.current_job_id = Some(2);
+        app.submit_note();
+        assert!(app.prompt.is_none());
+        assert_eq!(app.jobs[&1].note.as_deref(), Some("LR=0.01 run"));
+        assert_eq!(app.jobs[&2].note, None);
+
+        // Cancelling keeps the note; an empty one removes it
+        app.current_job_id = Some(1);
+        app.start_note();
+        assert_eq!(app.prompt.as_ref().unwrap().input, "LR=0.01 run");
+        app.cancel_prompt();
+        assert_eq!(app.jobs[&1].note.as_deref(), Some("LR=0.01 run"));
+        app.start_note();
+        app.prompt.as_mut().unwrap().input.clear();
+        app.submit_note();
+        assert_eq!(app.jobs[&1].note, None);
+    }
+
+    #[test]
+    fn test_unread_stderr() {
+        let mut app = App::new();
+        app.add_job(1);
+        app.add_job(2);
+        app.current_job_id = Some(1);
+
+        // Output while looking at stdout is unread until stderr is focused
+        app.update_log(1, "stderr", "Traceback\n");
+        app.update_log(2, "stdout", "progress\n");
+        assert!(app.jobs[&1].unread_stderr);
+        assert!(!app.jobs[&2].unread_stderr);
+
+        app.switch_focus();
+        assert!(!app.jobs[&1].unread_stderr);
+
+        // With stderr focused, output for the current job is read right away
+        app.update_log(1, "stderr", "more\n");
+        assert!(!app.jobs[&1].unread_stderr);
+        app.update_log(2, "stderr", "error\n");
+        assert!(app.jobs[&2].unread_stderr);
+
+        app.next_job();
+        assert_eq!(app.current_job_id, Some(2));
+        assert!(!app.jobs[&2].unread_stderr);
+    }
+
+    #[test]
+    fn test_status_counts() {
+        let mut app = App::new();
+        assert!(app.status_counts().is_empty());
+
+        app.update_job_status(1, JobStatus::Running, JobInfo::default());
+        app.update_job_status(2, JobStatus::Running, JobInfo::default());
+        app.update_job_status(3, JobStatus::Failed, JobInfo::default());
+        app.add_job(4);
+
+        let counts = app.status_counts();
+        assert_eq!(counts.get(&JobStatus::Running), Some(&2));
+        assert_eq!(counts.get(&JobStatus::Failed), Some(&1));
+        assert_eq!(counts.get(&JobStatus::Unknown), Some(&1));
+        assert_eq!(counts.get(&JobStatus::Completed), None);
+    }
+
+    #[test]
+    fn test_truncated_logs_can_be_kept_above_a_marker() {
+        let mut app = App::new();
+        app.add_job(1);
+        app.update_log(1, "stdout", "run 1\npartial");
+        app.reset_log(1, "stdout", "run 2\n", false);
+        assert_eq!(app.jobs[&1].stdout_lines, ["run 2"]);
+
+        app.keep_truncated_logs = true;
+        app.update_log(1, "stdout", "partial");
+        app.reset_log(1, "stdout", "run 3\n", false);
+        assert_eq!(app.jobs[&1].stdout_lines, ["run 2", "partial", LOG_RESET_MARKER, "run 3"]);
+
+        // Nothing to keep in an empty panel
+        app.reset_log(1, "stderr", "warning\n", false);
+        assert_eq!(app.jobs[&1].stderr_lines, ["warning"]);
+
+        // Reloading replaces whatever arrived since the panel was cleared
+        assert!(app.clear_focused_log().is_some());
+        app.update_log(1, "stdout", "run 4\n");
+        app.reset_log(1, "stdout", "run 3\nrun 4\n", true);
+        assert_eq!(app.jobs[&1].stdout_lines, ["run 3", "run 4"]);
+    }
+
+    #[test]
+    fn test_truncation_clamps_scroll() {
+        let mut app = App::new();
+        app.stdout_panel_height = 20;
+        app.add_job(1);
+        let lines = |n: usize| (0..n).map(|i| format!("line {}\n", i)).collect::<String>();
+
+        app.update_log(1, "stdout", &lines(100));
+        app.scroll_up(30);
+        let job = &app.jobs[&1];
+        assert!(job.stdout_scroll_mode);
+        assert_eq!(job.stdout_scroll, 50);
+
+        // Still longer than the panel: stay in scroll mode, clamped to the end
+        app.reset_log(1, "stdout", &lines(40), false);
+        let job = &app.jobs[&1];
+        assert_eq!(job.stdout_lines.len(), 40);
+        assert!(job.stdout_scroll_mode);
+        assert_eq!(job.stdout_scroll, 20);
+
+        // Everything fits: nothing to scroll any more
+        app.reset_log(1, "stdout", &lines(5), false);
+        let job = &app.jobs[&1];
+        assert!(!job.stdout_scroll_mode);
+        assert_eq!(job.stdout_scroll, 0);
+        assert!(!app.is_in_scroll_mode());
     }
 }
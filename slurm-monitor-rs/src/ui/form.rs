@@ -0,0 +1,327 @@
+//! Job submission form (`submit --interactive`).
+
+use crate::utils::{parse_slurm_duration, parse_slurm_memory};
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::path::{Path, PathBuf};
+
+/// One input of the submission form, passed to sbatch as `--{option}=value`.
+#[derive(Debug, Clone)]
+pub struct FormField {
+    pub label: &'static str,
+    /// Example of what to type, shown while the field is empty
+    pub hint: &'static str,
+    pub value: String,
+    /// Why the value was rejected at the last submit attempt
+    pub error: Option<String>,
+    /// Long sbatch option the value is passed as
+    option: &'static str,
+    /// Short sbatch option, for reading `#SBATCH -p gpu` from the script
+    short: Option<char>,
+    required: bool,
+    check: fn(&str) -> Result<(), String>,
+}
+
+impl FormField {
+    fn new(
+        label: &'static str,
+        option: &'static str,
+        short: Option<char>,
+        hint: &'static str,
+    ) -> Self {
+        Self {
+            label,
+            hint,
+            value: String::new(),
+            error: None,
+            option,
+            short,
+            required: false,
+            check: |_| Ok(()),
+        }
+    }
+
+    fn required(mut self) -> Self {
+        self.required = true;
+        self
+    }
+
+    fn checked(mut self, check: fn(&str) -> Result<(), String>) -> Self {
+        self.check = check;
+        self
+    }
+
+    /// Why the current value can't be submitted, if it can't.
+    fn validate(&self) -> Option<String> {
+        let value = self.value.trim();
+        if value.is_empty() {
+            return self.required.then(|| "required".to_string());
+        }
+        (self.check)(value).err()
+    }
+}
+
+/// What a key press asks of the form's caller.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FormAction {
+    /// Keep showing the form
+    None,
+    /// Submit with these sbatch options
+    Submit(Vec<String>),
+    /// Leave without submitting
+    Cancel,
+}
+
+/// Form for the common sbatch options of a script, prefilled from its
+/// `#SBATCH` lines.
+#[derive(Debug, Clone)]
+pub struct SubmitForm {
+    pub script: PathBuf,
+    pub fields: Vec<FormField>,
+    /// Index of the field being edited
+    pub focused: usize,
+    /// Error output of sbatch when it rejected the last submission
+    pub sbatch_error: Option<String>,
+}
+
+impl SubmitForm {
+    /// A form for `script`, whose content provides the initial values.
+    pub fn new(script: &Path, content: &str) -> Self {
+        let mut fields = vec![
+            FormField::new("Job name", "job-name", Some('J'), "train-resnet"),
+            FormField::new("Partition", "partition", Some('p'), "gpu").checked(check_word),
+            FormField::new("Time limit", "time", Some('t'), "1-12:00:00, 4:00:00 or 90 (minutes)")
+                .required()
+                .checked(check_time),
+            FormField::new("CPUs per task", "cpus-per-task", Some('c'), "8")
+                .required()
+                .checked(check_count),
+            FormField::new("Memory", "mem", None, "64G (per node)").checked(check_memory),
+        ];
+        for (option, value) in sbatch_directives(content) {
+            let field = fields.iter_mut().find(|field| match option {
+                Directive::Long(name) => field.option == name,
+                Directive::Short(letter) => field.short == Some(letter),
+            });
+            if let Some(field) = field {
+                field.value = value;
+            }
+        }
+        Self {
+            script: script.to_path_buf(),
+            fields,
+            focused: 0,
+            sbatch_error: None,
+        }
+    }
+
+    /// Handle a key: typing edits the focused field, Tab and the arrow keys
+    /// move between fields, Enter submits and Esc cancels.
+    pub fn handle_key(&mut self, key: KeyEvent) -> FormAction {
+        match key.code {
+            KeyCode::Esc => return FormAction::Cancel,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                return FormAction::Cancel;
+            }
+            KeyCode::Enter => {
+                if let Some(args) = self.submit() {
+                    return FormAction::Submit(args);
+                }
+            }
+            KeyCode::Tab | KeyCode::Down => self.focused = (self.focused + 1) % self.fields.len(),
+            KeyCode::BackTab | KeyCode::Up => {
+                self.focused = (self.focused + self.fields.len() - 1) % self.fields.len();
+            }
+            KeyCode::Backspace => {
+                self.fields[self.focused].value.pop();
+            }
+            KeyCode::Char(c) => self.fields[self.focused].value.push(c),
+            _ => {}
+        }
+        FormAction::None
+    }
+
+    /// Check every field and build the sbatch options if they are all valid.
+    /// Otherwise the errors are shown and the first invalid field focused.
+    fn submit(&mut self) -> Option<Vec<String>> {
+        for field in &mut self.fields {
+            field.error = field.validate();
+        }
+        if let Some(invalid) = self.fields.iter().position(|field| field.error.is_some()) {
+            self.focused = invalid;
+            return None;
+        }
+        Some(self.sbatch_args())
+    }
+
+    /// The sbatch options of the filled-in fields.
+    pub fn sbatch_args(&self) -> Vec<String> {
+        self.fields
+            .iter()
+            .filter(|field| !field.value.trim().is_empty())
+            .map(|field| format!("--{}={}", field.option, field.value.trim()))
+            .collect()
+    }
+}
+
+/// An sbatch option as written on an `#SBATCH` line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Directive<'a> {
+    Long(&'a str),
+    Short(char),
+}
+
+/// Options set by the script's `#SBATCH` lines. Like sbatch, stops at the
+/// first line that isn't a comment or blank.
+fn sbatch_directives(content: &str) -> Vec<(Directive<'_>, String)> {
+    let mut directives = Vec::new();
+    for line in content.lines().map(str::trim) {
+        if !line.is_empty() && !line.starts_with('#') {
+            break;
+        }
+        let rest = match line.strip_prefix("#SBATCH") {
+            Some(rest) => rest,
+            None => continue,
+        };
+        let mut words = rest.split_whitespace();
+        while let Some(word) = words.next() {
+            let (option, value) = if let Some(long) = word.strip_prefix("--") {
+                match long.split_once('=') {
+                    Some((name, value)) => (Directive::Long(name), Some(value.to_string())),
+                    None => (Directive::Long(long), None),
+                }
+            } else if let Some(short) = word.strip_prefix('-') {
+                let mut chars = short.chars();
+                match chars.next() {
+                    Some(letter) if chars.as_str().is_empty() => (Directive::Short(letter), None),
+                    Some(letter) => (Directive::Short(letter), Some(chars.as_str().to_string())),
+                    None => continue,
+                }
+            } else {
+                continue;
+            };
+            let value = match value {
+                Some(value) => value,
+                None => match words.next() {
+                    Some(value) => value.to_string(),
+                    None => break,
+                },
+            };
+            directives.push((option, value.trim_matches('"').to_string()));
+        }
+    }
+    directives
+}
+
+fn check_word(value: &str) -> Result<(), String> {
+    if value.contains(char::is_whitespace) {
+        return Err("no spaces".to_string());
+    }
+    Ok(())
+}
+
+fn check_time(value: &str) -> Result<(), String> {
+    match parse_slurm_duration(value) {
+        Some(time) if !time.is_zero() => Ok(()),
+        _ => Err("not a time like 1-12:00:00, 4:00:00 or 90".to_string()),
+    }
+}
+
+fn check_count(value: &str) -> Result<(), String> {
+    match value.parse::<u32>() {
+        Ok(count) if count > 0 => Ok(()),
+        _ => Err("not a whole number above 0".to_string()),
+    }
+}
+
+fn check_memory(value: &str) -> Result<(), String> {
+    match parse_slurm_memory(value, 'M') {
+        Some(mib) if mib > 0.0 && value.starts_with(|c: char| c.is_ascii_digit()) => Ok(()),
+        _ => Err("not a size like 64G or 4096M".to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(form: &mut SubmitForm, code: KeyCode) -> FormAction {
+        form.handle_key(KeyEvent::new(code, KeyModifiers::NONE))
+    }
+
+    fn type_text(form: &mut SubmitForm, text: &str) {
+        for c in text.chars() {
+            press(form, KeyCode::Char(c));
+        }
+    }
+
+    #[test]
+    fn test_form_is_prefilled_from_the_script() {
+        let script = "\
+#!/bin/bash
+#SBATCH --job-name=train
+#SBATCH -p gpu --time 2:00:00
+#SBATCH -c8 --mem=\"32G\"
+
+# Later options override earlier ones
+#SBATCH --partition=gpu-large
+echo hi
+#SBATCH --partition=too-late
+";
+        let form = SubmitForm::new(Path::new("job.sh"), script);
+        assert_eq!(
+            form.sbatch_args(),
+            [
+                "--job-name=train",
+                "--partition=gpu-large",
+                "--time=2:00:00",
+                "--cpus-per-task=8",
+                "--mem=32G",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_form_validates_before_submitting() {
+        let mut form = SubmitForm::new(Path::new("job.sh"), "#!/bin/bash\n#SBATCH --mem=lots\n");
+
+        // Time and CPUs are required, and the memory isn't a size
+        assert_eq!(press(&mut form, KeyCode::Enter), FormAction::None);
+        let errors: Vec<Option<&str>> = form.fields.iter().map(|f| f.error.as_deref()).collect();
+        assert_eq!(
+            errors,
+            [
+                None,
+                None,
+                Some("required"),
+                Some("required"),
+                Some("not a size like 64G or 4096M")
+            ]
+        );
+        assert_eq!(form.focused, 2);
+
+        type_text(&mut form, "90");
+        press(&mut form, KeyCode::Tab);
+        type_text(&mut form, "0");
+        assert_eq!(press(&mut form, KeyCode::Enter), FormAction::None);
+        assert_eq!(form.fields[3].error.as_deref(), Some("not a whole number above 0"));
+        assert_eq!(form.focused, 3);
+
+        press(&mut form, KeyCode::Backspace);
+        type_text(&mut form, "4");
+        press(&mut form, KeyCode::Down);
+        for _ in 0.."lots".len() {
+            press(&mut form, KeyCode::Backspace);
+        }
+        type_text(&mut form, "16G");
+        assert_eq!(
+            press(&mut form, KeyCode::Enter),
+            FormAction::Submit(vec![
+                "--time=90".to_string(),
+                "--cpus-per-task=4".to_string(),
+                "--mem=16G".to_string(),
+            ])
+        );
+
+        assert_eq!(press(&mut form, KeyCode::Esc), FormAction::Cancel);
+    }
+}
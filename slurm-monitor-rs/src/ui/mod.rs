@@ -1,7 +1,12 @@
 //! UI module for Ratatui-based terminal interface.
 
 mod app;
+mod form;
 mod render;
+mod search;
+mod theme;
 
-pub use app::App;
-pub use render::render;
+pub use app::{App, Column, LayoutMode, PromptKind, SortMode, TextOverlay, DASHBOARD_COLUMNS};
+pub use form::{FormAction, SubmitForm};
+pub use render::{render, render_submit_form};
+pub use theme::{Theme, ThemeName};
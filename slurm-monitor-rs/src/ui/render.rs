@@ -1,14 +1,34 @@
 //! Rendering logic using Ratatui.
 
-use super::app::{App, FocusedPanel};
-use crate::utils::JobStatus;
+use super::app::{
+    App, BatchProgress, Column, FocusedPanel, JobData, LayoutMode, PromptKind, TimeDisplayMode,
+    TimeLeft, TIME_LEFT_WARNING,
+};
+use super::form::SubmitForm;
+use super::search::{shown_line_count, shown_lines, Pattern};
+use super::theme::Theme;
+use crate::diagnostics::{self, Level};
+use crate::gpu_monitor::GpuUpdate;
+use crate::job_manager::{
+    array_task_summary, dependency_job_ids, step_suffix, ArrayTask, JobStep, JobSummary,
+};
+use crate::log_tailer::LogProblem;
+use crate::utils::{
+    format_mib, format_relative_time, format_slurm_duration, relative_slurm_time, JobStatus, Tres,
+};
+use chrono::Local;
 use ratatui::{
-    layout::{Constraint, Direction, Layout, Rect},
-    style::{Color, Modifier, Style},
-    text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table},
+    layout::{Constraint, Direction, Layout, Margin, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span, Text},
+    widgets::{
+        Block, Borders, Cell, Clear, Gauge, Paragraph, Row, Scrollbar, ScrollbarOrientation,
+        ScrollbarState, Sparkline, Table, Wrap,
+    },
     Frame,
 };
+use std::ops::Range;
+use std::time::{Instant, SystemTime, UNIX_EPOCH};
 
 /// Render the entire UI.
 pub fn render(frame: &mut Frame, app: &App) {
@@ -23,27 +43,175 @@ pub fn render(frame: &mut Frame, app: &App) {
 
     render_header(frame, app, chunks[0]);
 
+    let (body_area, diagnostics_area) = app.split_diagnostics(chunks[1]);
+    if let Some(area) = diagnostics_area {
+        render_diagnostics_panel(frame, app, area);
+    }
+
+    if app.errors_view {
+        render_errors_panel(frame, app, body_area);
+        if app.overlay.is_some() {
+            render_overlay(frame, app, frame.area());
+        }
+        return;
+    }
+
     // Split body into status panel and output panel
-    let body_chunks = Layout::default()
+    let (status_area, output_area) = app.split_body(body_area);
+    match app.layout_mode {
+        LayoutMode::Split | LayoutMode::Dashboard => render_status_panel(frame, app, status_area),
+        LayoutMode::Compact => render_compact_status(frame, app, status_area),
+    }
+    if app.layout_mode != LayoutMode::Dashboard {
+        render_output_panel(frame, app, output_area);
+    }
+
+    if app.overlay.is_some() {
+        render_overlay(frame, app, frame.area());
+    }
+}
+
+/// Render the most recent monitor diagnostics, newest at the bottom.
+fn render_diagnostics_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let inner_height = area.height.saturating_sub(2) as usize;
+    let lines: Vec<Line> = diagnostics::recent(inner_height)
+        .iter()
+        .map(|entry| {
+            let color = match entry.level {
+                Level::Warn => app.theme.warning,
+                Level::Debug => app.theme.muted,
+            };
+            Line::from(Span::styled(entry.to_line(), Style::default().fg(color)))
+        })
+        .collect();
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(format!("Diagnostics ({}: hide)", app.keys.diagnostics))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.panel_border)),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Render the text overlay centered over the main view.
+fn render_overlay(frame: &mut Frame, app: &App, area: Rect) {
+    let overlay = match app.overlay {
+        Some(ref o) => o,
+        None => return,
+    };
+
+    let area = centered_rect(area, 80, 80);
+    let inner_height = area.height.saturating_sub(2) as usize;
+    let visible_lines = get_visible_lines(&overlay.lines, overlay.scroll, inner_height);
+
+    let title = format!(
+        "{} ({} lines) [↑↓ scroll, q/Esc close]",
+        overlay.title,
+        overlay.lines.len()
+    );
+
+    let paragraph = Paragraph::new(visible_lines.join("\n")).block(
+        Block::default()
+            .title(Span::styled(title, Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.title)),
+    );
+
+    frame.render_widget(Clear, area);
+    frame.render_widget(paragraph, area);
+}
+
+/// Compute a rect of the given percentage size centered in `area`.
+fn centered_rect(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
+    let vertical = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(area);
+
+    Layout::default()
         .direction(Direction::Horizontal)
         .constraints([
-            Constraint::Percentage(35), // Status panel
-            Constraint::Percentage(65), // Output panel
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
         ])
-        .split(chunks[1]);
+        .split(vertical[1])[1]
+}
 
-    render_status_panel(frame, app, body_chunks[0]);
-    render_output_panel(frame, app, body_chunks[1]);
+/// Render the `submit --interactive` form over the whole screen.
+pub fn render_submit_form(frame: &mut Frame, form: &SubmitForm, theme: &Theme) {
+    let label_width = form.fields.iter().map(|field| field.label.len()).max().unwrap_or(0);
+    let mut lines = Vec::new();
+    for (i, field) in form.fields.iter().enumerate() {
+        let focused = i == form.focused;
+        let marker = if focused { "> " } else { "  " };
+        let label = format!("{}{:<width$}  ", marker, field.label, width = label_width);
+        let label_style = if focused { theme.selected } else { Style::default() };
+        let mut spans = vec![Span::styled(label, label_style)];
+        if !field.value.is_empty() {
+            spans.push(Span::raw(field.value.clone()));
+        }
+        if focused {
+            spans.push(Span::styled("_", Style::default().add_modifier(Modifier::SLOW_BLINK)));
+        }
+        if field.value.is_empty() {
+            spans.push(Span::styled(field.hint, Style::default().fg(theme.muted)));
+        }
+        if let Some(ref error) = field.error {
+            spans.push(Span::styled(format!("  {}", error), Style::default().fg(theme.failed)));
+        }
+        lines.push(Line::from(spans));
+    }
+    lines.push(Line::from(""));
+    if let Some(ref error) = form.sbatch_error {
+        lines.push(Line::styled("sbatch rejected the job:", Style::default().fg(theme.failed)));
+        for line in error.lines() {
+            lines.push(Line::styled(line.to_string(), Style::default().fg(theme.failed)));
+        }
+        lines.push(Line::from(""));
+    }
+    lines.push(Line::styled(
+        "Enter: submit, Tab/↑↓: move between fields, Esc: cancel",
+        Style::default().fg(theme.muted),
+    ));
+
+    let title = format!("Submit {}", form.script.display());
+    let paragraph = Paragraph::new(lines).wrap(Wrap { trim: false }).block(
+        Block::default()
+            .title(Span::styled(title, Style::default().fg(theme.title).add_modifier(Modifier::BOLD)))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border)),
+    );
+    frame.render_widget(Clear, frame.area());
+    frame.render_widget(paragraph, centered_rect(frame.area(), 80, 60));
 }
 
 /// Render the header panel.
 fn render_header(frame: &mut Frame, app: &App, area: Rect) {
     let job_count = app.jobs.len();
-    let mut title = format!(
-        "SLURM Job Monitor - {} job{}",
-        job_count,
-        if job_count == 1 { "" } else { "s" }
-    );
+    let mut title = if app.is_tail_mode() {
+        format!(
+            "SLURM Job Monitor - tailing {} file{}",
+            job_count,
+            if job_count == 1 { "" } else { "s" }
+        )
+    } else {
+        format!(
+            "SLURM Job Monitor - {} job{}",
+            job_count,
+            if job_count == 1 { "" } else { "s" }
+        )
+    };
+
+    if let Some(usage) = app.resource_usage().filter(|_| !app.is_tail_mode()) {
+        title.push_str(&format!(" | {}", format_resource_usage(&usage)));
+    }
 
     if let Some(job_id) = app.current_job_id {
         if let Some(job) = app.jobs.get(&job_id) {
@@ -52,41 +220,153 @@ fn render_header(frame: &mut Frame, app: &App, area: Rect) {
             } else {
                 job.info.job_name.clone()
             };
-            title.push_str(&format!(" | Current: {} (ID: {})", name, job_id));
+            if app.is_tail_mode() {
+                title.push_str(&format!(" | Current: {}", job.info.stdout_path.display()));
+            } else {
+                title.push_str(&format!(" | Current: {} (ID: {})", name, job_id));
+                if let Some(timing) = job_timing(job) {
+                    title.push_str(&format!(", {}", timing));
+                }
+            }
         }
     }
 
-    let help_text = "Press Ctrl+C to exit | Scroll with arrow keys | Tab to switch panels";
+    if let Some(ref cluster) = app.discovery_filter.cluster {
+        title.push_str(&format!(" | cluster: {}", cluster));
+    }
+
+    if !app.is_tail_mode() {
+        title.push_str(&format!(
+            " | auto-discover {} ({})",
+            if app.auto_discover { "on" } else { "off" },
+            app.keys.auto_discover
+        ));
+    }
+
+    let hidden = app.hidden_finished_count();
+    if hidden > 0 {
+        title.push_str(&format!(
+            " | {} finished hidden ({} to show)",
+            hidden, app.keys.hide_finished
+        ));
+    }
+
+    if app.jobs_over_limit > 0 {
+        title.push_str(&format!(
+            " | job limit reached, {} job{} not shown",
+            app.jobs_over_limit,
+            if app.jobs_over_limit == 1 { "" } else { "s" }
+        ));
+    }
+
+    let warnings = diagnostics::warning_count();
+    if warnings > 0 && !app.show_diagnostics {
+        title.push_str(&format!(
+            " | {} warning{} ({} to view)",
+            warnings,
+            if warnings == 1 { "" } else { "s" },
+            app.keys.diagnostics
+        ));
+    }
+
+    let help_text = format!(
+        "Press Ctrl+C to exit | Scroll with {}{} | {} to switch panels",
+        app.keys.scroll_up, app.keys.scroll_down, app.keys.toggle_focus
+    );
+
+    let status_line = if let Some(ref prompt) = app.prompt {
+        let (label, hint) = match prompt.kind {
+            PromptKind::Search => {
+                ("Search".to_string(), "Enter: search, Esc: cancel, empty: clear, re: for regex")
+            }
+            PromptKind::Note(job_id) => {
+                (format!("Note for job {}", job_id), "Enter: save, Esc: cancel, empty: remove")
+            }
+            PromptKind::Filter => {
+                ("Grep".to_string(), "Enter: keep, Esc: cancel, empty: show all, re: for regex")
+            }
+            PromptKind::Attach(job_id) => (
+                format!("Attach to job {} step ({} running)", job_id, prompt.choices.join(", ")),
+                "Enter: attach, Esc: cancel",
+            ),
+        };
+        match prompt.pattern.as_ref().and_then(Pattern::error) {
+            Some(error) => Span::styled(
+                format!("{}: {}_  (invalid regex, matching literally: {})", label, prompt.input, error),
+                Style::default().fg(app.theme.failed).add_modifier(Modifier::BOLD),
+            ),
+            None => Span::styled(
+                format!("{}: {}_  ({})", label, prompt.input, hint),
+                Style::default().fg(app.theme.warning).add_modifier(Modifier::BOLD),
+            ),
+        }
+    } else if let Some(job_id) = app.pending_delete_job() {
+        Span::styled(
+            format!(
+                "Press {} again to remove job {} from the monitor",
+                app.keys.delete, job_id
+            ),
+            Style::default().fg(app.theme.warning).add_modifier(Modifier::BOLD),
+        )
+    } else if app.slurm_unreachable {
+        Span::styled(
+            "SLURM controller unreachable, retrying (showing the last known state)",
+            Style::default().fg(app.theme.failed).add_modifier(Modifier::BOLD),
+        )
+    } else if let Some(message) = app.current_message() {
+        Span::styled(message.to_string(), Style::default().fg(app.theme.warning))
+    } else {
+        Span::styled(help_text, Style::default().fg(app.theme.muted))
+    };
 
     let header_text = vec![
-        Line::from(Span::styled(title, Style::default().fg(Color::Cyan).add_modifier(Modifier::BOLD))),
-        Line::from(Span::styled(help_text, Style::default().fg(Color::DarkGray))),
+        Line::from(Span::styled(title, Style::default().fg(app.theme.title).add_modifier(Modifier::BOLD))),
+        Line::from(status_line),
     ];
 
     let header = Paragraph::new(header_text)
-        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(Color::Blue)));
+        .block(Block::default().borders(Borders::ALL).border_style(Style::default().fg(app.theme.border)));
 
     frame.render_widget(header, area);
 }
 
-/// Render the status panel with job list.
-fn render_status_panel(frame: &mut Frame, app: &App, area: Rect) {
-    let panel_title = "Job Status (n: prev, p: next, d: delete)";
-    
-    if app.jobs.is_empty() {
-        let empty = Paragraph::new("No jobs")
-            .block(Block::default().title(panel_title).borders(Borders::ALL).border_style(Style::default().fg(Color::Yellow)));
-        frame.render_widget(empty, area);
-        return;
+/// Describe what the running jobs hold, e.g. `Using 128 CPUs, 8 GPUs, 512.0 GiB`.
+/// GPUs and memory are left out when none are allocated.
+fn format_resource_usage(usage: &Tres) -> String {
+    let plural = |count: u64| if count == 1 { "" } else { "s" };
+    let mut parts = vec![format!("{} CPU{}", usage.cpus, plural(usage.cpus))];
+    if usage.gpus > 0 {
+        parts.push(format!("{} GPU{}", usage.gpus, plural(usage.gpus)));
     }
+    if usage.mem_mib > 0.0 {
+        parts.push(format_mib(usage.mem_mib));
+    }
+    format!("Using {}", parts.join(", "))
+}
 
-    // Create table header
-    let header_cells = ["Job ID", "Status", "Runtime", "Name"]
+/// Render the "X of Y jobs complete" gauge with its ETA.
+fn render_batch_progress(frame: &mut Frame, theme: &Theme, progress: BatchProgress, area: Rect) {
+    let mut label = format!("{} of {} jobs complete", progress.complete, progress.total);
+    if let Some(eta) = progress.eta {
+        let now = chrono::Local::now();
+        let done_at = now + chrono::Duration::from_std(eta).unwrap_or_default();
+        label.push_str(&format!(", ETA {}", format_relative_time(done_at, now)));
+    }
+
+    let gauge = Gauge::default()
+        .gauge_style(Style::default().fg(theme.completed))
+        .ratio(progress.complete as f64 / progress.total as f64)
+        .label(label);
+    frame.render_widget(gauge, area);
+}
+
+/// Render the list of tailed files (tail mode).
+fn render_file_list_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let header_cells = ["#", "File", "Lines"]
         .iter()
-        .map(|h| Cell::from(*h).style(Style::default().fg(Color::Magenta).add_modifier(Modifier::BOLD)));
+        .map(|h| Cell::from(*h).style(Style::default().fg(app.theme.table_header).add_modifier(Modifier::BOLD)));
     let header = Row::new(header_cells).height(1);
 
-    // Create table rows
     let rows: Vec<Row> = app
         .get_sorted_job_ids()
         .iter()
@@ -94,50 +374,23 @@ fn render_status_panel(frame: &mut Frame, app: &App, area: Rect) {
             let job = app.jobs.get(&job_id)?;
             let is_current = Some(job_id) == app.current_job_id;
 
-            let job_id_display = if is_current {
+            let index_display = if is_current {
                 format!("▶ {}", job_id)
             } else {
                 job_id.to_string()
             };
 
-            let status_color = match job.status {
-                JobStatus::Queued => Color::Yellow,
-                JobStatus::Running => Color::Green,
-                JobStatus::Completed => Color::Blue,
-                JobStatus::Failed => Color::Red,
-                JobStatus::Unknown => Color::White,
-            };
-
-            let runtime = if job.info.elapsed.is_empty() {
-                "N/A".to_string()
-            } else {
-                job.info.elapsed.clone()
-            };
-
-            let name = if job.info.job_name.is_empty() {
-                format!("Job {}", job_id)
-            } else {
-                // Truncate long names
-                let max_len = 20;
-                if job.info.job_name.len() > max_len {
-                    format!("{}...", &job.info.job_name[..max_len - 3])
-                } else {
-                    job.info.job_name.clone()
-                }
-            };
-
             let row_style = if is_current {
-                Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD)
+                app.theme.selected
             } else {
                 Style::default()
             };
 
             Some(
                 Row::new(vec![
-                    Cell::from(job_id_display).style(Style::default().fg(Color::Cyan)),
-                    Cell::from(job.status.as_str()).style(Style::default().fg(status_color)),
-                    Cell::from(runtime),
-                    Cell::from(name),
+                    Cell::from(index_display).style(Style::default().fg(app.theme.title)),
+                    Cell::from(job.info.job_name.clone()),
+                    Cell::from(job.stdout_lines.len().to_string()),
                 ])
                 .style(row_style)
                 .height(1),
@@ -147,41 +400,701 @@ fn render_status_panel(frame: &mut Frame, app: &App, area: Rect) {
 
     let table = Table::new(
         rows,
-        [
-            Constraint::Length(12),
-            Constraint::Length(12),
-            Constraint::Length(12),
-            Constraint::Min(10),
-        ],
+        [Constraint::Length(6), Constraint::Min(10), Constraint::Length(8)],
     )
     .header(header)
     .block(
         Block::default()
-            .title(panel_title)
+            .title(format!(
+                "Files ({}: prev, {}: next, {}{}: remove, {}: undo)",
+                app.keys.prev_job, app.keys.next_job, app.keys.delete, app.keys.delete, app.keys.undo
+            ))
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.panel_border)),
+    );
+
+    frame.render_widget(table, area);
+}
+
+/// Render the current job on a single line, in place of the status panel.
+fn render_compact_status(frame: &mut Frame, app: &App, area: Rect) {
+    let muted = Style::default().fg(app.theme.muted);
+    let mut spans = Vec::new();
+
+    let current = app.current_job_id.and_then(|id| Some((id, app.jobs.get(&id)?)));
+    if let Some((job_id, job)) = current {
+        if app.is_tail_mode() {
+            spans.push(Span::styled(
+                format!(" {}", job.info.job_name),
+                Style::default().fg(app.theme.title),
+            ));
+        } else {
+            spans.push(Span::styled(
+                format!(" ▶ {} ", job_id),
+                Style::default().fg(app.theme.title),
+            ));
+            spans.push(Span::styled(
+                job.status.as_str(),
+                Style::default().fg(app.theme.status(job.status)).add_modifier(Modifier::BOLD),
+            ));
+            for value in [&job.runtime(Local::now()), &job.info.job_name] {
+                if !value.is_empty() {
+                    spans.push(Span::raw(format!("  {}", value)));
+                }
+            }
+            if let Some(ref note) = job.note {
+                spans.push(Span::styled(format!("  · {}", note), muted));
+            }
+            if let Some(code) = job.info.exit_code.as_deref().filter(|_| job.status.is_terminal()) {
+                let color = if job.info.exit_code_failed() {
+                    app.theme.failed
+                } else {
+                    app.theme.muted
+                };
+                spans.push(Span::styled(format!("  exit {}", code), Style::default().fg(color)));
+            }
+        }
+    }
+
+    let job_ids = app.get_sorted_job_ids();
+    let position = app.current_job_id.and_then(|id| job_ids.iter().position(|&j| j == id));
+    if let Some(index) = position {
+        spans.push(Span::styled(
+            format!("  ({}/{}, {}: job list)", index + 1, job_ids.len(), app.keys.layout),
+            muted,
+        ));
+    } else {
+        spans.push(Span::styled(format!(" {}: job list", app.keys.layout), muted));
+    }
+
+    frame.render_widget(Paragraph::new(Line::from(spans)), area);
+}
+
+/// Render the status panel with job list.
+fn render_status_panel(frame: &mut Frame, app: &App, area: Rect) {
+    if app.is_tail_mode() {
+        render_file_list_panel(frame, app, area);
+        return;
+    }
+
+    // Overall progress goes in the last line when several jobs are monitored
+    let area = if let Some(progress) = app.batch_progress(Instant::now()) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(1)])
+            .split(area);
+        render_batch_progress(frame, &app.theme, progress, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+
+    // Reserve space below the job table for the current job's GPUs, the
+    // usage summary of a replayed job, or the memory history of a running one
+    let current = app.current_job_id.and_then(|id| app.jobs.get(&id));
+    let gpu = current
+        .and_then(|job| job.gpu.as_ref())
+        .filter(|_| app.gpu_enabled);
+    let summary = current.and_then(|job| job.summary.as_ref());
+    let area = if let Some(gpu) = gpu {
+        let rows = gpu.gpus.len().max(1) as u16 + 2;
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(rows.min(area.height / 2))])
+            .split(area);
+        render_gpu_panel(frame, &app.theme, gpu, chunks[1]);
+        chunks[0]
+    } else if let Some(summary) = summary {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(5.min(area.height / 2))])
+            .split(area);
+        render_summary_panel(frame, &app.theme, summary, chunks[1]);
+        chunks[0]
+    } else if let Some(job) = current.filter(|job| job.has_memory_trend()) {
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Min(5), Constraint::Length(5.min(area.height / 2))])
+            .split(area);
+        render_memory_panel(frame, &app.theme, job, chunks[1]);
+        chunks[0]
+    } else {
+        area
+    };
+
+    let keys = &app.keys;
+    let filter = app
+        .status_filter
+        .map(|status| format!(", filter: {}", status))
+        .unwrap_or_default();
+    let columns_hint = if app.has_hidden_columns() {
+        format!(", {}/{}: columns", keys.columns_left, keys.columns_right)
+    } else {
+        String::new()
+    };
+    let times = match app.time_display_mode {
+        TimeDisplayMode::Live => String::new(),
+        mode => format!(", times: {}", mode.as_str()),
+    };
+    let panel_title = format!(
+        "Job Status [sort: {} {}{}{}] ({}: prev, {}: next, {}/{}: sort, {}: filter, {}: script, {}{}: delete, {}: undo{})",
+        app.sort_mode.as_str(),
+        if app.sort_reverse { "↑" } else { "↓" },
+        filter,
+        times,
+        keys.prev_job,
+        keys.next_job,
+        keys.sort,
+        keys.sort_reverse,
+        keys.filter,
+        keys.script,
+        keys.delete,
+        keys.delete,
+        keys.undo,
+        columns_hint,
+    );
+    
+    if app.jobs.is_empty() {
+        let empty = Paragraph::new("No jobs")
+            .block(Block::default().title(panel_title.as_str()).borders(Borders::ALL).border_style(Style::default().fg(app.theme.panel_border)));
+        frame.render_widget(empty, area);
+        return;
+    }
+
+    // The last column takes up any remaining width, which is the preview
+    // column when it is shown. Columns that don't fit are scrolled to.
+    let columns = app.shown_columns();
+    let mut widths: Vec<Constraint> = columns
+        .iter()
+        .enumerate()
+        .map(|(i, c)| {
+            if i + 1 == columns.len() && !app.show_preview {
+                Constraint::Min(c.width().min(10))
+            } else {
+                Constraint::Length(c.width())
+            }
+        })
+        .collect();
+    let mut titles: Vec<&str> = columns.iter().map(|c| c.title()).collect();
+    if app.show_preview {
+        widths.push(Constraint::Min(10));
+        titles.push("Last output");
+    }
+    // Inside the borders, less the fixed columns and one space between columns
+    let fixed: u16 = columns.iter().map(|c| c.width() + 1).sum();
+    let preview_width = area.width.saturating_sub(2 + fixed).max(10) as usize;
+
+    // Create table header
+    let header_cells = titles
+        .into_iter()
+        .map(|title| Cell::from(title).style(Style::default().fg(app.theme.table_header).add_modifier(Modifier::BOLD)));
+    let header = Row::new(header_cells).height(1);
+
+    // Create table rows, each job followed by its steps (with --steps) and,
+    // for the current job, its array tasks (with --array-tasks)
+    let rows: Vec<Row> = app
+        .get_sorted_job_ids()
+        .iter()
+        .filter_map(|&job_id| {
+            let job = app.jobs.get(&job_id)?;
+            let is_current = Some(job_id) == app.current_job_id;
+
+            let row_style = if is_current {
+                app.theme.selected
+            } else {
+                Style::default()
+            };
+
+            let mut cells: Vec<Cell> = columns
+                .iter()
+                .map(|&column| status_cell(app, column, job_id, job, is_current))
+                .collect();
+            if app.show_preview {
+                let preview = job.last_stdout_line().unwrap_or("");
+                cells.push(
+                    Cell::from(truncate_with_ellipsis(preview, preview_width))
+                        .style(Style::default().fg(app.theme.muted)),
+                );
+            }
+
+            let mut rows = vec![Row::new(cells).style(row_style).height(1)];
+            rows.extend(job.info.steps.iter().map(|step| {
+                let cells = columns.iter().map(|&column| step_cell(app, column, step));
+                Row::new(cells).height(1)
+            }));
+            if is_current {
+                let (tasks, hidden) = array_task_window(job);
+                rows.extend(tasks.iter().map(|task| {
+                    let cells = columns.iter().map(|&column| array_task_cell(app, column, job, task));
+                    Row::new(cells).height(1)
+                }));
+                if hidden > 0 {
+                    let more = Cell::from(format!("  +{} more", hidden))
+                        .style(Style::default().fg(app.theme.muted));
+                    rows.push(Row::new(vec![more]).height(1));
+                }
+            }
+            Some(rows)
+        })
+        .flatten()
+        .collect();
+
+    let table = Table::new(rows, widths)
+    .header(header)
+    .block(
+        Block::default()
+            .title(panel_title.as_str())
+            .title_bottom(status_counts_line(app))
             .borders(Borders::ALL)
-            .border_style(Style::default().fg(Color::Yellow)),
+            .border_style(Style::default().fg(app.theme.panel_border)),
     );
 
     frame.render_widget(table, area);
 }
 
+/// Display order of the per-status counts in the status panel footer.
+const STATUS_ORDER: [JobStatus; 9] = [
+    JobStatus::Running,
+    JobStatus::Queued,
+    JobStatus::Suspended,
+    JobStatus::Completed,
+    JobStatus::Failed,
+    JobStatus::Cancelled,
+    JobStatus::Timeout,
+    JobStatus::OutOfMemory,
+    JobStatus::Unknown,
+];
+
+/// Footer line summarizing how many jobs are in each status, e.g.
+/// ` 3 RUNNING · 1 QUEUED `. Statuses with no jobs are left out.
+fn status_counts_line(app: &App) -> Line<'static> {
+    let counts = app.status_counts();
+    let mut spans = vec![Span::raw(" ")];
+    for status in STATUS_ORDER {
+        let Some(&count) = counts.get(&status) else {
+            continue;
+        };
+        if spans.len() > 1 {
+            spans.push(Span::styled(" · ", Style::default().fg(app.theme.muted)));
+        }
+        spans.push(Span::styled(
+            format!("{} {}", count, status.as_str()),
+            Style::default().fg(app.theme.status(status)),
+        ));
+    }
+    spans.push(Span::raw(" "));
+    Line::from(spans)
+}
+
+/// Build the table cell for one column of a job's row.
+fn status_cell(
+    app: &App,
+    column: Column,
+    job_id: u64,
+    job: &JobData,
+    is_current: bool,
+) -> Cell<'static> {
+    let or_na = |value: &str| {
+        if value.is_empty() {
+            "N/A".to_string()
+        } else {
+            value.to_string()
+        }
+    };
+
+    match column {
+        Column::Id => {
+            let job_id_display = if is_current {
+                format!("▶ {}", job_id)
+            } else {
+                job_id.to_string()
+            };
+            Cell::from(job_id_display).style(Style::default().fg(app.theme.title))
+        }
+        Column::Status => {
+            let color = if app.is_stalled(job, Local::now()) {
+                app.theme.warning
+            } else {
+                app.theme.status(job.status)
+            };
+            Cell::from(job.status.as_str()).style(Style::default().fg(color))
+        }
+        Column::Runtime => match app.time_display_mode {
+            TimeDisplayMode::Raw => Cell::from(or_na(&job.info.elapsed)),
+            TimeDisplayMode::Live | TimeDisplayMode::Relative => {
+                Cell::from(or_na(&job.runtime(Local::now())))
+            }
+        },
+        Column::Name => {
+            let mut name = if job.info.job_name.is_empty() {
+                format!("Job {}", job_id)
+            } else {
+                // Truncate long names
+                truncate_with_ellipsis(&job.info.job_name, 20)
+            };
+            // Mark jobs waiting on another monitored job
+            if job.status == JobStatus::Queued && has_monitored_dependency(app, job) {
+                name = format!("↳ {}", name);
+            }
+            let mut spans = vec![Span::raw(name)];
+            if job.unread_stderr {
+                spans.push(Span::styled(
+                    " *",
+                    Style::default().fg(app.theme.stderr).add_modifier(Modifier::BOLD),
+                ));
+            }
+            if let Some(ref note) = job.note {
+                spans.push(Span::styled(
+                    format!(" · {}", truncate_with_ellipsis(note, 30)),
+                    Style::default().fg(app.theme.muted),
+                ));
+            }
+            Cell::from(Line::from(spans))
+        }
+        Column::Details => {
+            let details = if job.status == JobStatus::Queued {
+                let waiting = pending_details(&job.info.reason, &job.info.estimated_start);
+                let dependencies = dependency_details(app, &job.info.dependencies);
+                match (dependencies.is_empty(), waiting.is_empty()) {
+                    (false, false) => format!("{} | {}", dependencies, waiting),
+                    (false, true) => dependencies,
+                    _ => waiting,
+                }
+            } else if let Some(code) = job.info.exit_code.as_deref().filter(|_| job.status.is_terminal()) {
+                let color = if job.info.exit_code_failed() {
+                    app.theme.failed
+                } else {
+                    app.theme.muted
+                };
+                return Cell::from(format!("exit {}", code)).style(Style::default().fg(color));
+            } else if app.is_stalled(job, Local::now()) {
+                let last_output = job.last_output.unwrap_or_else(Local::now);
+                let details = format!(
+                    "stalled: last output {}",
+                    format_relative_time(last_output, Local::now())
+                );
+                return Cell::from(details).style(Style::default().fg(app.theme.warning));
+            } else if !job.info.array_tasks.is_empty() {
+                array_task_summary(&job.info.array_tasks)
+            } else if let Some(ref memory) = job.info.live_memory {
+                memory.to_string()
+            } else {
+                String::new()
+            };
+            Cell::from(details).style(Style::default().fg(app.theme.muted))
+        }
+        Column::Partition => Cell::from(or_na(&job.info.partition)),
+        Column::Nodes => Cell::from(or_na(&job.info.node_list)),
+        Column::Start => Cell::from(shown_time(app, &job.info.start_time)),
+        Column::End => Cell::from(shown_time(app, &job.info.end_time)),
+        Column::State => Cell::from(or_na(&job.info.state)),
+        Column::Comment => Cell::from(or_na(&job.info.comment)),
+        Column::Wckey => Cell::from(or_na(&job.info.wckey)),
+        Column::Remaining => match job.time_left() {
+            Some(TimeLeft::Unlimited) => Cell::from("∞"),
+            Some(TimeLeft::Left(left)) => {
+                let cell = Cell::from(format_slurm_duration(left));
+                if left < TIME_LEFT_WARNING {
+                    cell.style(Style::default().fg(app.theme.failed).add_modifier(Modifier::BOLD))
+                } else {
+                    cell
+                }
+            }
+            None => Cell::from(""),
+        },
+    }
+}
+
+/// Build the cell of `column` for a job step's row, shown under its job.
+fn step_cell(app: &App, column: Column, step: &JobStep) -> Cell<'static> {
+    let muted = Style::default().fg(app.theme.muted);
+    match column {
+        Column::Id => Cell::from(format!("  {}", step_suffix(&step.step_id))).style(muted),
+        Column::Status => {
+            let status = JobStatus::from_slurm_state(&step.state);
+            Cell::from(status.as_str()).style(Style::default().fg(app.theme.status(status)))
+        }
+        Column::Runtime => Cell::from(step.elapsed.clone()),
+        Column::Name => Cell::from(truncate_with_ellipsis(&step.name, 20)).style(muted),
+        Column::Details => match step.max_rss_mib {
+            Some(mib) => Cell::from(format!("MaxRSS {}", format_mib(mib))).style(muted),
+            None => Cell::from(""),
+        },
+        Column::State => Cell::from(step.state.clone()),
+        Column::Partition
+        | Column::Nodes
+        | Column::Start
+        | Column::End
+        | Column::Remaining
+        | Column::Comment
+        | Column::Wckey => Cell::from(""),
+    }
+}
+
+/// Most array task rows listed under the current job.
+const ARRAY_TASK_ROWS: usize = 8;
+
+/// The array tasks listed under `job`: up to `ARRAY_TASK_ROWS` of them,
+/// including the shown task, and how many more there are.
+fn array_task_window(job: &JobData) -> (&[ArrayTask], usize) {
+    let tasks = &job.info.array_tasks;
+    if tasks.len() <= ARRAY_TASK_ROWS {
+        return (tasks, 0);
+    }
+    let shown = job
+        .shown_task
+        .as_ref()
+        .and_then(|shown| tasks.iter().position(|task| task.task_id == shown.task_id))
+        .unwrap_or(0);
+    let start = (shown + 1).saturating_sub(ARRAY_TASK_ROWS);
+    let window = &tasks[start..start + ARRAY_TASK_ROWS];
+    (window, tasks.len() - ARRAY_TASK_ROWS)
+}
+
+/// Build the cell of `column` for an array task's row, shown under its job.
+/// The task whose logs are shown is marked.
+fn array_task_cell(app: &App, column: Column, job: &JobData, task: &ArrayTask) -> Cell<'static> {
+    let muted = Style::default().fg(app.theme.muted);
+    match column {
+        Column::Id => {
+            let shown = job.shown_task.as_ref().is_some_and(|shown| shown.task_id == task.task_id);
+            let marker = if shown { "▸" } else { " " };
+            Cell::from(format!(" {}_{}", marker, task.task_id)).style(muted)
+        }
+        Column::Status => {
+            let status = task.status();
+            Cell::from(status.as_str()).style(Style::default().fg(app.theme.status(status)))
+        }
+        Column::Runtime => Cell::from(task.elapsed.clone()),
+        Column::Details if task.count() > 1 => {
+            Cell::from(format!("{} tasks", task.count())).style(muted)
+        }
+        Column::State => Cell::from(task.state.clone()),
+        _ => Cell::from(""),
+    }
+}
+
+/// Shorten `text` to at most `max_chars` characters, ending in `...` if cut.
+///
+/// Counts chars rather than bytes so multi-byte names are never split
+/// inside a codepoint.
+fn truncate_with_ellipsis(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let head: String = text.chars().take(max_chars.saturating_sub(3)).collect();
+    format!("{}...", head)
+}
+
+/// Show a start or end time in the app's time display mode.
+///
+/// Values that aren't timestamps (`Unknown`, `None`) are shown as they are.
+fn shown_time(app: &App, value: &str) -> String {
+    if value.is_empty() {
+        return "N/A".to_string();
+    }
+    match app.time_display_mode {
+        TimeDisplayMode::Relative => {
+            relative_slurm_time(value).unwrap_or_else(|| value.to_string())
+        }
+        TimeDisplayMode::Live | TimeDisplayMode::Raw => value.to_string(),
+    }
+}
+
+/// Describe when a job started or ended, e.g. `started 3m ago`.
+fn job_timing(job: &JobData) -> Option<String> {
+    if let Some(ended) = relative_slurm_time(&job.info.end_time) {
+        return Some(format!("ended {}", ended));
+    }
+    // A pending job's start time is only an estimate
+    if job.status == JobStatus::Queued {
+        return None;
+    }
+    relative_slurm_time(&job.info.start_time).map(|started| format!("started {}", started))
+}
+
+/// Describe why a pending job is waiting and when it may start.
+fn pending_details(reason: &str, estimated_start: &str) -> String {
+    let estimated_start = relative_slurm_time(estimated_start)
+        .unwrap_or_else(|| estimated_start.to_string());
+    match (reason.is_empty(), estimated_start.is_empty()) {
+        (false, false) => format!("{}, est. start {}", reason, estimated_start),
+        (false, true) => reason.to_string(),
+        (true, false) => format!("est. start {}", estimated_start),
+        (true, true) => String::new(),
+    }
+}
+
+/// Describe a pending job's dependencies, with the status of any dependency
+/// that is also being monitored, e.g. `afterok:123 [FAILED]`.
+fn dependency_details(app: &App, dependencies: &[String]) -> String {
+    dependencies
+        .iter()
+        .map(|dependency| {
+            let term = dependency.trim_end_matches("(unfulfilled)");
+            let status = dependency_job_ids(dependency)
+                .into_iter()
+                .find_map(|id| app.jobs.get(&id))
+                .map(|upstream| format!(" [{}]", upstream.status.as_str()))
+                .unwrap_or_default();
+            format!("{}{}", term, status)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Whether `job` depends on another monitored job.
+fn has_monitored_dependency(app: &App, job: &JobData) -> bool {
+    job.info
+        .dependencies
+        .iter()
+        .flat_map(|dependency| dependency_job_ids(dependency))
+        .any(|id| app.jobs.contains_key(&id))
+}
+
+/// Render the resource usage summary of a finished job.
+fn render_summary_panel(frame: &mut Frame, theme: &Theme, summary: &JobSummary, area: Rect) {
+    let percent = |value: Option<f64>| {
+        value
+            .map(|v| format!("{:.1}%", v))
+            .unwrap_or_else(|| "N/A".to_string())
+    };
+    let gib = |mib: Option<f64>| {
+        mib.map(|m| format!("{:.1}", m / 1024.0))
+            .unwrap_or_else(|| "?".to_string())
+    };
+
+    let lines = vec![
+        Line::from(format!(
+            "Exit code {} | Elapsed {}",
+            summary.exit_code,
+            if summary.elapsed.is_empty() { "N/A" } else { &summary.elapsed }
+        )),
+        Line::from(format!("CPU efficiency: {}", percent(summary.cpu_efficiency))),
+        Line::from(format!(
+            "Memory: {}/{} GiB ({})",
+            gib(summary.max_rss_mib),
+            gib(summary.req_mem_mib),
+            percent(summary.memory_efficiency())
+        )),
+    ];
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title("Summary (finished job, not monitored)")
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.border)),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
+/// Render a sparkline of a running job's memory use over the session, newest
+/// at the right.
+fn render_memory_panel(frame: &mut Frame, theme: &Theme, job: &JobData, area: Rect) {
+    let current = job.info.live_memory.map(|m| m.to_string()).unwrap_or_default();
+    let peak = job.memory_history.iter().copied().fold(0.0, f64::max);
+    let title = format!(
+        "Memory (Job {}): {} | session peak {}",
+        job.info.job_id,
+        current,
+        format_mib(peak)
+    );
+    // Only as many samples as fit, so the newest are always shown
+    let width = area.width.saturating_sub(2) as usize;
+    let skip = job.memory_history.len().saturating_sub(width);
+    let data: Vec<u64> = job
+        .memory_history
+        .iter()
+        .skip(skip)
+        .map(|&mib| mib.round() as u64)
+        .collect();
+
+    let sparkline = Sparkline::default()
+        .block(
+            Block::default()
+                .title(title)
+                .borders(Borders::ALL)
+                .border_style(Style::default().fg(theme.panel_border)),
+        )
+        .data(&data)
+        .max(peak.round().max(1.0) as u64)
+        .style(Style::default().fg(theme.running));
+    frame.render_widget(sparkline, area);
+}
+
+/// Render GPU utilization bars for the current job.
+fn render_gpu_panel(frame: &mut Frame, theme: &Theme, gpu: &GpuUpdate, area: Rect) {
+    let title = format!("GPUs (Job {} on {})", gpu.job_id, gpu.nodes);
+
+    let lines: Vec<Line> = if let Some(ref error) = gpu.error {
+        vec![Line::from(Span::styled(error.clone(), Style::default().fg(theme.muted)))]
+    } else {
+        // "T0 GPU0 [" + bar + "] 100% " + "12.3/80.0 GiB"
+        let bar_width = (area.width as usize).saturating_sub(34).clamp(5, 30);
+        gpu.gpus
+            .iter()
+            .map(|g| {
+                let filled =
+                    ((g.utilization.clamp(0.0, 100.0) / 100.0) * bar_width as f64).round() as usize;
+                let color = if g.utilization >= 75.0 {
+                    theme.gpu_high
+                } else if g.utilization >= 25.0 {
+                    theme.gpu_medium
+                } else {
+                    theme.gpu_low
+                };
+                Line::from(vec![
+                    Span::raw(format!("T{} GPU{} [", g.task, g.index)),
+                    Span::styled("█".repeat(filled), Style::default().fg(color)),
+                    Span::styled(
+                        "░".repeat(bar_width - filled),
+                        Style::default().fg(theme.muted),
+                    ),
+                    Span::raw(format!(
+                        "] {:>3.0}% {:.1}/{:.1} GiB",
+                        g.utilization,
+                        g.memory_used_mib as f64 / 1024.0,
+                        g.memory_total_mib as f64 / 1024.0
+                    )),
+                ])
+            })
+            .collect()
+    };
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::default()
+            .title(title)
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(theme.table_header)),
+    );
+
+    frame.render_widget(paragraph, area);
+}
+
 /// Render the output panel with stdout and stderr.
 fn render_output_panel(frame: &mut Frame, app: &App, area: Rect) {
     if app.current_job_id.is_none() {
         let empty = Paragraph::new("Select a job to view output")
-            .block(Block::default().title("Output").borders(Borders::ALL).border_style(Style::default().fg(Color::Green)));
+            .block(Block::default().title("Output").borders(Borders::ALL).border_style(Style::default().fg(app.theme.stdout)));
         frame.render_widget(empty, area);
         return;
     }
 
-    // Split into stdout and stderr panels
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
-        .split(area);
+    // Tailed files have no stderr; give the file the whole area
+    if app.is_tail_mode() {
+        render_stdout_panel(frame, app, area);
+        return;
+    }
 
-    render_stdout_panel(frame, app, chunks[0]);
-    render_stderr_panel(frame, app, chunks[1]);
+    // Split into stdout and stderr panels; either may be collapsed
+    let (stdout_area, stderr_area) = app.split_output(area);
+    if stdout_area.height > 0 {
+        render_stdout_panel(frame, app, stdout_area);
+    }
+    if stderr_area.height > 0 {
+        render_stderr_panel(frame, app, stderr_area);
+    }
 }
 
 /// Render stdout panel.
@@ -198,53 +1111,94 @@ fn render_stdout_panel(frame: &mut Frame, app: &App, area: Rect) {
 
     let is_focused = app.focused_panel == FocusedPanel::Stdout;
     let border_color = if is_focused {
-        Color::LightGreen
+        app.theme.stdout
     } else {
-        Color::DarkGray
+        app.theme.muted
     };
 
     let focus_indicator = if is_focused {
-        " [FOCUSED]"
+        " [FOCUSED]".to_string()
     } else {
-        " [Press Tab to focus]"
+        format!(" [Press {} to focus]", app.keys.toggle_focus)
     };
 
     let scroll_indicator = if job.stdout_scroll_mode {
-        " [SCROLL MODE - Press 'q' to exit]"
+        format!(" [SCROLL MODE - Press '{}' to exit]", app.keys.quit)
     } else {
-        ""
+        String::new()
     };
 
-    let title = format!(
-        "STDOUT (Job {}){}{}",
-        job_id, focus_indicator, scroll_indicator
-    );
+    let replay_indicator = if job.replay { " [replay]" } else { "" };
+    let problem = job.log_problem(FocusedPanel::Stdout);
+    let problem_indicator = format_problem_indicator(problem);
+    let view = LogView::new(app, area);
+    let shown = view.shown_count(&job.stdout_lines);
+    let line_count = format_shown_count(app, shown, job.stdout_lines.len());
+    let match_count = format_match_count(app.match_position(FocusedPanel::Stdout));
+    let width = area.width.saturating_sub(2) as usize;
+    let title = if app.is_tail_mode() {
+        fit_title(
+            &job.info.job_name,
+            &[&problem_indicator, &line_count, &match_count, &scroll_indicator],
+            width,
+        )
+    } else {
+        fit_title(
+            &format!("STDOUT (Job {})", job.log_id()),
+            &[
+                &problem_indicator,
+                &line_count,
+                &match_count,
+                replay_indicator,
+                &scroll_indicator,
+                &focus_indicator,
+            ],
+            width,
+        )
+    };
 
     let title_style = if is_focused {
-        Style::default().fg(Color::LightGreen).add_modifier(Modifier::BOLD)
+        Style::default().fg(app.theme.stdout).add_modifier(Modifier::BOLD)
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(app.theme.muted)
     };
 
     // Calculate visible lines
     let inner_height = area.height.saturating_sub(2) as usize;
-    let visible_lines = get_visible_lines(&job.stdout_lines, job.stdout_scroll, inner_height);
-
-    let content = if visible_lines.is_empty() {
-        "[No output yet - waiting for file updates...]".to_string()
+    let content = if job.stdout_lines.is_empty() && job.replay {
+        Text::from("[No output]")
+    } else if let Some(problem) = problem.filter(|_| job.stdout_lines.is_empty()) {
+        Text::styled(format!("[{}]", problem), Style::default().fg(app.theme.warning))
+    } else if job.stdout_lines.is_empty() {
+        Text::from("[No output yet - waiting for file updates...]")
+    } else if shown == 0 {
+        Text::from("[No lines match the filter]")
     } else {
-        visible_lines.join("\n")
+        Text::from(view.visible_rows(
+            &job.stdout_lines,
+            job.stdout_scroll,
+            !job.stdout_scroll_mode,
+            inner_height,
+        ))
     };
 
+    let position = LogPosition::new(
+        shown,
+        job.stdout_scroll,
+        inner_height,
+        job.stdout_scroll_mode,
+    );
     let paragraph = Paragraph::new(content)
         .block(
             Block::default()
                 .title(Span::styled(title, title_style))
+                .title_bottom(position.marker(&app.theme).right_aligned())
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(border_color)),
         );
 
     frame.render_widget(paragraph, area);
+    position.render_scrollbar(frame, area, border_color);
 }
 
 /// Render stderr panel.
@@ -260,60 +1214,405 @@ fn render_stderr_panel(frame: &mut Frame, app: &App, area: Rect) {
     };
 
     let is_focused = app.focused_panel == FocusedPanel::Stderr;
-    let border_color = if is_focused {
-        Color::LightRed
+    let alert = job.unread_stderr && !is_focused;
+    let border_color = if is_focused || alert {
+        app.theme.stderr
     } else {
-        Color::DarkGray
+        app.theme.muted
     };
 
     let focus_indicator = if is_focused {
-        " [FOCUSED]"
+        " [FOCUSED]".to_string()
+    } else if alert {
+        format!(" [NEW - Press {} to view]", app.keys.toggle_focus)
     } else {
-        " [Press Tab to focus]"
+        format!(" [Press {} to focus]", app.keys.toggle_focus)
     };
 
     let scroll_indicator = if job.stderr_scroll_mode {
-        " [SCROLL MODE - Press 'q' to exit]"
+        format!(" [SCROLL MODE - Press '{}' to exit]", app.keys.quit)
     } else {
-        ""
+        String::new()
     };
 
-    let title = format!(
-        "STDERR (Job {}){}{}",
-        job_id, focus_indicator, scroll_indicator
+    let replay_indicator = if job.replay { " [replay]" } else { "" };
+    let problem = job.log_problem(FocusedPanel::Stderr);
+    let problem_indicator = format_problem_indicator(problem);
+    let view = LogView::new(app, area);
+    let shown = view.shown_count(&job.stderr_lines);
+    let line_count = format_shown_count(app, shown, job.stderr_lines.len());
+    let match_count = format_match_count(app.match_position(FocusedPanel::Stderr));
+    let title = fit_title(
+        &format!("STDERR (Job {})", job.log_id()),
+        &[
+            &problem_indicator,
+            &line_count,
+            &match_count,
+            replay_indicator,
+            &scroll_indicator,
+            &focus_indicator,
+        ],
+        area.width.saturating_sub(2) as usize,
     );
 
-    let title_style = if is_focused {
-        Style::default().fg(Color::LightRed).add_modifier(Modifier::BOLD)
+    let title_style = if is_focused || (alert && !blink_phase()) {
+        Style::default().fg(app.theme.stderr).add_modifier(Modifier::BOLD)
+    } else if alert {
+        app.theme.alert
     } else {
-        Style::default().fg(Color::DarkGray)
+        Style::default().fg(app.theme.muted)
     };
 
     // Calculate visible lines
     let inner_height = area.height.saturating_sub(2) as usize;
-    let visible_lines = get_visible_lines(&job.stderr_lines, job.stderr_scroll, inner_height);
-
-    let content = if visible_lines.is_empty() {
-        "[No output yet - waiting for file updates...]".to_string()
+    let content = if job.stderr_lines.is_empty() && job.replay {
+        Text::from("[No output]")
+    } else if let Some(problem) = problem.filter(|_| job.stderr_lines.is_empty()) {
+        Text::styled(format!("[{}]", problem), Style::default().fg(app.theme.warning))
+    } else if job.stderr_lines.is_empty() {
+        Text::from("[No output yet - waiting for file updates...]")
+    } else if shown == 0 {
+        Text::from("[No lines match the filter]")
     } else {
-        visible_lines.join("\n")
+        Text::from(view.visible_rows(
+            &job.stderr_lines,
+            job.stderr_scroll,
+            !job.stderr_scroll_mode,
+            inner_height,
+        ))
     };
 
+    let position = LogPosition::new(
+        shown,
+        job.stderr_scroll,
+        inner_height,
+        job.stderr_scroll_mode,
+    );
     let paragraph = Paragraph::new(content)
         .block(
             Block::default()
                 .title(Span::styled(title, title_style))
+                .title_bottom(position.marker(&app.theme).right_aligned())
                 .borders(Borders::ALL)
                 .border_style(Style::default().fg(border_color)),
         );
 
     frame.render_widget(paragraph, area);
+    position.render_scrollbar(frame, area, border_color);
+}
+
+/// Render the stderr of every job in one panel.
+fn render_errors_panel(frame: &mut Frame, app: &App, area: Rect) {
+    let lines = app.all_stderr_lines();
+    let scroll_indicator = if app.errors_scroll_mode {
+        format!(" [SCROLL MODE - Press '{}' to exit]", app.keys.quit)
+    } else {
+        String::new()
+    };
+    let view = LogView::new(app, area);
+    let shown = view.shown_count(&lines);
+    let line_count = format_shown_count(app, shown, lines.len());
+    let back = format!(" [Press {} for the job list]", app.keys.errors);
+    let title = fit_title(
+        "STDERR (all jobs)",
+        &[&line_count, &scroll_indicator, &back],
+        area.width.saturating_sub(2) as usize,
+    );
+
+    let inner_height = area.height.saturating_sub(2) as usize;
+    let content = if lines.is_empty() {
+        Text::from("[No job has written to stderr]")
+    } else if shown == 0 {
+        Text::from("[No lines match the filter]")
+    } else {
+        Text::from(view.visible_rows(
+            &lines,
+            app.errors_scroll,
+            !app.errors_scroll_mode,
+            inner_height,
+        ))
+    };
+
+    let position = LogPosition::new(shown, app.errors_scroll, inner_height, app.errors_scroll_mode);
+    let paragraph = Paragraph::new(content).block(
+        Block::default()
+            .title(Span::styled(
+                title,
+                Style::default().fg(app.theme.stderr).add_modifier(Modifier::BOLD),
+            ))
+            .title_bottom(position.marker(&app.theme).right_aligned())
+            .borders(Borders::ALL)
+            .border_style(Style::default().fg(app.theme.stderr)),
+    );
+
+    frame.render_widget(paragraph, area);
+    position.render_scrollbar(frame, area, app.theme.stderr);
+}
+
+/// Where a log panel is scrolled to, shown as a scrollbar and a marker on the
+/// bottom border.
+#[derive(Debug, PartialEq)]
+struct LogPosition {
+    /// First visible line
+    top: usize,
+    /// Last possible value of `top`
+    max_scroll: usize,
+    total: usize,
+    /// Following new output rather than in scroll mode
+    live: bool,
+}
+
+impl LogPosition {
+    fn new(total: usize, scroll: usize, height: usize, scroll_mode: bool) -> Self {
+        let max_scroll = total.saturating_sub(height);
+        Self {
+            top: if scroll_mode { scroll.min(max_scroll) } else { max_scroll },
+            max_scroll,
+            total,
+            live: !scroll_mode,
+        }
+    }
+
+    /// `● LIVE` while following output, the first visible line otherwise.
+    fn marker(&self, theme: &Theme) -> Line<'static> {
+        if self.live {
+            Line::from(Span::styled(" ● LIVE ", Style::default().fg(theme.running)))
+        } else {
+            Line::from(Span::styled(
+                format!(" ↑ line {}/{} ", self.top + 1, self.total),
+                Style::default().fg(theme.warning),
+            ))
+        }
+    }
+
+    /// Draw a scrollbar on the right border of `area`, if the log doesn't fit.
+    fn render_scrollbar(&self, frame: &mut Frame, area: Rect, color: ratatui::style::Color) {
+        if self.max_scroll == 0 {
+            return;
+        }
+        let mut state = ScrollbarState::new(self.max_scroll + 1).position(self.top);
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None)
+            .style(Style::default().fg(color));
+        frame.render_stateful_widget(
+            scrollbar,
+            area.inner(Margin {
+                vertical: 1,
+                horizontal: 0,
+            }),
+            &mut state,
+        );
+    }
+}
+
+/// How log lines are turned into panel rows: filtering, search highlighting
+/// and wrapping.
+struct LogView<'a> {
+    filter: Option<&'a Pattern>,
+    query: Option<&'a Pattern>,
+    highlight: Style,
+    /// Row width when wrapping long lines
+    wrap_width: Option<usize>,
+}
+
+impl<'a> LogView<'a> {
+    /// View settings for a log panel drawn in `area`.
+    fn new(app: &'a App, area: Rect) -> Self {
+        Self {
+            filter: app.log_filter.as_ref(),
+            query: app.search_query.as_ref(),
+            highlight: app.theme.search,
+            wrap_width: Some(area.width.saturating_sub(2).max(1) as usize).filter(|_| app.wrap_logs),
+        }
+    }
+
+    /// Rows for one log line.
+    fn rows(&self, line: &str) -> Vec<Line<'static>> {
+        let matches = self.query.map(|q| q.find_matches(line)).unwrap_or_default();
+        wrap_highlighted(line, &matches, self.wrap_width, self.highlight)
+    }
+
+    /// Number of `lines` passing the filter.
+    fn shown_count(&self, lines: &[String]) -> usize {
+        shown_line_count(lines, self.filter)
+    }
+
+    /// Up to `height` rows starting at shown line `scroll`, or ending at the
+    /// last one when `follow`ing the log.
+    fn visible_rows(
+        &self,
+        lines: &[String],
+        scroll: usize,
+        follow: bool,
+        height: usize,
+    ) -> Vec<Line<'static>> {
+        match self.filter {
+            Some(_) => self.shown_rows(&shown_lines(lines, self.filter), scroll, follow, height),
+            None => self.shown_rows(lines, scroll, follow, height),
+        }
+    }
+
+    fn shown_rows<S: AsRef<str>>(
+        &self,
+        lines: &[S],
+        scroll: usize,
+        follow: bool,
+        height: usize,
+    ) -> Vec<Line<'static>> {
+        if self.wrap_width.is_none() {
+            return get_visible_lines(lines, scroll, height)
+                .iter()
+                .flat_map(|line| self.rows(line.as_ref()))
+                .collect();
+        }
+
+        if follow {
+            // Walk back from the end until the panel is full
+            let mut rows: Vec<Line<'static>> = Vec::new();
+            for line in lines.iter().rev() {
+                let mut line_rows = self.rows(line.as_ref());
+                line_rows.append(&mut rows);
+                rows = line_rows;
+                if rows.len() >= height {
+                    break;
+                }
+            }
+            let excess = rows.len().saturating_sub(height);
+            rows.split_off(excess)
+        } else {
+            let start = scroll.min(lines.len().saturating_sub(height));
+            let mut rows = Vec::new();
+            for line in &lines[start..] {
+                rows.extend(self.rows(line.as_ref()));
+                if rows.len() >= height {
+                    break;
+                }
+            }
+            rows.truncate(height);
+            rows
+        }
+    }
+}
+
+/// Split a log line into rows of at most `width` chars (one row if `width`
+/// is None), styling the char ranges in `matches`.
+///
+/// Matches are found on the whole line before wrapping, so one cut by a row
+/// boundary is highlighted on both rows.
+fn wrap_highlighted(
+    line: &str,
+    matches: &[Range<usize>],
+    width: Option<usize>,
+    highlight: Style,
+) -> Vec<Line<'static>> {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.is_empty() {
+        return vec![Line::default()];
+    }
+    let width = width.unwrap_or(chars.len()).max(1);
+
+    let mut rows = Vec::new();
+    for row_start in (0..chars.len()).step_by(width) {
+        let row_end = (row_start + width).min(chars.len());
+
+        // Cut the row wherever a match starts or ends inside it
+        let mut cuts = vec![row_start, row_end];
+        for m in matches {
+            cuts.extend([m.start, m.end].into_iter().filter(|&c| c > row_start && c < row_end));
+        }
+        cuts.sort_unstable();
+        cuts.dedup();
+
+        let spans: Vec<Span<'static>> = cuts
+            .windows(2)
+            .map(|w| {
+                let text: String = chars[w[0]..w[1]].iter().collect();
+                if matches.iter().any(|m| m.start <= w[0] && w[1] <= m.end) {
+                    Span::styled(text, highlight)
+                } else {
+                    Span::raw(text)
+                }
+            })
+            .collect();
+        rows.push(Line::from(spans));
+    }
+    rows
+}
+
+/// Format the search results of a panel for its title.
+fn format_match_count(position: Option<(Option<usize>, usize)>) -> String {
+    match position {
+        None => String::new(),
+        Some((Some(current), count)) => format!(" [match {}/{}]", current, count),
+        Some((None, 1)) => " [1 match]".to_string(),
+        Some((None, count)) => format!(" [{} matches]", count),
+    }
+}
+
+/// Alternates every half second, for flashing alerts. The UI redraws every
+/// tick, so this is enough to animate them.
+fn blink_phase() -> bool {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    millis % 1000 < 500
+}
+
+/// Format a panel's line count for its title.
+fn format_line_count(count: usize) -> String {
+    if count == 1 {
+        " [1 line]".to_string()
+    } else {
+        format!(" [{} lines]", count)
+    }
+}
+
+/// Line count for a panel title; `shown` of `total` while filtering.
+fn format_shown_count(app: &App, shown: usize, total: usize) -> String {
+    match app.log_filter {
+        Some(ref filter) => format!(" [grep '{}': {}/{} lines]", filter.text(), shown, total),
+        None => format_line_count(total),
+    }
+}
+
+/// Title extra for a log file that can't be read, e.g. ` [permission denied]`.
+fn format_problem_indicator(problem: Option<&LogProblem>) -> String {
+    problem.map_or(String::new(), |problem| format!(" [{}]", problem.summary()))
+}
+
+/// Build a panel title that fits in `width` columns.
+///
+/// `extras` are appended in order and dropped from the end when there is no
+/// room; if `base` alone is too long it is cut short with an ellipsis.
+fn fit_title(base: &str, extras: &[&str], width: usize) -> String {
+    let mut kept = extras.len();
+    loop {
+        let title: String = std::iter::once(base)
+            .chain(extras[..kept].iter().copied())
+            .collect();
+        if title.chars().count() <= width {
+            return title;
+        }
+        if kept == 0 {
+            break;
+        }
+        kept -= 1;
+    }
+
+    if width == 0 {
+        return String::new();
+    }
+    let mut title: String = base.chars().take(width - 1).collect();
+    title.push('…');
+    title
 }
 
 /// Get visible lines based on scroll position.
-fn get_visible_lines(lines: &[String], scroll_pos: usize, max_height: usize) -> Vec<String> {
+fn get_visible_lines<S>(lines: &[S], scroll_pos: usize, max_height: usize) -> &[S] {
     if lines.is_empty() {
-        return Vec::new();
+        return lines;
     }
 
     let total = lines.len();
@@ -321,5 +1620,195 @@ fn get_visible_lines(lines: &[String], scroll_pos: usize, max_height: usize) ->
     let scroll = scroll_pos.min(max_scroll);
 
     let end = (scroll + max_height).min(total);
-    lines[scroll..end].to_vec()
+    &lines[scroll..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::job_manager::JobInfo;
+    use crate::ui::app::ShownTask;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_format_resource_usage() {
+        let usage = Tres {
+            cpus: 128,
+            mem_mib: 512.0 * 1024.0,
+            gpus: 8,
+        };
+        assert_eq!(format_resource_usage(&usage), "Using 128 CPUs, 8 GPUs, 512.0 GiB");
+        let usage = Tres {
+            cpus: 1,
+            ..Default::default()
+        };
+        assert_eq!(format_resource_usage(&usage), "Using 1 CPU");
+    }
+
+    #[test]
+    fn test_array_task_window_includes_the_shown_task() {
+        let mut job = JobData::new(50);
+        job.info.array_tasks = (0..10)
+            .map(|index| ArrayTask {
+                task_id: index.to_string(),
+                ..Default::default()
+            })
+            .collect();
+        let ids = |tasks: &[ArrayTask]| -> Vec<String> {
+            tasks.iter().map(|task| task.task_id.clone()).collect()
+        };
+
+        let (tasks, hidden) = array_task_window(&job);
+        assert_eq!(ids(tasks), ["0", "1", "2", "3", "4", "5", "6", "7"]);
+        assert_eq!(hidden, 2);
+
+        job.shown_task = Some(ShownTask {
+            task_id: "9".to_string(),
+            stdout_path: PathBuf::from("/logs/50_9.out"),
+            stderr_path: PathBuf::new(),
+        });
+        let (tasks, _) = array_task_window(&job);
+        assert_eq!(ids(tasks), ["2", "3", "4", "5", "6", "7", "8", "9"]);
+    }
+
+    #[test]
+    fn test_dependency_details_annotates_monitored_jobs() {
+        let mut app = App::new();
+        app.update_job_status(123, JobStatus::Failed, JobInfo::default());
+        let dependencies = vec![
+            "afterok:123(unfulfilled)".to_string(),
+            "afterany:999(unfulfilled)".to_string(),
+        ];
+        assert_eq!(
+            dependency_details(&app, &dependencies),
+            "afterok:123 [FAILED], afterany:999"
+        );
+
+        let mut job = JobData::new(124);
+        assert!(!has_monitored_dependency(&app, &job));
+        job.info.dependencies = dependencies;
+        assert!(has_monitored_dependency(&app, &job));
+    }
+
+    #[test]
+    fn test_shown_time_follows_the_display_mode() {
+        let mut app = App::new();
+        assert_eq!(shown_time(&app, "2024-01-31T08:00:00"), "2024-01-31T08:00:00");
+        assert_eq!(shown_time(&app, ""), "N/A");
+
+        app.cycle_time_display_mode();
+        assert_eq!(app.time_display_mode, TimeDisplayMode::Raw);
+        assert_eq!(shown_time(&app, "2024-01-31T08:00:00"), "2024-01-31T08:00:00");
+
+        app.cycle_time_display_mode();
+        assert_eq!(app.time_display_mode, TimeDisplayMode::Relative);
+        assert!(shown_time(&app, "2024-01-31T08:00:00").ends_with(" ago"));
+        assert_eq!(shown_time(&app, "Unknown"), "Unknown");
+
+        app.cycle_time_display_mode();
+        assert_eq!(app.time_display_mode, TimeDisplayMode::Live);
+    }
+
+    #[test]
+    fn test_fit_title_drops_extras_then_truncates() {
+        let extras = [" [42 lines]", " [FOCUSED]"];
+        assert_eq!(
+            fit_title("STDOUT (Job 7)", &extras, 80),
+            "STDOUT (Job 7) [42 lines] [FOCUSED]"
+        );
+        // Not enough room for the focus indicator
+        assert_eq!(fit_title("STDOUT (Job 7)", &extras, 30), "STDOUT (Job 7) [42 lines]");
+        assert_eq!(fit_title("STDOUT (Job 7)", &extras, 14), "STDOUT (Job 7)");
+        assert_eq!(fit_title("STDOUT (Job 7)", &extras, 8), "STDOUT …");
+        assert_eq!(fit_title("STDOUT (Job 7)", &extras, 0), "");
+    }
+
+    #[test]
+    fn test_truncate_with_ellipsis_on_char_boundaries() {
+        assert_eq!(truncate_with_ellipsis("short", 20), "short");
+        assert_eq!(truncate_with_ellipsis("exactly_twenty_chars", 20), "exactly_twenty_chars");
+        assert_eq!(truncate_with_ellipsis("a_rather_long_job_name", 20), "a_rather_long_job...");
+
+        // Multi-byte names longer than the limit must not panic
+        let cjk = "訓練ジョブ_深層学習モデル_バッチ処理_最終版";
+        let truncated = truncate_with_ellipsis(cjk, 20);
+        assert_eq!(truncated.chars().count(), 20);
+        assert!(truncated.starts_with("訓練ジョブ") && truncated.ends_with("..."));
+
+        let emoji = "🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀🚀";
+        assert_eq!(truncate_with_ellipsis(emoji, 20), format!("{}...", "🚀".repeat(17)));
+    }
+
+    #[test]
+    fn test_highlight_survives_wrapping() {
+        let highlight = Style::default().bg(ratatui::style::Color::Yellow);
+        let line = "epoch 3: CUDA error, retrying";
+        let matches = Pattern::new("cuda error").find_matches(line);
+        assert_eq!(matches, vec![9..19]);
+
+        // Width 12 wraps after "epoch 3: CUD"
+        let rows = wrap_highlighted(line, &matches, Some(12), highlight);
+        let texts: Vec<Vec<(String, bool)>> = rows
+            .iter()
+            .map(|row| {
+                row.spans
+                    .iter()
+                    .map(|span| (span.content.to_string(), span.style == highlight))
+                    .collect()
+            })
+            .collect();
+        assert_eq!(
+            texts,
+            vec![
+                vec![("epoch 3: ".to_string(), false), ("CUD".to_string(), true)],
+                vec![("A error".to_string(), true), (", ret".to_string(), false)],
+                vec![("rying".to_string(), false)],
+            ]
+        );
+
+        // Without wrapping it's one row
+        let rows = wrap_highlighted(line, &matches, None, highlight);
+        assert_eq!(rows.len(), 1);
+        assert_eq!(rows[0].spans.len(), 3);
+        assert_eq!(wrap_highlighted("", &[], Some(5), highlight), vec![Line::default()]);
+    }
+
+    #[test]
+    fn test_wrapped_rows_follow_the_end_of_the_log() {
+        let mut app = App::new();
+        app.wrap_logs = true;
+        let view = LogView::new(&app, Rect::new(0, 0, 7, 5));
+        let lines: Vec<String> = ["one", "a long line", "two"].iter().map(|s| s.to_string()).collect();
+
+        let row_text = |rows: Vec<Line>| -> Vec<String> {
+            rows.iter().map(|row| row.to_string()).collect()
+        };
+        // 5-column rows: "a long line" takes three, so only its tail fits
+        assert_eq!(row_text(view.visible_rows(&lines, 0, true, 3)), ["g lin", "e", "two"]);
+        // Scrolled to the top, rows start at the first line
+        assert_eq!(row_text(view.visible_rows(&lines, 0, false, 3)), ["one", "a lon", "g lin"]);
+    }
+
+    #[test]
+    fn test_log_position() {
+        // Following: pinned to the last page whatever the stored scroll
+        let live = LogPosition::new(100, 3, 20, false);
+        assert_eq!((live.top, live.max_scroll, live.live), (80, 80, true));
+
+        let scrolled = LogPosition::new(100, 10, 20, true);
+        assert_eq!((scrolled.top, scrolled.live), (10, false));
+        assert_eq!(scrolled.marker(&Theme::default()).to_string(), " ↑ line 11/100 ");
+        assert_eq!(LogPosition::new(100, 500, 20, true).top, 80);
+
+        // Everything fits: nothing to scroll
+        assert_eq!(LogPosition::new(5, 0, 20, true).max_scroll, 0);
+        assert_eq!(live.marker(&Theme::default()).to_string(), " ● LIVE ");
+    }
+
+    #[test]
+    fn test_format_line_count() {
+        assert_eq!(format_line_count(0), " [0 lines]");
+        assert_eq!(format_line_count(1), " [1 line]");
+        assert_eq!(format_line_count(1234), " [1234 lines]");
+    }
 }
@@ -0,0 +1,227 @@
+//! Case-insensitive search in log lines.
+
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+
+/// Prefix marking a search or filter as a regular expression.
+pub const REGEX_PREFIX: &str = "re:";
+
+/// A search query or filter, compiled once when it is entered.
+///
+/// Plain text matches as a case-insensitive substring. Text starting with
+/// `re:` is a case-insensitive regular expression; if it doesn't compile, the
+/// error is kept for display and the expression is matched literally instead.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    text: String,
+    regex: Option<Regex>,
+    error: Option<String>,
+}
+
+impl Pattern {
+    pub fn new(text: &str) -> Self {
+        let (regex, error) = match text.strip_prefix(REGEX_PREFIX) {
+            Some(expr) => match RegexBuilder::new(expr).case_insensitive(true).build() {
+                Ok(regex) => (Some(regex), None),
+                Err(e) => (None, Some(regex_error(&e))),
+            },
+            None => (None, None),
+        };
+        Self {
+            text: text.to_string(),
+            regex,
+            error,
+        }
+    }
+
+    /// The pattern as typed, including any `re:` prefix.
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Why a `re:` pattern fell back to literal matching.
+    pub fn error(&self) -> Option<&str> {
+        self.error.as_deref()
+    }
+
+    /// Char ranges of each match in `line`, for highlighting. Empty regex
+    /// matches (e.g. `^`) are left out.
+    pub fn find_matches(&self, line: &str) -> Vec<Range<usize>> {
+        match self.regex {
+            Some(ref regex) => {
+                let mut chars = CharOffsets::new(line);
+                regex
+                    .find_iter(line)
+                    .filter(|m| !m.is_empty())
+                    .map(|m| chars.at(m.start())..chars.at(m.end()))
+                    .collect()
+            }
+            None => find_matches(line, self.literal()),
+        }
+    }
+
+    /// Whether `line` contains a match.
+    pub fn is_match(&self, line: &str) -> bool {
+        match self.regex {
+            Some(ref regex) => regex.is_match(line),
+            None => line_matches(line, self.literal()),
+        }
+    }
+
+    /// Text matched when there is no regex. A `re:` pattern that failed to
+    /// compile is matched without its prefix.
+    fn literal(&self) -> &str {
+        match self.error {
+            Some(_) => self.text.strip_prefix(REGEX_PREFIX).unwrap_or(&self.text),
+            None => &self.text,
+        }
+    }
+}
+
+/// The regex crate's error, reduced to its last line (the others draw the
+/// expression with a caret under the problem, which doesn't fit one line).
+fn regex_error(error: &regex::Error) -> String {
+    let message = error.to_string();
+    message
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .unwrap_or(&message)
+        .trim_start_matches("error: ")
+        .to_string()
+}
+
+/// Converts increasing byte offsets into a line to char offsets.
+struct CharOffsets<'a> {
+    line: &'a str,
+    byte: usize,
+    chars: usize,
+}
+
+impl<'a> CharOffsets<'a> {
+    fn new(line: &'a str) -> Self {
+        Self {
+            line,
+            byte: 0,
+            chars: 0,
+        }
+    }
+
+    /// Char offset of `byte`, which must not be before the last one asked for.
+    fn at(&mut self, byte: usize) -> usize {
+        self.chars += self.line[self.byte..byte].chars().count();
+        self.byte = byte;
+        self.chars
+    }
+}
+
+/// Char ranges of each case-insensitive occurrence of `query` in `line`.
+///
+/// Ranges count chars rather than bytes so they map directly onto panel
+/// columns. Occurrences don't overlap.
+pub fn find_matches(line: &str, query: &str) -> Vec<Range<usize>> {
+    let query: Vec<char> = query.chars().map(fold_case).collect();
+    if query.is_empty() {
+        return Vec::new();
+    }
+
+    let chars: Vec<char> = line.chars().map(fold_case).collect();
+    let mut matches = Vec::new();
+    let mut start = 0;
+    while start + query.len() <= chars.len() {
+        if chars[start..start + query.len()] == query[..] {
+            matches.push(start..start + query.len());
+            start += query.len();
+        } else {
+            start += 1;
+        }
+    }
+    matches
+}
+
+/// Whether `line` contains `query`, ignoring case.
+pub fn line_matches(line: &str, query: &str) -> bool {
+    !find_matches(line, query).is_empty()
+}
+
+/// The lines of a log panel that are shown: all of `lines`, or only those
+/// matching `filter`.
+pub fn shown_lines<'a>(lines: &'a [String], filter: Option<&Pattern>) -> Vec<&'a str> {
+    let lines = lines.iter().map(String::as_str);
+    match filter {
+        Some(filter) => lines.filter(|line| filter.is_match(line)).collect(),
+        None => lines.collect(),
+    }
+}
+
+/// Number of lines `shown_lines` would return, without collecting them.
+pub fn shown_line_count(lines: &[String], filter: Option<&Pattern>) -> usize {
+    match filter {
+        Some(filter) => lines.iter().filter(|line| filter.is_match(line)).count(),
+        None => lines.len(),
+    }
+}
+
+/// Lowercase a char, keeping one char per char so offsets don't shift.
+fn fold_case(c: char) -> char {
+    c.to_lowercase().next().unwrap_or(c)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_matches() {
+        assert_eq!(find_matches("Error: disk error", "error"), vec![0..5, 12..17]);
+        assert_eq!(find_matches("aaaa", "aa"), vec![0..2, 2..4]);
+        assert!(find_matches("no match", "xyz").is_empty());
+        assert!(find_matches("anything", "").is_empty());
+        // Offsets are in chars, not bytes
+        assert_eq!(find_matches("損失 LOSS=0.1", "loss"), vec![3..7]);
+        assert!(line_matches("CUDA out of memory", "Out Of"));
+    }
+
+    #[test]
+    fn test_shown_lines() {
+        let lines: Vec<String> = ["epoch 1", "Loss 0.5", "epoch 2", "loss 0.3"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let loss = Pattern::new("LOSS");
+        assert_eq!(shown_lines(&lines, None).len(), 4);
+        assert_eq!(shown_lines(&lines, Some(&loss)), ["Loss 0.5", "loss 0.3"]);
+        assert_eq!(shown_line_count(&lines, Some(&loss)), 2);
+        assert_eq!(shown_line_count(&lines, Some(&Pattern::new("accuracy"))), 0);
+        assert_eq!(shown_line_count(&lines, None), 4);
+        assert_eq!(shown_lines(&lines, Some(&Pattern::new("re:^epoch \\d$"))), ["epoch 1", "epoch 2"]);
+    }
+
+    #[test]
+    fn test_regex_pattern() {
+        let pattern = Pattern::new("re:loss=[0-9.]+");
+        assert_eq!(pattern.error(), None);
+        assert!(pattern.is_match("step 3 LOSS=0.25"));
+        assert!(!pattern.is_match("step 3 loss=nan"));
+        // Offsets are in chars, not bytes
+        assert_eq!(pattern.find_matches("損失 loss=0.1, loss=0.2"), vec![3..11, 13..21]);
+        // Empty matches aren't highlighted but still count as matching
+        let anchor = Pattern::new("re:^");
+        assert!(anchor.find_matches("anything").is_empty());
+        assert!(anchor.is_match("anything"));
+
+        // Plain text is never a regex
+        assert!(Pattern::new("a.c").find_matches("abc").is_empty());
+        assert_eq!(Pattern::new("a.c").find_matches("A.C"), vec![0..3]);
+    }
+
+    #[test]
+    fn test_invalid_regex_falls_back_to_literal() {
+        let pattern = Pattern::new("re:step (");
+        assert!(pattern.error().unwrap().contains("unclosed group"));
+        assert!(!pattern.error().unwrap().contains('\n'));
+        assert_eq!(pattern.text(), "re:step (");
+        assert_eq!(pattern.find_matches("Step (1/3)"), vec![0..6]);
+        assert!(!pattern.is_match("step 1"));
+    }
+}
@@ -0,0 +1,218 @@
+//! Color themes for the TUI.
+
+use crate::config::StatusColors;
+use crate::utils::JobStatus;
+use clap::ValueEnum;
+use ratatui::style::{Color, Modifier, Style};
+use serde::Deserialize;
+
+/// Built-in theme presets, selected with `--theme` or `theme` in the config file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    /// Colors for dark terminal backgrounds
+    #[default]
+    Dark,
+    /// Colors for light terminal backgrounds
+    Light,
+    /// No colors, only bold and reverse video
+    Mono,
+}
+
+/// Colors used throughout the UI.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    /// Header title, overlay and job ID column
+    pub title: Color,
+    /// Header and summary panel borders
+    pub border: Color,
+    /// Job list and diagnostics borders
+    pub panel_border: Color,
+    /// Table column headers and the GPU panel border
+    pub table_header: Color,
+    /// Selected row in the job list
+    pub selected: Style,
+    /// Help text, details and unfocused panels
+    pub muted: Color,
+    /// Header messages and diagnostics warnings
+    pub warning: Color,
+    /// Focused stdout panel
+    pub stdout: Color,
+    /// Focused stderr panel and the unread stderr marker
+    pub stderr: Color,
+    /// Flashing title of a stderr panel with unread output
+    pub alert: Style,
+    /// Log text matching the search
+    pub search: Style,
+    /// GPU bars at high, medium and low utilization
+    pub gpu_high: Color,
+    pub gpu_medium: Color,
+    pub gpu_low: Color,
+    pub queued: Color,
+    pub running: Color,
+    pub suspended: Color,
+    pub completed: Color,
+    pub cancelled: Color,
+    pub timeout: Color,
+    pub oom: Color,
+    pub failed: Color,
+    pub unknown: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::dark()
+    }
+}
+
+impl Theme {
+    /// Look up a preset.
+    pub fn named(name: ThemeName) -> Self {
+        match name {
+            ThemeName::Dark => Self::dark(),
+            ThemeName::Light => Self::light(),
+            ThemeName::Mono => Self::mono(),
+        }
+    }
+
+    /// The original color scheme, for dark backgrounds.
+    pub fn dark() -> Self {
+        Self {
+            title: Color::Cyan,
+            border: Color::Blue,
+            panel_border: Color::Yellow,
+            table_header: Color::Magenta,
+            selected: Style::default().bg(Color::DarkGray).add_modifier(Modifier::BOLD),
+            muted: Color::DarkGray,
+            warning: Color::Yellow,
+            stdout: Color::LightGreen,
+            stderr: Color::LightRed,
+            alert: Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            search: Style::default().fg(Color::Black).bg(Color::Yellow),
+            gpu_high: Color::Green,
+            gpu_medium: Color::Yellow,
+            gpu_low: Color::Red,
+            queued: Color::Yellow,
+            running: Color::Green,
+            suspended: Color::LightYellow,
+            completed: Color::Blue,
+            cancelled: Color::Rgb(255, 165, 0),
+            timeout: Color::LightRed,
+            oom: Color::Magenta,
+            failed: Color::Red,
+            unknown: Color::White,
+        }
+    }
+
+    /// Darker colors that stay readable on light backgrounds.
+    pub fn light() -> Self {
+        let amber = Color::Rgb(175, 95, 0);
+        Self {
+            title: Color::Blue,
+            border: Color::Blue,
+            panel_border: Color::Magenta,
+            table_header: Color::Magenta,
+            selected: Style::default().bg(Color::Gray).add_modifier(Modifier::BOLD),
+            muted: Color::DarkGray,
+            warning: amber,
+            stdout: Color::Green,
+            stderr: Color::Red,
+            alert: Style::default().fg(Color::White).bg(Color::Red).add_modifier(Modifier::BOLD),
+            search: Style::default().fg(Color::Black).bg(Color::LightYellow),
+            gpu_high: Color::Green,
+            gpu_medium: amber,
+            gpu_low: Color::Red,
+            queued: amber,
+            running: Color::Green,
+            suspended: Color::Rgb(135, 95, 0),
+            completed: Color::Blue,
+            cancelled: Color::Rgb(200, 90, 0),
+            timeout: Color::Red,
+            oom: Color::Magenta,
+            failed: Color::Red,
+            unknown: Color::Black,
+        }
+    }
+
+    /// The terminal's default colors, for terminals with limited color support.
+    pub fn mono() -> Self {
+        Self {
+            title: Color::Reset,
+            border: Color::Reset,
+            panel_border: Color::Reset,
+            table_header: Color::Reset,
+            selected: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            muted: Color::Reset,
+            warning: Color::Reset,
+            stdout: Color::Reset,
+            stderr: Color::Reset,
+            alert: Style::default().add_modifier(Modifier::REVERSED | Modifier::BOLD),
+            search: Style::default().add_modifier(Modifier::REVERSED),
+            gpu_high: Color::Reset,
+            gpu_medium: Color::Reset,
+            gpu_low: Color::Reset,
+            queued: Color::Reset,
+            running: Color::Reset,
+            suspended: Color::Reset,
+            completed: Color::Reset,
+            cancelled: Color::Reset,
+            timeout: Color::Reset,
+            oom: Color::Reset,
+            failed: Color::Reset,
+            unknown: Color::Reset,
+        }
+    }
+
+    /// Replace status colors with the ones set in the config file.
+    pub fn with_status_colors(mut self, colors: &StatusColors) -> Self {
+        for (slot, color) in [
+            (&mut self.queued, colors.queued),
+            (&mut self.running, colors.running),
+            (&mut self.suspended, colors.suspended),
+            (&mut self.completed, colors.completed),
+            (&mut self.cancelled, colors.cancelled),
+            (&mut self.timeout, colors.timeout),
+            (&mut self.oom, colors.oom),
+            (&mut self.failed, colors.failed),
+            (&mut self.unknown, colors.unknown),
+        ] {
+            if let Some(color) = color {
+                *slot = color;
+            }
+        }
+        self
+    }
+
+    /// Color of a job status.
+    pub fn status(&self, status: JobStatus) -> Color {
+        match status {
+            JobStatus::Queued => self.queued,
+            JobStatus::Running => self.running,
+            JobStatus::Suspended => self.suspended,
+            JobStatus::Completed => self.completed,
+            JobStatus::Cancelled => self.cancelled,
+            JobStatus::Timeout => self.timeout,
+            JobStatus::OutOfMemory => self.oom,
+            JobStatus::Failed => self.failed,
+            JobStatus::Unknown => self.unknown,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_status_color_overrides() {
+        let colors = StatusColors {
+            running: Some(Color::Cyan),
+            ..Default::default()
+        };
+        let theme = Theme::named(ThemeName::Light).with_status_colors(&colors);
+        assert_eq!(theme.status(JobStatus::Running), Color::Cyan);
+        assert_eq!(theme.status(JobStatus::Failed), Theme::light().failed);
+
+        assert_eq!(Theme::mono().status(JobStatus::Failed), Color::Reset);
+    }
+}
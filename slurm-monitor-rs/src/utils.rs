@@ -1,12 +1,116 @@
 //! Utility functions for SLURM command execution and output parsing.
 
+use crate::diagnostics;
 use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone};
 use regex::Regex;
+use std::collections::hash_map::RandomState;
 use std::collections::HashMap;
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
 use std::process::Command;
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
 
-/// Result of running a SLURM command
+/// Field separator sacct is asked for with `SACCT_DELIMITER_ARG`. Unlike
+/// sacct's default `|`, it can't turn up in job names, paths or comments.
+pub const SACCT_DELIMITER: char = '\x1f';
+
+/// The sacct option selecting `SACCT_DELIMITER`, passed along with
+/// `--parsable2`.
+pub const SACCT_DELIMITER_ARG: &str = "--delimiter=\x1f";
+
+/// Most SLURM commands running at once unless configured otherwise.
+pub const DEFAULT_MAX_SLURM_COMMANDS: usize = 4;
+
+/// Limits on how fast SLURM commands are run, so that polling many jobs
+/// doesn't hammer the controller with bursts of squeue and sacct calls.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Throttle {
+    /// Least time between the starts of two commands
+    pub delay: Duration,
+    /// Up to this much is added to each delay at random, so that several
+    /// monitors don't poll in step
+    pub jitter: Duration,
+    /// Most commands running at once
+    pub max_concurrent: usize,
+}
+
+impl Default for Throttle {
+    fn default() -> Self {
+        Self {
+            delay: Duration::ZERO,
+            jitter: Duration::ZERO,
+            max_concurrent: DEFAULT_MAX_SLURM_COMMANDS,
+        }
+    }
+}
+
+/// Holds commands back until they may start under a `Throttle`.
 #[derive(Debug)]
+struct Limiter {
+    throttle: Throttle,
+    state: Mutex<LimiterState>,
+    /// Signalled when a running command finishes
+    finished: Condvar,
+}
+
+#[derive(Debug, Default)]
+struct LimiterState {
+    running: usize,
+    /// Earliest time the next command may start
+    next_start: Option<Instant>,
+}
+
+/// A command's place under the limit, given back when dropped.
+struct Permit<'a>(&'a Limiter);
+
+impl Limiter {
+    fn new(throttle: Throttle) -> Self {
+        Self {
+            throttle,
+            state: Mutex::new(LimiterState::default()),
+            finished: Condvar::new(),
+        }
+    }
+
+    /// Wait until a command may start: there is room under the concurrency
+    /// cap and the delay since the previous start has passed.
+    fn acquire(&self) -> Permit<'_> {
+        let mut state = self.state.lock().unwrap();
+        while state.running >= self.throttle.max_concurrent.max(1) {
+            state = self.finished.wait(state).unwrap();
+        }
+        state.running += 1;
+
+        let now = Instant::now();
+        let start = state.next_start.map_or(now, |next| next.max(now));
+        let jitter = self.throttle.jitter.mul_f64(random_fraction());
+        state.next_start = Some(start + self.throttle.delay + jitter);
+        drop(state);
+
+        thread::sleep(start - now);
+        Permit(self)
+    }
+}
+
+impl Drop for Permit<'_> {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().running -= 1;
+        self.0.finished.notify_one();
+    }
+}
+
+/// A pseudo-random number in `[0, 1]`, good enough to spread out polls.
+fn random_fraction() -> f64 {
+    // Each RandomState is seeded differently
+    RandomState::new().build_hasher().finish() as f64 / u64::MAX as f64
+}
+
+/// Result of running a SLURM command
+#[derive(Debug, Clone, Default)]
 pub struct CommandResult {
     pub stdout: String,
     pub stderr: String,
@@ -24,19 +128,37 @@ pub fn run_slurm_command(cmd: &[&str], check: bool) -> Result<CommandResult> {
 }
 
 /// Execute a SLURM command with custom timeout.
-pub fn run_slurm_command_with_timeout(
-    cmd: &[&str],
+///
+/// Arguments are passed to the process as they are, so paths that aren't
+/// valid UTF-8 work too. The command isn't throttled; go through a
+/// `SystemRunner` for that.
+pub fn run_slurm_command_with_timeout<S: AsRef<OsStr>>(
+    cmd: &[S],
     check: bool,
     _timeout_secs: u64,
+) -> Result<CommandResult> {
+    run_process(cmd, &[], check)
+}
+
+/// Run `cmd` as a child process with `env` added to the inherited
+/// environment, waiting for it to finish.
+fn run_process<S: AsRef<OsStr>>(
+    cmd: &[S],
+    env: &[(String, OsString)],
+    check: bool,
 ) -> Result<CommandResult> {
     if cmd.is_empty() {
         anyhow::bail!("Empty command");
     }
+    let program = cmd[0].as_ref().to_string_lossy();
 
-    let output = Command::new(cmd[0])
-        .args(&cmd[1..])
-        .output()
-        .with_context(|| format!("Failed to execute command: {}", cmd[0]))?;
+    let output = match slurm_command(cmd, env).output() {
+        Ok(output) => output,
+        Err(e) => {
+            diagnostics::warn(format!("Failed to execute {}: {}", command_line(cmd), e));
+            return Err(e).with_context(|| format!("Failed to execute command: {}", program));
+        }
+    };
 
     let result = CommandResult {
         stdout: String::from_utf8_lossy(&output.stdout).to_string(),
@@ -44,10 +166,19 @@ pub fn run_slurm_command_with_timeout(
         return_code: output.status.code().unwrap_or(-1),
     };
 
+    if result.return_code != 0 {
+        diagnostics::debug(format!(
+            "{} exited with code {}: {}",
+            command_line(cmd),
+            result.return_code,
+            result.stderr.trim()
+        ));
+    }
+
     if check && result.return_code != 0 {
         anyhow::bail!(
-            "Command {:?} failed with code {}: {}",
-            cmd,
+            "Command '{}' failed with code {}: {}",
+            command_line(cmd),
             result.return_code,
             result.stderr
         );
@@ -56,6 +187,249 @@ pub fn run_slurm_command_with_timeout(
     Ok(result)
 }
 
+/// Build the process for `cmd` (which must not be empty) with `env` added
+/// to the inherited environment.
+fn slurm_command<S: AsRef<OsStr>>(cmd: &[S], env: &[(String, OsString)]) -> Command {
+    let mut command = Command::new(&cmd[0]);
+    command.args(&cmd[1..]);
+    command.envs(env.iter().map(|(key, value)| (key, value)));
+    command
+}
+
+/// `cmd` joined with spaces for messages, with invalid UTF-8 replaced.
+fn command_line<S: AsRef<OsStr>>(cmd: &[S]) -> String {
+    cmd.iter()
+        .map(|arg| arg.as_ref().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// `cmd` as it would be typed in a shell, quoting arguments that need it, so
+/// it can be copied and run as it is.
+pub fn shell_words<S: AsRef<OsStr>>(cmd: &[S]) -> String {
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "_-./=:,+@%".contains(c);
+    cmd.iter()
+        .map(|arg| {
+            let arg = arg.as_ref().to_string_lossy();
+            if !arg.is_empty() && arg.chars().all(is_plain) {
+                arg.into_owned()
+            } else {
+                format!("'{}'", arg.replace('\'', "'\\''"))
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Runs SLURM commands on behalf of `JobManager`.
+///
+/// `SystemRunner` executes them for real; tests substitute canned output so
+/// the parsing and status logic can be exercised without a cluster.
+pub trait CommandRunner: fmt::Debug + Send + Sync {
+    /// Run `cmd`, with the same semantics as `run_slurm_command`.
+    fn run(&self, cmd: &[&str], check: bool) -> Result<CommandResult> {
+        let cmd: Vec<&OsStr> = cmd.iter().map(OsStr::new).collect();
+        self.run_os(&cmd, check)
+    }
+
+    /// Like `run`, for arguments that may not be valid UTF-8 such as paths.
+    fn run_os(&self, cmd: &[&OsStr], check: bool) -> Result<CommandResult>;
+
+    /// Build the process for `cmd` (which must not be empty) to run in the
+    /// foreground, e.g. attached to the terminal.
+    fn interactive_command(&self, cmd: &[&str]) -> Command {
+        slurm_command(cmd, &[])
+    }
+}
+
+/// Runs commands as child processes.
+///
+/// Clones share one throttle, so every thread of the monitor together stays
+/// within it.
+#[derive(Debug, Clone)]
+pub struct SystemRunner {
+    /// Environment variables set on every command, e.g. `SLURM_CONF`
+    env: Vec<(String, OsString)>,
+    limiter: Arc<Limiter>,
+}
+
+impl SystemRunner {
+    /// Run every command with `env` set on top of the monitor's own
+    /// environment, starting commands no faster than `throttle` allows.
+    pub fn new(env: Vec<(String, OsString)>, throttle: Throttle) -> Self {
+        Self {
+            env,
+            limiter: Arc::new(Limiter::new(throttle)),
+        }
+    }
+
+    /// A runner with the same environment that doesn't wait behind this
+    /// one's throttle, for the commands a user is waiting on.
+    pub fn unthrottled(&self) -> Self {
+        Self::new(self.env.clone(), Throttle::default())
+    }
+}
+
+impl Default for SystemRunner {
+    fn default() -> Self {
+        Self::new(Vec::new(), Throttle::default())
+    }
+}
+
+impl CommandRunner for SystemRunner {
+    fn run_os(&self, cmd: &[&OsStr], check: bool) -> Result<CommandResult> {
+        let _permit = self.limiter.acquire();
+        run_process(cmd, &self.env, check)
+    }
+
+    fn interactive_command(&self, cmd: &[&str]) -> Command {
+        slurm_command(cmd, &self.env)
+    }
+}
+
+/// Command runner returning canned output, for tests.
+///
+/// Each response is keyed by a command prefix such as `"squeue -j 42"`; the
+/// longest matching prefix wins. Unmatched commands exit with code 1 and no
+/// output, like SLURM tools asked about unknown jobs.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub(crate) struct FakeRunner {
+    responses: Vec<(String, CommandResult)>,
+    calls: std::sync::Mutex<Vec<String>>,
+}
+
+#[cfg(test)]
+impl FakeRunner {
+    /// Respond to commands starting with `prefix` with `stdout` and exit code 0.
+    pub(crate) fn respond(mut self, prefix: &str, stdout: &str) -> Self {
+        self.responses.push((
+            prefix.to_string(),
+            CommandResult {
+                stdout: stdout.to_string(),
+                ..Default::default()
+            },
+        ));
+        self
+    }
+
+    /// Respond to commands starting with `prefix` with `stderr` and exit code 1.
+    pub(crate) fn fail(mut self, prefix: &str, stderr: &str) -> Self {
+        self.responses.push((
+            prefix.to_string(),
+            CommandResult {
+                stderr: stderr.to_string(),
+                return_code: 1,
+                ..Default::default()
+            },
+        ));
+        self
+    }
+
+    /// Commands run so far, joined with spaces (invalid UTF-8 replaced).
+    pub(crate) fn calls(&self) -> Vec<String> {
+        self.calls.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+impl CommandRunner for FakeRunner {
+    fn run_os(&self, cmd: &[&OsStr], check: bool) -> Result<CommandResult> {
+        let line = command_line(cmd);
+        self.calls.lock().unwrap().push(line.clone());
+
+        let result = self
+            .responses
+            .iter()
+            .filter(|(prefix, _)| line.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map(|(_, result)| result.clone())
+            .unwrap_or(CommandResult {
+                return_code: 1,
+                ..Default::default()
+            });
+
+        if check && result.return_code != 0 {
+            anyhow::bail!("Command '{}' failed with code {}", line, result.return_code);
+        }
+        Ok(result)
+    }
+}
+
+/// Parse a SLURM memory size such as `1234K` or `4.5G` into MiB.
+///
+/// `default_unit` applies when the value has no suffix.
+pub fn parse_slurm_memory(value: &str, default_unit: char) -> Option<f64> {
+    let value = value.trim();
+    let (number, unit) = match value.chars().last()? {
+        c if c.is_ascii_alphabetic() => (&value[..value.len() - 1], c.to_ascii_uppercase()),
+        _ => (value, default_unit),
+    };
+
+    let number: f64 = number.parse().ok()?;
+    let scale = match unit {
+        'K' => 1.0 / 1024.0,
+        'M' => 1.0,
+        'G' => 1024.0,
+        'T' => 1024.0 * 1024.0,
+        'P' => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some(number * scale)
+}
+
+/// Resources allocated to a job, from a TRES (trackable resources) string.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Tres {
+    pub cpus: u64,
+    pub mem_mib: f64,
+    pub gpus: u64,
+}
+
+impl std::ops::AddAssign for Tres {
+    fn add_assign(&mut self, other: Self) {
+        self.cpus += other.cpus;
+        self.mem_mib += other.mem_mib;
+        self.gpus += other.gpus;
+    }
+}
+
+/// Parse a TRES string such as `cpu=16,mem=64G,node=1,gres/gpu=2` from
+/// sacct's AllocTRES or scontrol's TRES. None if it is empty, e.g. for a job
+/// that hasn't started.
+///
+/// SLURM lists typed GPUs (`gres/gpu:a100=2`) next to the total
+/// (`gres/gpu=2`); typed counts are only added up when there is no total.
+pub fn parse_tres(value: &str) -> Option<Tres> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+    let mut tres = Tres::default();
+    let mut gpus = None;
+    let mut typed_gpus = 0;
+    for (key, amount) in value.split(',').filter_map(|entry| entry.split_once('=')) {
+        match key.trim() {
+            "cpu" => tres.cpus = amount.parse().unwrap_or(0),
+            "mem" => tres.mem_mib = parse_slurm_memory(amount, 'M').unwrap_or(0.0),
+            "gres/gpu" => gpus = amount.parse().ok(),
+            key if key.starts_with("gres/gpu:") => typed_gpus += amount.parse().unwrap_or(0),
+            _ => {}
+        }
+    }
+    tres.gpus = gpus.unwrap_or(typed_gpus);
+    Some(tres)
+}
+
+/// Format a memory size in MiB, switching to GiB from 1 GiB up.
+pub fn format_mib(mib: f64) -> String {
+    if mib < 1024.0 {
+        format!("{:.0} MiB", mib)
+    } else {
+        format!("{:.1} GiB", mib / 1024.0)
+    }
+}
+
 /// Parse job ID from sbatch output.
 ///
 /// Typical sbatch output: "Submitted batch job 12345"
@@ -67,7 +441,6 @@ pub fn parse_job_id(sbatch_output: &str) -> Option<u64> {
 }
 
 /// Parse squeue output for a single job.
-#[allow(dead_code)]
 pub fn parse_squeue_output(output: &str) -> HashMap<String, String> {
     let mut result = HashMap::new();
     let lines: Vec<&str> = output.trim().lines().collect();
@@ -92,7 +465,8 @@ pub fn parse_squeue_output(output: &str) -> HashMap<String, String> {
 
 /// Parse sacct output for job information.
 /// Handles multiple rows (main job + batch step) by merging data,
-/// preferring non-empty values from batch steps for StdOut/StdErr.
+/// preferring non-empty values from batch steps for StdOut/StdErr, and the
+/// batch step's ExitCode (the script's own exit code) over the job's.
 pub fn parse_sacct_output(output: &str) -> HashMap<String, String> {
     let mut result = HashMap::new();
     let lines: Vec<&str> = output
@@ -106,23 +480,32 @@ pub fn parse_sacct_output(output: &str) -> HashMap<String, String> {
     }
 
     // Parse header
-    let header: Vec<&str> = lines[0].split('|').collect();
+    let delimiter = sacct_delimiter(lines[0]);
+    let header = sacct_header(lines[0], delimiter);
     
     // First pass: populate from main job line (lines[1])
-    let data: Vec<&str> = lines[1].split('|').collect();
+    let data = sacct_row(lines[1], &header, delimiter);
     for (i, field) in header.iter().enumerate() {
         if i < data.len() {
-            result.insert(field.trim().to_string(), data[i].trim().to_string());
+            result.insert(field.to_string(), data[i].clone());
         }
     }
 
     // Second pass: look for StdOut/StdErr in subsequent lines (batch steps like .0, .batch)
     // These fields are often only populated on the batch step, not the main job
+    let job_id_column = header.iter().position(|&field| field == "JobID");
     for line in lines.iter().skip(2) {
-        let data: Vec<&str> = line.split('|').collect();
+        let data = sacct_row(line, &header, delimiter);
+        let is_batch_step = job_id_column
+            .and_then(|i| data.get(i))
+            .is_some_and(|id| id.ends_with(".batch"));
         for (i, field) in header.iter().enumerate() {
-            let field_name = field.trim();
-            if (field_name == "StdOut" || field_name == "StdErr") && i < data.len() {
+            let field_name = *field;
+            // Other steps (e.g. .extern) report their own, usually 0:0
+            let preferred = field_name == "StdOut"
+                || field_name == "StdErr"
+                || (field_name == "ExitCode" && is_batch_step);
+            if preferred && i < data.len() {
                 let value = data[i].trim();
                 if !value.is_empty() {
                     // Found a non-empty StdOut/StdErr, use it
@@ -135,8 +518,77 @@ pub fn parse_sacct_output(output: &str) -> HashMap<String, String> {
     result
 }
 
+/// The field separator of a line of `--parsable` or `--parsable2` output:
+/// `SACCT_DELIMITER` if sacct was asked for it, otherwise the default `|`.
+pub(crate) fn sacct_delimiter(line: &str) -> char {
+    if line.contains(SACCT_DELIMITER) {
+        SACCT_DELIMITER
+    } else {
+        '|'
+    }
+}
+
+/// Column names from the header line of `--parsable` or `--parsable2` output.
+fn sacct_header(line: &str, delimiter: char) -> Vec<&str> {
+    let line = line.trim();
+    // --parsable ends every line with a delimiter
+    let line = line.strip_suffix(delimiter).unwrap_or(line);
+    line.split(delimiter).map(str::trim).collect()
+}
+
+/// Split a row of `--parsable` or `--parsable2` output into the columns of
+/// `header`, trimmed.
+///
+/// The trailing delimiter `--parsable` adds is dropped. With the default `|`,
+/// any `|` beyond the column count belongs to the last column, which is why
+/// free-text columns such as Comment go last in the formats requested. Rows
+/// with fewer pieces than columns are returned short.
+fn sacct_row(line: &str, header: &[&str], delimiter: char) -> Vec<String> {
+    let mut line = line.trim_end();
+    if line.matches(delimiter).count() >= header.len() {
+        line = line.strip_suffix(delimiter).unwrap_or(line);
+    }
+    line.splitn(header.len().max(1), delimiter)
+        .map(|piece| piece.trim().to_string())
+        .collect()
+}
+
+/// Parse the `Key=Value` fields of `scontrol show job` output.
+///
+/// scontrol separates fields with spaces and newlines. A value containing
+/// spaces (e.g. a `Command` with arguments) continues until the next token
+/// that looks like a field name followed by `=`.
+pub fn parse_scontrol_output(output: &str) -> HashMap<String, String> {
+    let mut result = HashMap::new();
+    let mut current: Option<String> = None;
+
+    for token in output.split_whitespace() {
+        match token.split_once('=') {
+            Some((key, value)) if is_scontrol_key(key) => {
+                result.insert(key.to_string(), value.to_string());
+                current = Some(key.to_string());
+            }
+            _ => {
+                if let Some(value) = current.as_ref().and_then(|key| result.get_mut(key)) {
+                    value.push(' ');
+                    value.push_str(token);
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Field names look like `JobState`, `AllocNode:Sid` or `Socks/Node`.
+fn is_scontrol_key(key: &str) -> bool {
+    key.starts_with(|c: char| c.is_ascii_uppercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, ':' | '/' | '_'))
+}
+
 /// Parse sacct output for multiple jobs.
-#[allow(dead_code)]
 pub fn parse_sacct_multiple_output(output: &str) -> Vec<HashMap<String, String>> {
     let lines: Vec<&str> = output
         .trim()
@@ -149,74 +601,315 @@ pub fn parse_sacct_multiple_output(output: &str) -> Vec<HashMap<String, String>>
     }
 
     // Parse header
-    let header: Vec<&str> = lines[0].split('|').collect();
+    let delimiter = sacct_delimiter(lines[0]);
+    let header = sacct_header(lines[0], delimiter);
 
     // Parse all data lines
     lines[1..]
         .iter()
         .map(|line| {
-            let data: Vec<&str> = line.split('|').collect();
-            let mut result = HashMap::new();
-            for (i, field) in header.iter().enumerate() {
-                if i < data.len() {
-                    result.insert(field.trim().to_string(), data[i].trim().to_string());
-                }
-            }
-            result
+            let data = sacct_row(line, &header, delimiter);
+            header
+                .iter()
+                .zip(data)
+                .map(|(field, value)| (field.to_string(), value))
+                .collect::<HashMap<_, _>>()
         })
         .filter(|m| !m.is_empty())
         .collect()
 }
 
-/// Get all job IDs from sacct (recent jobs visible to the user).
+/// Criteria for selecting jobs during discovery.
+#[derive(Debug, Clone, Default)]
+pub struct JobFilter {
+    /// Glob pattern (`*`, `?`) matched against the job name
+    pub name_pattern: Option<String>,
+    /// Only include jobs active after this time (sacct `--starttime`)
+    pub since: Option<String>,
+    /// Only include jobs active before this time (sacct `--endtime`)
+    pub until: Option<String>,
+    /// Query this cluster instead of the local one (sacct `--clusters`)
+    pub cluster: Option<String>,
+}
+
+impl JobFilter {
+    /// Extra sacct arguments restricting the query to the filter's time window.
+    pub fn sacct_window_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(ref since) = self.since {
+            args.push(format!("--starttime={}", since));
+        }
+        if let Some(ref until) = self.until {
+            args.push(format!("--endtime={}", until));
+        }
+        args
+    }
+}
+
+/// Discover job IDs from sacct that match the given filter, running sacct
+/// through `runner`.
+///
+/// Returns a vector of job IDs sorted in descending order, or an error with
+/// sacct's stderr if it could not be run or failed.
+pub fn try_discover_job_ids(runner: &dyn CommandRunner, filter: &JobFilter) -> Result<Vec<u64>> {
+    let window_args = filter.sacct_window_args();
+    let cluster_arg = filter.cluster.as_ref().map(|cluster| format!("--clusters={}", cluster));
+    let mut cmd = vec![
+        "sacct",
+        "--format=JobID,JobName",
+        "--noheader",
+        "--parsable2",
+        SACCT_DELIMITER_ARG,
+    ];
+    cmd.extend(window_args.iter().map(|s| s.as_str()));
+    cmd.extend(cluster_arg.as_deref());
+
+    let cmd_result = runner.run(&cmd, false)?;
+    if cmd_result.return_code != 0 {
+        let stderr = cmd_result.stderr.trim();
+        if stderr.is_empty() {
+            anyhow::bail!("sacct exited with code {}", cmd_result.return_code);
+        }
+        anyhow::bail!("{}", stderr);
+    }
+    Ok(match filter.name_pattern {
+        Some(ref pattern) => parse_job_ids_by_name(&cmd_result.stdout, pattern),
+        None => parse_job_ids(&cmd_result.stdout),
+    })
+}
+
+/// Like `try_discover_job_ids`, but a failed sacct yields no jobs.
+pub fn discover_job_ids(runner: &dyn CommandRunner, filter: &JobFilter) -> Vec<u64> {
+    try_discover_job_ids(runner, filter).unwrap_or_default()
+}
+
+/// Get all job IDs from sacct (recent jobs visible to the user), running
+/// sacct through `runner`.
+///
+/// Returns a vector of job IDs sorted in descending order, or an error if
+/// sacct failed.
+pub fn try_get_all_job_ids(runner: &dyn CommandRunner) -> Result<Vec<u64>> {
+    try_discover_job_ids(runner, &JobFilter::default())
+}
+
+/// Like `try_get_all_job_ids`, but a failed sacct yields no jobs.
+pub fn get_all_job_ids_from_sacct(runner: &dyn CommandRunner) -> Vec<u64> {
+    try_get_all_job_ids(runner).unwrap_or_default()
+}
+
+/// Get job IDs from sacct whose job name matches a glob pattern, running
+/// sacct through `runner`.
 ///
 /// Returns a vector of job IDs sorted in descending order.
-pub fn get_all_job_ids_from_sacct() -> Vec<u64> {
-    let result = run_slurm_command(
-        &["sacct", "--format=JobID", "--noheader", "--parsable2"],
-        false,
-    );
-
-    match result {
-        Ok(cmd_result) if cmd_result.return_code == 0 && !cmd_result.stdout.trim().is_empty() => {
-            let mut job_ids: Vec<u64> = cmd_result
-                .stdout
-                .trim()
-                .lines()
-                .filter_map(|line| {
-                    let line = line.trim();
-                    if line.is_empty() {
-                        return None;
-                    }
-                    // Extract job ID (may be in format like "12345" or "12345.batch" or "12345_0")
-                    let job_id_str = line
-                        .split('|')
-                        .next()?
-                        .split('.')
-                        .next()?
-                        .split('_')
-                        .next()?;
-                    job_id_str.parse().ok()
-                })
-                .collect();
-
-            // Remove duplicates and sort in descending order
-            job_ids.sort_unstable();
-            job_ids.dedup();
-            job_ids.reverse();
-            job_ids
+pub fn get_job_ids_by_name(runner: &dyn CommandRunner, pattern: &str) -> Vec<u64> {
+    discover_job_ids(runner, &JobFilter {
+        name_pattern: Some(pattern.to_string()),
+        ..Default::default()
+    })
+}
+
+/// Parse sacct rows whose first field is a JobID.
+fn parse_job_ids(output: &str) -> Vec<u64> {
+    let job_ids = output
+        .trim()
+        .lines()
+        .filter_map(|line| parse_job_id_field(line.split(sacct_delimiter(line)).next()?))
+        .collect();
+    sort_job_ids(job_ids)
+}
+
+/// Parse `JobID,JobName` sacct rows, keeping jobs whose name matches `pattern`.
+///
+/// Step rows (`12345.batch`, `12345.0`) are skipped because their JobName is
+/// the step name rather than the name the user gave the job.
+fn parse_job_ids_by_name(output: &str, pattern: &str) -> Vec<u64> {
+    let job_ids = output
+        .trim()
+        .lines()
+        .filter_map(|line| {
+            let (job_id, job_name) = line.trim().split_once(sacct_delimiter(line))?;
+            if job_id.contains('.') || !glob_match(pattern, job_name.trim()) {
+                return None;
+            }
+            parse_job_id_field(job_id)
+        })
+        .collect();
+    sort_job_ids(job_ids)
+}
+
+/// Extract the numeric job ID from a sacct JobID field.
+///
+/// Handles formats like "12345", "12345.batch" and "12345_0". The components
+/// of a heterogeneous job ("12345+0", "12345+1.batch") map to the job itself,
+/// since `squeue -j` and `sacct -j` with its ID cover every component.
+pub fn parse_job_id_field(field: &str) -> Option<u64> {
+    let field = field.trim();
+    if field.is_empty() {
+        return None;
+    }
+    field.split(['.', '_', '+']).next()?.parse().ok()
+}
+
+/// Remove duplicates and sort job IDs in descending order.
+fn sort_job_ids(mut job_ids: Vec<u64>) -> Vec<u64> {
+    job_ids.sort_unstable();
+    job_ids.dedup();
+    job_ids.reverse();
+    job_ids
+}
+
+/// Parse a SLURM duration such as sacct's `Elapsed` or `TimeLimit`.
+///
+/// Accepts `MM`, `MM:SS`, `HH:MM:SS`, `D-HH`, `D-HH:MM` and `D-HH:MM:SS`,
+/// with optional fractional seconds. Returns `None` for empty or special
+/// values like `UNLIMITED`, `INVALID` or `Partition_Limit`, and for values
+/// too large for a `Duration`.
+pub fn parse_slurm_duration(value: &str) -> Option<Duration> {
+    let value = value.trim();
+    if value.is_empty() {
+        return None;
+    }
+
+    let (days, rest) = match value.split_once('-') {
+        Some((days, rest)) => (Some(days.parse::<u64>().ok()?), rest),
+        None => (None, value),
+    };
+
+    let parts: Vec<&str> = rest.split(':').collect();
+    let field = |s: &str| s.parse::<u64>().ok();
+    let seconds_field = |s: &str| s.parse::<f64>().ok().filter(|v| v.is_finite() && *v >= 0.0);
+
+    let (hours, minutes, seconds) = match (days.is_some(), parts.as_slice()) {
+        // With a day component the first field is hours
+        (true, [h]) => (field(h)?, 0, 0.0),
+        (true, [h, m]) => (field(h)?, field(m)?, 0.0),
+        // Without one, a single field is minutes and two are MM:SS
+        (false, [m]) => (0, field(m)?, 0.0),
+        (false, [m, s]) => (0, field(m)?, seconds_field(s)?),
+        (_, [h, m, s]) => (field(h)?, field(m)?, seconds_field(s)?),
+        _ => return None,
+    };
+
+    let whole_secs = days
+        .unwrap_or(0)
+        .checked_mul(24)?
+        .checked_add(hours)?
+        .checked_mul(60)?
+        .checked_add(minutes)?
+        .checked_mul(60)?;
+    Duration::from_secs(whole_secs).checked_add(Duration::try_from_secs_f64(seconds).ok()?)
+}
+
+/// Format a duration the way sacct prints `Elapsed`: `[D-]HH:MM:SS`.
+pub fn format_slurm_duration(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    let (days, hours, minutes, seconds) =
+        (secs / 86400, secs / 3600 % 24, secs / 60 % 60, secs % 60);
+    if days > 0 {
+        format!("{}-{:02}:{:02}:{:02}", days, hours, minutes, seconds)
+    } else {
+        format!("{:02}:{:02}:{:02}", hours, minutes, seconds)
+    }
+}
+
+/// Parse a SLURM timestamp such as sacct's `Start`/`End` (`2024-01-31T08:00:00`).
+///
+/// SLURM prints local time. Returns `None` for special values like `Unknown`,
+/// `None` or `N/A`, and anything else that isn't a timestamp.
+pub fn parse_slurm_timestamp(value: &str) -> Option<DateTime<Local>> {
+    let naive = NaiveDateTime::parse_from_str(value.trim(), "%Y-%m-%dT%H:%M:%S").ok()?;
+    Local.from_local_datetime(&naive).earliest()
+}
+
+/// Describe `timestamp` relative to `now`, e.g. `3m ago` or `in 2h`.
+pub fn format_relative_time(timestamp: DateTime<Local>, now: DateTime<Local>) -> String {
+    let secs = now.signed_duration_since(timestamp).num_seconds();
+    let abs = secs.unsigned_abs();
+    let amount = match abs {
+        0..=59 => format!("{}s", abs),
+        60..=3599 => format!("{}m", abs / 60),
+        3600..=86399 => format!("{}h", abs / 3600),
+        _ => format!("{}d", abs / 86400),
+    };
+
+    if secs >= 0 {
+        format!("{} ago", amount)
+    } else {
+        format!("in {}", amount)
+    }
+}
+
+/// Describe a SLURM timestamp relative to now, or `None` if it isn't one.
+pub fn relative_slurm_time(value: &str) -> Option<String> {
+    parse_slurm_timestamp(value).map(|t| format_relative_time(t, Local::now()))
+}
+
+/// Validate a time specification accepted by sacct's `--starttime`/`--endtime`.
+///
+/// Accepts absolute dates (`2024-01-31`, `2024-01-31T08:00[:00]`, `MMDD[YY]`,
+/// `MM/DD[/YY]`), times of day (`HH:MM[:SS]`), relative expressions
+/// (`now-2days`, `now+30minutes`) and keywords like `today` or `midnight`.
+pub fn validate_sacct_time(value: &str) -> Result<String, String> {
+    const PATTERNS: &[&str] = &[
+        r"^now([+-]\d+(seconds|minutes|hours|days|weeks)?)?$",
+        r"^(today|tomorrow|midnight|noon|elevenses|fika|teatime)$",
+        r"^\d{4}-\d{2}-\d{2}(t\d{2}:\d{2}(:\d{2})?)?$",
+        r"^\d{2}:\d{2}(:\d{2})?$",
+        r"^\d{2}/\d{2}(/\d{2})?$",
+        r"^\d{4}(\d{2})?$",
+    ];
+
+    let value = value.trim();
+    let lower = value.to_lowercase();
+    if PATTERNS
+        .iter()
+        .any(|p| Regex::new(p).map(|re| re.is_match(&lower)).unwrap_or(false))
+    {
+        Ok(value.to_string())
+    } else {
+        Err(format!(
+            "invalid time '{}': expected e.g. 2024-01-31, 2024-01-31T08:00, now-2days or today",
+            value
+        ))
+    }
+}
+
+/// Match `text` against a shell-style glob supporting `*` and `?`.
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+    let regex_pattern = regex::escape(pattern)
+        .replace(r"\*", ".*")
+        .replace(r"\?", ".");
+    Regex::new(&format!("^{}$", regex_pattern))
+        .map(|re| re.is_match(text))
+        .unwrap_or(false)
+}
+
+/// Join `handle` if its thread finishes within `timeout`; otherwise leave it
+/// running detached. Returns whether the thread finished.
+pub fn join_with_timeout(handle: thread::JoinHandle<()>, timeout: Duration) -> bool {
+    let deadline = Instant::now() + timeout;
+    while !handle.is_finished() {
+        if Instant::now() >= deadline {
+            return false;
         }
-        _ => Vec::new(),
+        thread::sleep(Duration::from_millis(10));
     }
+    let _ = handle.join();
+    true
 }
 
 /// Job status enum
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
 pub enum JobStatus {
     Queued,
     Running,
+    Suspended,
     Completed,
+    Cancelled,
+    Timeout,
+    OutOfMemory,
     Failed,
+    #[default]
     Unknown,
 }
 
@@ -225,28 +918,51 @@ impl JobStatus {
         match self {
             JobStatus::Queued => "QUEUED",
             JobStatus::Running => "RUNNING",
+            JobStatus::Suspended => "SUSPENDED",
             JobStatus::Completed => "COMPLETED",
+            JobStatus::Cancelled => "CANCELLED",
+            JobStatus::Timeout => "TIMEOUT",
+            JobStatus::OutOfMemory => "OOM",
             JobStatus::Failed => "FAILED",
             JobStatus::Unknown => "UNKNOWN",
         }
     }
 
+    /// Map a SLURM job state to a `JobStatus`.
+    ///
+    /// SLURM decorates states in a few ways: sacct reports `CANCELLED by <uid>`
+    /// and appends `+` when a state has extra flags (e.g. `COMPLETING+`).
+    /// Only the leading state word is considered.
     pub fn from_slurm_state(state: &str) -> Self {
-        let state_upper = state.to_uppercase();
-        match state_upper.as_str() {
-            "PENDING" | "CONFIGURING" => JobStatus::Queued,
-            "RUNNING" | "COMPLETING" => JobStatus::Running,
+        let state_upper = state.trim().to_uppercase();
+        let base_state = state_upper
+            .split_whitespace()
+            .next()
+            .unwrap_or("")
+            .trim_end_matches('+');
+
+        match base_state {
+            "PENDING" | "CONFIGURING" | "REQUEUED" | "REQUEUE_FED" | "REQUEUE_HOLD"
+            | "RESV_DEL_HOLD" => JobStatus::Queued,
+            "RUNNING" | "COMPLETING" | "RESIZING" | "SIGNALING" | "STAGE_OUT" => {
+                JobStatus::Running
+            }
+            "SUSPENDED" | "STOPPED" => JobStatus::Suspended,
             "COMPLETED" => JobStatus::Completed,
-            "FAILED" | "CANCELLED" | "TIMEOUT" | "NODE_FAIL" | "PREEMPTED" | "OUT_OF_MEMORY" => {
+            "CANCELLED" => JobStatus::Cancelled,
+            "TIMEOUT" | "DEADLINE" => JobStatus::Timeout,
+            "OUT_OF_MEMORY" => JobStatus::OutOfMemory,
+            "FAILED" | "NODE_FAIL" | "BOOT_FAIL" | "PREEMPTED" | "REVOKED" | "SPECIAL_EXIT" => {
                 JobStatus::Failed
             }
             _ => {
                 if state_upper.contains("COMPLETED") {
                     JobStatus::Completed
-                } else if state_upper.contains("FAILED")
-                    || state_upper.contains("CANCELLED")
-                    || state_upper.contains("TIMEOUT")
-                {
+                } else if state_upper.contains("CANCELLED") {
+                    JobStatus::Cancelled
+                } else if state_upper.contains("TIMEOUT") {
+                    JobStatus::Timeout
+                } else if state_upper.contains("FAILED") {
                     JobStatus::Failed
                 } else if state_upper.contains("RUNNING") {
                     JobStatus::Running
@@ -256,6 +972,43 @@ impl JobStatus {
             }
         }
     }
+
+    /// Where the status comes when jobs are ordered by how much they need
+    /// attention: running, suspended, queued, then the outcomes from worst
+    /// (failed, out of memory, timeout, cancelled) to completed, and unknown
+    /// last.
+    pub fn rank(&self) -> u8 {
+        match self {
+            JobStatus::Running => 0,
+            JobStatus::Suspended => 1,
+            JobStatus::Queued => 2,
+            JobStatus::Failed => 3,
+            JobStatus::OutOfMemory => 4,
+            JobStatus::Timeout => 5,
+            JobStatus::Cancelled => 6,
+            JobStatus::Completed => 7,
+            JobStatus::Unknown => 8,
+        }
+    }
+
+    /// Whether the job ended without getting its work done: failed, timed out
+    /// or ran out of memory. Cancelled jobs don't count, someone meant to
+    /// stop them.
+    pub fn is_failure(&self) -> bool {
+        matches!(self, JobStatus::Failed | JobStatus::Timeout | JobStatus::OutOfMemory)
+    }
+
+    /// Whether the job has reached a final state and will not change again.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            JobStatus::Completed
+                | JobStatus::Cancelled
+                | JobStatus::Timeout
+                | JobStatus::OutOfMemory
+                | JobStatus::Failed
+        )
+    }
 }
 
 impl std::fmt::Display for JobStatus {
@@ -267,6 +1020,149 @@ impl std::fmt::Display for JobStatus {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_slurm_command_sets_env() {
+        let env = vec![("SLURM_CONF".to_string(), OsString::from("/etc/slurm-b/slurm.conf"))];
+        let command = slurm_command(&["squeue", "-h"], &env);
+        let envs: Vec<_> = command.get_envs().collect();
+        assert_eq!(
+            envs,
+            [(OsStr::new("SLURM_CONF"), Some(OsStr::new("/etc/slurm-b/slurm.conf")))]
+        );
+        assert_eq!(command.get_program(), "squeue");
+        assert_eq!(command.get_args().collect::<Vec<_>>(), ["-h"]);
+
+        assert_eq!(slurm_command(&["squeue"], &[]).get_envs().count(), 0);
+    }
+
+    #[test]
+    fn test_system_runner_applies_its_env() {
+        let env = vec![("SLURM_CONF".to_string(), OsString::from("/etc/slurm-b/slurm.conf"))];
+        let runner = SystemRunner::new(env, Throttle::default());
+        let result = runner.run(&["sh", "-c", "echo $SLURM_CONF"], true).unwrap();
+        assert_eq!(result.stdout.trim(), "/etc/slurm-b/slurm.conf");
+
+        let command = runner.interactive_command(&["sattach", "7.0"]);
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            [(OsStr::new("SLURM_CONF"), Some(OsStr::new("/etc/slurm-b/slurm.conf")))]
+        );
+    }
+
+    #[test]
+    fn test_system_runner_clones_share_the_throttle() {
+        let runner = SystemRunner::new(
+            Vec::new(),
+            Throttle {
+                delay: Duration::from_millis(100),
+                jitter: Duration::ZERO,
+                max_concurrent: 4,
+            },
+        );
+        let clone = runner.clone();
+        let start = Instant::now();
+        runner.run(&["true"], true).unwrap();
+        clone.run(&["true"], true).unwrap();
+        assert!(start.elapsed() >= Duration::from_millis(100));
+    }
+
+    #[test]
+    fn test_unthrottled_runner_skips_the_throttle() {
+        let env = vec![("SLURM_CONF".to_string(), OsString::from("/etc/slurm-b/slurm.conf"))];
+        let runner = SystemRunner::new(
+            env,
+            Throttle {
+                delay: Duration::from_secs(10),
+                jitter: Duration::ZERO,
+                max_concurrent: 1,
+            },
+        );
+        runner.run(&["true"], true).unwrap();
+
+        let ui_runner = runner.unthrottled();
+        let start = Instant::now();
+        let result = ui_runner.run(&["sh", "-c", "echo $SLURM_CONF"], true).unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        assert_eq!(result.stdout.trim(), "/etc/slurm-b/slurm.conf");
+    }
+
+    #[test]
+    fn test_limiter_caps_concurrent_commands() {
+        let limiter = Limiter::new(Throttle {
+            max_concurrent: 2,
+            ..Default::default()
+        });
+        let running = AtomicUsize::new(0);
+        let most_running = AtomicUsize::new(0);
+        thread::scope(|scope| {
+            for _ in 0..6 {
+                scope.spawn(|| {
+                    let _permit = limiter.acquire();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_running.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(20));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                });
+            }
+        });
+        assert_eq!(most_running.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_limiter_spaces_out_command_starts() {
+        let delay = Duration::from_millis(30);
+        let limiter = Limiter::new(Throttle {
+            delay,
+            jitter: Duration::from_millis(10),
+            ..Default::default()
+        });
+        let started = Instant::now();
+        let starts: Vec<Duration> = (0..3)
+            .map(|_| {
+                let _permit = limiter.acquire();
+                started.elapsed()
+            })
+            .collect();
+        assert!(starts[0] < delay);
+        assert!(starts[1] >= delay);
+        assert!(starts[2] >= delay * 2);
+        assert!(starts[2] - starts[1] >= delay);
+    }
+
+    #[test]
+    fn test_parse_tres() {
+        let tres = "cpu=16,mem=64G,node=1,billing=16,gres/gpu=2,gres/gpu:a100=2";
+        assert_eq!(
+            parse_tres(tres).unwrap(),
+            Tres {
+                cpus: 16,
+                mem_mib: 64.0 * 1024.0,
+                gpus: 2,
+            }
+        );
+
+        // Memory defaults to MiB and may have any unit
+        assert_eq!(parse_tres("cpu=1,mem=500").unwrap().mem_mib, 500.0);
+        assert_eq!(parse_tres("mem=2048K").unwrap().mem_mib, 2.0);
+        assert_eq!(parse_tres("mem=1.5T").unwrap().mem_mib, 1.5 * 1024.0 * 1024.0);
+        assert_eq!(parse_tres("mem=1P").unwrap().mem_mib, 1024.0 * 1024.0 * 1024.0);
+
+        // Typed GPUs without a total
+        assert_eq!(parse_tres("cpu=4,gres/gpu:a100=2,gres/gpu:v100=1").unwrap().gpus, 3);
+        assert_eq!(parse_tres("cpu=4,gres/mps=100").unwrap().gpus, 0);
+
+        assert_eq!(parse_tres(""), None);
+        assert_eq!(parse_tres("cpu=x,mem=lots").unwrap(), Tres::default());
+    }
+
+    #[test]
+    fn test_format_mib() {
+        assert_eq!(format_mib(512.4), "512 MiB");
+        assert_eq!(format_mib(1024.0), "1.0 GiB");
+        assert_eq!(format_mib(2867.2), "2.8 GiB");
+    }
 
     #[test]
     fn test_parse_job_id() {
@@ -288,6 +1184,226 @@ mod tests {
         assert_eq!(result.get("JobID"), Some(&"12345".to_string()));
         assert_eq!(result.get("JobName"), Some(&"test_job".to_string()));
         assert_eq!(result.get("State"), Some(&"RUNNING".to_string()));
+
+        // ExitCode comes from the batch step, not from .extern
+        let output = "JobID|ExitCode|StdOut\n\
+                      7|0:9|\n\
+                      7.batch|1:0|/logs/7.out\n\
+                      7.extern|0:0|\n";
+        let result = parse_sacct_output(output);
+        assert_eq!(result.get("ExitCode").unwrap(), "1:0");
+        assert_eq!(result.get("StdOut").unwrap(), "/logs/7.out");
+        let result = parse_sacct_output("JobID|ExitCode\n7|2:0\n7.extern|0:0\n");
+        assert_eq!(result.get("ExitCode").unwrap(), "2:0");
+    }
+
+    #[test]
+    fn test_parse_sacct_output_with_pipes_and_trailing_delimiters() {
+        // A comment containing the delimiter, after paths and allocation
+        let output = "JobID|State|ExitCode|StdOut|StdErr|AllocCPUS|AllocTRES|WCKey|Comment\n\
+                      78|RUNNING|0:0|/logs/78.out|/logs/78.err|16|cpu=16|*ml|project=x|owner=y\n";
+        let result = parse_sacct_output(output);
+        assert_eq!(result["StdErr"], "/logs/78.err");
+        assert_eq!(result["AllocCPUS"], "16");
+        assert_eq!(result["AllocTRES"], "cpu=16");
+        assert_eq!(result["WCKey"], "*ml");
+        assert_eq!(result["Comment"], "project=x|owner=y");
+
+        // --parsable ends every line with a delimiter
+        let output = "JobID|State|StdOut|\n7|FAILED||\n7.batch|FAILED|/logs/7.out|\n";
+        let result = parse_sacct_output(output);
+        assert_eq!(result.len(), 3);
+        assert_eq!(result["State"], "FAILED");
+        assert_eq!(result["StdOut"], "/logs/7.out");
+
+        let rows = parse_sacct_multiple_output("JobID|Elapsed|Comment|\n8|00:01:00|x|y|\n9|00:02:00|z|\n");
+        assert_eq!(rows[0]["Elapsed"], "00:01:00");
+        assert_eq!(rows[0]["Comment"], "x|y");
+        assert_eq!(rows[1]["Comment"], "z");
+        assert_eq!(rows[1].len(), 3);
+
+        // With SACCT_DELIMITER, every field may hold a `|`
+        let output = "JobID\x1fJobName\x1fWorkDir\x1fStdOut\n\
+                      80\x1fa|b\x1f/home/alice/x|y\x1f\n\
+                      80.batch\x1fbatch\x1f/home/alice/x|y\x1f/logs/a|b.out\n";
+        let result = parse_sacct_output(output);
+        assert_eq!(result["JobName"], "a|b");
+        assert_eq!(result["WorkDir"], "/home/alice/x|y");
+        assert_eq!(result["StdOut"], "/logs/a|b.out");
+    }
+
+    #[test]
+    fn test_parse_scontrol_output() {
+        let output = "JobId=123 JobName=train\n   JobState=RUNNING Reason=None\n   \
+                      NodeList=gpu[01-02] NumNodes=2 NumCPUs=16\n   \
+                      AllocNode:Sid=login01:4242\n   \
+                      Command=/home/alice/run.sh --lr=0.1 --epochs 10\n   \
+                      StdOut=/home/alice/slurm-123.out";
+        let fields = parse_scontrol_output(output);
+        assert_eq!(fields.get("JobState").unwrap(), "RUNNING");
+        assert_eq!(fields.get("NodeList").unwrap(), "gpu[01-02]");
+        assert_eq!(fields.get("NumNodes").unwrap(), "2");
+        assert_eq!(fields.get("AllocNode:Sid").unwrap(), "login01:4242");
+        assert_eq!(
+            fields.get("Command").unwrap(),
+            "/home/alice/run.sh --lr=0.1 --epochs 10"
+        );
+        assert_eq!(fields.get("StdOut").unwrap(), "/home/alice/slurm-123.out");
+
+        assert!(parse_scontrol_output("").is_empty());
+        assert!(parse_scontrol_output("slurm_load_jobs error: Invalid job id specified").is_empty());
+    }
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("train_*", "train_resnet"));
+        assert!(glob_match("train_*", "train_"));
+        assert!(!glob_match("train_*", "eval_resnet"));
+        assert!(glob_match("run?", "run1"));
+        assert!(!glob_match("run?", "run12"));
+        assert!(glob_match("a.b", "a.b"));
+        assert!(!glob_match("a.b", "axb"));
+    }
+
+    #[test]
+    fn test_parse_job_ids_by_name() {
+        let output = "\
+12345|train_resnet
+12345.batch|batch
+12345.0|python
+12346|eval_resnet
+12347_0|train_vit
+12347_1|train_vit
+12348|batch
+";
+        assert_eq!(parse_job_ids_by_name(output, "train_*"), vec![12347, 12345]);
+        assert_eq!(parse_job_ids_by_name(output, "batch"), vec![12348]);
+        assert!(parse_job_ids_by_name(output, "nothing*").is_empty());
+        assert!(parse_job_ids_by_name("", "*").is_empty());
+
+        let output = "12349\x1fa|b\n12350\x1fa\n";
+        assert_eq!(parse_job_ids_by_name(output, "a|*"), vec![12349]);
+        assert_eq!(parse_job_ids(output), vec![12350, 12349]);
+    }
+
+    #[test]
+    fn test_parse_job_ids() {
+        let output = "12345\n12345.batch\n12346_3\n\n12344|extra\n";
+        assert_eq!(parse_job_ids(output), vec![12346, 12345, 12344]);
+    }
+
+    #[test]
+    fn test_shell_words() {
+        let plain = ["sbatch", "--partition=gpu", "job.sh"];
+        assert_eq!(shell_words(&plain), "sbatch --partition=gpu job.sh");
+        assert_eq!(
+            shell_words(&["sbatch", "--job-name=my run", "", "it's.sh"]),
+            "sbatch '--job-name=my run' '' 'it'\\''s.sh'"
+        );
+    }
+
+    #[test]
+    fn test_parse_heterogeneous_job_ids() {
+        let output = "\
+12350+0|het_train
+12350+0.batch|batch
+12350+1|het_train
+12350+1.0|python
+12349|other
+";
+        assert_eq!(parse_job_ids(output), vec![12350, 12349]);
+        assert_eq!(parse_job_ids_by_name(output, "het_*"), vec![12350]);
+        assert_eq!(parse_job_ids_by_name(output, "python"), Vec::<u64>::new());
+        assert_eq!(parse_job_id_field("12350+1.extern"), Some(12350));
+        assert_eq!(parse_job_id_field("+1"), None);
+    }
+
+    #[test]
+    fn test_try_discover_job_ids_reports_sacct_errors() {
+        let filter = JobFilter::default();
+        let runner = FakeRunner::default().respond("sacct", "12345|train\n12346|eval\n");
+        assert_eq!(try_discover_job_ids(&runner, &filter).unwrap(), vec![12346, 12345]);
+
+        let runner = FakeRunner::default().respond("sacct", "");
+        assert!(try_discover_job_ids(&runner, &filter).unwrap().is_empty());
+
+        let runner = FakeRunner::default()
+            .fail("sacct", "sacct: error: Problem talking to the database\n");
+        let error = try_discover_job_ids(&runner, &filter).unwrap_err();
+        assert_eq!(error.to_string(), "sacct: error: Problem talking to the database");
+
+        let error = try_discover_job_ids(&FakeRunner::default(), &filter).unwrap_err();
+        assert_eq!(error.to_string(), "sacct exited with code 1");
+    }
+
+    #[test]
+    fn test_job_id_lookups_use_the_runner() {
+        let runner = FakeRunner::default().respond("sacct", "12345|train\n12346|eval\n");
+        assert_eq!(try_get_all_job_ids(&runner).unwrap(), vec![12346, 12345]);
+        assert_eq!(get_job_ids_by_name(&runner, "tr*"), vec![12345]);
+        assert_eq!(runner.calls().len(), 2);
+        assert!(get_all_job_ids_from_sacct(&FakeRunner::default()).is_empty());
+    }
+
+    #[test]
+    fn test_job_filter_window_args() {
+        assert!(JobFilter::default().sacct_window_args().is_empty());
+        let filter = JobFilter {
+            since: Some("now-2days".to_string()),
+            until: Some("2024-01-31".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            filter.sacct_window_args(),
+            vec!["--starttime=now-2days", "--endtime=2024-01-31"]
+        );
+    }
+
+    #[test]
+    fn test_parse_slurm_duration() {
+        let secs = |v: &str| parse_slurm_duration(v).map(|d| d.as_secs());
+        assert_eq!(secs("00:00:42"), Some(42));
+        assert_eq!(secs("01:02:03"), Some(3723));
+        assert_eq!(secs("2-03:04:05"), Some(2 * 86400 + 3 * 3600 + 4 * 60 + 5));
+        assert_eq!(secs("1-00"), Some(86400));
+        assert_eq!(secs("1-02:30"), Some(86400 + 2 * 3600 + 30 * 60));
+        assert_eq!(secs("30"), Some(1800));
+        assert_eq!(secs("05:30"), Some(330));
+        assert_eq!(secs("00:01.500"), Some(1));
+        assert_eq!(secs(""), None);
+        assert_eq!(secs("UNLIMITED"), None);
+        assert_eq!(secs("Partition_Limit"), None);
+        assert_eq!(secs("1:2:3:4"), None);
+        assert_eq!(secs("1:inf"), None);
+        assert_eq!(secs("1:NaN"), None);
+        assert_eq!(secs("99999999999999999-0"), None);
+        assert_eq!(secs("0:1e300"), None);
+        assert_eq!(secs(&format!("{}:1e19", u64::MAX / 60)), None);
+    }
+
+    #[test]
+    fn test_validate_sacct_time() {
+        for value in [
+            "now",
+            "now-2days",
+            "NOW+30minutes",
+            "now-3600",
+            "today",
+            "midnight",
+            "2024-01-31",
+            "2024-01-31T08:00",
+            "2024-01-31T08:00:30",
+            "08:00",
+            "01/31",
+            "01/31/24",
+            "0131",
+            "013124",
+        ] {
+            assert!(validate_sacct_time(value).is_ok(), "{}", value);
+        }
+        for value in ["", "yesterday", "now-2fortnights", "2024/01/31", "31-01-2024"] {
+            assert!(validate_sacct_time(value).is_err(), "{}", value);
+        }
     }
 
     #[test]
@@ -296,6 +1412,102 @@ mod tests {
         assert_eq!(JobStatus::from_slurm_state("RUNNING"), JobStatus::Running);
         assert_eq!(JobStatus::from_slurm_state("COMPLETED"), JobStatus::Completed);
         assert_eq!(JobStatus::from_slurm_state("FAILED"), JobStatus::Failed);
-        assert_eq!(JobStatus::from_slurm_state("CANCELLED"), JobStatus::Failed);
+        assert_eq!(JobStatus::from_slurm_state("CANCELLED"), JobStatus::Cancelled);
+        assert_eq!(JobStatus::from_slurm_state("TIMEOUT"), JobStatus::Timeout);
+        assert_eq!(JobStatus::from_slurm_state("OUT_OF_MEMORY"), JobStatus::OutOfMemory);
+    }
+
+    #[test]
+    fn test_job_status_from_decorated_slurm_state() {
+        assert_eq!(JobStatus::from_slurm_state("CANCELLED by 1234"), JobStatus::Cancelled);
+        assert_eq!(JobStatus::from_slurm_state("COMPLETING+"), JobStatus::Running);
+        assert_eq!(JobStatus::from_slurm_state("RUNNING+"), JobStatus::Running);
+        assert_eq!(JobStatus::from_slurm_state(" completed \n"), JobStatus::Completed);
+    }
+
+    #[test]
+    fn test_job_status_from_full_slurm_state_set() {
+        for state in ["REQUEUED", "REQUEUE_HOLD", "REQUEUE_FED", "RESV_DEL_HOLD", "CONFIGURING"] {
+            assert_eq!(JobStatus::from_slurm_state(state), JobStatus::Queued, "{}", state);
+        }
+        for state in ["RESIZING", "SIGNALING", "STAGE_OUT"] {
+            assert_eq!(JobStatus::from_slurm_state(state), JobStatus::Running, "{}", state);
+        }
+        for state in ["SUSPENDED", "STOPPED"] {
+            assert_eq!(JobStatus::from_slurm_state(state), JobStatus::Suspended, "{}", state);
+        }
+        assert_eq!(JobStatus::from_slurm_state("DEADLINE"), JobStatus::Timeout);
+        for state in ["BOOT_FAIL", "NODE_FAIL", "REVOKED", "SPECIAL_EXIT", "PREEMPTED"] {
+            assert_eq!(JobStatus::from_slurm_state(state), JobStatus::Failed, "{}", state);
+        }
+        assert_eq!(JobStatus::from_slurm_state(""), JobStatus::Unknown);
+        assert_eq!(JobStatus::from_slurm_state("BOGUS"), JobStatus::Unknown);
+    }
+
+    #[test]
+    fn test_job_status_is_terminal() {
+        assert!(!JobStatus::Queued.is_terminal());
+        assert!(!JobStatus::Running.is_terminal());
+        assert!(!JobStatus::Suspended.is_terminal());
+        assert!(!JobStatus::Unknown.is_terminal());
+        assert!(JobStatus::Completed.is_terminal());
+        assert!(JobStatus::Cancelled.is_terminal());
+        assert!(JobStatus::Timeout.is_terminal());
+        assert!(JobStatus::OutOfMemory.is_terminal());
+        assert!(JobStatus::Failed.is_terminal());
+    }
+
+    #[test]
+    fn test_job_status_rank() {
+        let mut statuses = [
+            JobStatus::Unknown,
+            JobStatus::Completed,
+            JobStatus::Queued,
+            JobStatus::Failed,
+            JobStatus::Running,
+            JobStatus::Cancelled,
+            JobStatus::Suspended,
+            JobStatus::Timeout,
+            JobStatus::OutOfMemory,
+        ];
+        statuses.sort_by_key(JobStatus::rank);
+        assert_eq!(
+            statuses,
+            [
+                JobStatus::Running,
+                JobStatus::Suspended,
+                JobStatus::Queued,
+                JobStatus::Failed,
+                JobStatus::OutOfMemory,
+                JobStatus::Timeout,
+                JobStatus::Cancelled,
+                JobStatus::Completed,
+                JobStatus::Unknown,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_slurm_timestamp() {
+        let t = parse_slurm_timestamp("2024-01-31T08:05:09").unwrap();
+        assert_eq!(t.format("%Y-%m-%d %H:%M:%S").to_string(), "2024-01-31 08:05:09");
+
+        for special in ["Unknown", "None", "N/A", "", "2024-01-31"] {
+            assert!(parse_slurm_timestamp(special).is_none(), "{}", special);
+            assert!(relative_slurm_time(special).is_none(), "{}", special);
+        }
+    }
+
+    #[test]
+    fn test_format_relative_time() {
+        let now = parse_slurm_timestamp("2024-01-31T12:00:00").unwrap();
+        let at = |s: &str| parse_slurm_timestamp(s).unwrap();
+
+        assert_eq!(format_relative_time(at("2024-01-31T11:59:48"), now), "12s ago");
+        assert_eq!(format_relative_time(at("2024-01-31T11:56:30"), now), "3m ago");
+        assert_eq!(format_relative_time(at("2024-01-31T09:00:00"), now), "3h ago");
+        assert_eq!(format_relative_time(at("2024-01-29T12:00:00"), now), "2d ago");
+        assert_eq!(format_relative_time(at("2024-01-31T12:05:00"), now), "in 5m");
+        assert_eq!(format_relative_time(now, now), "0s ago");
     }
 }
@@ -0,0 +1,190 @@
+//! Webhook notifications for jobs reaching a terminal state.
+
+use crate::diagnostics;
+use crate::utils::{join_with_timeout, JobStatus};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+/// Number of delivery attempts per event.
+const MAX_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry; doubled for each further retry.
+const RETRY_DELAY: Duration = Duration::from_secs(2);
+
+/// Per-request timeout.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A job that has just reached a terminal state.
+#[derive(Debug, Clone)]
+pub struct JobEvent {
+    pub job_id: u64,
+    pub name: String,
+    pub status: JobStatus,
+    /// Raw SLURM state, e.g. `CANCELLED by 1000`
+    pub state: String,
+    pub elapsed: String,
+}
+
+impl JobEvent {
+    /// JSON body POSTed to the webhook.
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::json!({
+            "event": "job_finished",
+            "job_id": self.job_id,
+            "name": self.name,
+            "status": self.status.as_str(),
+            "state": self.state,
+            "elapsed": self.elapsed,
+        })
+    }
+}
+
+/// Posts `JobEvent`s to a webhook URL from a dedicated thread.
+///
+/// Sending an event only enqueues it, so slow or failing HTTP requests never
+/// hold up status polling. Each event is retried a few times with backoff
+/// before being dropped. The thread exits once every sender is gone; call
+/// `finish` before the process exits so queued events are still delivered.
+pub struct WebhookNotifier {
+    event_tx: Sender<JobEvent>,
+    handle: JoinHandle<()>,
+}
+
+impl WebhookNotifier {
+    /// Start a notifier posting to `url`.
+    pub fn new(url: String) -> Self {
+        let (event_tx, event_rx) = mpsc::channel();
+        let handle = thread::spawn(move || Self::send_loop(event_rx, url));
+        Self { event_tx, handle }
+    }
+
+    /// Channel to send events on.
+    pub fn sender(&self) -> Sender<JobEvent> {
+        self.event_tx.clone()
+    }
+
+    /// Wait up to `timeout` for the events already sent to be delivered (or
+    /// given up on), once every other sender is dropped. Returns whether
+    /// they were.
+    pub fn finish(self, timeout: Duration) -> bool {
+        drop(self.event_tx);
+        join_with_timeout(self.handle, timeout)
+    }
+
+    /// Deliver events until all senders are dropped.
+    fn send_loop(event_rx: Receiver<JobEvent>, url: String) {
+        let agent = ureq::AgentBuilder::new().timeout(REQUEST_TIMEOUT).build();
+
+        for event in event_rx {
+            let body = event.to_json();
+            let mut delay = RETRY_DELAY;
+            for attempt in 1..=MAX_ATTEMPTS {
+                match agent.post(&url).send_json(&body) {
+                    Ok(_) => break,
+                    Err(e) => {
+                        diagnostics::warn(format!(
+                            "webhook: attempt {}/{} for job {} failed: {}",
+                            attempt, MAX_ATTEMPTS, event.job_id, e
+                        ));
+                        if attempt < MAX_ATTEMPTS {
+                            thread::sleep(delay);
+                            delay *= 2;
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Validate a `--webhook` URL (clap value_parser).
+pub fn validate_webhook_url(s: &str) -> Result<String, String> {
+    if s.starts_with("http://") || s.starts_with("https://") {
+        Ok(s.to_string())
+    } else {
+        Err(format!("'{}' is not an http(s) URL", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{BufRead, BufReader, Read, Write};
+    use std::net::TcpListener;
+
+    fn event() -> JobEvent {
+        JobEvent {
+            job_id: 12345,
+            name: "train".to_string(),
+            status: JobStatus::Failed,
+            state: "FAILED".to_string(),
+            elapsed: "00:10:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_event_payload() {
+        let payload = event().to_json();
+        assert_eq!(payload["job_id"], 12345);
+        assert_eq!(payload["name"], "train");
+        assert_eq!(payload["status"], "FAILED");
+        assert_eq!(payload["elapsed"], "00:10:00");
+    }
+
+    #[test]
+    fn test_notifier_posts_event() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+
+        let notifier = WebhookNotifier::new(url);
+        notifier.sender().send(event()).unwrap();
+
+        let (stream, _) = listener.accept().unwrap();
+        let mut reader = BufReader::new(stream);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line).unwrap();
+        assert!(request_line.starts_with("POST /hook"));
+
+        let mut content_length = 0;
+        loop {
+            let mut header = String::new();
+            reader.read_line(&mut header).unwrap();
+            if header.trim().is_empty() {
+                break;
+            }
+            if let Some(len) = header.to_lowercase().strip_prefix("content-length:") {
+                content_length = len.trim().parse().unwrap();
+            }
+        }
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body).unwrap();
+        reader
+            .get_mut()
+            .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+            .unwrap();
+
+        let body: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(body["job_id"], 12345);
+        assert_eq!(body["status"], "FAILED");
+    }
+
+    #[test]
+    fn test_finish_waits_for_the_other_senders() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let notifier = WebhookNotifier::new(format!("http://{}/", listener.local_addr().unwrap()));
+        let event_tx = notifier.sender();
+        assert!(!notifier.finish(Duration::from_millis(50)));
+        drop(event_tx);
+
+        let notifier = WebhookNotifier::new(format!("http://{}/", listener.local_addr().unwrap()));
+        assert!(notifier.finish(Duration::from_secs(5)));
+    }
+
+    #[test]
+    fn test_validate_webhook_url() {
+        assert!(validate_webhook_url("https://hooks.example.com/x").is_ok());
+        assert!(validate_webhook_url("http://localhost:8080").is_ok());
+        assert!(validate_webhook_url("hooks.example.com").is_err());
+    }
+}